@@ -0,0 +1,262 @@
+//! Benchmarks comparing the code generated for Y programs against equivalent C programs.
+//!
+//! Every program in `benches/programs` is compiled via the compiler library and its C counterpart
+//! with `-O0` and `-O2` (using `$CC` or `cc`). All binaries are run repeatedly and the ratios of
+//! the median run time of the Y program to the C programs are compared against
+//! `benches/baseline.txt`. The benchmark fails if a ratio exceeds its baseline by more than the
+//! threshold factor stored in this file.
+//!
+//! Run `WHY_BENCH_UPDATE=1 cargo bench` to rewrite the baseline with the current ratios.
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    time::{Duration, Instant},
+};
+
+use y_lang::{
+    compiler::Compiler,
+    loader::{load_module, Module},
+};
+
+const PROGRAMS_PATH: &str = "./benches/programs";
+const BASELINE_PATH: &str = "./benches/baseline.txt";
+const OUTPUT_PATH: &str = "./output/bench";
+
+const PROGRAMS: [&str; 3] = ["fib", "array_sum", "strings"];
+const OPTIMIZATIONS: [&str; 2] = ["O0", "O2"];
+
+const WARMUP_RUNS: usize = 2;
+const RUNS: usize = 10;
+
+/// Threshold used if the baseline does not specify one.
+const DEFAULT_THRESHOLD: f64 = 1.5;
+
+/// Environment variable which makes the benchmark rewrite the baseline instead of comparing
+/// against it.
+const UPDATE_BASELINE: &str = "WHY_BENCH_UPDATE";
+
+/// Expected ratios of the run time of Y programs to their C equivalents.
+#[derive(Debug, Clone, PartialEq)]
+struct Baseline {
+    /// Factor by which a ratio may exceed its baseline before it counts as a regression.
+    threshold: f64,
+    /// Ratios by program and optimization level of the C program.
+    ratios: BTreeMap<(String, String), f64>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            ratios: BTreeMap::new(),
+        }
+    }
+}
+
+impl Baseline {
+    /// Load the baseline from the given file. A missing file results in an empty baseline.
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Parse a baseline, which consists of a line `threshold <factor>` and one line
+    /// `<program> <optimization> <ratio>` per ratio. Empty lines and lines starting with `#` are
+    /// ignored.
+    fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        let mut baseline = Self::default();
+
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["threshold", threshold] => baseline.threshold = threshold.parse()?,
+                [program, optimization, ratio] => {
+                    baseline.ratios.insert(
+                        (program.to_owned(), optimization.to_owned()),
+                        ratio.parse()?,
+                    );
+                }
+                _ => return Err(format!("Invalid line in baseline: '{line}'").into()),
+            }
+        }
+
+        Ok(baseline)
+    }
+
+    fn serialize(&self) -> String {
+        let mut content = format!(
+            "# Ratios of the run time of Y programs to their C equivalents.\n\
+             # Regenerate with `{UPDATE_BASELINE}=1 cargo bench`.\n\
+             threshold {}\n",
+            self.threshold
+        );
+
+        for ((program, optimization), ratio) in &self.ratios {
+            content.push_str(&format!("{program} {optimization} {ratio:.2}\n"));
+        }
+
+        content
+    }
+}
+
+/// Compile a Y program via the library (like `why build` does).
+fn compile_y(src_path: &Path, target: &Path) -> Result<(), Box<dyn Error>> {
+    let main_module = load_module(fs::canonicalize(src_path)?)?;
+    let (Module { ast, .. }, modules) = main_module.type_check_program(&HashMap::new())?;
+
+    Compiler::from_ast(ast, modules).compile_program(target.to_path_buf())
+}
+
+fn compile_c(src_path: &Path, target: &Path, optimization: &str) -> Result<(), Box<dyn Error>> {
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+
+    let output = Command::new(compiler)
+        .arg(format!("-{optimization}"))
+        .arg("-o")
+        .arg(target)
+        .arg(src_path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to compile '{}': {}",
+            src_path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Run a binary once and return its output.
+fn run(binary: &Path) -> Result<String, Box<dyn Error>> {
+    let output = Command::new(binary).output()?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "'{}' exited with status {:?}",
+            binary.display(),
+            output.status.code()
+        )
+        .into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Run a binary repeatedly (after some warmup runs) and return the median of its run times.
+fn median_run_time(binary: &Path) -> Result<Duration, Box<dyn Error>> {
+    for _ in 0..WARMUP_RUNS {
+        run(binary)?;
+    }
+
+    let mut times = vec![];
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        run(binary)?;
+        times.push(start.elapsed());
+    }
+
+    times.sort();
+    Ok(times[times.len() / 2])
+}
+
+/// Compile and measure a single program. Returns the run time of the Y program and the run times
+/// of the C programs by optimization level.
+fn measure(program: &str) -> Result<(Duration, Vec<Duration>), Box<dyn Error>> {
+    let src_path = Path::new(PROGRAMS_PATH).join(program);
+    let target = Path::new(OUTPUT_PATH).join(program);
+
+    compile_y(&src_path.with_extension("why"), &target)?;
+    let expected_output = run(&target)?;
+    let y_time = median_run_time(&target)?;
+
+    let mut c_times = vec![];
+    for optimization in OPTIMIZATIONS {
+        let c_target = PathBuf::from(format!("{}-c-{optimization}", target.display()));
+        compile_c(&src_path.with_extension("c"), &c_target, optimization)?;
+
+        // make sure both programs actually compute the same
+        let output = run(&c_target)?;
+        if output != expected_output {
+            return Err(format!(
+                "Output of C program '{program}' (-{optimization}) differs: expected '{expected_output}', got '{output}'"
+            )
+            .into());
+        }
+
+        c_times.push(median_run_time(&c_target)?);
+    }
+
+    Ok((y_time, c_times))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(OUTPUT_PATH)?;
+
+    let baseline = Baseline::load(Path::new(BASELINE_PATH))?;
+    let mut current = Baseline {
+        threshold: baseline.threshold,
+        ..Default::default()
+    };
+    let mut regressions = vec![];
+
+    println!(
+        "{:<12}{:>12}{:>12}{:>12}{:>10}{:>10}",
+        "program", "Y [ms]", "C -O0 [ms]", "C -O2 [ms]", "Y/C -O0", "Y/C -O2"
+    );
+
+    for program in PROGRAMS {
+        let (y_time, c_times) = measure(program)?;
+
+        let mut row = format!("{program:<12}{:>12.2}", y_time.as_secs_f64() * 1000.0);
+        for c_time in &c_times {
+            row.push_str(&format!("{:>12.2}", c_time.as_secs_f64() * 1000.0));
+        }
+
+        for (optimization, c_time) in OPTIMIZATIONS.iter().zip(&c_times) {
+            let ratio = y_time.as_secs_f64() / c_time.as_secs_f64();
+            row.push_str(&format!("{ratio:>10.2}"));
+
+            let key = (program.to_owned(), optimization.to_string());
+            if let Some(expected) = baseline.ratios.get(&key) {
+                if ratio > expected * baseline.threshold {
+                    regressions.push(format!(
+                        "{program} (-{optimization}): ratio {ratio:.2} exceeds baseline {expected:.2} by more than {}x",
+                        baseline.threshold
+                    ));
+                }
+            }
+
+            current.ratios.insert(key, ratio);
+        }
+
+        println!("{row}");
+    }
+
+    if env::var_os(UPDATE_BASELINE).is_some() {
+        fs::write(BASELINE_PATH, current.serialize())?;
+        println!("\nUpdated baseline '{BASELINE_PATH}'");
+        return Ok(());
+    }
+
+    if !regressions.is_empty() {
+        eprintln!("\nRegressions compared to '{BASELINE_PATH}':");
+        for regression in regressions {
+            eprintln!("\t{regression}");
+        }
+        process::exit(1);
+    }
+
+    Ok(())
+}
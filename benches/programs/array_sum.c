@@ -0,0 +1,19 @@
+#include <stdio.h>
+
+int main(void) {
+    long xs[1000];
+
+    for (long i = 0; i < 1000; i++) {
+        xs[i] = i % 10;
+    }
+
+    long sum = 0;
+    for (long round = 0; round < 50000; round++) {
+        for (long j = 0; j < 1000; j++) {
+            sum = sum + xs[j];
+        }
+    }
+
+    printf("%ld", sum);
+    return 0;
+}
@@ -0,0 +1,27 @@
+#include <stdio.h>
+#include <string.h>
+
+long count(const char *text, long length, char c) {
+    long result = 0;
+
+    for (long i = 0; i < length; i++) {
+        if (text[i] == c) {
+            result = result + 1;
+        }
+    }
+
+    return result;
+}
+
+int main(void) {
+    const char *text = "hello world, hello y, all the lovely little loops";
+    long length = strlen(text);
+
+    long total = 0;
+    for (long round = 0; round < 500000; round++) {
+        total = total + count(text, length, 'l');
+    }
+
+    printf("%ld", total);
+    return 0;
+}
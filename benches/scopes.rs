@@ -0,0 +1,73 @@
+//! Micro-benchmark of variable lookups in the type checker and the interpreter.
+//!
+//! The benchmarked program defines many variables and updates all of them within a loop (nested
+//! in a few blocks, so lookups have to traverse several scopes). Type checking is repeated, since
+//! the checker visits the loop only once, while the interpreter runs all of its iterations.
+use std::{collections::HashMap, error::Error, fs, path::Path, time::Instant};
+
+use y_lang::{interpreter::Interpreter, loader::load_module};
+
+const OUTPUT_PATH: &str = "./output/bench";
+
+const VARIABLES: usize = 50;
+const ITERATIONS: usize = 10_000;
+const NESTING: usize = 8;
+
+const CHECK_RUNS: usize = 50;
+const INTERPRETER_RUNS: usize = 3;
+
+/// Generate a program with a loop of the given number of iterations, which touches all variables.
+fn program() -> String {
+    let mut program = String::new();
+
+    for index in 0..VARIABLES {
+        program.push_str(&format!("let mut v{index} := {index}\n"));
+    }
+
+    program.push_str("let mut i := 0\n");
+    program.push_str(&"{\n".repeat(NESTING));
+    program.push_str(&format!("while i < {ITERATIONS} {{\n"));
+    for index in 0..VARIABLES {
+        let previous = (index + VARIABLES - 1) % VARIABLES;
+        program.push_str(&format!("    v{index} = v{index} + v{previous} - i\n"));
+    }
+    program.push_str("    i = i + 1\n}\n");
+    program.push_str(&"}\n".repeat(NESTING));
+
+    program
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(OUTPUT_PATH)?;
+    let src_path = Path::new(OUTPUT_PATH).join("scopes.why");
+    fs::write(&src_path, program())?;
+
+    let main_module = load_module(fs::canonicalize(&src_path)?)?;
+    let cfg = HashMap::new();
+
+    let start = Instant::now();
+    for _ in 0..CHECK_RUNS {
+        main_module.type_check_program(&cfg)?;
+    }
+    let check_time = start.elapsed() / CHECK_RUNS as u32;
+
+    let (main_module, modules) = main_module.type_check_program(&cfg)?;
+
+    let start = Instant::now();
+    for _ in 0..INTERPRETER_RUNS {
+        Interpreter::new(&main_module, &modules).run()?;
+    }
+    let interpreter_time = start.elapsed() / INTERPRETER_RUNS as u32;
+
+    println!("{VARIABLES} variables, {ITERATIONS} iterations, {NESTING} nested blocks");
+    println!(
+        "type checking: {:>10.3} ms",
+        check_time.as_secs_f64() * 1000.0
+    );
+    println!(
+        "interpreting:  {:>10.3} ms",
+        interpreter_time.as_secs_f64() * 1000.0
+    );
+
+    Ok(())
+}
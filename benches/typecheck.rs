@@ -0,0 +1,135 @@
+//! Benchmark of the memory churn while loading and type checking a large program.
+//!
+//! The benchmarked program consists of many small functions (more than 10k lines in total). Every
+//! allocation is counted by a global allocator, which also tracks the peak of the allocated memory
+//! (relative to the start of the measured stage), so copies of the whole AST show up directly.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::HashMap,
+    error::Error,
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use y_lang::loader::load_module;
+
+const OUTPUT_PATH: &str = "./output/bench";
+
+const FUNCTIONS: usize = 2500;
+
+const RUNS: usize = 5;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocator counting all allocations (including reallocations) of the benchmark.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations and run time of a single stage.
+#[derive(Debug, Default, Clone, Copy)]
+struct Stats {
+    allocations: usize,
+    /// Peak of the allocated memory (in bytes) on top of the memory allocated before the stage.
+    peak: usize,
+    time: Duration,
+}
+
+/// Run the given stage and collect its statistics.
+fn measure<T>(stage: impl FnOnce() -> T) -> (T, Stats) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(allocated, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let result = stage();
+    let time = start.elapsed();
+
+    let stats = Stats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        peak: PEAK.load(Ordering::Relaxed) - allocated,
+        time,
+    };
+
+    (result, stats)
+}
+
+/// Generate a program of many functions, each of them calling its predecessor.
+fn program() -> String {
+    let mut program = String::from("let f0 := (a: int, b: int): int => {\n    a + b\n}\n");
+
+    for index in 1..FUNCTIONS {
+        let previous = index - 1;
+        program.push_str(&format!(
+            "\nlet f{index} := (a: int, b: int): int => {{\n    let c := a * {index} + b\n    c - f{previous}(b, a)\n}}\n"
+        ));
+    }
+
+    program.push_str(&format!("\nlet result := f{}(1, 2)\n", FUNCTIONS - 1));
+
+    program
+}
+
+fn print_stats(stage: &str, stats: &Stats) {
+    println!(
+        "{stage:<15}{:>10.3} ms{:>12} allocations{:>10.3} MB peak",
+        stats.time.as_secs_f64() * 1000.0,
+        stats.allocations,
+        stats.peak as f64 / (1024.0 * 1024.0)
+    );
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(OUTPUT_PATH)?;
+    let src_path = fs::canonicalize(OUTPUT_PATH)?.join("typecheck.why");
+    let program = program();
+    fs::write(&src_path, &program)?;
+
+    let cfg = HashMap::new();
+
+    // the fastest run of each stage is reported (allocations do not differ between runs)
+    let mut loading = Stats::default();
+    let mut checking = Stats::default();
+
+    for run in 0..RUNS {
+        let (main_module, load_stats) = measure(|| load_module(src_path.clone()));
+        let main_module = main_module?;
+
+        let (checked, check_stats) = measure(|| main_module.type_check_program(&cfg));
+        checked?;
+
+        if run == 0 || load_stats.time < loading.time {
+            loading = load_stats;
+        }
+        if run == 0 || check_stats.time < checking.time {
+            checking = check_stats;
+        }
+    }
+
+    println!("{FUNCTIONS} functions, {} lines", program.lines().count());
+    print_stats("loading:", &loading);
+    print_stats("type checking:", &checking);
+
+    Ok(())
+}
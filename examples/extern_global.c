@@ -0,0 +1 @@
+long answer = 41;
@@ -12,6 +12,33 @@ pub const EXIT_SYSCALL: InstructionOperand = InstructionOperand::Immediate(0x200
 #[cfg(target_os = "linux")]
 pub const EXIT_SYSCALL: InstructionOperand = InstructionOperand::Immediate(60);
 
+#[cfg(target_os = "macos")]
+pub const WRITE_SYSCALL: InstructionOperand = InstructionOperand::Immediate(0x2000004);
+
+#[cfg(target_os = "linux")]
+pub const WRITE_SYSCALL: InstructionOperand = InstructionOperand::Immediate(1);
+
+#[cfg(target_os = "macos")]
+pub const GETRLIMIT_SYSCALL: InstructionOperand = InstructionOperand::Immediate(0x20000c2);
+
+#[cfg(target_os = "linux")]
+pub const GETRLIMIT_SYSCALL: InstructionOperand = InstructionOperand::Immediate(97);
+
+/// Assemblers only accept ASCII symbols, so every other character (e.g., of a unicode identifier)
+/// is encoded as `_$u<code point>$`.
+pub fn ascii_symbols(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii() {
+                c.to_string()
+            } else {
+                format!("_$u{:x}$", c as u32)
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub enum InstructionOperand {
     Register(Reg),
@@ -43,11 +70,24 @@ pub enum Instruction {
     Imul(InstructionOperand, InstructionOperand),
     Idiv(InstructionOperand),
     Xor(InstructionOperand, InstructionOperand),
+    And(InstructionOperand, InstructionOperand),
+    Or(InstructionOperand, InstructionOperand),
+    /// Shift left by the number of bits in the second operand (an immediate or CL).
+    Shl(InstructionOperand, InstructionOperand),
+    /// Arithmetic shift right (i.e., keeping the sign) by the number of bits in the second
+    /// operand (an immediate or CL).
+    Sar(InstructionOperand, InstructionOperand),
+    Not(InstructionOperand),
+    Neg(InstructionOperand),
     Cmp(InstructionOperand, InstructionOperand),
     Sete(InstructionOperand),
     Setl(InstructionOperand),
     Setg(InstructionOperand),
     Je(String),
+    Jne(String),
+    Ja(String),
+    /// Jump, if the last operation did not overflow (as signed integers).
+    Jno(String),
     Jmp(String),
     Inc(Reg),
     Syscall,
@@ -60,6 +100,19 @@ pub enum Instruction {
     Literal(String),
 }
 
+impl Instruction {
+    /// Render this instruction for an assembly file, i.e., with all symbols encoded as ASCII.
+    /// Comments and raw assembly are rendered as they are.
+    pub fn render(&self) -> String {
+        match self {
+            Instruction::Comment(_) | Instruction::Raw(_) | Instruction::Literal(_) => {
+                self.to_string()
+            }
+            _ => ascii_symbols(&self.to_string()),
+        }
+    }
+}
+
 impl Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let value = match self {
@@ -73,11 +126,20 @@ impl Display for Instruction {
             Instruction::Idiv(source) => format!("\tidiv \t{source}"),
             Instruction::Sub(target, source) => format!("\tsub \t{target}, \t{source}"),
             Instruction::Xor(target, source) => format!("\txor \t{target}, \t{source}"),
+            Instruction::And(target, source) => format!("\tand \t{target}, \t{source}"),
+            Instruction::Or(target, source) => format!("\tor \t{target}, \t{source}"),
+            Instruction::Shl(target, source) => format!("\tshl \t{target}, \t{source}"),
+            Instruction::Sar(target, source) => format!("\tsar \t{target}, \t{source}"),
+            Instruction::Not(target) => format!("\tnot \t{target}"),
+            Instruction::Neg(target) => format!("\tneg \t{target}"),
             Instruction::Cmp(target, source) => format!("\tcmp \t{target}, \t{source}"),
             Instruction::Sete(target) => format!("\tsete \t{target}"),
             Instruction::Setl(target) => format!("\tsetl \t{target}"),
             Instruction::Setg(target) => format!("\tsetg \t{target}"),
             Instruction::Je(target) => format!("\tje {target}"),
+            Instruction::Jne(target) => format!("\tjne {target}"),
+            Instruction::Ja(target) => format!("\tja {target}"),
+            Instruction::Jno(target) => format!("\tjno {target}"),
             Instruction::Jmp(target) => format!("\tjmp {target}"),
             Instruction::Inc(target) => format!("\tinc {target}"),
             Instruction::Syscall => "\tsyscall".to_string(),
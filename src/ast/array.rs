@@ -1,11 +1,14 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
-use super::{Expression, Integer, Position, Rule};
+use super::{Expression, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Array<T> {
     pub initializer: Box<Expression<T>>,
-    pub size: Integer<()>,
+    /// The number of items, which has to be a constant expression.
+    pub size: Box<Expression<T>>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -21,11 +24,11 @@ impl Array<()> {
         let initializer = Expression::from_pair(initializer, file);
 
         let size = inner.next().unwrap();
-        let size = Integer::from_pair(size, file);
+        let size = Expression::from_pair(size, file);
 
         Array {
             initializer: Box::new(initializer),
-            size,
+            size: Box::new(size),
             position: (file.to_owned(), line, col),
             info: (),
         }
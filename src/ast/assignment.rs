@@ -1,11 +1,13 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Expression, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Assignment<T> {
     pub lhs: Expression<T>,
     pub value: Expression<T>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -1,12 +1,14 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{BinaryOp, Expression, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BinaryExpr<T> {
     pub op: BinaryOp,
     pub lhs: Box<Expression<T>>,
     pub rhs: Box<Expression<T>>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -16,18 +18,76 @@ impl BinaryExpr<()> {
         lhs: Expression<()>,
         op_pair: Pair<Rule>,
         rhs: Expression<()>,
-        file: &str,
     ) -> BinaryExpr<()> {
-        let (line, col) = op_pair.line_col();
-
         let op = BinaryOp::from(op_pair.as_rule());
 
+        // a binary expression starts where its leftmost operand starts (and not at the operator),
+        // so errors point at the beginning of the whole (sub-)expression
+        let position = lhs.position();
+
         BinaryExpr {
             lhs: Box::new(lhs),
             rhs: Box::new(rhs),
             op,
-            position: (file.to_owned(), line, col),
+            position,
             info: (),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, Expression, Statement, YParser};
+
+    use super::BinaryExpr;
+
+    fn parse_expression(src: &str) -> Expression<()> {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        match ast.nodes().first() {
+            Some(Statement::Expression(expression)) => expression.clone(),
+            node => panic!("expected expression, got {node:?}"),
+        }
+    }
+
+    fn unwrap_binary(expression: &Expression<()>) -> &BinaryExpr<()> {
+        match expression {
+            Expression::Binary(binary) => binary,
+            expression => panic!("expected binary expression, got {expression:?}"),
+        }
+    }
+
+    #[test]
+    fn test_position_of_nested_binary_expressions() {
+        let root = parse_expression("a + b * c");
+        let root = unwrap_binary(&root);
+        assert_eq!(root.position, ("test.why".to_owned(), 1, 1));
+
+        let rhs = unwrap_binary(&root.rhs);
+        assert_eq!(rhs.position, ("test.why".to_owned(), 1, 5));
+    }
+
+    #[test]
+    fn test_position_of_left_associative_binary_expressions() {
+        let root = parse_expression("a - b - c * d");
+        let root = unwrap_binary(&root);
+        assert_eq!(root.position, ("test.why".to_owned(), 1, 1));
+
+        let lhs = unwrap_binary(&root.lhs);
+        assert_eq!(lhs.position, ("test.why".to_owned(), 1, 1));
+
+        let rhs = unwrap_binary(&root.rhs);
+        assert_eq!(rhs.position, ("test.why".to_owned(), 1, 9));
+    }
+
+    #[test]
+    fn test_position_of_grouped_binary_expressions() {
+        let root = parse_expression("(a + b) * c");
+        let root = unwrap_binary(&root);
+        assert_eq!(root.position, ("test.why".to_owned(), 1, 2));
+
+        let lhs = unwrap_binary(&root.lhs);
+        assert_eq!(lhs.position, ("test.why".to_owned(), 1, 2));
+    }
+}
@@ -11,10 +11,15 @@ pub enum BinaryOp {
     Minus,
     Times,
     DividedBy,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
 #[derive(Debug)]
-pub struct UndefinedOpError(String);
+pub struct UndefinedOpError(pub String);
 
 impl FromStr for BinaryOp {
     type Err = UndefinedOpError;
@@ -28,6 +33,11 @@ impl FromStr for BinaryOp {
             "-" => Ok(BinaryOp::Minus),
             "*" => Ok(BinaryOp::Times),
             "/" => Ok(BinaryOp::DividedBy),
+            "&" => Ok(BinaryOp::BitAnd),
+            "|" => Ok(BinaryOp::BitOr),
+            "^" => Ok(BinaryOp::BitXor),
+            "<<" => Ok(BinaryOp::ShiftLeft),
+            ">>" => Ok(BinaryOp::ShiftRight),
             _ => Err(UndefinedOpError(format!("Unexpected binary op '{s}'"))),
         }
     }
@@ -43,6 +53,11 @@ impl Display for BinaryOp {
             BinaryOp::Minus => "-",
             BinaryOp::Times => "*",
             BinaryOp::DividedBy => "/",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::ShiftLeft => "<<",
+            BinaryOp::ShiftRight => ">>",
         })
     }
 }
@@ -57,6 +72,11 @@ impl From<Rule> for BinaryOp {
             Rule::minus => BinaryOp::Minus,
             Rule::times => BinaryOp::Times,
             Rule::dividedBy => BinaryOp::DividedBy,
+            Rule::bitAnd => BinaryOp::BitAnd,
+            Rule::bitOr => BinaryOp::BitOr,
+            Rule::bitXor => BinaryOp::BitXor,
+            Rule::shiftLeft => BinaryOp::ShiftLeft,
+            Rule::shiftRight => BinaryOp::ShiftRight,
             _ => unreachable!("Unexpected rule {:?}", rule),
         }
     }
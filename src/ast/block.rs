@@ -1,10 +1,12 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
-use super::{Position, Rule, Statement};
+use super::{Expression, Position, Rule, Statement};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Block<T> {
     pub block: Vec<Statement<T>>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -29,4 +31,13 @@ impl Block<()> {
             info: (),
         }
     }
+
+    /// Create a block which only consists of a single expression.
+    pub fn from_expression(expression: Expression<()>) -> Block<()> {
+        Block {
+            position: expression.position(),
+            block: vec![Statement::Expression(expression)],
+            info: (),
+        }
+    }
 }
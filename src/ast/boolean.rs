@@ -1,9 +1,11 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Boolean<T> {
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub value: bool,
     pub info: T,
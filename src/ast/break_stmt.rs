@@ -0,0 +1,41 @@
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+
+use super::{label_name, Expression, Position, Rule};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Break<T> {
+    /// The label of the block to exit (without the leading `'`). Without a label, the innermost
+    /// loop is exited.
+    pub label: Option<String>,
+    pub value: Option<Expression<T>>,
+    #[serde(with = "crate::ast_json::position")]
+    pub position: Position,
+    pub info: T,
+}
+
+impl Break<()> {
+    pub fn from_pair(pair: Pair<Rule>, file: &str) -> Break<()> {
+        assert_eq!(pair.as_rule(), Rule::breakStmt);
+
+        let (line, col) = pair.line_col();
+
+        let mut label = None;
+        let mut value = None;
+
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::label => label = Some(label_name(inner)),
+                Rule::expr => value = Some(Expression::from_pair(inner, file)),
+                _ => {}
+            }
+        }
+
+        Break {
+            label,
+            value,
+            position: (file.to_owned(), line, col),
+            info: (),
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Expression, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Call<T> {
     pub params: Vec<Expression<T>>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -1,10 +1,13 @@
+use serde::{Deserialize, Serialize};
+
 use super::{Expression, Position, Rule, Statement};
 use pest::iterators::Pair;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CompilerDirective<T> {
     pub directive: Expression<()>,
     pub statement: Option<Box<Statement<T>>>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
 }
 
@@ -1,11 +1,13 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Ident, Position, Rule, TypeAnnotation};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Declaration {
     pub ident: Ident<()>,
     pub type_annotation: TypeAnnotation,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
 }
 
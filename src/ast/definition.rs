@@ -1,11 +1,13 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Expression, Ident, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Definition<T> {
     pub ident: Ident<T>,
     pub value: Expression<T>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub is_mutable: bool,
     pub info: T,
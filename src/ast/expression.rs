@@ -1,15 +1,15 @@
 use once_cell::sync::Lazy;
-use pest::{
-    iterators::Pair,
-    pratt_parser::{Assoc, Op, PrattParser},
-};
+use pest::{iterators::Pair, pratt_parser::PrattParser};
+use serde::{Deserialize, Serialize};
 
 use super::{
-    Array, BinaryExpr, Block, Boolean, Character, FnDef, Ident, If, Integer, Position, PostfixExpr,
-    PrefixExpr, Rule, Str,
+    precedence::pratt_parser, Array, BinaryExpr, BinaryOp, Block, Boolean, Character, FnDef, Ident,
+    If, Integer, LabeledBlock, Loop, Position, PostfixExpr, PostfixOp, PrefixExpr, Rule, Str,
+    ATOM_PRECEDENCE, POSTFIX_PRECEDENCE, PREFIX_PRECEDENCE,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum Expression<T> {
     If(If<T>),
     Binary(BinaryExpr<T>),
@@ -23,19 +23,11 @@ pub enum Expression<T> {
     Block(Block<T>),
     Boolean(Boolean<T>),
     Array(Array<T>),
+    Loop(Loop<T>),
+    LabeledBlock(LabeledBlock<T>),
 }
 
-static PRATT_PARSER: Lazy<PrattParser<Rule>> = Lazy::new(|| {
-    PrattParser::new()
-        .op(Op::infix(Rule::lessThan, Assoc::Left)
-            | Op::infix(Rule::greaterThan, Assoc::Left)
-            | Op::infix(Rule::equal, Assoc::Left))
-        .op(Op::infix(Rule::plus, Assoc::Left) | Op::infix(Rule::minus, Assoc::Left))
-        .op(Op::infix(Rule::times, Assoc::Left) | Op::infix(Rule::dividedBy, Assoc::Left))
-        .op(Op::prefix(Rule::unaryMinus) | Op::prefix(Rule::not))
-        .op(Op::postfix(Rule::call))
-        .op(Op::postfix(Rule::indexing))
-});
+static PRATT_PARSER: Lazy<PrattParser<Rule>> = Lazy::new(pratt_parser);
 
 impl Expression<()> {
     pub fn from_pair(pair: Pair<Rule>, file: &str) -> Expression<()> {
@@ -47,21 +39,47 @@ impl Expression<()> {
                 }
                 Rule::character => Expression::Character(Character::from_pair(primary, file)),
                 Rule::ident => Expression::Ident(Ident::from_pair(primary, file)),
-                Rule::string => Expression::Str(Str::from_pair(primary, file)),
+                Rule::string | Rule::rawString => Expression::Str(Str::from_pair(primary, file)),
                 Rule::fnDef => Expression::FnDef(FnDef::from_pair(primary, file)),
-                Rule::ifStmt => Expression::If(If::from_pair(primary, file)),
+                Rule::ifStmt | Rule::compactIf => Expression::If(If::from_pair(primary, file)),
                 Rule::block => Expression::Block(Block::from_pair(primary, file)),
                 Rule::boolean => Expression::Boolean(Boolean::from_pair(primary, file)),
                 Rule::array => Expression::Array(Array::from_pair(primary, file)),
+                Rule::loopExpr => Expression::Loop(Loop::from_pair(primary, file)),
+                Rule::labeledBlock => {
+                    Expression::LabeledBlock(LabeledBlock::from_pair(primary, file))
+                }
                 rule => unreachable!("Unexpected rule {:?} while parsing primary", rule),
             })
             .map_prefix(|op, rhs| Expression::Prefix(PrefixExpr::from_op_rhs(op, rhs, file)))
             .map_postfix(|lhs, op| Expression::Postfix(PostfixExpr::from_lhs_op(lhs, op, file)))
-            .map_infix(|lhs, op, rhs| {
-                Expression::Binary(BinaryExpr::from_lhs_op_rhs(lhs, op, rhs, file))
-            })
+            .map_infix(|lhs, op, rhs| Expression::Binary(BinaryExpr::from_lhs_op_rhs(lhs, op, rhs)))
             .parse(pair.into_inner())
     }
+
+    /// Parse the condition of an if or while loop. An assignment (which is no valid condition)
+    /// is parsed as a comparison instead and the position of its `=` is returned, so it can be
+    /// reported without aborting.
+    pub fn from_condition_pair(pair: Pair<Rule>, file: &str) -> (Expression<()>, Option<Position>) {
+        if pair.as_rule() != Rule::conditionAssignment {
+            return (Expression::from_pair(pair, file), None);
+        }
+
+        let mut inner = pair.into_inner();
+        let lhs = Expression::from_pair(inner.next().unwrap(), file);
+        let (line, col) = inner.next().unwrap().line_col();
+        let rhs = Expression::from_pair(inner.next().unwrap(), file);
+
+        let comparison = Expression::Binary(BinaryExpr {
+            op: BinaryOp::Equal,
+            position: lhs.position(),
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            info: (),
+        });
+
+        (comparison, Some((file.to_owned(), line, col)))
+    }
 }
 
 impl<T> Expression<T>
@@ -81,7 +99,9 @@ where
             | Expression::FnDef(FnDef { position, .. })
             | Expression::Block(Block { position, .. })
             | Expression::Boolean(Boolean { position, .. })
-            | Expression::Array(Array { position, .. }) => position.to_owned(),
+            | Expression::Array(Array { position, .. })
+            | Expression::Loop(Loop { position, .. })
+            | Expression::LabeledBlock(LabeledBlock { position, .. }) => position.to_owned(),
         }
     }
 
@@ -98,7 +118,318 @@ where
             | Expression::FnDef(FnDef { info, .. })
             | Expression::Block(Block { info, .. })
             | Expression::Boolean(Boolean { info, .. })
-            | Expression::Array(Array { info, .. }) => info.clone(),
+            | Expression::Array(Array { info, .. })
+            | Expression::Loop(Loop { info, .. })
+            | Expression::LabeledBlock(LabeledBlock { info, .. }) => info.clone(),
+        }
+    }
+
+    /// Convert this expression back to (normalized) source code, e.g., for messages of failed
+    /// assertions. The contents of blocks and functions are omitted.
+    pub fn to_source(&self) -> String {
+        match self {
+            Expression::If(If {
+                condition,
+                else_block,
+                ..
+            }) => {
+                let else_block = if else_block.is_some() {
+                    " else { ... }"
+                } else {
+                    ""
+                };
+                format!("if {} {{ ... }}{else_block}", condition.to_source())
+            }
+            Expression::Binary(BinaryExpr { op, lhs, rhs, .. }) => {
+                // operators are left associative, so the right operand also needs parentheses
+                // for the same precedence
+                let lhs = lhs.to_source_within(op.precedence());
+                let rhs = rhs.to_source_within(op.precedence() + 1);
+                format!("{lhs} {op} {rhs}")
+            }
+            Expression::Prefix(PrefixExpr { op, rhs, .. }) => {
+                format!("{op}{}", rhs.to_source_within(PREFIX_PRECEDENCE))
+            }
+            Expression::Postfix(PostfixExpr { lhs, op, .. }) => {
+                let lhs = lhs.to_source_within(POSTFIX_PRECEDENCE);
+                match op {
+                    PostfixOp::Call(call) => {
+                        let params = call
+                            .params
+                            .iter()
+                            .map(Expression::to_source)
+                            .collect::<Vec<_>>();
+                        format!("{lhs}({})", params.join(", "))
+                    }
+                    PostfixOp::Indexing(indexing) => {
+                        format!("{lhs}[{}]", indexing.index.to_source())
+                    }
+                }
+            }
+            Expression::Integer(Integer { value, .. }) => value.to_string(),
+            Expression::Character(Character { value, .. }) => format!("{value:?}"),
+            Expression::Ident(Ident { value, .. }) => value.to_owned(),
+            Expression::Str(Str {
+                value,
+                raw: Some(hashes),
+                ..
+            }) => {
+                let hashes = "#".repeat(*hashes);
+                format!("r{hashes}\"{value}\"{hashes}")
+            }
+            Expression::Str(Str { value, .. }) => format!("{value:?}"),
+            Expression::FnDef(FnDef { params, .. }) => {
+                let params = params
+                    .iter()
+                    .map(|param| param.ident.value.to_owned())
+                    .collect::<Vec<_>>();
+                format!("({}) => {{ ... }}", params.join(", "))
+            }
+            Expression::Block(_) => "{ ... }".to_owned(),
+            Expression::Boolean(Boolean { value, .. }) => value.to_string(),
+            Expression::Array(Array {
+                initializer, size, ..
+            }) => format!("[{}; {}]", initializer.to_source(), size.to_source()),
+            Expression::Loop(_) => "loop { ... }".to_owned(),
+            Expression::LabeledBlock(LabeledBlock { label, .. }) => format!("'{label}: {{ ... }}"),
+        }
+    }
+
+    /// Convert this expression to source code like `to_source`, but wrap it in parentheses if it
+    /// would otherwise not be parsed as a whole by an operation of the given precedence.
+    pub(crate) fn to_source_within(&self, precedence: usize) -> String {
+        if self.precedence() < precedence {
+            format!("({})", self.to_source())
+        } else {
+            self.to_source()
+        }
+    }
+
+    /// The precedence of the (outermost) operation of this expression.
+    fn precedence(&self) -> usize {
+        match self {
+            Expression::Binary(BinaryExpr { op, .. }) => op.precedence(),
+            Expression::Prefix(_) => PREFIX_PRECEDENCE,
+            Expression::Postfix(_) => POSTFIX_PRECEDENCE,
+            _ => ATOM_PRECEDENCE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{
+        Ast, BinaryExpr, BinaryOp, Call, Expression, Ident, Indexing, Integer, PostfixExpr,
+        PostfixOp, PrefixExpr, PrefixOp, Statement, YParser,
+    };
+
+    fn to_source(src: &str) -> String {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        match ast.nodes().first() {
+            Some(Statement::Expression(expression)) => expression.to_source(),
+            node => panic!("expected expression, got {node:?}"),
+        }
+    }
+
+    #[test]
+    fn test_to_source() {
+        assert_eq!(to_source("a   +b*2"), "a + b * 2");
+        assert_eq!(to_source("(a + b) * 2"), "(a + b) * 2");
+        assert_eq!(to_source("a - (b - c)"), "a - (b - c)");
+        assert_eq!(
+            to_source("foo(x[1], \"a\\\"b\", 'c')"),
+            "foo(x[1], \"a\\\"b\", 'c')"
+        );
+        assert_eq!(
+            to_source("if x == 1 { 2 } else { 3 }"),
+            "if x == 1 { ... } else { ... }"
+        );
+        assert_eq!(to_source("'calc:{ break 'calc 1 }"), "'calc: { ... }");
+        assert_eq!(to_source("f('a', 'b')"), "f('a', 'b')");
+    }
+
+    #[test]
+    fn test_raw_string_round_trip() {
+        let sources = [
+            r#"r"C:\Users\why""#,
+            r##"r#"match "\d+" here"#"##,
+            r###"r##"a "# inside"##"###,
+        ];
+
+        for src in sources {
+            let formatted = to_source(src);
+            assert_eq!(formatted, src);
+            assert_eq!(to_source(&formatted), src);
+        }
+    }
+
+    #[test]
+    fn test_unterminated_raw_string() {
+        let error =
+            YParser::parse_program("test.why", "let a := 1\nlet b := r#\"abc\"\nlet c := 2")
+                .unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Unterminated raw string; expected closing `\"#`"
+        );
+        assert_eq!(error.position, ("test.why".to_owned(), 2, 10));
+    }
+
+    /// Small xorshift generator, so the generated expressions are the same for every run.
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+    }
+
+    fn position() -> (String, usize, usize) {
+        ("test.why".to_owned(), 0, 0)
+    }
+
+    fn random_expression(random: &mut Random, depth: usize) -> Expression<()> {
+        const BINARY_OPS: [BinaryOp; 12] = [
+            BinaryOp::GreaterThan,
+            BinaryOp::LessThan,
+            BinaryOp::Equal,
+            BinaryOp::Plus,
+            BinaryOp::Minus,
+            BinaryOp::Times,
+            BinaryOp::DividedBy,
+            BinaryOp::BitAnd,
+            BinaryOp::BitOr,
+            BinaryOp::BitXor,
+            BinaryOp::ShiftLeft,
+            BinaryOp::ShiftRight,
+        ];
+
+        let choice = if depth == 0 { 0 } else { random.below(10) };
+        match choice {
+            0 | 1 => Expression::Ident(Ident {
+                value: ["a", "b", "c", "d", "e"][random.below(5) as usize].to_owned(),
+                position: position(),
+                info: (),
+            }),
+            2 => Expression::Integer(Integer {
+                value: random.below(100) as i64,
+                position: position(),
+                info: (),
+            }),
+            3 => Expression::Prefix(PrefixExpr {
+                op: [PrefixOp::UnaryMinus, PrefixOp::Not, PrefixOp::BitNot]
+                    [random.below(3) as usize],
+                rhs: Box::new(random_expression(random, depth - 1)),
+                position: position(),
+                info: (),
+            }),
+            4 => {
+                let op = if random.below(2) == 0 {
+                    PostfixOp::Call(Call {
+                        params: vec![random_expression(random, depth - 1)],
+                        position: position(),
+                        info: (),
+                    })
+                } else {
+                    PostfixOp::Indexing(Indexing {
+                        index: Box::new(random_expression(random, depth - 1)),
+                        position: position(),
+                        info: (),
+                    })
+                };
+                Expression::Postfix(PostfixExpr {
+                    op,
+                    lhs: Box::new(random_expression(random, depth - 1)),
+                    position: position(),
+                    info: (),
+                })
+            }
+            _ => Expression::Binary(BinaryExpr {
+                op: BINARY_OPS[random.below(BINARY_OPS.len() as u64) as usize],
+                lhs: Box::new(random_expression(random, depth - 1)),
+                rhs: Box::new(random_expression(random, depth - 1)),
+                position: position(),
+                info: (),
+            }),
+        }
+    }
+
+    /// Fully parenthesized form of an expression, which only depends on its structure (and not
+    /// on positions).
+    fn structure(expression: &Expression<()>) -> String {
+        match expression {
+            Expression::Binary(BinaryExpr { op, lhs, rhs, .. }) => {
+                format!("({op} {} {})", structure(lhs), structure(rhs))
+            }
+            Expression::Prefix(PrefixExpr { op, rhs, .. }) => format!("({op} {})", structure(rhs)),
+            Expression::Postfix(PostfixExpr {
+                lhs,
+                op: PostfixOp::Call(call),
+                ..
+            }) => format!("(call {} {})", structure(lhs), structure(&call.params[0])),
+            Expression::Postfix(PostfixExpr {
+                lhs,
+                op: PostfixOp::Indexing(indexing),
+                ..
+            }) => format!("(index {} {})", structure(lhs), structure(&indexing.index)),
+            expression => expression.to_source(),
+        }
+    }
+
+    fn parse_expression(src: &str) -> Expression<()> {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        match ast.nodes().first() {
+            Some(Statement::Expression(expression)) => expression.clone(),
+            node => panic!("expected expression, got {node:?}"),
+        }
+    }
+
+    #[test]
+    fn test_to_source_parentheses() {
+        assert_eq!(to_source("a + b * c - d / e"), "a + b * c - d / e");
+        assert_eq!(to_source("((a + b) * c) - (d / e)"), "(a + b) * c - d / e");
+        assert_eq!(to_source("a - (b + c) - (d - e)"), "a - (b + c) - (d - e)");
+        assert_eq!(to_source("-(a + b) * !c"), "-(a + b) * !c");
+        assert_eq!(to_source("(-a)[1] + (a + b)(c)"), "(-a)[1] + (a + b)(c)");
+        assert_eq!(to_source("-a[1] < (b == c)"), "-a[1] < (b == c)");
+    }
+
+    #[test]
+    fn test_bitwise_precedence() {
+        assert_eq!(to_source("(1 << 2) + 1"), "(1 << 2) + 1");
+        assert_eq!(to_source("1 << (2 + 1)"), "1 << 2 + 1");
+        assert_eq!(to_source("(a & b) == c"), "(a & b) == c");
+        assert_eq!(to_source("a | (b ^ (c & d))"), "a | b ^ c & d");
+        assert_eq!(to_source("(a | b) & c"), "(a | b) & c");
+        assert_eq!(to_source("~(a >> 1)"), "~(a >> 1)");
+        assert_eq!(to_source("(a < b) >> c"), "(a < b) >> c");
+    }
+
+    #[test]
+    fn test_logical_operators_are_reserved() {
+        assert!(YParser::parse_program("test.why", "a && b").is_err());
+        assert!(YParser::parse_program("test.why", "a || b").is_err());
+    }
+
+    #[test]
+    fn test_to_source_round_trip() {
+        let mut random = Random(0x5eed);
+
+        for _ in 0..500 {
+            let expression = random_expression(&mut random, 5);
+
+            let formatted = expression.to_source();
+            let parsed = parse_expression(&formatted);
+            assert_eq!(structure(&parsed), structure(&expression), "{formatted}");
+            assert_eq!(parsed.to_source(), formatted);
         }
     }
 }
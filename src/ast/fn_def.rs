@@ -1,13 +1,16 @@
 use log::error;
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Block, Param, Position, Rule, TypeAnnotation};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct FnDef<T> {
     pub params: Vec<Param<T>>,
-    pub type_annotation: TypeAnnotation,
+    /// The annotated return type. If omitted, the return type is inferred from the block.
+    pub type_annotation: Option<TypeAnnotation>,
     pub block: Block<T>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -18,7 +21,7 @@ impl FnDef<()> {
 
         let (line, col) = pair.line_col();
 
-        let mut inner = pair.into_inner();
+        let mut inner = pair.into_inner().peekable();
 
         let Some(param_list) = inner.next() else {
             error!(
@@ -29,14 +32,9 @@ impl FnDef<()> {
         };
         let param_list = Self::from_param_list(param_list, file);
 
-        let Some(type_annotation) = inner.next() else {
-            error!(
-                "Expected return type annotation in function definition at {}:{}",
-                line, col
-            );
-            std::process::exit(-1);
-        };
-        let type_annotation = TypeAnnotation::from_pair(type_annotation, file);
+        let type_annotation = inner
+            .next_if(|pair| pair.as_rule() == Rule::typeAnnotation)
+            .map(|type_annotation| TypeAnnotation::from_pair(type_annotation, file));
 
         let Some(block) = inner.next() else {
             error!("Expected block in function definition at {}:{}", line, col);
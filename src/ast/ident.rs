@@ -1,10 +1,13 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
 
 use super::{Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Ident<T> {
     pub value: String,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -13,7 +16,8 @@ impl Ident<()> {
     pub fn from_pair(pair: Pair<Rule>, file: &str) -> Ident<()> {
         let (line, col) = pair.line_col();
         Ident {
-            value: pair.as_str().to_owned(),
+            // normalize identifiers, so visually identical names refer to the same variable
+            value: pair.as_str().nfc().collect(),
             position: (file.to_owned(), line, col),
             info: (),
         }
@@ -1,24 +1,34 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Block, Expression, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct If<T> {
     pub condition: Box<Expression<T>>,
     pub if_block: Block<T>,
     pub else_block: Option<Block<T>>,
+    /// Position of an assignment which was (wrongly) used as the condition.
+    #[serde(with = "crate::ast_json::optional_position")]
+    pub misplaced_assignment: Option<Position>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
 
 impl If<()> {
     pub fn from_pair(pair: Pair<Rule>, file: &str) -> If<()> {
+        if pair.as_rule() == Rule::compactIf {
+            return Self::from_compact_pair(pair, file);
+        }
+
         assert_eq!(pair.as_rule(), Rule::ifStmt);
 
         let (line, col) = pair.line_col();
 
         let mut inner = pair.into_inner();
-        let condition = Expression::from_pair(inner.next().unwrap(), file);
+        let (condition, misplaced_assignment) =
+            Expression::from_condition_pair(inner.next().unwrap(), file);
         let if_block = inner.next().unwrap();
         let else_block = inner.next().map(|block| Block::from_pair(block, file));
 
@@ -26,6 +36,35 @@ impl If<()> {
             condition: Box::new(condition),
             if_block: Block::from_pair(if_block, file),
             else_block,
+            misplaced_assignment,
+            position: (file.to_owned(), line, col),
+            info: (),
+        }
+    }
+
+    /// Parse a compact if (`if cond then a else b`). Both branches are wrapped in blocks, so it
+    /// can be handled exactly like a regular if-else.
+    fn from_compact_pair(pair: Pair<Rule>, file: &str) -> If<()> {
+        assert_eq!(pair.as_rule(), Rule::compactIf);
+
+        let (line, col) = pair.line_col();
+
+        let mut inner = pair
+            .into_inner()
+            .filter(|pair| matches!(pair.as_rule(), Rule::expr | Rule::conditionAssignment));
+
+        let (condition, misplaced_assignment) =
+            Expression::from_condition_pair(inner.next().unwrap(), file);
+
+        let mut inner = inner.map(|pair| Expression::from_pair(pair, file));
+        let if_branch = inner.next().unwrap();
+        let else_branch = inner.next().unwrap();
+
+        If {
+            condition: Box::new(condition),
+            if_block: Block::from_expression(if_branch),
+            else_block: Some(Block::from_expression(else_branch)),
+            misplaced_assignment,
             position: (file.to_owned(), line, col),
             info: (),
         }
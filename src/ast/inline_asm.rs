@@ -1,10 +1,12 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct InlineAssembly<T> {
     pub statements: Vec<String>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
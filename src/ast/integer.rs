@@ -1,10 +1,12 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Integer<T> {
     pub value: i64,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -13,6 +15,7 @@ impl Integer<()> {
     pub fn from_pair(pair: Pair<Rule>, file: &str) -> Integer<()> {
         let (line, col) = pair.line_col();
 
+        // literals which do not fit into an `int` are rejected by the parser
         match pair.as_rule() {
             Rule::decimalNumber => Integer {
                 value: pair.as_str().parse::<i64>().unwrap(),
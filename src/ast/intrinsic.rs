@@ -1,15 +1,19 @@
-use super::{Declaration, Rule, WhileLoop};
+use serde::{Deserialize, Serialize};
+
+use super::{Break, Declaration, Position, Rule, WhileLoop};
 
 use pest::iterators::Pair;
 
 use super::{Assignment, Definition};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum Intrinsic<T> {
     Declaration(Declaration),
     Definition(Definition<T>),
     Assignment(Assignment<T>),
     WhileLoop(WhileLoop<T>),
+    Break(Break<T>),
 }
 
 impl Intrinsic<()> {
@@ -19,6 +23,7 @@ impl Intrinsic<()> {
             Rule::definition => Intrinsic::Definition(Definition::from_pair(pair, file)),
             Rule::assignment => Intrinsic::Assignment(Assignment::from_pair(pair, file)),
             Rule::whileLoop => Intrinsic::WhileLoop(WhileLoop::from_pair(pair, file)),
+            Rule::breakStmt => Intrinsic::Break(Break::from_pair(pair, file)),
             _ => panic!("Unexpected intrinsic '{pair:#?}'"),
         }
     }
@@ -31,8 +36,20 @@ where
     pub fn info(&self) -> T {
         match self {
             Intrinsic::Definition(Definition { info, .. })
-            | Intrinsic::Assignment(Assignment { info, .. }) => info.clone(),
+            | Intrinsic::Assignment(Assignment { info, .. })
+            | Intrinsic::WhileLoop(WhileLoop { info, .. })
+            | Intrinsic::Break(Break { info, .. }) => info.clone(),
             _ => unimplemented!(),
         }
     }
+
+    pub fn position(&self) -> Position {
+        match self {
+            Intrinsic::Declaration(Declaration { position, .. })
+            | Intrinsic::Definition(Definition { position, .. })
+            | Intrinsic::Assignment(Assignment { position, .. })
+            | Intrinsic::WhileLoop(WhileLoop { position, .. })
+            | Intrinsic::Break(Break { position, .. }) => position.clone(),
+        }
+    }
 }
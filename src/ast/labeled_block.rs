@@ -0,0 +1,52 @@
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+
+use super::{Block, Position, Rule};
+
+/// A block with a label (e.g., `'calc: { ... }`), which can be exited early via `break 'calc`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LabeledBlock<T> {
+    /// The name of the label (without the leading `'`).
+    pub label: String,
+    pub block: Block<T>,
+    #[serde(with = "crate::ast_json::position")]
+    pub position: Position,
+    pub info: T,
+}
+
+impl LabeledBlock<()> {
+    pub fn from_pair(pair: Pair<Rule>, file: &str) -> LabeledBlock<()> {
+        assert_eq!(pair.as_rule(), Rule::labeledBlock);
+
+        let (line, col) = pair.line_col();
+
+        let mut inner = pair.into_inner();
+
+        let label = label_name(
+            inner
+                .next()
+                .unwrap_or_else(|| panic!("Expected label in labeled block at {line}:{col}")),
+        );
+
+        let block = Block::from_pair(
+            inner
+                .next()
+                .unwrap_or_else(|| panic!("Expected block in labeled block at {line}:{col}")),
+            file,
+        );
+
+        LabeledBlock {
+            label,
+            block,
+            position: (file.to_owned(), line, col),
+            info: (),
+        }
+    }
+}
+
+/// Get the name of a label (i.e., strip the leading `'`).
+pub fn label_name(pair: Pair<Rule>) -> String {
+    assert_eq!(pair.as_rule(), Rule::label);
+
+    pair.as_str().trim_start_matches('\'').to_owned()
+}
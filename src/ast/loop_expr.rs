@@ -0,0 +1,35 @@
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+
+use super::{Block, Position, Rule};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Loop<T> {
+    pub block: Block<T>,
+    #[serde(with = "crate::ast_json::position")]
+    pub position: Position,
+    pub info: T,
+}
+
+impl Loop<()> {
+    pub fn from_pair(pair: Pair<Rule>, file: &str) -> Loop<()> {
+        assert_eq!(pair.as_rule(), Rule::loopExpr);
+
+        let (line, col) = pair.line_col();
+
+        let mut inner = pair.into_inner();
+
+        let block = Block::from_pair(
+            inner
+                .next()
+                .unwrap_or_else(|| panic!("Expected block in loop at {line}:{col}")),
+            file,
+        );
+
+        Loop {
+            block,
+            position: (file.to_owned(), line, col),
+            info: (),
+        }
+    }
+}
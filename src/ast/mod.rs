@@ -7,6 +7,7 @@ mod binary_expr;
 mod binary_op;
 mod block;
 mod boolean;
+mod break_stmt;
 mod call;
 mod character;
 mod compiler_directive;
@@ -21,25 +22,32 @@ mod indexing;
 mod inline_asm;
 mod integer;
 mod intrinsic;
+mod labeled_block;
+mod loop_expr;
 mod param;
 mod parse_error;
 mod parser;
 mod postfix_expr;
 mod postfix_op;
+mod precedence;
 mod prefix_expr;
 mod prefix_op;
+mod recovery;
 mod statement;
 mod str;
 mod type_annotation;
 mod types;
 mod while_loop;
 
+use serde::{Deserialize, Serialize};
+
 pub use self::array::*;
 pub use self::assignment::*;
 pub use self::binary_expr::*;
 pub use self::binary_op::*;
 pub use self::block::*;
 pub use self::boolean::*;
+pub use self::break_stmt::*;
 pub use self::call::*;
 pub use self::character::*;
 pub use self::compiler_directive::*;
@@ -54,10 +62,14 @@ pub use self::indexing::*;
 pub use self::inline_asm::*;
 pub use self::integer::*;
 pub use self::intrinsic::*;
+pub use self::labeled_block::*;
+pub use self::loop_expr::*;
 pub use self::param::*;
+pub use self::parse_error::*;
 pub use self::parser::*;
 pub use self::postfix_expr::*;
 pub use self::postfix_op::*;
+pub use self::precedence::*;
 pub use self::prefix_expr::*;
 pub use self::prefix_op::*;
 pub use self::statement::*;
@@ -70,13 +82,12 @@ use pest::iterators::Pair;
 
 pub use self::parser::Rule;
 
-pub use self::parser::*;
-
 /// A position within a file (i.e., line and column)
 pub type Position = (String, usize, usize);
 
 /// AST, representing a single Y program.
-#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Ast<T> {
     /// Nodes within this AST.
     nodes: Vec<Statement<T>>,
@@ -89,7 +100,8 @@ impl Ast<()> {
         let mut ast = vec![];
 
         for statement in program {
-            if statement.as_rule() != Rule::EOI {
+            // the shebang line is only relevant for executing scripts
+            if !matches!(statement.as_rule(), Rule::EOI | Rule::shebang) {
                 ast.push(Statement::from_pair(statement, file));
             }
         }
@@ -105,7 +117,11 @@ where
         Self { nodes }
     }
 
-    pub fn nodes(&self) -> Vec<Statement<T>> {
-        self.nodes.clone()
+    pub fn nodes(&self) -> &[Statement<T>] {
+        &self.nodes
+    }
+
+    pub fn into_nodes(self) -> Vec<Statement<T>> {
+        self.nodes
     }
 }
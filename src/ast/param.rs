@@ -1,11 +1,15 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Ident, Position, Rule, TypeAnnotation};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Param<T> {
     pub ident: Ident<T>,
-    pub type_annotation: TypeAnnotation,
+    /// The annotated type, which may be omitted if the type is given by the context (e.g., if the
+    /// function is passed to another function).
+    pub type_annotation: Option<TypeAnnotation>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
 }
 
@@ -20,8 +24,9 @@ impl Param<()> {
         let ident = inner.next().unwrap();
         let ident = Ident::from_pair(ident, file);
 
-        let type_annotation = inner.next().unwrap();
-        let type_annotation = TypeAnnotation::from_pair(type_annotation, file);
+        let type_annotation = inner
+            .next()
+            .map(|type_annotation| TypeAnnotation::from_pair(type_annotation, file));
 
         Param {
             ident,
@@ -13,7 +13,7 @@ pub struct ParseError {
     pub position: Position,
     /// The "inner error" which caused this parse error. It is only used when trying to pretty
     /// print a ParseError
-    error: Error<Rule>,
+    error: Box<Error<Rule>>,
 }
 
 impl Display for ParseError {
@@ -29,16 +29,32 @@ where
     T: ToString,
 {
     fn from((value, file): (Error<Rule>, T)) -> Self {
-        match value.line_col {
-            pest::error::LineColLocation::Pos((line, col)) => ParseError {
-                message: value.variant.message().to_string(),
-                position: (file.to_string(), line, col),
-                error: value,
-            },
-            pest::error::LineColLocation::Span(_, _) => todo!(),
+        let (line, col) = match value.line_col {
+            pest::error::LineColLocation::Pos(position) => position,
+            pest::error::LineColLocation::Span(start, _) => start,
+        };
+
+        ParseError {
+            message: value.variant.message().to_string(),
+            position: (file.to_string(), line, col),
+            error: Box::new(value),
         }
     }
 }
 
+/// Multiple parse errors (in the order of their positions), e.g., found by recovering from
+/// parse errors.
+#[derive(Clone, Debug)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self.0.iter().map(ParseError::to_string).collect::<Vec<_>>();
+        f.write_str(&errors.join("\n"))
+    }
+}
+
+impl std::error::Error for ParseErrors {}
+
 /// The result of parsing a pair.
 pub type ParseResult<T> = Result<T, ParseError>;
@@ -1,4 +1,10 @@
-use pest::{iterators::Pairs, Parser};
+use pest::{
+    error::{Error, ErrorVariant, InputLocation},
+    iterators::Pairs,
+    unicode::XID_CONTINUE,
+    Parser,
+};
+use unescape::unescape;
 
 use super::parse_error::{ParseError, ParseResult};
 
@@ -7,7 +13,96 @@ use super::parse_error::{ParseError, ParseResult};
 pub struct YParser;
 
 impl YParser {
-    pub fn parse_program(file: impl ToString, program: &str) -> ParseResult<Pairs<Rule>> {
-        Self::parse(Rule::program, program).map_err(|error| ParseError::from((error, file)))
+    pub fn parse_program(file: impl ToString, program: &str) -> ParseResult<Pairs<'_, Rule>> {
+        let pairs = Self::parse(Rule::program, program).map_err(|error| {
+            ParseError::from((Self::invalid_character(error, program), file.to_string()))
+        })?;
+
+        match Self::unterminated_raw_string(&pairs).or_else(|| Self::invalid_literal(&pairs)) {
+            Some(error) => Err(ParseError::from((error, file))),
+            None => Ok(pairs),
+        }
+    }
+
+    /// Find the first literal which matches the grammar, but has no valid value (i.e., an integer
+    /// which does not fit into an `int` or a string with an invalid escape sequence). Building the
+    /// AST relies on all literals being valid.
+    fn invalid_literal(pairs: &Pairs<'_, Rule>) -> Option<Error<Rule>> {
+        pairs.clone().flatten().find_map(|pair| {
+            let text = pair.as_str();
+            let message = match pair.as_rule() {
+                Rule::decimalNumber if text.parse::<i64>().is_err() => {
+                    format!("Integer literal '{text}' is too large for type 'int'")
+                }
+                Rule::hexNumber if i64::from_str_radix(&text[2..], 16).is_err() => {
+                    format!("Integer literal '{text}' is too large for type 'int'")
+                }
+                Rule::string if unescape(&text[1..text.len() - 1]).is_none() => {
+                    format!("Invalid escape sequence in string {text}")
+                }
+                _ => return None,
+            };
+
+            Some(Error::new_from_span(
+                ErrorVariant::CustomError { message },
+                pair.as_span(),
+            ))
+        })
+    }
+
+    /// Raw strings without a closing delimiter extend to the end of the input (so they can be
+    /// reported as a whole instead of some unrelated token).
+    fn unterminated_raw_string(pairs: &Pairs<'_, Rule>) -> Option<Error<Rule>> {
+        let raw_string = pairs.clone().flatten().find(|pair| {
+            pair.as_rule() == Rule::rawString
+                && pair
+                    .clone()
+                    .into_inner()
+                    .any(|inner| inner.as_rule() == Rule::rawUnterminated)
+        })?;
+
+        let text = raw_string.as_str();
+        let opening = text.find('"').unwrap_or_default();
+        let delimiter = format!("\"{}", &text[1..opening]);
+        let start = raw_string.as_span().start();
+        let span = pest::Span::new(raw_string.get_input(), start, start + opening + 1)?;
+
+        Some(Error::new_from_span(
+            ErrorVariant::CustomError {
+                message: format!("Unterminated raw string; expected closing `{delimiter}`"),
+            },
+            span,
+        ))
+    }
+
+    /// Replace errors caused by a character, which is not valid anywhere outside of strings and
+    /// comments (e.g., an emoji), by an error naming this character.
+    fn invalid_character(error: Error<Rule>, program: &str) -> Error<Rule> {
+        let InputLocation::Pos(pos) = error.location else {
+            return error;
+        };
+
+        let Some(character) = program[pos..].chars().next() else {
+            return error;
+        };
+
+        let is_join_control = matches!(character, '\u{200C}' | '\u{200D}');
+        if character.is_ascii() || (XID_CONTINUE(character) && !is_join_control) {
+            return error;
+        }
+
+        let Some(position) = pest::Position::new(program, pos) else {
+            return error;
+        };
+
+        Error::new_from_pos(
+            ErrorVariant::CustomError {
+                message: format!(
+                    "Invalid character '{character}' (U+{:04X})",
+                    character as u32
+                ),
+            },
+            position,
+        )
     }
 }
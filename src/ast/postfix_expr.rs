@@ -1,11 +1,13 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Expression, Position, PostfixOp, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct PostfixExpr<T> {
     pub op: PostfixOp<T>,
     pub lhs: Box<Expression<T>>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
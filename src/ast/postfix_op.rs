@@ -1,8 +1,10 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Call, Indexing, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum PostfixOp<T> {
     Call(Call<T>),
     Indexing(Indexing<T>),
@@ -0,0 +1,70 @@
+use pest::pratt_parser::{Assoc, Op, PrattParser};
+
+use super::{BinaryOp, Rule};
+
+/// Binary operators grouped by their precedence (from the weakest to the strongest binding). All
+/// of them are left associative. Like in C, shifts bind stronger and bitwise operators weaker than
+/// comparisons.
+const BINARY_OPERATORS: [&[BinaryOp]; 7] = [
+    &[BinaryOp::BitOr],
+    &[BinaryOp::BitXor],
+    &[BinaryOp::BitAnd],
+    &[BinaryOp::LessThan, BinaryOp::GreaterThan, BinaryOp::Equal],
+    &[BinaryOp::ShiftLeft, BinaryOp::ShiftRight],
+    &[BinaryOp::Plus, BinaryOp::Minus],
+    &[BinaryOp::Times, BinaryOp::DividedBy],
+];
+
+/// Prefix operators (`-`, `!` and `~`) bind stronger than all binary operators.
+pub const PREFIX_PRECEDENCE: usize = BINARY_OPERATORS.len();
+
+/// Postfix operators (calls and indexing) bind the strongest.
+pub const POSTFIX_PRECEDENCE: usize = PREFIX_PRECEDENCE + 1;
+
+/// Precedence of expressions which are no operations at all (e.g., literals or identifiers).
+pub const ATOM_PRECEDENCE: usize = usize::MAX;
+
+impl BinaryOp {
+    /// The precedence of this operator (higher binds stronger).
+    pub fn precedence(self) -> usize {
+        BINARY_OPERATORS
+            .iter()
+            .position(|operators| operators.contains(&self))
+            .expect("every binary operator has a precedence")
+    }
+
+    fn rule(self) -> Rule {
+        match self {
+            BinaryOp::GreaterThan => Rule::greaterThan,
+            BinaryOp::LessThan => Rule::lessThan,
+            BinaryOp::Equal => Rule::equal,
+            BinaryOp::Plus => Rule::plus,
+            BinaryOp::Minus => Rule::minus,
+            BinaryOp::Times => Rule::times,
+            BinaryOp::DividedBy => Rule::dividedBy,
+            BinaryOp::BitAnd => Rule::bitAnd,
+            BinaryOp::BitOr => Rule::bitOr,
+            BinaryOp::BitXor => Rule::bitXor,
+            BinaryOp::ShiftLeft => Rule::shiftLeft,
+            BinaryOp::ShiftRight => Rule::shiftRight,
+        }
+    }
+}
+
+/// Create the parser for operations, which follows the precedences defined above.
+pub(super) fn pratt_parser() -> PrattParser<Rule> {
+    let parser = BINARY_OPERATORS
+        .iter()
+        .fold(PrattParser::new(), |parser, operators| {
+            let mut operators = operators
+                .iter()
+                .map(|operator| Op::infix(operator.rule(), Assoc::Left));
+            let first = operators.next().expect("precedence levels are not empty");
+            parser.op(operators.fold(first, |ops, op| ops | op))
+        });
+
+    parser
+        .op(Op::prefix(Rule::unaryMinus) | Op::prefix(Rule::not) | Op::prefix(Rule::bitNot))
+        .op(Op::postfix(Rule::call))
+        .op(Op::postfix(Rule::indexing))
+}
@@ -6,10 +6,11 @@ use super::Rule;
 pub enum PrefixOp {
     UnaryMinus,
     Not,
+    BitNot,
 }
 
 #[derive(Debug)]
-pub struct UndefinedPrefixOpError(String);
+pub struct UndefinedPrefixOpError(pub String);
 
 impl FromStr for PrefixOp {
     type Err = UndefinedPrefixOpError;
@@ -18,6 +19,7 @@ impl FromStr for PrefixOp {
         match s {
             "-" => Ok(PrefixOp::UnaryMinus),
             "!" => Ok(PrefixOp::Not),
+            "~" => Ok(PrefixOp::BitNot),
             _ => Err(UndefinedPrefixOpError(format!(
                 "Unexpected prefix op '{s}'"
             ))),
@@ -30,6 +32,7 @@ impl Display for PrefixOp {
         f.write_str(match self {
             PrefixOp::UnaryMinus => "-",
             PrefixOp::Not => "!",
+            PrefixOp::BitNot => "~",
         })
     }
 }
@@ -39,6 +42,7 @@ impl From<Rule> for PrefixOp {
         match rule {
             Rule::unaryMinus => PrefixOp::UnaryMinus,
             Rule::not => PrefixOp::Not,
+            Rule::bitNot => PrefixOp::BitNot,
             _ => unreachable!("Unexpected rule {:?}", rule),
         }
     }
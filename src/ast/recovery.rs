@@ -0,0 +1,124 @@
+use super::{Ast, ParseError, Rule, Statement, YParser};
+
+impl Ast<()> {
+    /// Parse a program, recovering from parse errors. If the program does not parse as a whole,
+    /// it is split into its top-level statements (which start at the beginning of a line) and
+    /// every statement is parsed on its own. Statements which fail to parse are left out of the
+    /// returned AST, all others are kept, so the rest of the program can still be analysed.
+    pub fn from_source_recovering(file: &str, source: &str) -> (Ast<()>, Vec<ParseError>) {
+        let error = match YParser::parse_program(file, source) {
+            Ok(pairs) => return (Ast::from_program(pairs.collect(), file), vec![]),
+            Err(error) => error,
+        };
+
+        let mut statements = vec![];
+        let mut errors = vec![];
+
+        for (line, start, end) in top_level_chunks(source) {
+            // pad the chunk, so positions within it match the positions within the whole program
+            let chunk = format!("{}{}", "\n".repeat(line), &source[start..end]);
+
+            match YParser::parse_program(file, &chunk) {
+                Ok(pairs) => statements.extend(
+                    pairs
+                        .filter(|pair| !matches!(pair.as_rule(), Rule::EOI | Rule::shebang))
+                        .map(|pair| Statement::from_pair(pair, file)),
+                ),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        // the program may be invalid as a whole, although each chunk is valid on its own (e.g., due
+        // to a string spanning multiple chunks)
+        if errors.is_empty() {
+            return (Ast::default(), vec![error]);
+        }
+
+        (Ast::from_nodes(statements), errors)
+    }
+}
+
+/// Split the given source into chunks of top-level statements. Every line starting with anything
+/// but whitespace or a closing bracket starts a new chunk, unless it follows a compiler directive.
+/// Returns the (0-based) first line and the byte range of each chunk.
+fn top_level_chunks(source: &str) -> Vec<(usize, usize, usize)> {
+    let mut chunks = vec![];
+    let mut start = (0, 0);
+    let mut previous = "";
+    let mut offset = 0;
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let starts_statement = line
+            .chars()
+            .next()
+            .is_some_and(|first| !first.is_whitespace() && !matches!(first, '}' | ')' | ']'));
+
+        if index > 0 && starts_statement && !previous.starts_with("#[") {
+            chunks.push((start.0, start.1, offset));
+            start = (index, offset);
+        }
+
+        if !line.trim().is_empty() {
+            previous = line;
+        }
+        offset += line.len();
+    }
+
+    chunks.push((start.0, start.1, offset));
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, Statement};
+
+    use super::top_level_chunks;
+
+    #[test]
+    fn test_top_level_chunks() {
+        let source =
+            "let a := 1\nlet f := (): int => {\n    1\n}\n\n#[os == \"linux\"]\nlet b := 2\r\nb";
+
+        let chunks = top_level_chunks(source)
+            .into_iter()
+            .map(|(line, start, end)| (line, &source[start..end]))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            chunks,
+            [
+                (0, "let a := 1\n"),
+                (1, "let f := (): int => {\n    1\n}\n\n"),
+                (5, "#[os == \"linux\"]\nlet b := 2\r\n"),
+                (7, "b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recover_from_invalid_statements() {
+        let source =
+            "let a := 1\nlet f := (x: int): int => {\n    x +\n}\nlet b := a + 2\nlet := 3\nb";
+
+        let (ast, errors) = Ast::from_source_recovering("test.why", source);
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.position.clone())
+                .collect::<Vec<_>>(),
+            [("test.why".to_owned(), 4, 1), ("test.why".to_owned(), 6, 5)]
+        );
+        assert_eq!(ast.nodes().len(), 3);
+        assert!(matches!(ast.nodes()[2], Statement::Expression(_)));
+        assert_eq!(ast.nodes()[2].position(), ("test.why".to_owned(), 7, 1));
+    }
+
+    #[test]
+    fn test_valid_program() {
+        let (ast, errors) = Ast::from_source_recovering("test.why", "let a := 1\na");
+
+        assert!(errors.is_empty());
+        assert_eq!(ast.nodes().len(), 2);
+    }
+}
@@ -1,8 +1,11 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
-use super::{CompilerDirective, Expression, Import, InlineAssembly, Intrinsic, Rule};
+use super::{CompilerDirective, Expression, Import, InlineAssembly, Intrinsic, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum Statement<T> {
     Import(Import),
     Expression(Expression<T>),
@@ -15,9 +18,11 @@ impl Statement<()> {
     pub fn from_pair(pair: Pair<Rule>, file: &str) -> Statement<()> {
         match pair.as_rule() {
             Rule::importDirective => Statement::Import(Import::from_pair(pair, file)),
-            Rule::declaration | Rule::definition | Rule::assignment | Rule::whileLoop => {
-                Statement::Intrinsic(Intrinsic::from_pair(pair, file))
-            }
+            Rule::declaration
+            | Rule::definition
+            | Rule::assignment
+            | Rule::whileLoop
+            | Rule::breakStmt => Statement::Intrinsic(Intrinsic::from_pair(pair, file)),
             Rule::expr => Statement::Expression(Expression::from_pair(pair, file)),
             Rule::compiler_directive => {
                 Statement::CompilerDirective(CompilerDirective::from_pair(pair, file))
@@ -41,4 +46,14 @@ where
             _ => unreachable!(),
         }
     }
+
+    pub fn position(&self) -> Position {
+        match self {
+            Statement::Import(Import { position, .. })
+            | Statement::CompilerDirective(CompilerDirective { position, .. })
+            | Statement::InlineAssembly(InlineAssembly { position, .. }) => position.clone(),
+            Statement::Expression(expression) => expression.position(),
+            Statement::Intrinsic(intrinsic) => intrinsic.position(),
+        }
+    }
 }
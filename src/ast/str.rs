@@ -1,22 +1,37 @@
+use serde::{Deserialize, Serialize};
+
 use super::{Position, Rule};
 use pest::iterators::Pair;
 use unescape::unescape;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Str<T> {
     pub value: String,
+    /// The number of hashes delimiting a raw string (`r#"..."#`), which is not escaped.
+    pub raw: Option<usize>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
 
 impl Str<()> {
     pub fn from_pair(pair: Pair<Rule>, file: &str) -> Str<()> {
-        assert_eq!(pair.as_rule(), Rule::string);
         let (line, col) = pair.line_col();
+        let inner = pair.clone().into_inner().next().unwrap().as_str();
+
+        let (value, raw) = match pair.as_rule() {
+            // invalid escape sequences are rejected by the parser
+            Rule::string => (unescape(inner).expect("Invalid character escaped"), None),
+            Rule::rawString => {
+                let hashes = pair.as_str()[1..].chars().take_while(|c| *c == '#').count();
+                (inner.to_owned(), Some(hashes))
+            }
+            rule => unreachable!("Unexpected rule {rule:?} for a string"),
+        };
 
         Str {
-            value: unescape(pair.clone().into_inner().next().unwrap().as_str())
-                .expect("Invalid character escaped"),
+            value,
+            raw,
             position: (file.to_string(), line, col),
             info: (),
         }
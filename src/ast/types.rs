@@ -1,8 +1,12 @@
+use std::fmt::Display;
+
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
-use super::{Integer, Rule};
+use super::{Expression, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum Type {
     Literal(String),
     Function {
@@ -12,7 +16,8 @@ pub enum Type {
     ArraySlice(Box<Type>),
     TupleArray {
         item_type: Box<Type>,
-        size: Integer<()>,
+        /// The number of items, which has to be a constant expression.
+        size: Box<Expression<()>>,
     },
     Reference(Box<Type>),
 }
@@ -55,11 +60,11 @@ impl Type {
                 let item_type = Type::from_pair(item_type);
 
                 let size = inner.next().unwrap();
-                let size = Integer::from_pair(size, "");
+                let size = Expression::from_pair(size, "");
 
                 Self::TupleArray {
                     item_type: Box::new(item_type),
-                    size,
+                    size: Box::new(size),
                 }
             }
             Rule::reference => {
@@ -74,3 +79,112 @@ impl Type {
         }
     }
 }
+
+impl Display for Type {
+    /// Format this type like it is written in the source code.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Literal(name) => f.write_str(name),
+            Type::Function {
+                params,
+                return_type,
+            } => {
+                let params = params
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "({params}) -> {return_type}")
+            }
+            Type::ArraySlice(item_type) => write!(f, "&[{item_type}]"),
+            Type::TupleArray { item_type, size } => {
+                write!(f, "[{item_type}; {}]", size.to_source())
+            }
+            Type::Reference(inner) => write!(f, "&{inner}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, Expression, Ident, Intrinsic, Statement, YParser};
+
+    use super::Type;
+
+    /// Parse the type of a declaration with the given type annotation.
+    fn parse_type(type_annotation: &str) -> Type {
+        let src = format!("declare value: {type_annotation}");
+        let pairs = YParser::parse_program("test.why", &src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        match ast.nodes().first() {
+            Some(Statement::Intrinsic(Intrinsic::Declaration(declaration))) => {
+                declaration.type_annotation.value.clone()
+            }
+            node => panic!("expected declaration, got {node:?}"),
+        }
+    }
+
+    /// Remove the positions of the sizes of arrays, which differ between differently formatted
+    /// sources. Sizes are compared by their (normalized) source code.
+    fn without_positions(type_: Type) -> Type {
+        match type_ {
+            Type::Literal(_) => type_,
+            Type::Function {
+                params,
+                return_type,
+            } => Type::Function {
+                params: params.into_iter().map(without_positions).collect(),
+                return_type: Box::new(without_positions(*return_type)),
+            },
+            Type::ArraySlice(item_type) => {
+                Type::ArraySlice(Box::new(without_positions(*item_type)))
+            }
+            Type::TupleArray { item_type, size } => Type::TupleArray {
+                item_type: Box::new(without_positions(*item_type)),
+                size: Box::new(Expression::Ident(Ident {
+                    value: size.to_source(),
+                    position: Default::default(),
+                    info: (),
+                })),
+            },
+            Type::Reference(inner) => Type::Reference(Box::new(without_positions(*inner))),
+        }
+    }
+
+    #[test]
+    fn test_type_round_trip() {
+        let types = [
+            ("int", "int"),
+            ("&int", "&int"),
+            ("&[ char ]", "&[char]"),
+            ("[int;3]", "[int; 3]"),
+            ("[[int; 2]; 0x10]", "[[int; 2]; 16]"),
+            ("()->void", "() -> void"),
+            ("((int)->int,int)->int", "((int) -> int, int) -> int"),
+            // a function returning a function
+            ("(int) -> (int) -> int", "(int) -> (int) -> int"),
+            (
+                "(int) -> ((int) -> int) -> void",
+                "(int) -> ((int) -> int) -> void",
+            ),
+            // an array of functions
+            ("[(str) -> void; 4]", "[(str) -> void; 4]"),
+            ("&[() -> &[int]]", "&[() -> &[int]]"),
+            // a slice of references
+            ("&[&int]", "&[&int]"),
+            ("&[[&char; 2]]", "&[[&char; 2]]"),
+            // sizes given by constants
+            ("[int; SIZE]", "[int; SIZE]"),
+            ("[[bool; 2*N]; (N+1)]", "[[bool; 2 * N]; N + 1]"),
+        ];
+
+        for (source, formatted) in types {
+            let parsed = parse_type(source);
+            assert_eq!(parsed.to_string(), formatted);
+
+            let reparsed = parse_type(formatted);
+            assert_eq!(without_positions(reparsed), without_positions(parsed));
+        }
+    }
+}
@@ -1,11 +1,16 @@
 use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
 
 use super::{Block, Expression, Position, Rule};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct WhileLoop<T> {
     pub condition: Expression<T>,
     pub block: Block<T>,
+    /// Position of an assignment which was (wrongly) used as the condition.
+    #[serde(with = "crate::ast_json::optional_position")]
+    pub misplaced_assignment: Option<Position>,
+    #[serde(with = "crate::ast_json::position")]
     pub position: Position,
     pub info: T,
 }
@@ -16,7 +21,7 @@ impl WhileLoop<()> {
 
         let mut inner = pair.into_inner();
 
-        let condition = Expression::from_pair(
+        let (condition, misplaced_assignment) = Expression::from_condition_pair(
             inner.next().unwrap_or_else(|| {
                 panic!("Expected expression in while loop header at {line}:{col}")
             }),
@@ -33,6 +38,7 @@ impl WhileLoop<()> {
         WhileLoop {
             condition,
             block,
+            misplaced_assignment,
             position: (file.to_owned(), line, col),
             info: (),
         }
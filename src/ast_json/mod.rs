@@ -0,0 +1,311 @@
+//! JSON representation of the AST.
+//!
+//! This module provides a stable, machine-readable dump of (typed and untyped) ASTs for external
+//! tooling. Every dump contains a `version` field, which is incremented on every incompatible
+//! change of the schema. Enums are adjacently tagged (`{ "kind": ..., "value": ... }`) and every
+//! node contains its position within the source file.
+use std::{error::Error, fmt::Display};
+
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    ast::{Ast, BinaryOp, Position, PrefixOp, UndefinedOpError, UndefinedPrefixOpError},
+    typechecker::TypeInfo,
+};
+
+/// The current version of the JSON schema.
+pub const VERSION: i64 = 4;
+
+/// Error which occurs when a JSON document can not be parsed or does not describe a valid AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub message: String,
+}
+
+impl JsonError {
+    pub fn new(message: impl ToString) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(error: serde_json::Error) -> Self {
+        JsonError::new(error)
+    }
+}
+
+pub type JsonResult<T> = Result<T, JsonError>;
+
+#[derive(Serialize)]
+struct Dump<'a, T> {
+    version: i64,
+    ast: &'a Ast<T>,
+}
+
+#[derive(Deserialize)]
+struct Document {
+    version: i64,
+    ast: serde_json::Value,
+}
+
+/// Serialize an AST as (pretty printed) JSON.
+pub fn to_json<T: Serialize>(ast: &Ast<T>) -> String {
+    to_json_string(&Dump {
+        version: VERSION,
+        ast,
+    })
+}
+
+/// Deserialize an untyped AST from its JSON representation.
+pub fn from_json(input: &str) -> JsonResult<Ast<()>> {
+    let Document { version, ast } = serde_json::from_str(input)?;
+
+    if version != VERSION {
+        return Err(JsonError::new(format!(
+            "Unsupported version {version} (expected {VERSION})"
+        )));
+    }
+
+    Ok(Ast::deserialize(ast)?)
+}
+
+/// Serialize a value as (pretty printed) JSON. All documents of this crate consist of plain
+/// structs, sequences and strings, which can always be serialized.
+pub(crate) fn to_json_string(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).expect("JSON documents can always be serialized")
+}
+
+/// A position as an object (instead of a tuple),
+/// i.e., `{ "file": ..., "line": ..., "column": ... }`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct JsonPosition<F> {
+    file: F,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> From<&'a Position> for JsonPosition<&'a str> {
+    fn from((file, line, column): &'a Position) -> Self {
+        JsonPosition {
+            file,
+            line: *line,
+            column: *column,
+        }
+    }
+}
+
+impl From<JsonPosition<String>> for Position {
+    fn from(JsonPosition { file, line, column }: JsonPosition<String>) -> Self {
+        (file, line, column)
+    }
+}
+
+/// (De-)Serialization of positions via `#[serde(with = "crate::ast_json::position")]`.
+pub(crate) mod position {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::ast::Position;
+
+    use super::JsonPosition;
+
+    pub fn serialize<S: Serializer>(position: &Position, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonPosition::from(position).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Position, D::Error> {
+        JsonPosition::<String>::deserialize(deserializer).map(Position::from)
+    }
+}
+
+/// (De-)Serialization of optional positions via
+/// `#[serde(with = "crate::ast_json::optional_position")]`.
+pub(crate) mod optional_position {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::ast::Position;
+
+    use super::JsonPosition;
+
+    pub fn serialize<S: Serializer>(
+        position: &Option<Position>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        position
+            .as_ref()
+            .map(JsonPosition::from)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Position>, D::Error> {
+        Option::<JsonPosition<String>>::deserialize(deserializer)
+            .map(|position| position.map(Position::from))
+    }
+}
+
+/// Serialize a value via its `Display` implementation.
+pub(crate) fn serialize_display<S: Serializer>(
+    value: &impl Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Serialize an optional value via its `Display` implementation.
+pub(crate) fn serialize_optional_display<S: Serializer>(
+    value: &Option<impl Display>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value
+        .as_ref()
+        .map(ToString::to_string)
+        .serialize(serializer)
+}
+
+/// Only the type of a typed node is part of the dump.
+impl Serialize for TypeInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut info = serializer.serialize_struct("TypeInfo", 1)?;
+        info.serialize_field("type", &self._type.to_string())?;
+        info.end()
+    }
+}
+
+/// Operators are written as in the source code.
+impl Serialize for BinaryOp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BinaryOp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|UndefinedOpError(message)| de::Error::custom(message))
+    }
+}
+
+impl Serialize for PrefixOp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PrefixOp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|UndefinedPrefixOpError(message)| de::Error::custom(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pest::Parser;
+
+    use crate::ast::{Ast, Rule, YParser};
+
+    use super::{from_json, to_json};
+
+    fn parse(program: &str) -> Ast<()> {
+        let pairs = YParser::parse(Rule::program, program).unwrap();
+        Ast::from_program(pairs.collect(), "test.why")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let ast = parse(
+            r#"import @std::io::*
+
+declare answer: int
+
+#[os == "linux"]
+let add := (a: int, b: &[int]): int => {
+    let mut sum := a + b[0]
+    sum = sum * 2
+    sum
+}
+
+let values := [0; 10]
+let c := if values[1] < 3 then 'x' else '"'
+
+while true {
+    loop {
+        break add(1, values)
+    }
+}
+
+print("Hello,\n \"World\"! 🦀")
+"#,
+        );
+
+        assert_eq!(from_json(&to_json(&ast)), Ok(ast));
+    }
+
+    #[test]
+    fn test_dump() {
+        let ast = parse("let a := 42");
+
+        assert_eq!(
+            to_json(&ast),
+            r#"{
+  "version": 4,
+  "ast": [
+    {
+      "kind": "Intrinsic",
+      "value": {
+        "kind": "Definition",
+        "value": {
+          "ident": {
+            "value": "a",
+            "position": {
+              "file": "test.why",
+              "line": 1,
+              "column": 5
+            },
+            "info": null
+          },
+          "value": {
+            "kind": "Integer",
+            "value": {
+              "value": 42,
+              "position": {
+                "file": "test.why",
+                "line": 1,
+                "column": 10
+              },
+              "info": null
+            }
+          },
+          "position": {
+            "file": "test.why",
+            "line": 1,
+            "column": 1
+          },
+          "is_mutable": false,
+          "info": null
+        }
+      }
+    }
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn test_reject_unknown_version() {
+        assert!(from_json(r#"{ "version": 0, "ast": [] }"#).is_err());
+    }
+}
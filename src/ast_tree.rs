@@ -0,0 +1,650 @@
+//! Compact rendering of parse trees (e.g., for `--dump-parsed` and `--dump-lexed`).
+//!
+//! Every node is printed on a line of its own, indented by its depth, with its kind and its salient
+//! payload (names, literal values, operators). Positions are only printed in the verbose style,
+//! which keeps the default output stable across unrelated edits of the source. The expressions of
+//! typed trees are followed by their type.
+use std::fmt::Write;
+
+use pest::iterators::Pairs;
+
+use crate::{
+    ast::{
+        Array, Assignment, Ast, BinaryExpr, Block, Boolean, Break, Call, Character,
+        CompilerDirective, Declaration, Definition, Expression, FnDef, Ident, If, Import, Indexing,
+        InlineAssembly, Integer, Intrinsic, LabeledBlock, Loop, Param, Position, PostfixExpr,
+        PostfixOp, PrefixExpr, Rule, Statement, Str, WhileLoop,
+    },
+    typechecker::{TypeInfo, VariableType},
+};
+
+/// Whether positions are part of the rendered tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TreeStyle {
+    #[default]
+    Compact,
+    Verbose,
+}
+
+/// Printer collecting the lines of a rendered tree.
+pub struct TreePrinter {
+    output: String,
+    depth: usize,
+    style: TreeStyle,
+}
+
+impl TreePrinter {
+    pub fn new(style: TreeStyle) -> Self {
+        Self {
+            output: String::new(),
+            depth: 0,
+            style,
+        }
+    }
+
+    /// Print a node with the given kind and payload (which may be empty), followed by its children
+    /// (which are printed by the given closure).
+    pub fn node(
+        &mut self,
+        kind: &str,
+        payload: &str,
+        position: &Position,
+        children: impl FnOnce(&mut TreePrinter),
+    ) {
+        self.annotated_node(kind, payload, None, position, children)
+    }
+
+    /// Print a node like `node`, followed by the given annotation (i.e., the type of the node).
+    pub fn annotated_node(
+        &mut self,
+        kind: &str,
+        payload: &str,
+        annotation: Option<String>,
+        position: &Position,
+        children: impl FnOnce(&mut TreePrinter),
+    ) {
+        self.output.push_str(&"  ".repeat(self.depth));
+        self.output.push_str(kind);
+
+        if !payload.is_empty() {
+            self.output.push(' ');
+            self.output.push_str(payload);
+        }
+
+        if let Some(annotation) = annotation {
+            let _ = write!(self.output, ": {annotation}");
+        }
+
+        if self.style == TreeStyle::Verbose {
+            let (file, line, col) = position;
+            let _ = write!(self.output, " ({file}:{line}:{col})");
+        }
+
+        self.output.push('\n');
+
+        self.depth += 1;
+        children(self);
+        self.depth -= 1;
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+/// Rendering of a node (and all of its children) as a tree.
+pub trait ToTree {
+    fn to_tree(&self, printer: &mut TreePrinter);
+}
+
+/// Information attached to the nodes of an AST, which is printed along with them.
+pub trait NodeInfo {
+    /// The type of the node, if it is known (i.e., after type checking).
+    fn node_type(&self) -> Option<&VariableType>;
+
+    fn annotation(&self) -> Option<String> {
+        self.node_type().map(type_annotation)
+    }
+}
+
+impl NodeInfo for () {
+    fn node_type(&self) -> Option<&VariableType> {
+        None
+    }
+}
+
+impl NodeInfo for TypeInfo {
+    fn node_type(&self) -> Option<&VariableType> {
+        Some(&self._type)
+    }
+}
+
+/// Print a type the way it is written in the source.
+pub(crate) fn type_annotation(variable_type: &VariableType) -> String {
+    match variable_type {
+        VariableType::Func {
+            params,
+            return_type,
+            ..
+        } => {
+            let params = params
+                .iter()
+                .map(type_annotation)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({params}) -> {}", type_annotation(return_type))
+        }
+        variable_type => variable_type.to_string(),
+    }
+}
+
+/// Render the given AST, one statement (and its children) after another.
+pub fn render<T: Clone + NodeInfo>(ast: &Ast<T>, style: TreeStyle) -> String {
+    let mut printer = TreePrinter::new(style);
+    for statement in ast.nodes() {
+        statement.to_tree(&mut printer);
+    }
+    printer.finish()
+}
+
+/// Render the pairs of the grammar (e.g., of a whole program), i.e., their rules and, for pairs
+/// without inner pairs, the matched source.
+pub fn render_pairs(pairs: Pairs<Rule>, file: &str, style: TreeStyle) -> String {
+    let mut printer = TreePrinter::new(style);
+    pairs_to_tree(pairs, file, &mut printer);
+    printer.finish()
+}
+
+fn pairs_to_tree(pairs: Pairs<Rule>, file: &str, printer: &mut TreePrinter) {
+    for pair in pairs {
+        let (line, col) = pair.line_col();
+        let mut inner = pair.clone().into_inner().peekable();
+        let payload = if inner.peek().is_none() {
+            format!("{:?}", pair.as_str())
+        } else {
+            String::new()
+        };
+
+        printer.node(
+            &format!("{:?}", pair.as_rule()),
+            &payload,
+            &(file.to_owned(), line, col),
+            |printer| pairs_to_tree(pair.into_inner(), file, printer),
+        );
+    }
+}
+
+impl<T: ToTree> ToTree for Box<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        self.as_ref().to_tree(printer)
+    }
+}
+
+impl<T: ToTree> ToTree for Option<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        if let Some(node) = self {
+            node.to_tree(printer);
+        }
+    }
+}
+
+impl<T: ToTree> ToTree for [T] {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        for node in self {
+            node.to_tree(printer);
+        }
+    }
+}
+
+impl<T: NodeInfo> ToTree for Statement<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        match self {
+            Statement::Import(import) => import.to_tree(printer),
+            Statement::Expression(expression) => expression.to_tree(printer),
+            Statement::Intrinsic(intrinsic) => intrinsic.to_tree(printer),
+            Statement::CompilerDirective(directive) => directive.to_tree(printer),
+            Statement::InlineAssembly(asm) => asm.to_tree(printer),
+        }
+    }
+}
+
+impl<T: NodeInfo> ToTree for Intrinsic<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        match self {
+            Intrinsic::Declaration(declaration) => declaration.to_tree(printer),
+            Intrinsic::Definition(definition) => definition.to_tree(printer),
+            Intrinsic::Assignment(assignment) => assignment.to_tree(printer),
+            Intrinsic::WhileLoop(while_loop) => while_loop.to_tree(printer),
+            Intrinsic::Break(break_stmt) => break_stmt.to_tree(printer),
+        }
+    }
+}
+
+impl<T: NodeInfo> ToTree for Expression<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        match self {
+            Expression::If(if_expr) => if_expr.to_tree(printer),
+            Expression::Binary(binary) => binary.to_tree(printer),
+            Expression::Prefix(prefix) => prefix.to_tree(printer),
+            Expression::Postfix(postfix) => postfix.to_tree(printer),
+            Expression::Integer(integer) => integer.to_tree(printer),
+            Expression::Character(character) => character.to_tree(printer),
+            Expression::Ident(ident) => ident.to_tree(printer),
+            Expression::Str(str) => str.to_tree(printer),
+            Expression::FnDef(fn_def) => fn_def.to_tree(printer),
+            Expression::Block(block) => block.to_tree(printer),
+            Expression::Boolean(boolean) => boolean.to_tree(printer),
+            Expression::Array(array) => array.to_tree(printer),
+            Expression::Loop(loop_expr) => loop_expr.to_tree(printer),
+            Expression::LabeledBlock(labeled_block) => labeled_block.to_tree(printer),
+        }
+    }
+}
+
+impl ToTree for Import {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.node("Import", &self.path, &self.position, |_| {})
+    }
+}
+
+impl ToTree for Declaration {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.node(
+            "Declaration",
+            &format!("{}: {}", self.ident.value, self.type_annotation.value),
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for CompilerDirective<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.node("CompilerDirective", "", &self.position, |printer| {
+            self.directive.to_tree(printer);
+            self.statement.to_tree(printer);
+        })
+    }
+}
+
+impl<T: NodeInfo> ToTree for InlineAssembly<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.node("InlineAssembly", "", &self.position, |printer| {
+            for statement in &self.statements {
+                printer.node("Instruction", statement.trim(), &self.position, |_| {});
+            }
+        })
+    }
+}
+
+impl<T: NodeInfo> ToTree for Definition<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        let payload = if self.is_mutable {
+            format!("mut {}", self.ident.value)
+        } else {
+            self.ident.value.to_owned()
+        };
+
+        // the type of a function is already given by its parameters and its return type
+        let annotation = match self.value {
+            Expression::FnDef(_) => None,
+            _ => self.ident.info.annotation(),
+        };
+
+        printer.annotated_node(
+            "Definition",
+            &payload,
+            annotation,
+            &self.position,
+            |printer| self.value.to_tree(printer),
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Assignment<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.node("Assignment", "", &self.position, |printer| {
+            self.lhs.to_tree(printer);
+            self.value.to_tree(printer);
+        })
+    }
+}
+
+impl<T: NodeInfo> ToTree for WhileLoop<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.node("WhileLoop", "", &self.position, |printer| {
+            self.condition.to_tree(printer);
+            self.block.to_tree(printer);
+        })
+    }
+}
+
+impl<T: NodeInfo> ToTree for Break<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        let payload = self
+            .label
+            .as_ref()
+            .map(|label| format!("'{label}"))
+            .unwrap_or_default();
+
+        printer.node("Break", &payload, &self.position, |printer| {
+            self.value.to_tree(printer)
+        })
+    }
+}
+
+impl<T: NodeInfo> ToTree for If<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "If",
+            "",
+            self.info.annotation(),
+            &self.position,
+            |printer| {
+                self.condition.to_tree(printer);
+                self.if_block.to_tree(printer);
+                self.else_block.to_tree(printer);
+            },
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for BinaryExpr<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Binary",
+            &self.op.to_string(),
+            self.info.annotation(),
+            &self.position,
+            |printer| {
+                self.lhs.to_tree(printer);
+                self.rhs.to_tree(printer);
+            },
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for PrefixExpr<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Prefix",
+            &self.op.to_string(),
+            self.info.annotation(),
+            &self.position,
+            |printer| self.rhs.to_tree(printer),
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for PostfixExpr<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        match &self.op {
+            PostfixOp::Call(Call { params, .. }) => printer.annotated_node(
+                "Call",
+                "",
+                self.info.annotation(),
+                &self.position,
+                |printer| {
+                    self.lhs.to_tree(printer);
+                    params.to_tree(printer);
+                },
+            ),
+            PostfixOp::Indexing(Indexing { index, .. }) => printer.annotated_node(
+                "Indexing",
+                "",
+                self.info.annotation(),
+                &self.position,
+                |printer| {
+                    self.lhs.to_tree(printer);
+                    index.to_tree(printer);
+                },
+            ),
+        }
+    }
+}
+
+impl<T: NodeInfo> ToTree for Integer<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Integer",
+            &self.value.to_string(),
+            self.info.annotation(),
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Character<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Character",
+            &format!("{:?}", self.value),
+            self.info.annotation(),
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Ident<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Ident",
+            &self.value,
+            self.info.annotation(),
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Str<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Str",
+            &format!("{:?}", self.value),
+            self.info.annotation(),
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Boolean<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Boolean",
+            &self.value.to_string(),
+            self.info.annotation(),
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for FnDef<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        let payload = match self.info.node_type() {
+            Some(VariableType::Func { return_type, .. }) => {
+                Some(format!("-> {}", type_annotation(return_type)))
+            }
+            _ => self
+                .type_annotation
+                .as_ref()
+                .map(|type_annotation| format!("-> {}", type_annotation.value)),
+        }
+        .unwrap_or_default();
+
+        printer.node("FnDef", &payload, &self.position, |printer| {
+            self.params.to_tree(printer);
+            self.block.to_tree(printer);
+        })
+    }
+}
+
+impl<T: NodeInfo> ToTree for Param<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        // parameters without annotation are annotated with their inferred type (if known)
+        let annotation = self.ident.info.annotation().or_else(|| {
+            self.type_annotation
+                .as_ref()
+                .map(|type_annotation| type_annotation.value.to_string())
+        });
+
+        printer.annotated_node(
+            "Param",
+            &self.ident.value,
+            annotation,
+            &self.position,
+            |_| {},
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Block<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Block",
+            "",
+            self.info.annotation(),
+            &self.position,
+            |printer| self.block.to_tree(printer),
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Array<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Array",
+            "",
+            self.info.annotation(),
+            &self.position,
+            |printer| {
+                self.initializer.to_tree(printer);
+                self.size.to_tree(printer);
+            },
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for Loop<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "Loop",
+            "",
+            self.info.annotation(),
+            &self.position,
+            |printer| self.block.to_tree(printer),
+        )
+    }
+}
+
+impl<T: NodeInfo> ToTree for LabeledBlock<T> {
+    fn to_tree(&self, printer: &mut TreePrinter) {
+        printer.annotated_node(
+            "LabeledBlock",
+            &format!("'{}", self.label),
+            self.info.annotation(),
+            &self.position,
+            |printer| self.block.to_tree(printer),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, YParser};
+
+    use super::{render, render_pairs, TreeStyle};
+
+    const PROGRAM: &str = r#"import @std::io::*
+
+declare answer: int
+
+let add := (a: int, b): int => {
+    let mut sum := a + b
+    sum = -sum * 2
+    sum
+}
+
+while answer < 3 {
+    print("hi\n")
+}
+let c := if add(1, 2)[0] == 3 { 'x' } else { '"' }
+"#;
+
+    fn parse(program: &str) -> Ast<()> {
+        let pairs = YParser::parse_program("test.why", program).unwrap();
+        Ast::from_program(pairs.collect(), "test.why")
+    }
+
+    #[test]
+    fn test_render_compact() {
+        assert_eq!(
+            render(&parse(PROGRAM), TreeStyle::Compact),
+            r#"Import @std::io::*
+Declaration answer: int
+Definition add
+  FnDef -> int
+    Param a: int
+    Param b
+    Block
+      Definition mut sum
+        Binary +
+          Ident a
+          Ident b
+      Assignment
+        Ident sum
+        Binary *
+          Prefix -
+            Ident sum
+          Integer 2
+      Ident sum
+WhileLoop
+  Binary <
+    Ident answer
+    Integer 3
+  Block
+    Call
+      Ident print
+      Str "hi\n"
+Definition c
+  If
+    Binary ==
+      Indexing
+        Call
+          Ident add
+          Integer 1
+          Integer 2
+        Integer 0
+      Integer 3
+    Block
+      Character 'x'
+    Block
+      Character '"'
+"#
+        );
+    }
+
+    #[test]
+    fn test_render_verbose() {
+        let tree = render(&parse("let a := 42"), TreeStyle::Verbose);
+
+        assert_eq!(
+            tree,
+            "Definition a (test.why:1:1)\n  Integer 42 (test.why:1:10)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_pairs() {
+        let pairs = YParser::parse_program("test.why", "let a := 42").unwrap();
+
+        assert_eq!(
+            render_pairs(pairs, "test.why", TreeStyle::Compact),
+            "definition\n  localIdent \"a\"\n  expr\n    decimalNumber \"42\"\nEOI \"\"\n"
+        );
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! This module contains everything needed for parsing the CLI arguments for Why.
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use y_lang::diagnostics::DEFAULT_ERROR_LIMIT;
 
 /// Struct containing the CLI configuration for Why.
 #[derive(Parser, Debug)]
@@ -13,13 +16,64 @@ pub struct Cli {
     pub command: Commands,
 
     /// Specify the log level of the compiler.
-    #[arg(value_enum, short, long, default_value_t = LogLevel::default())]
+    #[arg(value_enum, long, default_value_t = LogLevel::default())]
     pub verbosity: LogLevel,
+
+    /// Log what the compiler does internally to stderr (`-v` for its stages, `-vv` also for each
+    /// item and function, `-vvv` for everything, with timestamps).
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Specify when to color the output (logs and diagnostics) of the compiler.
+    #[arg(value_enum, long, global = true, default_value_t = ColorMode::default())]
+    pub color: ColorMode,
+
+    /// Specify how many errors to report at most, if there are multiple errors (0 reports all of
+    /// them).
+    #[arg(long, global = true, value_name = "N", default_value_t = DEFAULT_ERROR_LIMIT)]
+    pub error_limit: usize,
 }
 
 impl Cli {
     pub fn init() -> Self {
-        Cli::parse()
+        Cli::parse_from(Self::deprecated_log_level(std::env::args_os().collect()))
+    }
+
+    /// Rewrite the deprecated `-v <level>` (from before `-v` was counted) to `--verbosity <level>`.
+    /// Only the arguments in front of the subcommand are rewritten, since `-v` may be followed by a
+    /// positional argument of a subcommand.
+    fn deprecated_log_level(mut args: Vec<OsString>) -> Vec<OsString> {
+        let command = Cli::command();
+        let subcommand = args.iter().skip(1).position(|arg| {
+            command
+                .get_subcommands()
+                .any(|subcommand| arg.to_str() == Some(subcommand.get_name()))
+        });
+        let end = subcommand.map_or(args.len(), |index| index + 1);
+
+        for index in 1..end.saturating_sub(1) {
+            let is_level = args[index + 1]
+                .to_str()
+                .is_some_and(|level| LogLevel::from_str(level, false).is_ok());
+            if args[index] == "-v" && is_level {
+                eprintln!("warning: `-v <level>` is deprecated, use `--verbosity <level>` instead");
+                args[index] = OsString::from("--verbosity");
+            }
+        }
+
+        args
+    }
+
+    /// The log level resulting from `--verbosity` and `-v` (whichever is more verbose).
+    pub fn log_level(&self) -> log::Level {
+        let verbose = match self.verbose {
+            0 => log::Level::Error,
+            1 => log::Level::Info,
+            2 => log::Level::Debug,
+            _ => log::Level::Trace,
+        };
+
+        log::Level::from(&self.verbosity).max(verbose)
     }
 }
 
@@ -63,30 +117,250 @@ impl From<&LogLevel> for log::Level {
     }
 }
 
+/// Enum for specifying the relocation model of the output binary.
+#[derive(ValueEnum, Clone, Copy, Default, Debug)]
+pub enum RelocModel {
+    /// Link a position-dependent executable.
+    Static,
+
+    /// Link a position-independent executable.
+    #[default]
+    Pic,
+
+    /// Use the default of the system linker.
+    Default,
+}
+
+impl From<RelocModel> for y_lang::compiler::RelocModel {
+    fn from(value: RelocModel) -> Self {
+        match value {
+            RelocModel::Static => y_lang::compiler::RelocModel::Static,
+            RelocModel::Pic => y_lang::compiler::RelocModel::Pic,
+            RelocModel::Default => y_lang::compiler::RelocModel::Default,
+        }
+    }
+}
+
+impl From<y_lang::compiler::RelocModel> for RelocModel {
+    fn from(value: y_lang::compiler::RelocModel) -> Self {
+        match value {
+            y_lang::compiler::RelocModel::Static => RelocModel::Static,
+            y_lang::compiler::RelocModel::Pic => RelocModel::Pic,
+            y_lang::compiler::RelocModel::Default => RelocModel::Default,
+        }
+    }
+}
+
+/// Enum for specifying how parse trees are dumped.
+#[derive(ValueEnum, Clone, Copy, Default, Debug)]
+pub enum TreeStyle {
+    /// One node per line with its kind and payload.
+    #[default]
+    Compact,
+
+    /// Like `compact`, but with the position of each node.
+    Verbose,
+}
+
+impl From<TreeStyle> for y_lang::ast_tree::TreeStyle {
+    fn from(value: TreeStyle) -> Self {
+        match value {
+            TreeStyle::Compact => y_lang::ast_tree::TreeStyle::Compact,
+            TreeStyle::Verbose => y_lang::ast_tree::TreeStyle::Verbose,
+        }
+    }
+}
+
+/// Enum for specifying when the output of Why is colored.
+#[derive(ValueEnum, Clone, Copy, Default, Debug)]
+pub enum ColorMode {
+    /// Color the output if it is written to a terminal. This respects the environment variables
+    /// `NO_COLOR`, `CLICOLOR` and `CLICOLOR_FORCE`.
+    #[default]
+    Auto,
+
+    /// Always color the output (e.g., when piping it into a pager).
+    Always,
+
+    /// Never color the output.
+    Never,
+}
+
+impl From<ColorMode> for y_lang::diagnostics::ColorChoice {
+    fn from(value: ColorMode) -> Self {
+        match value {
+            ColorMode::Auto => y_lang::diagnostics::ColorChoice::Auto,
+            ColorMode::Always => y_lang::diagnostics::ColorChoice::Always,
+            ColorMode::Never => y_lang::diagnostics::ColorChoice::Never,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Build a Y executable from source files.
     Build(BuildArgs),
 
+    /// Build a Y executable for every source file within a directory.
+    BuildAll(BuildAllArgs),
+
+    /// Type check and interpret a Y program (without compiling it).
+    Run(RunArgs),
+
+    /// Compile a Y program (if it changed since the last time) and run it. Meant to be used in
+    /// shebang lines, e.g., `#!/usr/bin/env -S why script`.
+    Script(ScriptArgs),
+
     /// Setup the buildin library (i.e., std and core) on your machine
     Setup,
+
+    /// Check whether the tools needed for compiling programs (assembler and linker) are installed.
+    Doctor,
+}
+
+impl Commands {
+    /// Whether this command compiles a program into an executable (and, therefore, needs an
+    /// assembler and a linker).
+    pub fn needs_toolchain(&self) -> bool {
+        match self {
+            Commands::Build(args) => args.output.is_some(),
+            Commands::BuildAll(_) | Commands::Script(_) => true,
+            Commands::Run(_) | Commands::Setup | Commands::Doctor => false,
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct BuildArgs {
-    /// The path to the why source file.
+    /// The path to the why source file. Defaults to the entry point of the project (given by the
+    /// `why.toml` in the current directory or any of its parents).
     #[arg(index = 1)]
-    pub file: std::path::PathBuf,
+    pub file: Option<std::path::PathBuf>,
 
-    /// Whether to dump the parsed AST (for debugging).
-    #[arg(long)]
-    pub dump_parsed: bool,
+    /// Dump the parse tree of the source file as given by the grammar (for debugging). Positions
+    /// are only printed with `--dump-lexed=verbose`.
+    #[arg(long, value_enum, value_name = "STYLE", num_args = 0..=1, require_equals = true, default_missing_value = "compact")]
+    pub dump_lexed: Option<TreeStyle>,
+
+    /// Dump the parsed AST (for debugging). Positions are only printed with
+    /// `--dump-parsed=verbose`.
+    #[arg(long, value_enum, value_name = "STYLE", num_args = 0..=1, require_equals = true, default_missing_value = "compact")]
+    pub dump_parsed: Option<TreeStyle>,
+
+    /// Explain the top-level item containing the given line, i.e., print its source, its parse
+    /// tree and its type-checked tree (with the types of all expressions inline).
+    #[arg(long, value_name = "LINE")]
+    pub explain_lowering: Option<usize>,
 
     /// Whether to dump the type-checked AST (for debugging).
     #[arg(long)]
     pub dump_typed: bool,
 
+    /// Write the parsed AST as JSON to the given path (for external tooling).
+    #[arg(long, value_name = "PATH")]
+    pub emit_ast: Option<std::path::PathBuf>,
+
+    /// Write the type-checked AST as JSON to the given path (for external tooling).
+    #[arg(long, value_name = "PATH")]
+    pub emit_typed_ast: Option<std::path::PathBuf>,
+
+    /// Write an index of all definitions and references to the given path (as JSON if it ends
+    /// with `.json`, in the ctags format otherwise).
+    #[arg(long, value_name = "PATH")]
+    pub emit_xref: Option<std::path::PathBuf>,
+
+    /// Report all external declarations (with their symbol and their references) and the ones
+    /// which are never used. The report is printed to stdout or, if a path is given (via
+    /// `--report-externals=PATH`), written to it as JSON.
+    #[arg(long, value_name = "PATH", num_args = 0..=1, require_equals = true)]
+    pub report_externals: Option<Option<std::path::PathBuf>>,
+
     /// The path to the output binary.
     #[arg(short, long)]
     pub output: Option<std::path::PathBuf>,
+
+    /// The relocation model to link the output binary with [default: pic].
+    #[arg(value_enum, long)]
+    pub reloc_model: Option<RelocModel>,
+
+    /// Do not reuse the stack space of variables in blocks which already ended (for debugging).
+    #[arg(long)]
+    pub no_slot_reuse: bool,
+
+    /// Omit assertions (`assert` and `assert_eq`) from the output binary.
+    #[arg(long)]
+    pub release_asserts: bool,
+
+    /// Check for stack overflows in functions and terminate the program with a message (and exit
+    /// code 70) instead of crashing.
+    #[arg(long)]
+    pub stack_guard: bool,
+
+    /// Terminate the program with a message (and exit code 102), if an integer addition,
+    /// subtraction or multiplication overflows, instead of wrapping around.
+    #[arg(long)]
+    pub checked_arithmetic: bool,
+
+    /// Print the assembly of the given function of the program (and of all functions it
+    /// references) to stdout (for debugging). May be given multiple times.
+    #[arg(long, value_name = "NAME")]
+    pub emit_asm_function: Vec<String>,
+
+    /// Show the progress of the compilation on stderr.
+    #[arg(long)]
+    pub progress: bool,
+
+    /// Additional object files to link into the output binary (e.g., for `declare`d symbols).
+    #[arg(long)]
+    pub link_obj: Vec<std::path::PathBuf>,
+
+    /// Override a value compiler directives are evaluated against (e.g., `--cfg os=macos`).
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_cfg)]
+    pub cfg: Vec<(String, String)>,
+}
+
+fn parse_cfg(value: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = value.split_once('=') else {
+        return Err(format!("expected 'key=value', got '{value}'"));
+    };
+
+    Ok((key.trim().to_owned(), value.trim().to_owned()))
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BuildAllArgs {
+    /// The directory to search for why source files (recursively).
+    #[arg(index = 1)]
+    pub dir: std::path::PathBuf,
+
+    /// The directory to write the output binaries to. The binaries are named after the path of
+    /// their source file relative to the source directory.
+    #[arg(short, long)]
+    pub output: std::path::PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RunArgs {
+    /// The path to the why source file.
+    #[arg(index = 1)]
+    pub file: std::path::PathBuf,
+
+    /// Override a value compiler directives are evaluated against (e.g., `--cfg os=macos`).
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_cfg)]
+    pub cfg: Vec<(String, String)>,
+
+    /// Arguments passed to the interpreted program.
+    #[arg(index = 2, trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScriptArgs {
+    /// The path to the why source file.
+    #[arg(index = 1)]
+    pub file: std::path::PathBuf,
+
+    /// Arguments passed to the program.
+    #[arg(index = 2, trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
 }
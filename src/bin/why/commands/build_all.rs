@@ -0,0 +1,158 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use log::{error, info};
+use y_lang::diagnostics::{colors_enabled, render_error_with_limit, ColorChoice};
+
+use crate::{
+    cli::{BuildAllArgs, BuildArgs},
+    STACK_SIZE,
+};
+
+use super::build_executable;
+
+/// Upper bound for the combined stack size of all worker threads. Every worker runs on a stack of
+/// `STACK_SIZE`, so the number of workers is limited to avoid reserving a multiple of it for each
+/// available CPU.
+const MAX_WORKER_STACKS: usize = 2 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+struct BuildAllError {
+    failed: usize,
+    total: usize,
+}
+
+impl Display for BuildAllError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!(
+            "{} of {} files failed to compile",
+            self.failed, self.total
+        ))
+    }
+}
+
+impl Error for BuildAllError {}
+
+pub fn build_all(
+    args: &BuildAllArgs,
+    color: ColorChoice,
+    error_limit: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut files = vec![];
+    find_source_files(&args.dir, &mut files)?;
+    files.sort();
+
+    let total = files.len();
+    info!(
+        "Found {total} source files in '{}'",
+        args.dir.to_string_lossy()
+    );
+
+    let results = Mutex::new(vec![None; total]);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_WORKER_STACKS / STACK_SIZE);
+
+    thread::scope(|scope| {
+        for _ in 0..workers.min(total) {
+            thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(scope, || loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(file) = files.get(index) else {
+                        break;
+                    };
+
+                    let result = build_file(&args.dir, &args.output, file, color, error_limit);
+                    results.lock().unwrap()[index] = Some(result);
+
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    eprintln!("{done}/{total} compiled");
+                })
+                .expect("failed to spawn worker thread");
+        }
+    });
+
+    // report diagnostics in the (sorted) order of the files, regardless of which finished first
+    let mut failed = 0;
+    for (file, result) in files.iter().zip(results.into_inner().unwrap()) {
+        if let Some(Err(message)) = result {
+            failed += 1;
+            error!("{}: {message}", file.to_string_lossy());
+        }
+    }
+
+    if failed > 0 {
+        return Err(Box::new(BuildAllError { failed, total }));
+    }
+
+    Ok(())
+}
+
+/// Compile a single file into the output directory. Errors are converted into strings, so they
+/// can be sent back from the worker threads.
+fn build_file(
+    src_dir: &Path,
+    out_dir: &Path,
+    file: &Path,
+    color: ColorChoice,
+    error_limit: usize,
+) -> Result<(), String> {
+    let relative = file.strip_prefix(src_dir).unwrap_or(file);
+    let output = out_dir.join(relative).with_extension("");
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let args = BuildArgs {
+        file: Some(file.to_path_buf()),
+        dump_lexed: None,
+        dump_parsed: None,
+        explain_lowering: None,
+        dump_typed: false,
+        emit_ast: None,
+        emit_typed_ast: None,
+        emit_xref: None,
+        report_externals: None,
+        output: Some(output),
+        reloc_model: None,
+        no_slot_reuse: false,
+        release_asserts: false,
+        stack_guard: false,
+        checked_arithmetic: false,
+        emit_asm_function: vec![],
+        progress: false,
+        link_obj: vec![],
+        cfg: vec![],
+    };
+
+    build_executable(&args)
+        .map_err(|e| render_error_with_limit(e.as_ref(), colors_enabled(color), error_limit))
+}
+
+fn find_source_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            find_source_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "why") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -1,44 +1,175 @@
-use std::{collections::HashMap, error::Error, fs};
+use std::{collections::HashMap, env, error::Error, fs, rc::Rc};
 
-use log::error;
 use y_lang::{
+    ast::YParser,
+    ast_json, ast_tree,
     compiler::Compiler,
-    loader::{load_module, load_modules, Module, Modules},
+    explain::explain_lowering,
+    externals::ExternalsReport,
+    loader::{load_module, Module},
+    pipeline::{run_stage, NoObserver, PipelineObserver, Stage},
+    project::{find_manifest, ProjectConfig, MANIFEST_NAME},
+    xref::Xref,
 };
 
-use crate::cli::BuildArgs;
+use crate::{
+    cli::{BuildArgs, RelocModel},
+    progress::Spinner,
+};
 
-pub fn build_executable(args: &BuildArgs) -> Result<(), Box<dyn Error>> {
-    let file = fs::canonicalize(&args.file)?;
+impl BuildArgs {
+    /// Fill in the options which are not given on the command line from the manifest of the
+    /// project the source file (or, without a source file, the current directory) belongs to.
+    pub fn with_project_config(&self) -> Result<BuildArgs, Box<dyn Error>> {
+        let dir = match &self.file {
+            Some(file) => fs::canonicalize(file)?
+                .parent()
+                .map(|dir| dir.to_path_buf())
+                .unwrap_or_default(),
+            None => env::current_dir()?,
+        };
+
+        let Some(manifest) = find_manifest(&dir) else {
+            if self.file.is_none() {
+                return Err(format!(
+                    "No source file given and no {MANIFEST_NAME} found in '{}' or any of its parents",
+                    dir.display()
+                )
+                .into());
+            }
+            return Ok(self.clone());
+        };
+
+        let config = ProjectConfig::load(&manifest)?;
+
+        let file = match &self.file {
+            Some(file) => fs::canonicalize(file)?,
+            None => config.entry()?.to_path_buf(),
+        };
 
-    let main_module = load_module(file.clone())?;
+        let mut link_obj = config.link_obj.clone();
+        link_obj.extend(self.link_obj.iter().cloned());
 
-    if args.dump_parsed {
-        println!("Parsed AST:\n{:#?}", main_module.ast);
+        Ok(BuildArgs {
+            output: self.output.clone().or_else(|| config.output_path(&file)),
+            file: Some(file),
+            reloc_model: self
+                .reloc_model
+                .or(config.reloc_model.map(RelocModel::from)),
+            emit_ast: self.emit_ast.clone().or(config.emit_ast),
+            emit_typed_ast: self.emit_typed_ast.clone().or(config.emit_typed_ast),
+            emit_xref: self.emit_xref.clone().or(config.emit_xref),
+            // flags can only enable options on the command line
+            no_slot_reuse: self.no_slot_reuse || config.slot_reuse == Some(false),
+            release_asserts: self.release_asserts || config.release_asserts == Some(true),
+            stack_guard: self.stack_guard || config.stack_guard == Some(true),
+            checked_arithmetic: self.checked_arithmetic || config.checked_arithmetic == Some(true),
+            link_obj,
+            ..self.clone()
+        })
     }
+}
 
-    let modules = match load_modules(&main_module.ast, file, Modules::default()) {
-        Err(load_error) => {
-            error!("{load_error}");
-            std::process::exit(-1);
-        }
-        Ok(modules) => modules,
+/// Parse, type check and (if an output path is given) compile a single why source file.
+pub fn build_executable(args: &BuildArgs) -> Result<(), Box<dyn Error>> {
+    let Some(file) = &args.file else {
+        return Err("No source file given".into());
     };
+    let file = fs::canonicalize(file)?;
 
-    let mut type_safe_modules = HashMap::default();
+    let observer: Rc<dyn PipelineObserver> = if args.progress {
+        Rc::new(Spinner::default())
+    } else {
+        Rc::new(NoObserver)
+    };
 
-    for (key, module) in &modules {
-        type_safe_modules.insert(key.to_owned(), module.type_check(&modules)?);
+    if let Some(style) = args.dump_lexed {
+        let source = fs::read_to_string(&file)?;
+        let file = file.to_string_lossy();
+        let pairs = YParser::parse_program(&file, &source)?;
+        print!(
+            "Parse tree:\n{}",
+            ast_tree::render_pairs(pairs, &file, style.into())
+        );
     }
 
-    let Module { ast, .. } = main_module.type_check(&modules)?;
+    let main_module = run_stage(observer.as_ref(), Stage::Parse, || {
+        load_module(file.clone())
+    })?;
+
+    if let Some(style) = args.dump_parsed {
+        print!(
+            "Parsed AST:\n{}",
+            ast_tree::render(&main_module.ast, style.into())
+        );
+    }
+
+    if let Some(path) = &args.emit_ast {
+        fs::write(path, ast_json::to_json(&main_module.ast))?;
+    }
+
+    let cfg = args.cfg.iter().cloned().collect::<HashMap<_, _>>();
+
+    let (Module { ast, .. }, type_safe_modules) = main_module
+        .with_observer(observer.as_ref())
+        .type_check_program(&cfg)?;
+
+    if let Some(line) = args.explain_lowering {
+        let source = fs::read_to_string(&file)?;
+        let file = file.to_string_lossy();
+        print!("{}", explain_lowering(&file, &source, &ast, line)?);
+    }
 
     if args.dump_typed {
         println!("Typed AST:\n{:#?}", ast);
     }
 
+    if let Some(path) = &args.emit_typed_ast {
+        fs::write(path, ast_json::to_json(&ast))?;
+    }
+
+    if let Some(path) = &args.emit_xref {
+        let xref = Xref::collect(&ast);
+        let content = match path.extension() {
+            Some(extension) if extension == "json" => xref.to_json_string(),
+            _ => xref.to_tags(),
+        };
+        fs::write(path, content)?;
+    }
+
+    if let Some(path) = &args.report_externals {
+        let report = ExternalsReport::collect(&ast);
+        match path {
+            Some(path) => fs::write(path, report.to_json_string())?,
+            None => print!("{report}"),
+        }
+    }
+
+    if args.output.is_none() && args.emit_asm_function.is_empty() {
+        return Ok(());
+    }
+
+    let mut compiler = Compiler::from_ast(ast, type_safe_modules);
+
+    compiler.set_reloc_model(args.reloc_model.unwrap_or_default().into());
+    compiler.set_slot_reuse(!args.no_slot_reuse);
+    compiler.set_asserts(!args.release_asserts);
+    compiler.set_stack_guard(args.stack_guard);
+    compiler.set_checked_arithmetic(args.checked_arithmetic);
+    compiler.set_observer(observer);
+
+    if !args.emit_asm_function.is_empty() {
+        print!("{}", compiler.function_assembly(&args.emit_asm_function)?);
+    }
+
     if let Some(output) = &args.output {
-        let mut compiler = Compiler::from_ast(ast, type_safe_modules.clone());
+        if let Some(dir) = output.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        for object in &args.link_obj {
+            compiler.add_link_object(object.clone());
+        }
 
         compiler.compile_program(output.clone())?;
     }
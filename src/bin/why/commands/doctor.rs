@@ -0,0 +1,28 @@
+use std::error::Error;
+
+use y_lang::compiler::{Tool, ToolNotFound};
+
+/// Check whether all tools needed for compiling programs are installed and explain how to install
+/// the missing ones.
+pub fn doctor() -> Result<(), Box<dyn Error>> {
+    let mut missing: Option<ToolNotFound> = None;
+
+    for tool in Tool::ALL {
+        match tool.find() {
+            Ok(path) => println!("[OK] {tool}: {}", path.display()),
+            Err(error) => {
+                println!(
+                    "[MISSING] {tool}: '{}' not found. {}.",
+                    error.binary,
+                    tool.install_hint()
+                );
+                missing.get_or_insert(error);
+            }
+        }
+    }
+
+    match missing {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
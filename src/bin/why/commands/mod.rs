@@ -1,5 +1,13 @@
+mod build_all;
 mod build_executable;
+mod doctor;
+mod run;
+mod script;
 mod setup;
 
+pub use build_all::*;
 pub use build_executable::*;
+pub use doctor::*;
+pub use run::*;
+pub use script::*;
 pub use setup::*;
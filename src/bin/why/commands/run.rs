@@ -0,0 +1,35 @@
+use std::{collections::HashMap, error::Error, fs, process};
+
+use y_lang::{interpreter::Interpreter, loader::load_module};
+
+use crate::cli::RunArgs;
+
+/// Type check and interpret a single why source file. The process exits with the exit code of the
+/// interpreted program.
+pub fn run_program(args: &RunArgs) -> Result<(), Box<dyn Error>> {
+    let file = fs::canonicalize(&args.file)?;
+
+    let main_module = load_module(file)?;
+
+    let cfg = args.cfg.iter().cloned().collect::<HashMap<_, _>>();
+    let (main_module, modules) = main_module.type_check_program(&cfg)?;
+
+    let mut interpreter = Interpreter::new(&main_module, &modules);
+
+    // like compiled programs, the interpreted program gets its own path as first argument
+    let program_name = args.file.to_string_lossy().to_string();
+    interpreter.set_args(
+        [program_name]
+            .into_iter()
+            .chain(args.args.clone())
+            .collect(),
+    );
+
+    let exit_code = interpreter.run()?;
+
+    if exit_code != 0 {
+        process::exit(exit_code);
+    }
+
+    Ok(())
+}
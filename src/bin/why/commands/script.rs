@@ -0,0 +1,116 @@
+use std::{
+    error::Error,
+    fs,
+    os::unix::process::{CommandExt, ExitStatusExt},
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use log::info;
+use y_lang::loader::{load_module, load_modules, Modules};
+
+use crate::cli::{BuildArgs, ScriptArgs};
+
+use super::build_executable;
+
+/// Compile a why source file into the script cache (unless it is already cached) and run it. The
+/// process exits with the exit code of the script.
+pub fn run_script(args: &ScriptArgs) -> Result<(), Box<dyn Error>> {
+    let file = fs::canonicalize(&args.file)?;
+
+    let main_module = load_module(file.clone())?;
+    let modules = load_modules(&main_module.ast, file.clone(), Modules::default())?;
+
+    // the cached binary depends on the script and every module imported by it
+    let mut sources = vec![file.clone()];
+    sources.extend(modules.values().map(|module| module.file_path.clone()));
+
+    let cache_dir = cache_dir();
+    let binary = cache_dir.join(format!("{:016x}", hash_sources(&sources)?));
+
+    if binary.exists() {
+        info!("Using cached binary '{}'", binary.to_string_lossy());
+    } else {
+        info!(
+            "No cached binary for '{}', compiling it to '{}'",
+            file.to_string_lossy(),
+            binary.to_string_lossy()
+        );
+        fs::create_dir_all(&cache_dir)?;
+
+        build_executable(&BuildArgs {
+            file: Some(file),
+            dump_lexed: None,
+            dump_parsed: None,
+            explain_lowering: None,
+            dump_typed: false,
+            emit_ast: None,
+            emit_typed_ast: None,
+            emit_xref: None,
+            report_externals: None,
+            output: Some(binary.clone()),
+            reloc_model: None,
+            no_slot_reuse: false,
+            release_asserts: false,
+            stack_guard: false,
+            checked_arithmetic: false,
+            emit_asm_function: vec![],
+            progress: false,
+            link_obj: vec![],
+            cfg: vec![],
+        })?;
+    }
+
+    let status = Command::new(&binary)
+        .arg0(&args.file)
+        .args(&args.args)
+        .status()?;
+
+    let exit_code = match (status.code(), status.signal()) {
+        (Some(code), _) => code,
+        // mimic the shell for scripts terminated by a signal
+        (None, Some(signal)) => 128 + signal,
+        (None, None) => 1,
+    };
+
+    if exit_code != 0 {
+        process::exit(exit_code);
+    }
+
+    Ok(())
+}
+
+/// The directory compiled scripts are cached in (`$XDG_CACHE_HOME/why` or `~/.cache/why`).
+fn cache_dir() -> PathBuf {
+    match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("why"),
+        _ => home::home_dir()
+            .unwrap_or(".".into())
+            .join(".cache")
+            .join("why"),
+    }
+}
+
+/// Hash the contents of the given files (together with the version of the compiler) using 64 bit
+/// FNV-1a, which (in contrast to the hasher of the standard library) is stable across builds.
+fn hash_sources(sources: &[impl AsRef<Path>]) -> Result<u64, Box<dyn Error>> {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut hash = OFFSET_BASIS;
+    let mut update = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+
+    update(env!("CARGO_PKG_VERSION").as_bytes());
+
+    for source in sources {
+        update(source.as_ref().to_string_lossy().as_bytes());
+        update(&fs::read(source)?);
+    }
+
+    Ok(hash)
+}
@@ -0,0 +1,64 @@
+//! # Logger
+//!
+//! Every log message is written to STDERR, so logs never mix with the output of a command (e.g.,
+//! dumped trees or the rendered diagnostics of a failed compilation).
+use std::time::Instant;
+
+use colored::Colorize;
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+pub struct Logger {
+    level: LevelFilter,
+    /// The start of the compiler, if messages are prefixed with the time elapsed since then.
+    start: Option<Instant>,
+}
+
+impl Logger {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level: level.to_level_filter(),
+            start: None,
+        }
+    }
+
+    /// Prefix every message with the time elapsed since initializing the logger.
+    pub fn with_timestamps(mut self, timestamps: bool) -> Self {
+        self.start = timestamps.then(Instant::now);
+        self
+    }
+
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        log::set_max_level(self.level);
+        log::set_boxed_logger(Box::new(self))
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let level = format!("{:<5}", record.level().to_string());
+        let level = match record.level() {
+            Level::Error => level.red(),
+            Level::Warn => level.yellow(),
+            Level::Info => level.cyan(),
+            Level::Debug => level.purple(),
+            Level::Trace => level.normal(),
+        };
+
+        let timestamp = match self.start {
+            Some(start) => format!("{:>9.3}s ", start.elapsed().as_secs_f64()),
+            None => String::new(),
+        };
+
+        eprintln!("{timestamp}{level} [{}] {}", record.target(), record.args());
+    }
+
+    fn flush(&self) {}
+}
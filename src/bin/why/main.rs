@@ -7,23 +7,94 @@ extern crate y_lang;
 
 mod cli;
 mod commands;
+mod logger;
+mod progress;
 
 use cli::*;
 use commands::*;
 use include_dir::{include_dir, Dir};
-use log::error;
+use logger::Logger;
+use std::{error::Error, thread};
+use y_lang::{
+    compiler::{check_toolchain, ToolNotFound},
+    diagnostics::{colors_enabled, render_error_with_limit, ColorChoice},
+};
 
 pub static LIBRARY_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/lib");
 
+/// Stack size of the threads running the compiler. Parsing and type checking are recursive, so
+/// deeply nested (e.g., generated) programs need more than the default stack size.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Exit code if a tool needed for compiling (i.e., the assembler or linker) is not installed (like
+/// shells use for unknown commands).
+const TOOL_NOT_FOUND_EXIT_CODE: i32 = 127;
+
 fn main() {
     let args = Cli::init();
 
-    simple_logger::init_with_level((&args.verbosity).into()).unwrap();
+    let color = ColorChoice::from(args.color);
+
+    // logs and diagnostics are colored alike
+    let colored = colors_enabled(color);
+    colored::control::set_override(colored);
+    let level = args.log_level();
+    Logger::new(level)
+        .with_timestamps(level == log::Level::Trace)
+        .init()
+        .unwrap();
+
+    let compiler = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            run_command(&args.command, color, args.error_limit).map_err(|error| {
+                let exit_code = if error.is::<ToolNotFound>() {
+                    TOOL_NOT_FOUND_EXIT_CODE
+                } else {
+                    -1
+                };
+
+                (
+                    render_error_with_limit(error.as_ref(), colored, args.error_limit),
+                    exit_code,
+                )
+            })
+        })
+        .expect("failed to spawn compiler thread");
+
+    match compiler.join() {
+        Ok(Err((error, exit_code))) => {
+            // diagnostics go to stderr, like all other messages of the compiler
+            eprintln!("{error}");
+            std::process::exit(exit_code);
+        }
+        Ok(Ok(())) => {}
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
+
+fn run_command(
+    command: &Commands,
+    color: ColorChoice,
+    error_limit: usize,
+) -> Result<(), Box<dyn Error>> {
+    // the manifest of a project provides defaults for options which are not given explicitly
+    let command = match command {
+        Commands::Build(args) => &Commands::Build(args.with_project_config()?),
+        command => command,
+    };
+
+    // fail early (instead of after generating all the code) if tools are missing
+    if command.needs_toolchain() {
+        check_toolchain()?;
+    }
 
-    if let Err(error) = match &args.command {
+    match command {
         Commands::Build(args) => build_executable(args),
+        Commands::BuildAll(args) => build_all(args, color, error_limit),
+        Commands::Run(args) => run_program(args),
+        Commands::Script(args) => run_script(args),
         Commands::Setup => setup_library(),
-    } {
-        error!("{error}");
+        Commands::Doctor => doctor(),
     }
 }
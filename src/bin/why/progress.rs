@@ -0,0 +1,58 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    io::{self, Write},
+    time::Duration,
+};
+
+use y_lang::pipeline::{PipelineObserver, Stage};
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Observer which shows the current stage of the pipeline (with a spinner) on stderr.
+#[derive(Default)]
+pub struct Spinner {
+    stage: Cell<Option<Stage>>,
+    frame: Cell<usize>,
+    failed: Cell<bool>,
+}
+
+impl Spinner {
+    fn tick(&self) {
+        let Some(stage) = self.stage.get() else {
+            return;
+        };
+
+        let frame = self.frame.get();
+        self.frame.set((frame + 1) % FRAMES.len());
+
+        let mut stderr = io::stderr();
+        let _ = write!(stderr, "\r{} {stage}...", FRAMES[frame]);
+        let _ = stderr.flush();
+    }
+}
+
+impl PipelineObserver for Spinner {
+    fn on_stage_start(&self, stage: Stage) {
+        self.stage.set(Some(stage));
+        self.tick();
+    }
+
+    fn on_stage_end(&self, stage: Stage, duration: Duration) {
+        self.stage.set(None);
+
+        let status = if self.failed.get() { '✗' } else { '✓' };
+        eprintln!("\r{status} {stage} ({duration:.2?})");
+    }
+
+    fn on_diagnostic(&self, _diagnostic: &dyn Error) {
+        // the diagnostics themselves are reported once the build failed
+        self.failed.set(true);
+    }
+
+    fn should_cancel(&self) -> bool {
+        // type checking asks after each statement, which keeps the spinner moving
+        self.tick();
+        false
+    }
+}
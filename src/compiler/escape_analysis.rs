@@ -0,0 +1,99 @@
+use crate::{
+    ast::{
+        Assignment, BinaryExpr, Block, Break, Call, CompilerDirective, Definition, Expression, If,
+        Intrinsic, LabeledBlock, Loop, PostfixExpr, PostfixOp, PrefixExpr, Statement, WhileLoop,
+    },
+    typechecker::TypeInfo,
+};
+
+use super::scope::Scope;
+
+/// Check, whether a value pointing into the stack space of a block with the given statements
+/// (e.g., an array defined within it) may be stored outside of the block, i.e., it is assigned to
+/// a variable or passed to a function (which may store it in a global variable). Inline assembly may do anything, so blocks
+/// containing it are considered to leak their stack space, too.
+pub fn may_escape(statements: &[Statement<TypeInfo>]) -> bool {
+    statements.iter().any(statement_escapes)
+}
+
+fn block_escapes(Block { block, .. }: &Block<TypeInfo>) -> bool {
+    may_escape(block)
+}
+
+fn statement_escapes(statement: &Statement<TypeInfo>) -> bool {
+    match statement {
+        Statement::Import(_) => false,
+        Statement::Expression(expression) => expression_escapes(expression),
+        Statement::Intrinsic(intrinsic) => intrinsic_escapes(intrinsic),
+        Statement::CompilerDirective(CompilerDirective { statement, .. }) => {
+            statement.as_deref().is_some_and(statement_escapes)
+        }
+        Statement::InlineAssembly(_) => true,
+    }
+}
+
+fn intrinsic_escapes(intrinsic: &Intrinsic<TypeInfo>) -> bool {
+    match intrinsic {
+        Intrinsic::Definition(Definition { value, .. }) => expression_escapes(value),
+        Intrinsic::Assignment(Assignment { lhs, value, .. }) => {
+            points_into_stack(value) || expression_escapes(lhs) || expression_escapes(value)
+        }
+        Intrinsic::WhileLoop(WhileLoop {
+            condition, block, ..
+        }) => expression_escapes(condition) || block_escapes(block),
+        Intrinsic::Break(Break { value, .. }) => value.as_ref().is_some_and(expression_escapes),
+        Intrinsic::Declaration(_) => false,
+    }
+}
+
+fn expression_escapes(expression: &Expression<TypeInfo>) -> bool {
+    match expression {
+        Expression::If(If {
+            condition,
+            if_block,
+            else_block,
+            ..
+        }) => {
+            expression_escapes(condition)
+                || block_escapes(if_block)
+                || else_block.as_ref().is_some_and(block_escapes)
+        }
+        Expression::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            expression_escapes(lhs) || expression_escapes(rhs)
+        }
+        Expression::Prefix(PrefixExpr { rhs, .. }) => expression_escapes(rhs),
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Call(Call { params, .. }),
+            ..
+        }) => {
+            expression_escapes(lhs)
+                || params
+                    .iter()
+                    .any(|param| points_into_stack(param) || expression_escapes(param))
+        }
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Indexing(indexing),
+            ..
+        }) => expression_escapes(lhs) || expression_escapes(&indexing.index),
+        Expression::Block(block) => block_escapes(block),
+        Expression::Array(array) => expression_escapes(&array.initializer),
+        Expression::Loop(Loop { block, .. })
+        | Expression::LabeledBlock(LabeledBlock { block, .. }) => block_escapes(block),
+        // nested functions have their own stack frame
+        Expression::FnDef(_)
+        | Expression::Integer(_)
+        | Expression::Character(_)
+        | Expression::Ident(_)
+        | Expression::Str(_)
+        | Expression::Boolean(_) => false,
+    }
+}
+
+/// Check, whether the value of the given expression might point into the stack. String literals
+/// are stored in the data section.
+fn points_into_stack(expression: &Expression<TypeInfo>) -> bool {
+    !matches!(expression, Expression::Str(_))
+        && Scope::may_point_into_stack(&expression.info()._type)
+}
@@ -2,42 +2,200 @@
 //!
 //! This module provides capabilities for compiling type correct Y programs. Therefore, you should
 //! utilize the type checker beforehand.
+mod escape_analysis;
 mod scope;
+mod toolchain;
 mod ystd;
 
-use std::{error::Error, fs::File, io::prelude::*, path::PathBuf, process::Command};
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::Display,
+    fs,
+    fs::File,
+    io::prelude::*,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use Instruction::*;
 use InstructionOperand::*;
 use InstructionSize::*;
 use Reg::*;
 
-use log::{error, info};
+use log::{debug, error, info};
+use serde::Deserialize;
 
 use crate::{
-    asm::{Instruction, InstructionOperand, InstructionSize, Reg, EXIT_SYSCALL},
-    ast::Ast,
+    asm::{
+        ascii_symbols, Instruction, InstructionOperand, InstructionSize, Reg, EXIT_SYSCALL,
+        GETRLIMIT_SYSCALL, WRITE_SYSCALL,
+    },
+    ast::{Ast, Position},
     loader::{Module, Modules},
-    typechecker::TypeInfo,
+    pipeline::{run_stage, Cancelled, NoObserver, PipelineObserver, Stage},
+    typechecker::{captured_variables, TypeInfo},
 };
 
 use self::{
-    scope::{Constant, Scope},
-    ystd::INT_TO_STR,
+    scope::{Constant, Function, Scope},
+    ystd::{INT_TO_STR, IS_INT, STR_TO_INT},
 };
+
+pub use self::toolchain::{check_toolchain, Tool, ToolNotFound, LINKER_ENV};
+
+/// Exit code of programs compiled with stack guards, which overflow their stack (`EX_SOFTWARE`).
+pub const STACK_OVERFLOW_EXIT_CODE: i64 = 70;
+
+/// Exit code of programs compiled with checked arithmetic, whose integer arithmetic overflows
+/// (distinct from the exit code of failed assertions, which is 101).
+pub const INTEGER_OVERFLOW_EXIT_CODE: i64 = 102;
+
+/// The name of the linker symbol a declared (i.e., external) name of the main module refers to.
+pub fn external_symbol(name: &str) -> String {
+    ascii_symbols(name)
+}
+
+/// The resource whose limit is the size of the stack (for `getrlimit`).
+const RLIMIT_STACK: i64 = 3;
+
+/// Stack size assumed if the size of the stack is unknown or (practically) unlimited.
+const DEFAULT_STACK_SIZE: i64 = 8 * 1024 * 1024;
+
+/// Stack sizes beyond this are treated as unlimited.
+const MAX_STACK_SIZE: i64 = 1024 * 1024 * 1024;
+
+/// Space at the end of the stack, which stays available for reporting an overflow (and for the
+/// arguments and environment stored above the entry point).
+const STACK_GUARD_MARGIN: i64 = 256 * 1024;
+
+/// The relocation model used when linking a program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelocModel {
+    /// Link a position-dependent executable (`-no-pie`).
+    Static,
+    /// Link a position-independent executable (`-pie`).
+    #[default]
+    Pic,
+    /// Use whatever the system linker defaults to.
+    Default,
+}
+
+/// Error returned when the assembly of a function is requested, which is not defined in the main
+/// program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFunction {
+    pub name: String,
+    /// The names of all functions of the main program.
+    pub available: Vec<String>,
+}
+
+impl Display for UnknownFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unknown function '{}'. Available functions: {}",
+            self.name,
+            self.available.join(", ")
+        )
+    }
+}
+
+impl Error for UnknownFunction {}
+
+/// Error returned when a (type checked) program contains a construct, which can not be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub message: String,
+    pub position: Position,
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (file, line, col) = &self.position;
+        write!(f, "{} ({file}:{line}:{col})", self.message)
+    }
+}
+
+impl Error for CompileError {}
+
 pub struct Compiler {
     scope: Scope,
     modules: Modules<TypeInfo>,
+    link_objects: Vec<PathBuf>,
+    reloc_model: RelocModel,
+    slot_reuse: bool,
+    asserts: bool,
+    stack_guard: bool,
+    checked_arithmetic: bool,
+    observer: Rc<dyn PipelineObserver>,
 }
 
 impl Compiler {
     pub fn from_ast(ast: Ast<TypeInfo>, modules: Modules<TypeInfo>) -> Self {
+        let captured = captured_variables(ast.nodes());
+        let mut scope = Scope::from_statements(ast.into_nodes(), 0, true, Option::None);
+        scope.captured = captured;
+
         Self {
-            scope: Scope::from_statements(ast.nodes(), 0, true, Option::None),
+            scope,
             modules,
+            link_objects: vec![],
+            reloc_model: RelocModel::default(),
+            slot_reuse: true,
+            asserts: true,
+            stack_guard: false,
+            checked_arithmetic: false,
+            observer: Rc::new(NoObserver),
         }
     }
 
+    /// Set the relocation model used for linking the resulting program.
+    pub fn set_reloc_model(&mut self, reloc_model: RelocModel) {
+        self.reloc_model = reloc_model;
+    }
+
+    /// Set whether blocks may reuse the stack space of previous (already ended) blocks.
+    pub fn set_slot_reuse(&mut self, slot_reuse: bool) {
+        self.slot_reuse = slot_reuse;
+        self.scope.slot_reuse = slot_reuse;
+    }
+
+    /// Set whether assertions (`assert` and `assert_eq`) are compiled. If not, they are omitted
+    /// entirely, i.e., their parameters are not evaluated either.
+    pub fn set_asserts(&mut self, asserts: bool) {
+        self.asserts = asserts;
+        self.scope.asserts = asserts;
+    }
+
+    /// Set whether functions check for stack overflows. If so, every function calling other
+    /// functions compares the stack pointer against the limit of the stack (determined at the
+    /// start of the program) and terminates the program with a message naming the function
+    /// instead of crashing.
+    pub fn set_stack_guard(&mut self, stack_guard: bool) {
+        self.stack_guard = stack_guard;
+    }
+
+    /// Set whether integer additions, subtractions and multiplications check for overflows. If
+    /// so, an overflow terminates the program with a message naming the position of the
+    /// operation. Otherwise, they wrap around.
+    pub fn set_checked_arithmetic(&mut self, checked_arithmetic: bool) {
+        self.checked_arithmetic = checked_arithmetic;
+        self.scope.checked_arithmetic = checked_arithmetic;
+    }
+
+    /// Notify the given observer about generating the code and linking the program. If it
+    /// cancels before linking, the intermediate files are removed again.
+    pub fn set_observer(&mut self, observer: Rc<dyn PipelineObserver>) {
+        self.observer = observer;
+    }
+
+    /// Add an (already compiled) object file, which gets linked into the resulting program.
+    pub fn add_link_object(&mut self, object: PathBuf) {
+        self.link_objects.push(object);
+    }
+
     fn prelude() -> Vec<Instruction> {
         vec![
             Label("str_len".to_owned()),
@@ -49,12 +207,200 @@ impl Compiler {
             Jmp(".str_len_loop".to_owned()),
             Label(".str_len_end".to_owned()),
             Ret,
+            Label("char_to_int".to_owned()),
+            Movzx(Register(Rax), Register(Dil)),
+            Ret,
+            // characters are single bytes, so everything outside of 0..=255 terminates the program
+            Label("int_to_char".to_owned()),
+            Cmp(Register(Rdi), Immediate(255)),
+            Ja(".int_to_char_out_of_range".to_owned()),
+            Mov(Register(Rax), Register(Rdi)),
+            Ret,
+            Label(".int_to_char_out_of_range".to_owned()),
+            Mov(Register(Rax), EXIT_SYSCALL),
+            Mov(Register(Rdi), Immediate(1)),
+            Syscall,
+            // the command line arguments are stored by the entry point of the program
+            Label("args".to_owned()),
+            Mov(Register(Rax), Memory(Qword, "args_values".to_owned())),
+            Mov(Register(Rdx), Memory(Qword, "args_count".to_owned())),
+            Ret,
+            Label("arg_count".to_owned()),
+            Mov(Register(Rax), Memory(Qword, "args_count".to_owned())),
+            Ret,
+            // look for an entry `<name>=<value>` in the environment (which the C runtime passes
+            // to the entry point as well), an unset variable results in an empty string
+            Label("env".to_owned()),
+            Mov(Register(Rsi), Memory(Qword, "env_values".to_owned())),
+            Label(".env_loop".to_owned()),
+            Mov(Register(Rcx), Memory(Qword, format!("{Rsi}"))),
+            Cmp(Register(Rcx), Immediate(0)),
+            Je(".env_unset".to_owned()),
+            Xor(Register(Rax), Register(Rax)),
+            Label(".env_compare".to_owned()),
+            Mov(Register(Dl), Memory(Byte, format!("{Rdi}+{Rax}"))),
+            Cmp(Register(Dl), Immediate(0)),
+            Je(".env_name_end".to_owned()),
+            Cmp(Register(Dl), Memory(Byte, format!("{Rcx}+{Rax}"))),
+            Jne(".env_next".to_owned()),
+            Inc(Rax),
+            Jmp(".env_compare".to_owned()),
+            Label(".env_name_end".to_owned()),
+            Cmp(Memory(Byte, format!("{Rcx}+{Rax}")), Immediate(0x3d)),
+            Jne(".env_next".to_owned()),
+            Add(Register(Rax), Register(Rcx)),
+            Inc(Rax),
+            Ret,
+            Label(".env_next".to_owned()),
+            Add(Register(Rsi), Immediate(8)),
+            Jmp(".env_loop".to_owned()),
+            Label(".env_unset".to_owned()),
+            Lea(Register(Rax), Identifier("env_empty".to_owned())),
+            Ret,
+            Label("exit".to_owned()),
+            Mov(Register(Rax), EXIT_SYSCALL),
+            Syscall,
+            // failing assertions write their message (and values) to STDERR
+            Label("assert_write".to_owned()),
+            Push(Rdi),
+            Call("str_len".to_owned()),
+            Mov(Register(Rdx), Register(Rax)),
+            Pop(Rsi),
+            Mov(Register(Rax), WRITE_SYSCALL),
+            Mov(Register(Rdi), Immediate(2)),
+            Syscall,
+            Ret,
+            Label("assert_write_char".to_owned()),
+            Mov(Memory(Byte, "assert_char_val".to_owned()), Register(Dil)),
+            Lea(Register(Rdi), Identifier("assert_char_val".to_owned())),
+            Jmp("assert_write".to_owned()),
+            Label("assert_write_bool".to_owned()),
+            Cmp(Register(Dil), Immediate(0)),
+            Lea(Register(Rdi), Identifier("assert_false".to_owned())),
+            Je("assert_write".to_owned()),
+            Lea(Register(Rdi), Identifier("assert_true".to_owned())),
+            Jmp("assert_write".to_owned()),
             Literal(INT_TO_STR.to_owned()),
+            Literal(STR_TO_INT.to_owned()),
+            Literal(IS_INT.to_owned()),
         ]
     }
 
+    /// Handler for stack overflows, which expects the name of the overflowing function in RDI.
+    fn stack_overflow_handler() -> Vec<Instruction> {
+        vec![
+            Label("stack_overflow".to_owned()),
+            Push(Rdi),
+            Lea(
+                Register(Rdi),
+                Identifier("stack_overflow_message".to_owned()),
+            ),
+            Call("assert_write".to_owned()),
+            Pop(Rdi),
+            Call("assert_write".to_owned()),
+            Lea(
+                Register(Rdi),
+                Identifier("stack_overflow_newline".to_owned()),
+            ),
+            Call("assert_write".to_owned()),
+            Mov(Register(Rdi), Immediate(STACK_OVERFLOW_EXIT_CODE)),
+            Jmp("exit".to_owned()),
+        ]
+    }
+
+    /// Handler for integer overflows, which expects the message to write in RDI.
+    fn integer_overflow_handler() -> Vec<Instruction> {
+        vec![
+            Label("integer_overflow".to_owned()),
+            Call("assert_write".to_owned()),
+            Mov(Register(Rdi), Immediate(INTEGER_OVERFLOW_EXIT_CODE)),
+            Jmp("exit".to_owned()),
+        ]
+    }
+
+    /// Determine the limit of the stack from the current stack pointer and the maximum size of
+    /// the stack.
+    fn stack_limit_setup() -> Vec<Instruction> {
+        vec![
+            Comment("Determine the limit of the stack for stack guards".to_owned()),
+            Mov(Register(Rax), GETRLIMIT_SYSCALL),
+            Mov(Register(Rdi), Immediate(RLIMIT_STACK)),
+            Lea(Register(Rsi), Identifier("stack_rlimit".to_owned())),
+            Syscall,
+            Cmp(Register(Rax), Immediate(0)),
+            Jne(".stack_size_default".to_owned()),
+            Mov(Register(Rax), Memory(Qword, "stack_rlimit".to_owned())),
+            Mov(Register(Rcx), Immediate(MAX_STACK_SIZE)),
+            Cmp(Register(Rax), Register(Rcx)),
+            Ja(".stack_size_default".to_owned()),
+            Jmp(".stack_size_known".to_owned()),
+            Label(".stack_size_default".to_owned()),
+            Mov(Register(Rax), Immediate(DEFAULT_STACK_SIZE)),
+            Label(".stack_size_known".to_owned()),
+            Mov(Register(Rcx), Register(Rsp)),
+            Sub(Register(Rcx), Register(Rax)),
+            Add(Register(Rcx), Immediate(STACK_GUARD_MARGIN)),
+            Mov(Memory(Qword, "stack_limit".to_owned()), Register(Rcx)),
+        ]
+    }
+
+    /// Add a stack guard to the given function, if it calls other functions. The guard checks the
+    /// stack pointer after the stack frame of the function has been allocated.
+    fn guard_stack(identifier: &str, instructions: &[Instruction]) -> Vec<Instruction> {
+        let frame_allocation = instructions
+            .iter()
+            .position(|instruction| matches!(instruction, Sub(Register(Rsp), _)));
+
+        let Some(frame_allocation) = frame_allocation.filter(|_| {
+            instructions
+                .iter()
+                .any(|instruction| matches!(instruction, Call(_)))
+        }) else {
+            return instructions.to_vec();
+        };
+
+        let overflow_label = format!("{identifier}_stack_overflow");
+        let name_label = format!("{identifier}_stack_guard_name");
+
+        let mut guarded = instructions.to_vec();
+        guarded.splice(
+            frame_allocation + 1..frame_allocation + 1,
+            [
+                Cmp(Memory(Qword, "stack_limit".to_owned()), Register(Rsp)),
+                Ja(overflow_label.clone()),
+            ],
+        );
+        guarded.extend([
+            Label(overflow_label),
+            Lea(Register(Rdi), Identifier(name_label.clone())),
+            Jmp("stack_overflow".to_owned()),
+            Raw(format!(
+                "{} db \"{identifier}\", 0",
+                ascii_symbols(&name_label)
+            )),
+        ]);
+
+        guarded
+    }
+
     fn write_data_from_standard_library(&mut self, file: &mut File) -> Result<(), Box<dyn Error>> {
-        file.write_all("\tint_to_str_val: times 64 db 0\n\n".as_bytes())?;
+        file.write_all("\tint_to_str_val: times 64 db 0\n".as_bytes())?;
+        file.write_all("\targs_count: dq 0\n".as_bytes())?;
+        file.write_all("\targs_values: dq 0\n".as_bytes())?;
+        file.write_all("\tenv_values: dq 0\n".as_bytes())?;
+        file.write_all("\tenv_empty db 0\n".as_bytes())?;
+        file.write_all("\tassert_char_val: times 2 db 0\n".as_bytes())?;
+        file.write_all("\tassert_true db \"true\", 0\n".as_bytes())?;
+        file.write_all("\tassert_false db \"false\", 0\n".as_bytes())?;
+
+        if self.stack_guard {
+            file.write_all("\tstack_limit: dq 0\n".as_bytes())?;
+            file.write_all("\tstack_rlimit: dq 0, 0\n".as_bytes())?;
+            file.write_all("\tstack_overflow_message db \"stack overflow in \", 0\n".as_bytes())?;
+            file.write_all("\tstack_overflow_newline db 0xa, 0\n".as_bytes())?;
+        }
+
+        file.write_all("\n".as_bytes())?;
 
         Ok(())
     }
@@ -67,22 +413,38 @@ impl Compiler {
         file.write_all("section .data\n".as_bytes())?;
         for Constant { value, name } in scope.constants.values() {
             // write the name of the string constant
-            file.write_all(format!("\t{name} db ").as_bytes())?;
-
-            // split string into lines
-            let string = &value;
-            let mut parts = string.split('\n').peekable();
-
-            while let Some(part) = parts.next() {
-                file.write_all(format!("\"{part}\", ").as_bytes())?;
-                // if this is not the last line, we append a CRLF
-                if parts.peek().is_some() {
-                    file.write_all("0xa, 0xd, ".as_bytes())?;
+            file.write_all(format!("\t{} db ", ascii_symbols(name)).as_bytes())?;
+
+            // line breaks and quotes can not be part of a quoted string, so they are written as
+            // plain bytes
+            let mut part = String::new();
+            for c in value.chars() {
+                let byte = match c {
+                    '\n' => "0xa",
+                    '"' => "0x22",
+                    c => {
+                        part.push(c);
+                        continue;
+                    }
+                };
+
+                if !part.is_empty() {
+                    file.write_all(format!("\"{part}\", ").as_bytes())?;
+                    part.clear();
                 }
+                file.write_all(format!("{byte}, ").as_bytes())?;
+            }
+            if !part.is_empty() {
+                file.write_all(format!("\"{part}\", ").as_bytes())?;
             }
             file.write_all("0\n".as_bytes())?;
         }
 
+        for (symbol, size) in &scope.global_variables {
+            let quad_words = vec!["0"; size.div_ceil(8)].join(", ");
+            file.write_all(format!("\t{}: dq {quad_words}\n", ascii_symbols(symbol)).as_bytes())?;
+        }
+
         Ok(())
     }
 
@@ -101,6 +463,26 @@ impl Compiler {
 
         file.write_all("\tglobal str_len\n".as_bytes())?;
         file.write_all("\tglobal int_to_str\n".as_bytes())?;
+        file.write_all("\tglobal str_to_int\n".as_bytes())?;
+        file.write_all("\tglobal is_int\n".as_bytes())?;
+        file.write_all("\tglobal char_to_int\n".as_bytes())?;
+        file.write_all("\tglobal int_to_char\n".as_bytes())?;
+        file.write_all("\tglobal args\n".as_bytes())?;
+        file.write_all("\tglobal arg_count\n".as_bytes())?;
+        file.write_all("\tglobal env\n".as_bytes())?;
+        file.write_all("\tglobal exit\n".as_bytes())?;
+        file.write_all("\tglobal assert_write\n".as_bytes())?;
+        file.write_all("\tglobal assert_write_char\n".as_bytes())?;
+        file.write_all("\tglobal assert_write_bool\n".as_bytes())?;
+
+        if self.stack_guard {
+            file.write_all("\tglobal stack_limit\n".as_bytes())?;
+            file.write_all("\tglobal stack_overflow\n".as_bytes())?;
+        }
+
+        if self.checked_arithmetic {
+            file.write_all("\tglobal integer_overflow\n".as_bytes())?;
+        }
 
         Ok(())
     }
@@ -111,30 +493,129 @@ impl Compiler {
         scope: &Scope,
     ) -> Result<(), Box<dyn Error>> {
         for external in &scope.externals {
-            file.write_all(format!("extern {external}\n").as_bytes())?;
+            file.write_all(format!("extern {}\n", ascii_symbols(external)).as_bytes())?;
         }
 
         Ok(())
     }
 
-    fn write_functions(&mut self, file: &mut File, scope: &Scope) -> Result<(), Box<dyn Error>> {
+    /// Render the label and the instructions of a function.
+    fn render_function(&self, identifier: &str, function: &Function) -> String {
+        let instructions = if self.stack_guard {
+            Self::guard_stack(identifier, &function.instructions)
+        } else {
+            function.instructions.clone()
+        };
+
+        let mut assembly = Label(identifier.to_owned()).render();
+        for instruction in &instructions {
+            assembly.push_str(&format!("{}\n", instruction.render()));
+        }
+
+        assembly
+    }
+
+    fn write_functions<'s>(
+        &mut self,
+        file: &mut File,
+        functions: impl IntoIterator<Item = (&'s String, &'s Function)>,
+    ) -> Result<(), Box<dyn Error>> {
         file.write_all("\nsection .text\n".as_bytes())?;
 
-        for (identifier, function) in &scope.functions {
-            file.write_all(format!("{}", Label(identifier.to_owned())).as_bytes())?;
+        for (identifier, function) in functions {
+            debug!(
+                "Generated function '{identifier}' ({} instructions)",
+                function.instructions.len()
+            );
+            file.write_all(self.render_function(identifier, function).as_bytes())?;
+        }
+
+        Ok(())
+    }
 
-            for instruction in &function.instructions {
-                file.write_all(format!("{instruction}\n").as_bytes())?;
+    /// Collect the names of all functions of a scope, which are (transitively) referenced by its
+    /// top level instructions.
+    fn reachable_functions(scope: &Scope) -> BTreeSet<String> {
+        Self::referenced_functions(scope, &scope.instructions)
+    }
+
+    /// Collect the names of all functions of a scope, which are (transitively) referenced by the
+    /// given instructions.
+    fn referenced_functions(scope: &Scope, instructions: &Vec<Instruction>) -> BTreeSet<String> {
+        let mut reachable = BTreeSet::new();
+        let mut pending = vec![instructions];
+
+        while let Some(instructions) = pending.pop() {
+            for instruction in instructions {
+                // comments might mention functions without referencing them
+                if let Comment(_) = instruction {
+                    continue;
+                }
+
+                // look at every symbol of the instruction, so that we also catch references from
+                // inline assembly (hoisted functions contain a `$` in their symbol)
+                let instruction = instruction.to_string();
+                let symbols =
+                    instruction.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'));
+
+                for symbol in symbols {
+                    if let Some((name, function)) = scope.functions.get_key_value(symbol) {
+                        if reachable.insert(name.to_owned()) {
+                            pending.push(&function.instructions);
+                        }
+                    }
+                }
             }
         }
 
-        Ok(())
+        reachable
+    }
+
+    /// Generate the assembly of the given functions of the main program (without compiling the
+    /// whole program), together with all functions of the main program they reference. Functions
+    /// can be named like in the source code or like their (ASCII encoded) assembly symbol.
+    pub fn function_assembly(&self, names: &[String]) -> Result<String, UnknownFunction> {
+        let mut scope = self.scope.clone();
+        scope.compile();
+
+        let mut functions = BTreeSet::new();
+
+        for name in names {
+            let Some(identifier) = scope
+                .functions
+                .keys()
+                .find(|identifier| *identifier == name || ascii_symbols(identifier) == *name)
+            else {
+                return Err(UnknownFunction {
+                    name: name.to_owned(),
+                    available: scope.functions.keys().cloned().collect(),
+                });
+            };
+
+            functions.insert(identifier.to_owned());
+            functions.extend(Self::referenced_functions(
+                &scope,
+                &scope.functions[identifier].instructions,
+            ));
+        }
+
+        Ok(functions
+            .iter()
+            .map(|identifier| self.render_function(identifier, &scope.functions[identifier]))
+            .collect())
     }
 
     fn write_prelude(&mut self, file: &mut File) -> Result<(), Box<dyn Error>> {
-        let prelude = Self::prelude();
+        let mut prelude = Self::prelude();
+        if self.stack_guard {
+            prelude.append(&mut Self::stack_overflow_handler());
+        }
+        if self.checked_arithmetic {
+            prelude.append(&mut Self::integer_overflow_handler());
+        }
+
         for instruction in &prelude {
-            file.write_all(format!("{instruction}\n").as_bytes())?;
+            file.write_all(format!("{}\n", instruction.render()).as_bytes())?;
         }
 
         Ok(())
@@ -145,7 +626,16 @@ impl Compiler {
 
         self.write_external_symbols(file, scope)?;
 
-        self.write_functions(file, scope)?;
+        // functions of the main program are not visible to other modules, so we can omit all
+        // functions which are never referenced
+        let reachable = Self::reachable_functions(scope);
+        self.write_functions(
+            file,
+            scope
+                .functions
+                .iter()
+                .filter(|(identifier, _)| reachable.contains(*identifier)),
+        )?;
         self.write_prelude(file)?;
 
         #[cfg(target_os = "macos")]
@@ -154,20 +644,33 @@ impl Compiler {
         #[cfg(target_os = "linux")]
         let mut instructions = vec![Label("main".to_owned())];
 
+        // the C runtime passes argc, argv and envp to the entry point
+        instructions.append(&mut vec![
+            Mov(Memory(Qword, "args_count".to_owned()), Register(Rdi)),
+            Mov(Memory(Qword, "args_values".to_owned()), Register(Rsi)),
+            Mov(Memory(Qword, "env_values".to_owned()), Register(Rdx)),
+        ]);
+
+        if self.stack_guard {
+            instructions.append(&mut Self::stack_limit_setup());
+        }
+
         instructions.append(&mut self.scope.instructions.clone());
 
+        debug!(
+            "Generated the top-level statements ({} instructions)",
+            instructions.len()
+        );
         for instruction in &instructions {
-            file.write_all(format!("{instruction}\n").as_bytes())?;
+            file.write_all(format!("{}\n", instruction.render()).as_bytes())?;
         }
 
         Ok(())
     }
 
     fn write_exit(&self, file: &mut File) -> Result<(), Box<dyn Error>> {
-        file.write_all(format!("{}\n", Label("exit".to_owned())).as_bytes())?;
-        file.write_all(format!("{}\n", Mov(Register(Rax), EXIT_SYSCALL)).as_bytes())?;
         file.write_all(format!("{}\n", Mov(Register(Rdi), Immediate(0))).as_bytes())?;
-        file.write_all(format!("{Syscall}\n").as_bytes())?;
+        file.write_all(format!("{}\n", Jmp("exit".to_owned())).as_bytes())?;
 
         Ok(())
     }
@@ -188,18 +691,15 @@ impl Compiler {
         info!("Compiling '{}.asm'...", target.to_string_lossy());
 
         #[cfg(target_os = "macos")]
-        let output = Command::new("nasm")
-            .args([
-                "-f",
-                "macho64",
-                &format!("{}.asm", target.to_string_lossy()),
-            ])
-            .output()?;
+        let output = Tool::Assembler.run([
+            "-f",
+            "macho64",
+            &format!("{}.asm", target.to_string_lossy()),
+        ])?;
 
         #[cfg(target_os = "linux")]
-        let output = Command::new("nasm")
-            .args(["-f", "elf64", &format!("{}.asm", target.to_string_lossy())])
-            .output()?;
+        let output =
+            Tool::Assembler.run(["-f", "elf64", &format!("{}.asm", target.to_string_lossy())])?;
 
         let stderr = std::str::from_utf8(&output.stderr)?;
 
@@ -211,8 +711,6 @@ impl Compiler {
     }
 
     fn link_program(&mut self, target: PathBuf, files: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
-        info!("Linking program...");
-
         let mut args = Vec::<String>::new();
 
         #[cfg(target_os = "macos")]
@@ -220,6 +718,13 @@ impl Compiler {
             args.extend(["-arch", "x86_64"].map(|s| s.to_string()));
         }
 
+        // the generated assembly only uses rip-relative addressing, so it can be linked either way
+        match self.reloc_model {
+            RelocModel::Static => args.push("-no-pie".to_string()),
+            RelocModel::Pic => args.push("-pie".to_string()),
+            RelocModel::Default => {}
+        }
+
         args.push("-o".to_string());
 
         let target = target.to_string_lossy();
@@ -235,7 +740,13 @@ impl Compiler {
 
         args.append(&mut files);
 
-        let output = Command::new("cc").args(args.as_slice()).output()?;
+        args.extend(
+            self.link_objects
+                .iter()
+                .map(|object| object.to_string_lossy().to_string()),
+        );
+
+        let output = Tool::Linker.run(args)?;
 
         let stderr = std::str::from_utf8(&output.stderr)?;
 
@@ -248,14 +759,30 @@ impl Compiler {
 
     fn compile_module(
         &mut self,
-        module: &Module<TypeInfo>,
-        folder: PathBuf,
+        mut module: Module<TypeInfo>,
+        target: &Path,
     ) -> Result<PathBuf, Box<dyn Error>> {
-        let mut scope = Scope::from_statements(module.ast.nodes(), 0, true, Some(module.clone()));
+        // the statements are moved into the scope, which only needs the module to resolve names
+        let statements = std::mem::take(&mut module.ast).into_nodes();
+        let module = Rc::new(module);
+        let mut scope = Scope::from_statements(statements, 0, true, Some(module.clone()));
+        scope.slot_reuse = self.slot_reuse;
+        scope.asserts = self.asserts;
+        scope.checked_arithmetic = self.checked_arithmetic;
         scope.compile();
 
-        let mut output = folder;
-        output.push(module.name.clone());
+        if let Some(error) = scope.errors.first() {
+            return Err(error.clone().into());
+        }
+
+        // prefix module artifacts with the name of the target, so that multiple programs can be
+        // compiled into the same folder at the same time
+        let mut output = target.to_path_buf();
+        output.set_file_name(format!(
+            "{}_{}",
+            target.file_name().unwrap_or_default().to_string_lossy(),
+            module.name
+        ));
 
         let mut file = File::create(format!("{}.asm", output.to_string_lossy()))?;
 
@@ -267,8 +794,12 @@ impl Compiler {
 
         self.write_external_symbols(&mut file, &scope)?;
 
+        if self.stack_guard {
+            file.write_all("extern stack_limit\nextern stack_overflow\n".as_bytes())?;
+        }
+
         self.write_data_from_scope(&mut file, &scope)?;
-        self.write_functions(&mut file, &scope)?;
+        self.write_functions(&mut file, &scope.functions)?;
 
         self.compile_nasm(output.clone())?;
 
@@ -276,25 +807,45 @@ impl Compiler {
     }
 
     pub fn compile_program(&mut self, target: PathBuf) -> Result<(), Box<dyn Error>> {
-        info!("Generating code...");
+        let observer = self.observer.clone();
 
-        self.scope.compile();
+        let others = run_stage(observer.as_ref(), Stage::Codegen, || {
+            self.scope.compile();
 
-        let mut folder = target.clone();
-        folder.pop();
+            if let Some(error) = self.scope.errors.first() {
+                return Err(error.clone().into());
+            }
 
-        let modules = self.modules.clone();
+            let modules = std::mem::take(&mut self.modules);
 
-        let mut others = vec![];
+            let mut others = vec![];
 
-        for module in modules.values() {
-            others.push(self.compile_module(module, folder.clone())?);
-        }
+            for module in modules.into_values() {
+                others.push(self.compile_module(module, &target)?);
+            }
 
-        self.write_code(target.clone())?;
-        self.compile_nasm(target.clone())?;
-        self.link_program(target, others)?;
+            self.write_code(target.clone())?;
+            self.compile_nasm(target.clone())?;
 
-        Ok(())
+            Ok(others)
+        })?;
+
+        run_stage(observer.as_ref(), Stage::Link, || {
+            self.link_program(target.clone(), others.clone())
+        })
+        .inspect_err(|error| {
+            if error.is::<Cancelled>() {
+                remove_artifacts(&target, &others);
+            }
+        })
+    }
+}
+
+/// Remove the assembly and object files generated for the given target (and its modules).
+fn remove_artifacts(target: &Path, modules: &[PathBuf]) {
+    for file in modules.iter().chain([&target.to_path_buf()]) {
+        for extension in ["asm", "o"] {
+            let _ = fs::remove_file(format!("{}.{extension}", file.to_string_lossy()));
+        }
     }
 }
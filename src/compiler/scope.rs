@@ -1,20 +1,26 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
 
 use Instruction::*;
 use InstructionOperand::*;
 use Reg::*;
 
 use crate::{
-    asm::{Instruction, InstructionOperand, InstructionSize, Reg},
+    asm::{Instruction, InstructionOperand, InstructionSize, Reg, EXIT_SYSCALL},
     ast::{
-        Array, Assignment, BinaryOp, Block, Boolean, Call, Character, CompilerDirective,
-        Definition, Expression, Ident, If, InlineAssembly, Integer, Intrinsic, PostfixExpr,
-        PostfixOp, Statement, WhileLoop,
+        Array, Assignment, BinaryExpr, BinaryOp, Block, Boolean, Break, Call, Character,
+        CompilerDirective, Declaration, Definition, Expression, Ident, If, InlineAssembly, Integer,
+        Intrinsic, LabeledBlock, Loop, Param, Position, PostfixExpr, PostfixOp, PrefixExpr,
+        PrefixOp, Statement, Type, WhileLoop,
     },
     loader::Module,
-    typechecker::{TypeInfo, VariableType},
+    typechecker::{invariant_reads, is_assertion, modified_variables, TypeInfo, VariableType},
 };
 
+use super::{escape_analysis::may_escape, CompileError};
+
 #[derive(Debug, Clone)]
 pub struct Variable {
     offset: usize,
@@ -37,17 +43,51 @@ struct Parameter {
     name: String,
     info: TypeInfo,
     source: InstructionOperand,
+    /// The source of the length of a slice parameter.
+    length_source: Option<InstructionOperand>,
 }
 
 type Parameters = Vec<Parameter>;
 
-type VariableMap = HashMap<String, Variable>;
+type VariableMap = BTreeMap<String, Variable>;
+
+type ConstantsMap = BTreeMap<String, Constant>;
+
+type FunctionMap = BTreeMap<String, Function>;
 
-type ConstantsMap = HashMap<String, Constant>;
+type ExternSymbols = BTreeSet<String>;
 
-type FunctionMap = HashMap<String, Function>;
+/// A data symbol holding the value of a variable.
+#[derive(Debug, Clone)]
+struct Global {
+    symbol: String,
+    /// Whether the variable holds a slice, i.e., its pointer and its length.
+    is_slice: bool,
+}
 
-type ExternSymbols = HashSet<String>;
+type GlobalSymbols = BTreeMap<String, Global>;
+
+/// Functions provided by the prelude of every program (each taking at most a single parameter).
+const BUILTINS: [&str; 10] = [
+    "str_len",
+    "int_to_str",
+    "str_to_int",
+    "is_int",
+    "char_to_int",
+    "int_to_char",
+    "args",
+    "arg_count",
+    "env",
+    "exit",
+];
+
+/// Registers used for passing parameters to functions (in this order). Slices occupy two of them:
+/// one for the pointer to their first element and one for their length.
+const PARAM_REGISTERS: [Reg; 6] = [Rdi, Rsi, Rdx, Rcx, R8, R9];
+
+/// Slices are stored as a pointer to their first element, followed by their length. While being
+/// evaluated, the pointer is held in RAX and the length in RDX.
+const SLICE_SIZE: usize = 16;
 
 #[derive(Clone, Debug, Default)]
 pub struct Scope {
@@ -58,12 +98,44 @@ pub struct Scope {
     pub functions: FunctionMap,
     pub instructions: Vec<Instruction>,
     pub externals: ExternSymbols,
+    /// Errors of constructs, which could not be compiled.
+    pub errors: Vec<CompileError>,
     var_count: usize,
     pub stack_offset: usize,
+    /// The largest stack offset reached within this scope (including nested blocks, whose space
+    /// is reused after they end).
+    max_stack_offset: usize,
+    /// Whether nested blocks may reuse the stack space of previous blocks.
+    pub slot_reuse: bool,
+    /// Whether assertions are compiled.
+    pub asserts: bool,
+    /// Whether integer additions, subtractions and multiplications terminate the program on
+    /// overflow (instead of wrapping around).
+    pub checked_arithmetic: bool,
     level: usize,
     level_count: usize,
     new_stack_frame: bool,
-    module: Option<Module<TypeInfo>>,
+    module: Option<Rc<Module<TypeInfo>>>,
+    /// Labels marking the end of each loop and labeled block enclosing the current position
+    /// (innermost last), along with the label of the respective labeled block and the offset of the
+    /// stack slot holding the stack pointer at its start.
+    loop_labels: Vec<(Option<String>, String, usize)>,
+    /// Data symbols by the name of the variable they hold, i.e., data declared via `declare`
+    /// (defined outside of this program) and top-level variables accessed by functions.
+    globals: GlobalSymbols,
+    /// Names of the identifiers referenced within functions, which decide which top-level
+    /// variables have to be stored as data instead of on the stack.
+    pub captured: BTreeSet<String>,
+    /// Data symbols of the top-level variables accessed by functions, along with their size (in
+    /// bytes).
+    pub global_variables: BTreeMap<String, usize>,
+    /// The return type of the function this scope is the body of.
+    return_type: VariableType,
+    /// The symbol of the function this scope belongs to (`None` for the top-level statements).
+    function: Option<String>,
+    /// Symbols of the functions defined within other functions, by their name. They are hoisted
+    /// to the top level, named after the function they are defined in.
+    local_functions: BTreeMap<String, String>,
 }
 
 impl Scope {
@@ -71,22 +143,34 @@ impl Scope {
         statements: Vec<Statement<TypeInfo>>,
         level: usize,
         new_stack_frame: bool,
-        module: Option<Module<TypeInfo>>,
+        module: Option<Rc<Module<TypeInfo>>>,
     ) -> Self {
         Self {
             statements,
             level,
             params: vec![],
-            variables: HashMap::default(),
-            constants: HashMap::default(),
-            functions: HashMap::default(),
+            variables: BTreeMap::default(),
+            constants: BTreeMap::default(),
+            functions: BTreeMap::default(),
             instructions: vec![],
-            externals: HashSet::default(),
+            externals: BTreeSet::default(),
+            errors: vec![],
             var_count: 0,
             stack_offset: 0,
+            max_stack_offset: 0,
+            slot_reuse: true,
+            asserts: true,
+            checked_arithmetic: false,
             level_count: level,
             new_stack_frame,
             module,
+            loop_labels: vec![],
+            globals: BTreeMap::default(),
+            captured: BTreeSet::default(),
+            global_variables: BTreeMap::default(),
+            return_type: VariableType::Void,
+            function: None,
+            local_functions: BTreeMap::default(),
         }
     }
 
@@ -96,23 +180,50 @@ impl Scope {
         var_name
     }
 
+    /// Take over the functions of a nested scope (e.g., functions defined within a function or
+    /// a block), since all functions are emitted at the top level.
+    fn hoist_functions(&mut self, functions: FunctionMap) {
+        for (key, function) in functions {
+            self.functions.entry(key).or_insert(function);
+        }
+    }
+
     fn level(&mut self) -> usize {
         self.level_count += 1;
         self.level_count
     }
 
-    pub fn add_param(&mut self, name: impl ToString, info: TypeInfo, source: InstructionOperand) {
-        self.params.push(Parameter {
-            name: name.to_string(),
-            info,
-            source,
-        });
+    fn add_params(&mut self, params: &[Param<TypeInfo>]) {
+        let mut registers = PARAM_REGISTERS.iter();
+        let mut next_register = || match registers.next() {
+            Some(register) => *register,
+            None => unimplemented!("More than 6 function parameters are currently not supported"),
+        };
+
+        for Param { ident, .. } in params {
+            let info = &ident.info;
+            let source = Register(next_register().to_sized(info));
+            let length_source = Self::is_slice(&info._type).then(|| Register(next_register()));
+
+            self.params.push(Parameter {
+                name: ident.value.to_owned(),
+                info: info.clone(),
+                source,
+                length_source,
+            });
+        }
     }
 
     pub fn compile(&mut self) {
         let statements = self.statements.clone();
 
-        for Parameter { name, info, source } in &self.params {
+        for Parameter {
+            name,
+            info,
+            source,
+            length_source,
+        } in &self.params.clone()
+        {
             match info._type.clone() {
                 VariableType::Void => {
                     unimplemented!("Parameters of type void are currently not supported")
@@ -125,7 +236,6 @@ impl Scope {
                 | VariableType::Any
                 | VariableType::Unknown
                 | VariableType::Func { .. }
-                | VariableType::ArraySlice(_)
                 | VariableType::Reference(_) => {
                     self.stack_offset += info.var_size();
 
@@ -145,10 +255,39 @@ impl Scope {
                         source.to_owned(),
                     ));
                 }
+                // slices consist of a pointer and their length
+                VariableType::ArraySlice(_) => {
+                    self.stack_offset += SLICE_SIZE;
+
+                    let variable = Variable {
+                        offset: self.stack_offset,
+                        _type: info._type.clone(),
+                    };
+                    self.variables.insert(name.to_owned(), variable);
+                    self.instructions.push(Comment(format!(
+                        "{name} = {source}, {}",
+                        length_source.as_ref().unwrap()
+                    )));
+
+                    self.instructions.push(Mov(
+                        Memory(
+                            InstructionSize::Qword,
+                            format!("{}-{}", Rbp, self.stack_offset),
+                        ),
+                        source.to_owned(),
+                    ));
+                    self.instructions.push(Mov(
+                        Memory(
+                            InstructionSize::Qword,
+                            format!("{}-{}", Rbp, self.stack_offset - 8),
+                        ),
+                        length_source.to_owned().unwrap(),
+                    ));
+                }
                 // for arrays on the other hand, we need to copy each element from the calling
                 // function into our own stack
-                VariableType::TupleArray { item_type, size } => {
-                    self.stack_offset += item_type.size() * size;
+                VariableType::TupleArray { .. } => {
+                    self.stack_offset += Self::storage_size(&info._type);
                     let variable = Variable {
                         offset: self.stack_offset,
                         _type: info._type.clone(),
@@ -156,34 +295,30 @@ impl Scope {
                     self.variables.insert(name.to_owned(), variable);
                     self.instructions
                         .push(Comment(format!("{name} = {source}")));
-                    for i in 0..size {
-                        self.instructions.push(Mov(
-                            Register(Rax.to_sized(info)),
-                            Memory(
-                                InstructionSize::from(info.clone()),
-                                format!("{}+{}", source, i as i64 * item_type.size() as i64),
-                            ),
-                        ));
-                        self.instructions.push(Mov(
-                            Memory(
-                                InstructionSize::from(info.clone()),
-                                format!(
-                                    "{}-{}",
-                                    Rbp,
-                                    self.stack_offset as i64 - i as i64 * item_type.size() as i64
-                                ),
-                            ),
-                            Register(Rax.to_sized(info)),
-                        ));
-                    }
+                    self.copy_array(
+                        &source.to_string(),
+                        &format!("{Rbp}-{}", self.stack_offset),
+                        &info._type,
+                    );
                 }
             }
         }
 
-        for node in statements {
-            self.compile_statement(&node);
+        for node in &statements {
+            self.compile_statement(node);
+        }
+
+        // the value of a function body might decay to a slice (e.g., an array), when the function
+        // returns one
+        if self.new_stack_frame {
+            if let Some(Statement::Expression(expression)) = statements.last() {
+                let return_type = self.return_type.clone();
+                self.compile_slice_conversion(&expression.info()._type, &return_type);
+            }
         }
 
+        let frame_size = self.frame_size();
+
         let mut instructions = if self.new_stack_frame {
             vec![
                 Comment("Save old stack pointer".to_owned()),
@@ -193,10 +328,7 @@ impl Scope {
                     "Adjust stack pointer by the amount of space allocated in this stack frame"
                         .to_owned(),
                 ),
-                Sub(
-                    Register(Rsp),
-                    Immediate(((self.stack_offset as i64 / 16) + 1) * 16),
-                ),
+                Sub(Register(Rsp), Immediate(frame_size)),
             ]
         } else {
             vec![]
@@ -209,14 +341,169 @@ impl Scope {
             self.instructions.push(Comment(
                 "Adjust stack pointer to fit the previous one".to_owned(),
             ));
-            self.instructions.push(Add(
-                Register(Rsp),
-                Immediate(((self.stack_offset as i64 / 16) + 1) * 16),
-            ));
+            self.instructions
+                .push(Add(Register(Rsp), Immediate(frame_size)));
             self.instructions.push(Pop(Rbp));
         }
     }
 
+    /// The (16 byte aligned) amount of stack space needed by this scope.
+    fn frame_size(&self) -> i64 {
+        let stack_offset = self.stack_offset.max(self.max_stack_offset) as i64;
+        ((stack_offset / 16) + 1) * 16
+    }
+
+    /// Check, whether the value of a block might point into the stack space of the block (e.g.,
+    /// an array defined within it).
+    pub(super) fn may_point_into_stack(_type: &VariableType) -> bool {
+        matches!(
+            _type,
+            VariableType::Str
+                | VariableType::Any
+                | VariableType::Unknown
+                | VariableType::ArraySlice(_)
+                | VariableType::TupleArray { .. }
+                | VariableType::Reference(_)
+        )
+    }
+
+    fn is_slice(_type: &VariableType) -> bool {
+        matches!(_type, VariableType::ArraySlice(_))
+    }
+
+    /// The amount of memory occupied by a value of the given type. Arrays within arrays are stored
+    /// inline (instead of as a pointer), so arrays occupy the memory of all their items.
+    fn storage_size(_type: &VariableType) -> usize {
+        match _type {
+            VariableType::TupleArray { item_type, size } => Self::storage_size(item_type) * size,
+            _type => _type.size(),
+        }
+    }
+
+    /// Copy the array of the given type at the address in `source` to the address `target` (e.g.,
+    /// `rbp-16`). The items (of nested arrays) are copied one by one, so no memory after the
+    /// array is touched.
+    fn copy_array(&mut self, source: &str, target: &str, _type: &VariableType) {
+        let mut item_type = _type;
+        while let VariableType::TupleArray {
+            item_type: inner, ..
+        } = item_type
+        {
+            item_type = inner;
+        }
+
+        let item_info = TypeInfo {
+            _type: item_type.clone(),
+            source: None,
+        };
+        let item_size = item_type.size();
+
+        for offset in (0..Self::storage_size(_type)).step_by(item_size) {
+            self.instructions.push(Mov(
+                Register(R9.to_sized(&item_info)),
+                Memory(
+                    InstructionSize::from(item_info.clone()),
+                    format!("{source}+{offset}"),
+                ),
+            ));
+            self.instructions.push(Mov(
+                Memory(
+                    InstructionSize::from(item_info.clone()),
+                    format!("{target}+{offset}"),
+                ),
+                Register(R9.to_sized(&item_info)),
+            ));
+        }
+    }
+
+    /// Compute the address of the item (of the given array type) with the index in RCX within the
+    /// array RAX points to (in RAX).
+    fn compile_item_address(&mut self, item_type: &VariableType) {
+        self.instructions.push(Imul(
+            Register(Rcx),
+            Immediate(Self::storage_size(item_type) as i64),
+        ));
+        self.instructions.push(Add(Register(Rax), Register(Rcx)));
+    }
+
+    /// Load the slice stored at the given offset into RAX (pointer) and RDX (length).
+    fn load_slice(&mut self, offset: usize) {
+        self.instructions.push(Mov(
+            Register(Rax),
+            Memory(InstructionSize::Qword, format!("{Rbp}-{offset}")),
+        ));
+        self.instructions.push(Mov(
+            Register(Rdx),
+            Memory(InstructionSize::Qword, format!("{Rbp}-{}", offset - 8)),
+        ));
+    }
+
+    /// Store the slice in RAX (pointer) and RDX (length) at the given offset.
+    fn store_slice(&mut self, offset: usize) {
+        self.instructions.push(Mov(
+            Memory(InstructionSize::Qword, format!("{Rbp}-{offset}")),
+            Register(Rax),
+        ));
+        self.instructions.push(Mov(
+            Memory(InstructionSize::Qword, format!("{Rbp}-{}", offset - 8)),
+            Register(Rdx),
+        ));
+    }
+
+    /// Load the slice stored at the given data symbol into RAX (pointer) and RDX (length).
+    fn load_global_slice(&mut self, symbol: &str) {
+        self.instructions.push(Mov(
+            Register(Rax),
+            Memory(InstructionSize::Qword, symbol.to_owned()),
+        ));
+        self.instructions.push(Mov(
+            Register(Rdx),
+            Memory(InstructionSize::Qword, format!("{symbol} + 8")),
+        ));
+    }
+
+    /// Store the slice in RAX (pointer) and RDX (length) at the given data symbol.
+    fn store_global_slice(&mut self, symbol: &str) {
+        self.instructions.push(Mov(
+            Memory(InstructionSize::Qword, symbol.to_owned()),
+            Register(Rax),
+        ));
+        self.instructions.push(Mov(
+            Memory(InstructionSize::Qword, format!("{symbol} + 8")),
+            Register(Rdx),
+        ));
+    }
+
+    /// Convert the value in RAX (of type `from`) to a slice, if `to` is one. Arrays and strings
+    /// decay to a pointer to their first element (which they already are) and their length (in
+    /// RDX). The length of a string includes its terminating `0` character.
+    fn compile_slice_conversion(&mut self, from: &VariableType, to: &VariableType) {
+        if !Self::is_slice(to) {
+            return;
+        }
+
+        match from {
+            VariableType::TupleArray { size, .. } => {
+                self.instructions
+                    .push(Mov(Register(Rdx), Immediate(*size as i64)));
+            }
+            VariableType::Str => {
+                self.instructions.push(Push(Rax));
+                self.instructions.push(Mov(Register(Rdi), Register(Rax)));
+                self.instructions.push(Call("str_len".to_owned()));
+                if self.module.is_some() {
+                    self.externals.insert("str_len".to_owned());
+                }
+                self.instructions.push(Inc(Rax));
+                self.instructions.push(Mov(Register(Rdx), Register(Rax)));
+                self.instructions.push(Pop(Rax));
+            }
+            VariableType::Reference(inner) => self.compile_slice_conversion(inner, to),
+            // slices already carry their length
+            _ => {}
+        }
+    }
+
     fn compile_statement(&mut self, statement: &Statement<TypeInfo>) {
         match statement {
             Statement::Expression(expression) => self.compile_expression(expression),
@@ -288,14 +575,14 @@ impl Scope {
                 // Compile the seconds expression. (RTL evaluation)
                 // This will store the result of this expression in RAX
                 self.compile_expression(rhs);
-                // Save value on stack
-                self.instructions.push(Push(Rax.to_sized(&rhs.info())));
+                // Save value on stack (the stack only supports full registers)
+                self.instructions.push(Push(Rax));
 
                 // Evaluate second expression
                 self.compile_expression(lhs);
 
                 // Get value from first expression
-                self.instructions.push(Pop(Rcx.to_sized(&rhs.info())));
+                self.instructions.push(Pop(Rcx));
 
                 self.instructions.push(Comment(format!(
                     "{:?} {} {:?}",
@@ -336,18 +623,53 @@ impl Scope {
                         self.instructions.push(Setl(Register(Al)));
                         self.instructions.push(Movzx(Register(Eax), Register(Al)));
                     }
-                    BinaryOp::Equal => {
-                        self.instructions.push(Cmp(
-                            Register(Rax.to_sized(&info)),
-                            Register(Rcx.to_sized(&info)),
-                        ));
-                        self.instructions.push(Sete(Register(Al)));
-                        self.instructions.push(Movzx(Register(Eax), Register(Al)));
-                    }
+                    BinaryOp::Equal => self.compile_equality(&info),
+                    BinaryOp::BitAnd => self.instructions.push(And(
+                        Register(Rax.to_sized(&info)),
+                        Register(Rcx.to_sized(&info)),
+                    )),
+                    BinaryOp::BitOr => self.instructions.push(Or(
+                        Register(Rax.to_sized(&info)),
+                        Register(Rcx.to_sized(&info)),
+                    )),
+                    BinaryOp::BitXor => self.instructions.push(Xor(
+                        Register(Rax.to_sized(&info)),
+                        Register(Rcx.to_sized(&info)),
+                    )),
+                    // the shift count is taken from CL (of which only the lowest 6 bits are used)
+                    BinaryOp::ShiftLeft => self
+                        .instructions
+                        .push(Shl(Register(Rax.to_sized(&info)), Register(Cl))),
+                    BinaryOp::ShiftRight => self
+                        .instructions
+                        .push(Sar(Register(Rax.to_sized(&info)), Register(Cl))),
                 };
+
+                if self.checked_arithmetic
+                    && binary_expression.info._type == VariableType::Int
+                    && matches!(
+                        binary_expression.op,
+                        BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Times
+                    )
+                {
+                    self.compile_overflow_check(&binary_expression.position);
+                }
             }
-            Expression::Prefix(_) => {
-                unimplemented!("Compiling prefix expressions is not supported yet!")
+            Expression::Prefix(PrefixExpr { op, rhs, info, .. }) => {
+                self.compile_expression(rhs);
+
+                self.instructions.push(Comment(format!("{op}{rhs:?}")));
+
+                match op {
+                    PrefixOp::UnaryMinus => {
+                        self.instructions.push(Neg(Register(Rax.to_sized(info))))
+                    }
+                    PrefixOp::BitNot => self.instructions.push(Not(Register(Rax.to_sized(info)))),
+                    // booleans are either 0 or 1
+                    PrefixOp::Not => self
+                        .instructions
+                        .push(Xor(Register(Rax.to_sized(info)), Immediate(1))),
+                }
             }
             Expression::Postfix(PostfixExpr {
                 lhs,
@@ -355,9 +677,7 @@ impl Scope {
                 ..
             }) => match **lhs {
                 Expression::Ident(ref ident) => self.compile_fn_call(ident, call),
-                _ => unimplemented!(
-                    "Compiling calls on non-identifier expressions is not supported yet!"
-                ),
+                ref callee => self.compile_expression_call(callee, call),
             },
             Expression::Postfix(PostfixExpr {
                 lhs,
@@ -370,6 +690,13 @@ impl Scope {
                 self.compile_expression(lhs);
 
                 self.instructions.push(Pop(Rcx));
+
+                // items of arrays of arrays are stored inline, so their address is their value
+                if let VariableType::TupleArray { .. } = indexing.info._type {
+                    self.compile_item_address(&indexing.info._type);
+                    return;
+                }
+
                 self.instructions.push(Mov(
                     Register(Rax.to_sized(&indexing.info)),
                     Memory(
@@ -426,6 +753,7 @@ impl Scope {
                                 Memory(InstructionSize::from(info.clone()), format!("{Rax}")),
                             ));
                         }
+                        VariableType::ArraySlice(_) => self.load_slice(offset),
                         _ => {
                             self.instructions.push(Mov(
                                 Register(Rax.to_sized(info)),
@@ -436,16 +764,50 @@ impl Scope {
                             ));
                         }
                     }
+                } else if let Some(symbol) = self.local_functions.get(identifier) {
+                    self.instructions.push(Lea(
+                        Register(Rax.to_sized(info)),
+                        Identifier(symbol.to_owned()),
+                    ));
                 } else if let Some(constant) = self.constants.get(identifier) {
                     self.instructions.push(Lea(
                         Register(Rax.to_sized(info)),
                         Identifier(constant.name.to_owned()),
                     ));
-                } else if self.functions.get(identifier).is_some() {
+                } else if self.functions.contains_key(identifier) {
                     self.instructions.push(Lea(
                         Register(Rax.to_sized(info)),
                         Identifier(identifier.to_owned()),
                     ));
+                } else if let Some(Global { symbol, is_slice }) =
+                    self.globals.get(identifier).cloned()
+                {
+                    if is_slice {
+                        self.load_global_slice(&symbol);
+                    } else {
+                        self.instructions.push(Mov(
+                            Register(Rax.to_sized(info)),
+                            Memory(InstructionSize::from(info.clone()), symbol),
+                        ));
+                    }
+                } else if let VariableType::Func {
+                    source: Some(source),
+                    ..
+                } = &info._type
+                {
+                    // imported functions are referenced via the module they are defined in
+                    let fn_name = source.resolve(identifier);
+                    self.externals.insert(fn_name.clone());
+                    self.instructions
+                        .push(Lea(Register(Rax.to_sized(info)), Identifier(fn_name)));
+                } else if let VariableType::Func { .. } = &info._type {
+                    // functions of the top level may be referenced before their definition
+                    let fn_name = match &self.module {
+                        Some(module) => module.resolve(identifier),
+                        None => identifier.to_owned(),
+                    };
+                    self.instructions
+                        .push(Lea(Register(Rax.to_sized(info)), Identifier(fn_name)));
                 } else {
                     unreachable!(
                         "Could not find variable, constant or function '{identifier}' ({}:{})",
@@ -470,24 +832,18 @@ impl Scope {
                     true,
                     self.module.clone(),
                 );
+                let fn_name = self.var("fn");
 
-                for (index, param) in fn_definition.params.iter().enumerate() {
-                    let identifier = &param.ident;
-
-                    let info = &identifier.info;
-                    let source = match index {
-                        0 => InstructionOperand::Register(Rdi.to_sized(info)),
-                        1 => InstructionOperand::Register(Rsi.to_sized(info)),
-                        2 => InstructionOperand::Register(Rdx.to_sized(info)),
-                        3 => InstructionOperand::Register(Rcx.to_sized(info)),
-                        4 => InstructionOperand::Register(R8.to_sized(info)),
-                        5 => InstructionOperand::Register(R9.to_sized(info)),
-                        _ => unimplemented!(
-                            "More than 6 function parameters are currently not supported"
-                        ),
-                    };
+                function_scope.globals = self.globals.clone();
+                function_scope.slot_reuse = self.slot_reuse;
+                function_scope.asserts = self.asserts;
+                function_scope.checked_arithmetic = self.checked_arithmetic;
+                function_scope.function = Some(fn_name.clone());
+                function_scope.local_functions = self.local_functions.clone();
 
-                    function_scope.add_param(&identifier.value, info.clone(), source);
+                function_scope.add_params(&fn_definition.params);
+                if let VariableType::Func { return_type, .. } = &fn_definition.info._type {
+                    function_scope.return_type = return_type.as_ref().clone();
                 }
 
                 function_scope.compile();
@@ -503,8 +859,8 @@ impl Scope {
                 function_scope.externals.into_iter().for_each(|external| {
                     self.externals.insert(external);
                 });
-
-                let fn_name = self.var("fn");
+                self.errors.append(&mut function_scope.errors);
+                self.hoist_functions(function_scope.functions);
 
                 self.functions
                     .insert(fn_name.to_owned(), Function { instructions });
@@ -515,7 +871,7 @@ impl Scope {
                     Identifier(fn_name),
                 ));
             }
-            Expression::Block(Block { block, .. }) => {
+            Expression::Block(Block { block, info, .. }) => {
                 let mut scope =
                     Scope::from_statements(block.clone(), self.level(), false, self.module.clone());
 
@@ -523,7 +879,19 @@ impl Scope {
                     scope.variables.insert(key.to_owned(), value.to_owned());
                 }
 
+                // functions may be referenced (e.g., passed as callbacks) within the block
+                for (key, function) in &self.functions {
+                    scope.functions.insert(key.to_owned(), function.to_owned());
+                }
+
+                scope.loop_labels = self.loop_labels.clone();
+                scope.function = self.function.clone();
+                scope.local_functions = self.local_functions.clone();
+                scope.globals = self.globals.clone();
                 scope.stack_offset = self.stack_offset;
+                scope.slot_reuse = self.slot_reuse;
+                scope.asserts = self.asserts;
+                scope.checked_arithmetic = self.checked_arithmetic;
                 scope.compile();
 
                 let mut instructions = scope.instructions.clone();
@@ -538,8 +906,20 @@ impl Scope {
                 scope.externals.into_iter().for_each(|external| {
                     self.externals.insert(external);
                 });
-
-                self.stack_offset = scope.stack_offset;
+                self.errors.append(&mut scope.errors);
+                self.hoist_functions(scope.functions);
+
+                self.max_stack_offset = self
+                    .max_stack_offset
+                    .max(scope.stack_offset.max(scope.max_stack_offset));
+
+                // the variables of the block are not accessible after it, so the following
+                // statements can reuse their stack space (as long as neither the value of the block
+                // nor any value stored outside of it points into it)
+                if !self.slot_reuse || Self::may_point_into_stack(&info._type) || may_escape(block)
+                {
+                    self.stack_offset = scope.stack_offset;
+                }
             }
             Expression::Array(array) => {
                 self.instructions.push(Comment(format!(
@@ -547,34 +927,265 @@ impl Scope {
                     array.initializer, array.size
                 )));
 
-                self.store_array_on_stack(array);
+                self.stack_offset += Self::storage_size(&array.info._type);
+                let offset = self.stack_offset;
+                self.store_array_on_stack(array, offset);
 
                 self.instructions.push(Mov(Register(Rax), Register(Rbp)));
                 self.instructions
-                    .push(Sub(Register(Rax), Immediate(self.stack_offset as i64)));
+                    .push(Sub(Register(Rax), Immediate(offset as i64)));
+            }
+            Expression::Loop(loop_expr) => self.compile_loop(loop_expr),
+            Expression::LabeledBlock(labeled_block) => self.compile_labeled_block(labeled_block),
+        }
+    }
+
+    /// Compare the values in RAX and RCX (of the given type) and store the result in RAX. Arrays
+    /// are compared element-wise, strings within them by their content.
+    fn compile_equality(&mut self, info: &TypeInfo) {
+        match &info._type {
+            VariableType::TupleArray { item_type, size } => {
+                self.compile_array_equality(item_type, *size)
             }
+            _ => {
+                self.instructions.push(Cmp(
+                    Register(Rax.to_sized(info)),
+                    Register(Rcx.to_sized(info)),
+                ));
+                self.instructions.push(Sete(Register(Al)));
+                self.instructions.push(Movzx(Register(Eax), Register(Al)));
+            }
+        }
+    }
+
+    fn compile_array_equality(&mut self, item_type: &VariableType, size: usize) {
+        let loop_label = format!(".{}", self.var("array_eq"));
+        let unequal_label = format!("{loop_label}_ne");
+        let end_label = format!("{loop_label}_end");
+
+        let item_info = TypeInfo {
+            _type: item_type.clone(),
+            source: None,
+        };
+        let item_size = item_info.var_size();
+
+        // keep both arrays and the current index on the stack, since comparing the items might
+        // need all registers (e.g., for nested arrays)
+        self.instructions.push(Push(Rax));
+        self.instructions.push(Push(Rcx));
+        self.instructions.push(Xor(Register(Rax), Register(Rax)));
+        self.instructions.push(Push(Rax));
+
+        self.instructions.push(Label(loop_label.clone()));
+        self.instructions.push(Cmp(
+            Memory(InstructionSize::Qword, format!("{Rsp}")),
+            Immediate(size as i64),
+        ));
+        self.instructions.push(Je(end_label.clone()));
+
+        self.instructions.push(Mov(
+            Register(Rdx),
+            Memory(InstructionSize::Qword, format!("{Rsp}")),
+        ));
+        self.instructions.push(Mov(
+            Register(Rax),
+            Memory(InstructionSize::Qword, format!("{Rsp}+16")),
+        ));
+        self.instructions.push(Mov(
+            Register(Rcx),
+            Memory(InstructionSize::Qword, format!("{Rsp}+8")),
+        ));
+        if let VariableType::TupleArray { .. } = item_type {
+            // items of arrays of arrays are stored inline, so they are compared via their address
+            self.instructions.push(Imul(
+                Register(Rdx),
+                Immediate(Self::storage_size(item_type) as i64),
+            ));
+            self.instructions.push(Add(Register(Rax), Register(Rdx)));
+            self.instructions.push(Add(Register(Rcx), Register(Rdx)));
+        } else {
+            self.instructions.push(Mov(
+                Register(Rax.to_sized(&item_info)),
+                Memory(
+                    InstructionSize::from(item_info.clone()),
+                    format!("{Rax} + {Rdx} * {item_size}"),
+                ),
+            ));
+            self.instructions.push(Mov(
+                Register(Rcx.to_sized(&item_info)),
+                Memory(
+                    InstructionSize::from(item_info.clone()),
+                    format!("{Rcx} + {Rdx} * {item_size}"),
+                ),
+            ));
+        }
+
+        match item_type {
+            VariableType::Str => self.compile_string_equality(),
+            _ => self.compile_equality(&item_info),
         }
+
+        self.instructions.push(Cmp(Register(Rax), Immediate(0)));
+        self.instructions.push(Je(unequal_label.clone()));
+        self.instructions.push(Add(
+            Memory(InstructionSize::Qword, format!("{Rsp}")),
+            Immediate(1),
+        ));
+        self.instructions.push(Jmp(loop_label));
+
+        // all items are equal, if we reach the end of the arrays
+        self.instructions.push(Label(unequal_label));
+        self.instructions.push(Mov(
+            Memory(InstructionSize::Qword, format!("{Rsp}")),
+            Immediate(size as i64 + 1),
+        ));
+        self.instructions.push(Label(end_label));
+        self.instructions.push(Pop(Rax));
+        self.instructions
+            .push(Cmp(Register(Rax), Immediate(size as i64)));
+        self.instructions.push(Sete(Register(Al)));
+        self.instructions.push(Movzx(Register(Eax), Register(Al)));
+        self.instructions.push(Add(Register(Rsp), Immediate(16)));
+    }
+
+    /// Compare the (`0` terminated) strings pointed to by RAX and RCX by their content.
+    fn compile_string_equality(&mut self) {
+        let loop_label = format!(".{}", self.var("str_eq"));
+        let end_label = format!("{loop_label}_end");
+
+        self.instructions.push(Label(loop_label.clone()));
+        self.instructions.push(Mov(
+            Register(Dl),
+            Memory(InstructionSize::Byte, format!("{Rax}")),
+        ));
+        self.instructions.push(Cmp(
+            Register(Dl),
+            Memory(InstructionSize::Byte, format!("{Rcx}")),
+        ));
+        self.instructions.push(Jne(end_label.clone()));
+        self.instructions.push(Cmp(Register(Dl), Immediate(0)));
+        self.instructions.push(Je(end_label.clone()));
+        self.instructions.push(Inc(Rax));
+        self.instructions.push(Inc(Rcx));
+        self.instructions.push(Jmp(loop_label));
+
+        self.instructions.push(Label(end_label));
+        self.instructions.push(Sete(Register(Al)));
+        self.instructions.push(Movzx(Register(Eax), Register(Al)));
     }
 
+    fn compile_loop(&mut self, Loop { block, info, .. }: &Loop<TypeInfo>) {
+        let loop_label = format!(".{}", self.var("loop"));
+        let end_label = format!("{loop_label}_end");
+
+        let stack_pointer = self.save_stack_pointer();
+        self.instructions.push(Label(loop_label.clone()));
+
+        // a value passed to break might point into the stack space of any block within the loop
+        let slot_reuse = self.slot_reuse;
+        self.slot_reuse = slot_reuse && !Self::may_point_into_stack(&info._type);
+
+        self.loop_labels
+            .push((None, end_label.clone(), stack_pointer));
+        self.compile_expression(&Expression::Block(block.to_owned()));
+        self.loop_labels.pop();
+
+        self.slot_reuse = slot_reuse;
+
+        self.instructions.push(Jmp(loop_label));
+        // the value of the loop (if any) is placed into RAX by the respective break
+        self.instructions.push(Label(end_label));
+    }
+
+    fn compile_labeled_block(
+        &mut self,
+        LabeledBlock {
+            label, block, info, ..
+        }: &LabeledBlock<TypeInfo>,
+    ) {
+        let end_label = format!(".{}_end", self.var("block"));
+        let stack_pointer = self.save_stack_pointer();
+
+        // a value passed to break might point into the stack space of any block within this one
+        let slot_reuse = self.slot_reuse;
+        self.slot_reuse = slot_reuse && !Self::may_point_into_stack(&info._type);
+
+        self.loop_labels
+            .push((Some(label.to_owned()), end_label.clone(), stack_pointer));
+        self.compile_expression(&Expression::Block(block.to_owned()));
+        self.loop_labels.pop();
+
+        self.slot_reuse = slot_reuse;
+
+        // the value of the block is placed into RAX by its last statement or the respective break
+        self.instructions.push(Label(end_label));
+    }
+
+    fn compile_break(&mut self, Break { label, value, .. }: &Break<TypeInfo>) {
+        if let Some(value) = value {
+            self.compile_expression(value);
+        }
+
+        let Some((_, end_label, stack_pointer)) = self
+            .loop_labels
+            .iter()
+            .rev()
+            .find(|(target, ..)| target == label)
+            .cloned()
+        else {
+            unreachable!("Break outside of loop");
+        };
+
+        // the break might leave expressions which pushed intermediate values onto the stack
+        self.instructions.push(Mov(
+            Register(Rsp),
+            Memory(InstructionSize::Qword, format!("{Rbp}-{stack_pointer}")),
+        ));
+        self.instructions.push(Jmp(end_label));
+    }
+
+    /// Store the stack pointer at the start of a loop or labeled block in a new stack slot, so
+    /// breaks can restore it. Returns the offset of the slot.
+    fn save_stack_pointer(&mut self) -> usize {
+        self.stack_offset += 8;
+        let offset = self.stack_offset;
+        self.instructions.push(Mov(
+            Memory(InstructionSize::Qword, format!("{Rbp}-{offset}")),
+            Register(Rsp),
+        ));
+        offset
+    }
+
+    /// Store an array in the stack space at the given offset.
     fn store_array_on_stack(
         &mut self,
         Array {
-            initializer, size, ..
+            initializer, info, ..
         }: &Array<TypeInfo>,
+        offset: usize,
     ) {
+        // the size of the array is resolved while type checking
+        let VariableType::TupleArray { size, .. } = info._type else {
+            unreachable!("Arrays are always of a tuple array type");
+        };
+
         self.compile_expression(initializer);
 
+        let item_type = initializer.info()._type;
+        let item_size = Self::storage_size(&item_type) as i64;
+
         // TODO: Maybe introduce an ASM loop for that
-        for i in 0..size.value {
+        for i in 0..size as i64 {
+            let target = format!("{}-{}", Rbp, offset as i64 - i * item_size);
+
+            // every item of an array of arrays gets its own copy of the initializer
+            if let VariableType::TupleArray { .. } = item_type {
+                self.copy_array(&Rax.to_string(), &target, &item_type);
+                continue;
+            }
+
             self.instructions.push(Mov(
-                Memory(
-                    InstructionSize::from(initializer.info().clone()),
-                    format!(
-                        "{}-{}",
-                        Rbp,
-                        self.stack_offset as i64 - i * initializer.info().var_size() as i64
-                    ),
-                ),
+                Memory(InstructionSize::from(initializer.info().clone()), target),
                 Register(Rax.to_sized(&initializer.info())),
             ));
         }
@@ -585,35 +1196,150 @@ impl Scope {
             Intrinsic::Definition(definition) => self.compile_definition(definition),
             Intrinsic::Assignment(assignment) => self.compile_assignment(assignment),
             Intrinsic::WhileLoop(while_loop) => self.compile_while_loop(while_loop),
-            // TODO: Maybe compile as "extern"
-            Intrinsic::Declaration(_) => (),
+            Intrinsic::Break(break_stmt) => self.compile_break(break_stmt),
+            Intrinsic::Declaration(declaration) => self.compile_declaration(declaration),
+        }
+    }
+
+    fn compile_declaration(&mut self, declaration: &Declaration) {
+        // declared functions are resolved when they are called, but declared data has to be
+        // referenced as an external symbol
+        if let Type::Function { .. } = declaration.type_annotation.value {
+            return;
         }
+
+        let name = &declaration.ident.value;
+        self.globals.insert(
+            name.to_owned(),
+            Global {
+                symbol: name.to_owned(),
+                is_slice: false,
+            },
+        );
+        self.externals.insert(name.to_owned());
     }
 
     fn compile_while_loop(&mut self, while_loop: &WhileLoop<TypeInfo>) {
         let condition = &while_loop.condition;
         let block = &while_loop.block;
 
-        let while_label = self.var("while");
-        let end_label = format!(".{while_label}_end");
+        // use local labels, so labels of surrounding constructs stay reachable from within the body
+        let while_label = format!(".{}", self.var("while"));
+        let end_label = format!("{while_label}_end");
 
+        let condition = match modified_variables(block) {
+            Some(modified) => self.hoist_invariants(condition, &modified),
+            None => condition.to_owned(),
+        };
+
+        let stack_pointer = self.save_stack_pointer();
         self.instructions.push(Label(while_label.clone()));
 
-        self.compile_expression(condition);
+        self.compile_expression(&condition);
 
         self.instructions
             .push(Cmp(Register(Rax.to_sized(&condition.info())), Immediate(0)));
         self.instructions.push(Je(end_label.clone()));
 
+        self.loop_labels
+            .push((None, end_label.clone(), stack_pointer));
         self.compile_expression(&Expression::Block(block.to_owned()));
+        self.loop_labels.pop();
 
         self.instructions.push(Jmp(while_label));
         self.instructions.push(Label(end_label));
     }
 
+    /// Compute the operations of a loop condition, which do not change while executing the loop
+    /// (i.e., they only read local variables not modified in the loop), once before the loop and
+    /// replace them by a variable holding their value.
+    fn hoist_invariants(
+        &mut self,
+        expression: &Expression<TypeInfo>,
+        modified: &BTreeSet<String>,
+    ) -> Expression<TypeInfo> {
+        let Expression::Binary(binary_expression) = expression else {
+            return expression.to_owned();
+        };
+
+        // only local variables are safe, since functions called in the loop may modify globals or
+        // the target of references
+        let is_local = |ident: &Ident<TypeInfo>| {
+            self.variables.get(&ident.value).is_some_and(|variable| {
+                !matches!(
+                    variable._type,
+                    VariableType::Reference(_) | VariableType::ArraySlice(_)
+                )
+            })
+        };
+
+        if !invariant_reads(expression, modified)
+            .is_some_and(|reads| reads.into_iter().all(is_local))
+        {
+            return Expression::Binary(BinaryExpr {
+                lhs: Box::new(self.hoist_invariants(&binary_expression.lhs, modified)),
+                rhs: Box::new(self.hoist_invariants(&binary_expression.rhs, modified)),
+                ..binary_expression.to_owned()
+            });
+        }
+
+        let BinaryExpr { info, position, .. } = binary_expression;
+        let name = format!(".{}", self.var("invariant"));
+
+        self.compile_expression(expression);
+
+        self.stack_offset += info.var_size();
+        let variable = Variable {
+            offset: self.stack_offset,
+            _type: info._type.clone(),
+        };
+        self.variables.insert(name.to_owned(), variable);
+
+        self.instructions
+            .push(Comment(format!("{name} = {expression:?}")));
+        self.instructions.push(Mov(
+            Memory(
+                InstructionSize::from(info.clone()),
+                format!("{}-{}", Rbp, self.stack_offset),
+            ),
+            Register(Rax.to_sized(info)),
+        ));
+
+        Expression::Ident(Ident {
+            value: name,
+            position: position.to_owned(),
+            info: info.to_owned(),
+        })
+    }
+
     fn compile_definition(&mut self, definition: &Definition<TypeInfo>) {
+        if self.is_global_variable(definition) {
+            self.compile_global_definition(definition);
+        } else {
+            self.compile_local_definition(definition);
+        }
+    }
+
+    fn compile_local_definition(&mut self, definition: &Definition<TypeInfo>) {
         let name = &definition.ident.value;
 
+        // slices (e.g., returned by functions) are stored as their pointer and length
+        if Self::is_slice(&definition.value.info()._type) {
+            self.compile_expression(&definition.value);
+
+            self.stack_offset += SLICE_SIZE;
+            let variable = Variable {
+                offset: self.stack_offset,
+                _type: definition.value.info()._type,
+            };
+            self.variables.insert(name.to_owned(), variable);
+
+            self.instructions
+                .push(Comment(format!("{name} = {:?}", definition.value)));
+            self.store_slice(self.stack_offset);
+            return;
+        }
+
         match &definition.value {
             Expression::Str(string) => {
                 self.add_string_constant(Some(name.to_owned()), &string.value.to_owned());
@@ -628,13 +1354,21 @@ impl Scope {
 
                 self.instructions.push(Comment(format!("{name} = {value}")));
 
-                self.instructions.push(Mov(
-                    Memory(
-                        InstructionSize::from(info.clone()),
-                        format!("{}-{}", Rbp, self.stack_offset),
-                    ),
-                    Immediate(*value),
-                ));
+                let target = Memory(
+                    InstructionSize::from(info.clone()),
+                    format!("{}-{}", Rbp, self.stack_offset),
+                );
+
+                // immediates stored in memory are limited to 32 bits, so larger values have to
+                // be loaded into a register first
+                if i32::try_from(*value).is_ok() {
+                    self.instructions.push(Mov(target, Immediate(*value)));
+                } else {
+                    self.instructions
+                        .push(Mov(Register(Rax.to_sized(info)), Immediate(*value)));
+                    self.instructions
+                        .push(Mov(target, Register(Rax.to_sized(info))));
+                }
             }
             Expression::Character(Character { value, info, .. }) => {
                 self.stack_offset += info.var_size();
@@ -725,8 +1459,29 @@ impl Scope {
                     Register(Rax.to_sized(info)),
                 ));
             }
-            Expression::Prefix(_) => {
-                unimplemented!("Definitions cannot be generated from prefix expressions yet")
+            Expression::Prefix(prefix_expression) => {
+                self.compile_expression(&definition.value);
+
+                let info = &prefix_expression.info;
+                self.stack_offset += info.var_size();
+                let variable = Variable {
+                    offset: self.stack_offset,
+                    _type: info._type.clone(),
+                };
+                self.variables.insert(name.to_owned(), variable);
+
+                self.instructions.push(Comment(format!(
+                    "{} = {}{:?}",
+                    name, prefix_expression.op, prefix_expression.rhs
+                )));
+
+                self.instructions.push(Mov(
+                    Memory(
+                        InstructionSize::from(info.clone()),
+                        format!("{}-{}", Rbp, self.stack_offset),
+                    ),
+                    Register(Rax.to_sized(info)),
+                ));
             }
             Expression::Postfix(PostfixExpr {
                 op: PostfixOp::Call(call),
@@ -764,37 +1519,10 @@ impl Scope {
                             Register(Rax.to_sized(&call.info)),
                         ));
                     }
-                    VariableType::TupleArray { item_type, size } => {
-                        self.stack_offset += item_type.size() * size;
-                        let variable = Variable {
-                            offset: self.stack_offset,
-                            _type: info._type.clone(),
-                        };
-                        self.variables.insert(name.to_owned(), variable);
-
+                    VariableType::TupleArray { .. } => {
                         self.instructions
                             .push(Comment(format!("{name} = {:?}", definition.value)));
-                        for i in 0..size {
-                            self.instructions.push(Mov(
-                                Register(Rcx.to_sized(info)),
-                                Memory(
-                                    InstructionSize::from(info.clone()),
-                                    format!("{}+{}", Rax, i as i64 * item_type.size() as i64),
-                                ),
-                            ));
-                            self.instructions.push(Mov(
-                                Memory(
-                                    InstructionSize::from(info.clone()),
-                                    format!(
-                                        "{}-{}",
-                                        Rbp,
-                                        self.stack_offset as i64
-                                            - i as i64 * item_type.size() as i64
-                                    ),
-                                ),
-                                Register(Rcx.to_sized(info)),
-                            ));
-                        }
+                        self.define_array_copy(name, &call.info._type);
                     }
                 }
             }
@@ -805,6 +1533,14 @@ impl Scope {
             }) => {
                 self.compile_expression(&definition.value);
 
+                // items of arrays of arrays are arrays themselves, which are copied
+                if let VariableType::TupleArray { .. } = indexing.info._type {
+                    self.instructions
+                        .push(Comment(format!("{name} = {:?}", definition.value)));
+                    self.define_array_copy(name, &indexing.info._type);
+                    return;
+                }
+
                 self.stack_offset += indexing.info.var_size();
                 let variable = Variable {
                     offset: self.stack_offset,
@@ -825,6 +1561,13 @@ impl Scope {
             }
             Expression::Ident(Ident { value, info, .. }) => {
                 self.compile_expression(&definition.value);
+
+                if let VariableType::TupleArray { .. } = info._type {
+                    self.instructions.push(Comment(format!("{name} = {value}")));
+                    self.define_array_copy(name, &info._type);
+                    return;
+                }
+
                 self.stack_offset += info.var_size();
                 let variable = Variable {
                     offset: self.stack_offset,
@@ -842,6 +1585,30 @@ impl Scope {
                 ));
             }
             Expression::FnDef(fn_definition) => {
+                let symbol = match &self.function {
+                    // functions defined within other functions are hoisted to the top level
+                    Some(function) => {
+                        let mut symbol = format!("{function}${name}");
+                        if self.functions.contains_key(&symbol) {
+                            symbol = self.var(&symbol);
+                        }
+                        self.local_functions.insert(name.to_owned(), symbol.clone());
+                        symbol
+                    }
+                    None => {
+                        let mut symbol = match &self.module {
+                            Some(module) => module.resolve(name),
+                            None => name.to_owned(),
+                        };
+                        // functions of different top-level blocks may share their name
+                        if self.functions.contains_key(&symbol) {
+                            symbol = self.var(&symbol);
+                            self.local_functions.insert(name.to_owned(), symbol.clone());
+                        }
+                        symbol
+                    }
+                };
+
                 let statements = &fn_definition.block.block;
                 let mut function_scope = Scope::from_statements(
                     statements.clone(),
@@ -855,24 +1622,16 @@ impl Scope {
                         .functions
                         .insert(key.to_owned(), function.to_owned());
                 }
-
-                for (index, param) in fn_definition.params.iter().enumerate() {
-                    let identifier = &param.ident;
-
-                    let info = &identifier.info;
-                    let source = match index {
-                        0 => InstructionOperand::Register(Rdi.to_sized(info)),
-                        1 => InstructionOperand::Register(Rsi.to_sized(info)),
-                        2 => InstructionOperand::Register(Rdx.to_sized(info)),
-                        3 => InstructionOperand::Register(Rcx.to_sized(info)),
-                        4 => InstructionOperand::Register(R8.to_sized(info)),
-                        5 => InstructionOperand::Register(R9.to_sized(info)),
-                        _ => unimplemented!(
-                            "More than 6 function parameters are currently not supported"
-                        ),
-                    };
-
-                    function_scope.add_param(&identifier.value, info.clone(), source);
+                function_scope.function = Some(symbol.clone());
+                function_scope.local_functions = self.local_functions.clone();
+                function_scope.globals = self.globals.clone();
+                function_scope.slot_reuse = self.slot_reuse;
+                function_scope.asserts = self.asserts;
+                function_scope.checked_arithmetic = self.checked_arithmetic;
+
+                function_scope.add_params(&fn_definition.params);
+                if let VariableType::Func { return_type, .. } = &fn_definition.info._type {
+                    function_scope.return_type = return_type.as_ref().clone();
                 }
 
                 function_scope.compile();
@@ -888,15 +1647,10 @@ impl Scope {
                 function_scope.externals.into_iter().for_each(|external| {
                     self.externals.insert(external);
                 });
+                self.errors.append(&mut function_scope.errors);
+                self.hoist_functions(function_scope.functions);
 
-                let mut name = name.clone();
-
-                if let Some(module) = &self.module {
-                    name = module.resolve(&name);
-                }
-
-                // TODO: This does not allow for function definitions in functions
-                self.functions.insert(name, Function { instructions });
+                self.functions.insert(symbol, Function { instructions });
             }
             Expression::Block(Block { block, info, .. }) => {
                 self.compile_expression(&definition.value);
@@ -919,13 +1673,38 @@ impl Scope {
                     Register(Rax),
                 ));
             }
+            Expression::Loop(Loop { info, .. })
+            | Expression::LabeledBlock(LabeledBlock { info, .. }) => {
+                self.compile_expression(&definition.value);
+
+                self.stack_offset += info.var_size();
+                let variable = Variable {
+                    offset: self.stack_offset,
+                    _type: info._type.clone(),
+                };
+                self.variables.insert(name.to_owned(), variable);
+
+                self.instructions.push(Comment(format!(
+                    "{name} = {}",
+                    definition.value.to_source()
+                )));
+
+                self.instructions.push(Mov(
+                    Memory(
+                        InstructionSize::from(info.clone()),
+                        format!("{}-{}", Rbp, self.stack_offset),
+                    ),
+                    Register(Rax.to_sized(info)),
+                ));
+            }
             Expression::Array(array) => {
                 let info = &array.info;
                 let size = &array.size;
 
-                self.stack_offset += info.var_size() * size.value as usize;
+                self.stack_offset += Self::storage_size(&info._type);
+                let offset = self.stack_offset;
                 let variable = Variable {
-                    offset: self.stack_offset,
+                    offset,
                     _type: info._type.clone(),
                 };
                 self.variables.insert(name.to_owned(), variable);
@@ -935,11 +1714,114 @@ impl Scope {
                     array.initializer
                 )));
 
-                self.store_array_on_stack(array);
+                self.store_array_on_stack(array, offset);
             }
         };
     }
 
+    /// Check, whether the given definition is a top-level variable accessed by functions. Since
+    /// functions have no access to the stack frame of the top-level statements, these variables
+    /// are stored in the data section (arrays via their address, see
+    /// `compile_global_definition`).
+    fn is_global_variable(&self, definition: &Definition<TypeInfo>) -> bool {
+        self.function.is_none()
+            && self.module.is_none()
+            && self.level == 0
+            && self.captured.contains(&definition.ident.value)
+            && !matches!(definition.value, Expression::FnDef(_))
+            && matches!(
+                definition.value.info()._type,
+                VariableType::Bool
+                    | VariableType::Str
+                    | VariableType::Int
+                    | VariableType::Char
+                    | VariableType::Func { .. }
+                    | VariableType::ArraySlice(_)
+                    | VariableType::TupleArray { .. }
+            )
+    }
+
+    fn compile_global_definition(&mut self, definition: &Definition<TypeInfo>) {
+        let name = &definition.ident.value;
+        let info = definition.value.info();
+        let symbol = self.var(name);
+
+        // the items of arrays stay on the stack of the top-level statements (which outlives all
+        // functions called from them), only their address is stored as data
+        if let VariableType::TupleArray { .. } = info._type {
+            self.compile_local_definition(definition);
+            let Some(variable) = self.variables.get(name) else {
+                unreachable!("Could not find array '{name}'");
+            };
+
+            self.instructions.push(Mov(Register(Rax), Register(Rbp)));
+            self.instructions
+                .push(Sub(Register(Rax), Immediate(variable.offset as i64)));
+            self.instructions.push(Mov(
+                Memory(InstructionSize::Qword, symbol.clone()),
+                Register(Rax),
+            ));
+
+            self.globals.insert(
+                name.to_owned(),
+                Global {
+                    symbol: symbol.clone(),
+                    is_slice: false,
+                },
+            );
+            self.global_variables.insert(symbol, info.var_size());
+            return;
+        }
+
+        self.compile_expression(&definition.value);
+
+        let is_slice = Self::is_slice(&info._type);
+        self.instructions
+            .push(Comment(format!("{name} = {:?}", definition.value)));
+        if is_slice {
+            self.store_global_slice(&symbol);
+        } else {
+            self.instructions.push(Mov(
+                Memory(InstructionSize::from(info.clone()), symbol.clone()),
+                Register(Rax.to_sized(&info)),
+            ));
+        }
+
+        // a previous definition of the same name is shadowed
+        self.variables.remove(name);
+        self.globals.insert(
+            name.to_owned(),
+            Global {
+                symbol: symbol.clone(),
+                is_slice,
+            },
+        );
+        self.global_variables.insert(
+            symbol,
+            if is_slice {
+                SLICE_SIZE
+            } else {
+                info.var_size()
+            },
+        );
+    }
+
+    /// Define a variable holding a copy of the array (of the given type) RAX points to.
+    fn define_array_copy(&mut self, name: &str, _type: &VariableType) {
+        self.stack_offset += Self::storage_size(_type);
+        let variable = Variable {
+            offset: self.stack_offset,
+            _type: _type.clone(),
+        };
+        self.variables.insert(name.to_owned(), variable);
+
+        self.copy_array(
+            &Rax.to_string(),
+            &format!("{Rbp}-{}", self.stack_offset),
+            _type,
+        );
+    }
+
     fn compile_assignment(&mut self, assignment: &Assignment<TypeInfo>) {
         let value = &assignment.value;
         self.compile_expression(value);
@@ -976,6 +1858,15 @@ impl Scope {
                 // rvalue -> Rax
                 self.instructions.push(Pop(Rax));
 
+                // items of arrays of arrays are stored inline, so the whole array is copied
+                if let VariableType::TupleArray { .. } = indexing.info._type {
+                    self.instructions.push(Mov(Register(Rdx), Register(Rax)));
+                    self.instructions.push(Mov(Register(Rax), Register(R8)));
+                    self.compile_item_address(&indexing.info._type);
+                    self.copy_array(&Rdx.to_string(), &Rax.to_string(), &indexing.info._type);
+                    return;
+                }
+
                 // rvalue -> lvalue[index]
                 self.instructions.push(Mov(
                     Memory(
@@ -985,6 +1876,44 @@ impl Scope {
                     Register(Rax.to_sized(&indexing.info)),
                 ));
             }
+            Expression::Ident(identifier) if !self.variables.contains_key(&identifier.value) => {
+                let info = &identifier.info;
+                let Some(Global { symbol, is_slice }) =
+                    self.globals.get(&identifier.value).cloned()
+                else {
+                    self.errors.push(CompileError {
+                        message: format!(
+                            "Can not assign to '{}', since it is neither a local nor a global variable",
+                            identifier.value
+                        ),
+                        position: identifier.position.clone(),
+                    });
+                    return;
+                };
+
+                self.instructions
+                    .push(Comment(format!("{} = {value:?}", identifier.value)));
+                match &info._type {
+                    // the type of the identifier is the type of the assigned value, which may be
+                    // converted to a slice
+                    _ if is_slice => {
+                        let slice = VariableType::ArraySlice(Box::new(VariableType::Any));
+                        self.compile_slice_conversion(&value.info()._type, &slice);
+                        self.store_global_slice(&symbol);
+                    }
+                    // global arrays are stored via their address, so the items are copied
+                    VariableType::TupleArray { .. } => {
+                        self.instructions.push(Mov(Register(Rdx), Register(Rax)));
+                        self.instructions
+                            .push(Mov(Register(Rax), Memory(InstructionSize::Qword, symbol)));
+                        self.copy_array(&Rdx.to_string(), &Rax.to_string(), &info._type);
+                    }
+                    _ => self.instructions.push(Mov(
+                        Memory(InstructionSize::from(info.clone()), symbol),
+                        Register(Rax.to_sized(info)),
+                    )),
+                }
+            }
             Expression::Ident(identifier) => {
                 let info = &identifier.info;
                 let Some(variable) = self.variables.get(&identifier.value) else {
@@ -1012,6 +1941,14 @@ impl Scope {
                             Register(Rax.to_sized(&info)),
                         ));
                     }
+                    VariableType::ArraySlice(_) => {
+                        let offset = variable.offset;
+                        let var_type = variable._type.clone();
+                        self.instructions
+                            .push(Comment(format!("{} = {value:?}", identifier.value)));
+                        self.compile_slice_conversion(&value.info()._type, &var_type);
+                        self.store_slice(offset);
+                    }
                     // in every other case, we can just store it on the stack
                     _ => {
                         self.instructions
@@ -1031,59 +1968,111 @@ impl Scope {
     }
 
     fn compile_fn_call(&mut self, ident: &Ident<TypeInfo>, call: &Call<TypeInfo>) {
-        let mut name = ident.value.to_owned();
+        let name = ident.value.to_owned();
 
         self.instructions
             .push(Comment(format!("CALL {name} ({:?})", call.params)));
 
-        if name.as_str() == "str_len" {
-            let param = call.params[0].to_owned();
-            match param {
-                Expression::If(_)
-                | Expression::Binary(_)
-                | Expression::Prefix(_)
-                | Expression::Postfix(_)
-                | Expression::Block(_)
-                | Expression::Integer(_)
-                | Expression::Ident(_) => {
-                    self.compile_expression(&param);
-                    self.instructions.append(&mut vec![
-                        Mov(Register(Rdi), Register(Rax)),
-                        Call("str_len".to_owned()),
-                    ])
-                }
-                _ => unreachable!(),
-            }
+        if is_assertion(&name)
+            && !self.variables.contains_key(&name)
+            && !self.functions.contains_key(&name)
+            && !self.local_functions.contains_key(&name)
+        {
+            self.compile_assertion(ident, call);
+            return;
+        }
 
-            self.externals.insert("str_len".to_owned());
+        // the length of a slice is passed along with it, so it does not need a function
+        if name == "len" && !self.variables.contains_key(&name) {
+            if let Some(param) = call.params.first() {
+                self.compile_expression(param);
+                self.compile_slice_conversion(
+                    &param.info()._type,
+                    &VariableType::ArraySlice(Box::new(VariableType::Any)),
+                );
+                self.instructions.push(Mov(Register(Rax), Register(Rdx)));
+            }
             return;
-        } else if name.as_str() == "int_to_str" {
-            let param = call.params[0].to_owned();
-            match param {
-                Expression::If(_)
-                | Expression::Binary(_)
-                | Expression::Prefix(_)
-                | Expression::Postfix(_)
-                | Expression::Block(_)
-                | Expression::Integer(_)
-                | Expression::Ident(_) => {
-                    self.compile_expression(&param);
-                    self.instructions.append(&mut vec![
-                        Mov(Register(Rdi), Register(Rax)),
-                        Call("int_to_str".to_owned()),
-                    ])
-                }
-                _ => unreachable!(),
+        }
+
+        // builtins are part of the prelude of the main program and, therefore, never resolved
+        // via the module they are declared in
+        if BUILTINS.contains(&name.as_str()) {
+            if let Some(param) = call.params.first() {
+                self.compile_expression(param);
+                self.instructions.push(Mov(Register(Rdi), Register(Rax)));
             }
+            self.instructions.push(Call(name.to_owned()));
 
-            self.externals.insert("int_to_str".to_owned());
+            // the main program defines the builtins itself
+            if self.module.is_some() {
+                self.externals.insert(name);
+            }
             return;
         }
 
+        // the type checker rejects calls of values which are no functions
         let VariableType::Func { params, .. } = &ident.info._type else {
             unreachable!("Trying to call a non-function expression");
         };
 
+        self.push_call_params(params, call);
+
+        if self.variables.contains_key(&name) || self.globals.contains_key(&name) {
+            // if we have a variable with this name, we need to load it first
+            self.compile_expression(&Expression::Ident(ident.to_owned()));
+            self.pop_call_params(params);
+            self.instructions.push(Call(Rax.to_string()));
+            return;
+        }
+
+        self.pop_call_params(params);
+
+        match call.info.source() {
+            Some(source) => {
+                let fn_name = name.split("::").last().unwrap();
+                let fn_name = source.resolve(&fn_name.to_string());
+                self.externals.insert(fn_name.clone());
+                self.instructions.push(Call(fn_name));
+            }
+            None => {
+                let fn_name = match (self.local_functions.get(&name), &self.module) {
+                    (Some(symbol), _) => symbol.to_owned(),
+                    (None, Some(module)) => module.resolve(&name),
+                    (None, None) => name,
+                };
+                self.instructions.push(Call(fn_name));
+            }
+        }
+    }
+
+    /// Compile a call of the function which is the value of an arbitrary expression, e.g., a
+    /// function returned by another call or stored in an array.
+    fn compile_expression_call(&mut self, callee: &Expression<TypeInfo>, call: &Call<TypeInfo>) {
+        self.instructions.push(Comment(format!(
+            "CALL {} ({:?})",
+            callee.to_source(),
+            call.params
+        )));
+
+        // the type checker rejects calls of values which are no functions
+        let VariableType::Func { params, .. } = &callee.info()._type else {
+            unreachable!("Trying to call a non-function expression");
+        };
+
+        // the callee is evaluated first and kept on the stack while evaluating the parameters
+        self.compile_expression(callee);
+        self.instructions.push(Push(Rax));
+
+        self.push_call_params(params, call);
+        self.pop_call_params(params);
+
+        self.instructions.push(Pop(Rax));
+        self.instructions.push(Call(Rax.to_string()));
+    }
+
+    /// Evaluate the parameters of a call (with the given parameter types) onto the stack.
+    fn push_call_params(&mut self, params: &[VariableType], call: &Call<TypeInfo>) {
         for (index, param) in call.params.iter().enumerate() {
             // if the type of the parameter is a reference, we need to load the address of it
             if let VariableType::Reference(_) = params[index] {
@@ -1116,43 +2105,160 @@ impl Scope {
             }
 
             self.instructions.push(Push(Rax));
+
+            // slices are passed along with their length
+            if Self::is_slice(&params[index]) {
+                self.compile_slice_conversion(&param.info()._type, &params[index]);
+                self.instructions.push(Push(Rdx));
+            }
         }
+    }
 
-        if self.variables.get(&name).is_some() {
-            // if we have a variable with this name, we need to load it first
-            self.compile_expression(&Expression::Ident(ident.to_owned()));
-            name = Rax.to_string();
+    /// Move the parameters of a call (with the given parameter types) from the stack into their
+    /// registers.
+    fn pop_call_params(&mut self, params: &[VariableType]) {
+        let register_count = params
+            .iter()
+            .map(|param| if Self::is_slice(param) { 2 } else { 1 })
+            .sum::<usize>();
+
+        for index in (0..register_count).rev() {
+            match PARAM_REGISTERS.get(index) {
+                Some(register) => self.instructions.push(Pop(*register)),
+                None => {
+                    unimplemented!("More than 6 function parameters are currently not supported")
+                }
+            }
         }
+    }
 
-        for (index, _) in call.params.iter().enumerate() {
-            match call.params.len() - (index + 1) {
-                0 => self.instructions.push(Pop(Rdi)),
-                1 => self.instructions.push(Pop(Rsi)),
-                2 => self.instructions.push(Pop(Rdx)),
-                3 => self.instructions.push(Pop(Rcx)),
-                4 => self.instructions.push(Pop(R8)),
-                5 => self.instructions.push(Pop(R9)),
-                _ => unimplemented!("More than 6 function parameters are currently not supported"),
+    /// Compile an assertion. If it fails, a message with the asserted source code and its
+    /// location (and the compared values for `assert_eq`) is written to STDERR and the program
+    /// exits with code 101.
+    fn compile_assertion(&mut self, ident: &Ident<TypeInfo>, call: &Call<TypeInfo>) {
+        if !self.asserts {
+            return;
+        }
+
+        let (file, line, col) = &ident.position;
+        let location = format!("{file}:{line}:{col}");
+        let ok_label = format!(".{}_ok", self.var("assert"));
+
+        match call.params.as_slice() {
+            [condition] => {
+                self.compile_expression(condition);
+                self.instructions
+                    .push(Cmp(Register(Rax.to_sized(&condition.info())), Immediate(0)));
+                self.instructions.push(Jne(ok_label.clone()));
+
+                self.compile_assertion_message(&format!(
+                    "assertion failed: `{}` ({location})\n",
+                    condition.to_source()
+                ));
+                self.compile_assertion_exit();
+
+                self.instructions.push(Label(ok_label));
             }
+            [left, right] => {
+                // keep both values on the stack, so they can be written if the assertion fails
+                self.compile_expression(right);
+                self.instructions.push(Push(Rax));
+                self.compile_expression(left);
+                self.instructions.push(Push(Rax));
+
+                self.instructions.push(Mov(
+                    Register(Rcx),
+                    Memory(InstructionSize::Qword, format!("{Rsp}+8")),
+                ));
+                self.compile_equality(&left.info());
+                self.instructions.push(Cmp(Register(Rax), Immediate(0)));
+                self.instructions.push(Jne(ok_label.clone()));
+
+                self.compile_assertion_message(&format!(
+                    "assertion failed: `{} == {}` ({location})\n  left: ",
+                    left.to_source(),
+                    right.to_source()
+                ));
+                self.instructions.push(Mov(
+                    Register(Rdi),
+                    Memory(InstructionSize::Qword, format!("{Rsp}")),
+                ));
+                self.compile_assertion_value(&left.info()._type);
+
+                self.compile_assertion_message("\n right: ");
+                self.instructions.push(Mov(
+                    Register(Rdi),
+                    Memory(InstructionSize::Qword, format!("{Rsp}+8")),
+                ));
+                self.compile_assertion_value(&right.info()._type);
+
+                self.compile_assertion_message("\n");
+                self.compile_assertion_exit();
+
+                self.instructions.push(Label(ok_label));
+                self.instructions.push(Add(Register(Rsp), Immediate(16)));
+            }
+            _ => unreachable!("Invalid amount of parameters for '{}'", ident.value),
         }
 
-        match call.info.source() {
-            Some(source) => {
-                let fn_name = name.split("::").last().unwrap();
-                let fn_name = source.resolve(&fn_name.to_string());
-                self.externals.insert(fn_name.clone());
-                self.instructions.push(Call(fn_name));
+        // the main program defines the functions for writing the message itself
+        if self.module.is_some() {
+            for function in ["assert_write", "assert_write_char", "assert_write_bool"] {
+                self.externals.insert(function.to_owned());
             }
-            None => {
-                let mut fn_name = name;
-                if let Some(module) = &self.module {
-                    fn_name = module.resolve(&fn_name);
+        }
+    }
+
+    /// Terminate the program with a message naming the given position, if the last arithmetic
+    /// operation overflowed.
+    fn compile_overflow_check(&mut self, position: &Position) {
+        let (file, line, col) = position;
+        let ok_label = format!(".{}_ok", self.var("overflow"));
+        let message =
+            self.add_string_constant(None, &format!("integer overflow at {file}:{line}:{col}\n"));
+
+        self.instructions.push(Jno(ok_label.clone()));
+        self.instructions
+            .push(Lea(Register(Rdi), Identifier(message)));
+        self.instructions.push(Jmp("integer_overflow".to_owned()));
+        self.instructions.push(Label(ok_label));
+
+        // the main program defines the handler
+        if self.module.is_some() {
+            self.externals.insert("integer_overflow".to_owned());
+        }
+    }
+
+    fn compile_assertion_message(&mut self, message: &str) {
+        let constant = self.add_string_constant(None, message);
+        self.instructions
+            .push(Lea(Register(Rdi), Identifier(constant)));
+        self.instructions.push(Call("assert_write".to_owned()));
+    }
+
+    /// Write the value (of the given type) in RDI to STDERR.
+    fn compile_assertion_value(&mut self, _type: &VariableType) {
+        match _type {
+            VariableType::Int => {
+                self.instructions.push(Call("int_to_str".to_owned()));
+                self.instructions.push(Mov(Register(Rdi), Register(Rax)));
+                self.instructions.push(Call("assert_write".to_owned()));
+                if self.module.is_some() {
+                    self.externals.insert("int_to_str".to_owned());
                 }
-                self.instructions.push(Call(fn_name));
             }
+            VariableType::Char => self.instructions.push(Call("assert_write_char".to_owned())),
+            VariableType::Bool => self.instructions.push(Call("assert_write_bool".to_owned())),
+            _ => unreachable!("Can not write value of type '{_type}'"),
         }
     }
 
+    fn compile_assertion_exit(&mut self) {
+        self.instructions.push(Mov(Register(Rax), EXIT_SYSCALL));
+        self.instructions.push(Mov(Register(Rdi), Immediate(101)));
+        self.instructions.push(Syscall);
+    }
+
     fn add_string_constant(&mut self, name: Option<String>, value: &str) -> String {
         let var_name = self.var(&name.clone().unwrap_or_else(|| "c".to_owned()));
         let con = Constant {
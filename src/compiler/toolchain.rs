@@ -0,0 +1,141 @@
+use std::{
+    env,
+    error::Error,
+    ffi::OsStr,
+    fmt::Display,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use log::debug;
+
+/// Environment variable to override the linker (`cc` by default) with.
+pub const LINKER_ENV: &str = "WHY_LINKER";
+
+/// The external tools needed to turn the generated assembly into an executable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Assembler,
+    Linker,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 2] = [Tool::Assembler, Tool::Linker];
+
+    /// The binary which is invoked for this tool.
+    pub fn binary(self) -> String {
+        match self {
+            Tool::Assembler => "nasm".to_owned(),
+            Tool::Linker => env::var(LINKER_ENV).unwrap_or_else(|_| "cc".to_owned()),
+        }
+    }
+
+    /// A hint on how to install this tool on the current operating system.
+    pub fn install_hint(self) -> String {
+        let package = match self {
+            Tool::Assembler => "nasm",
+            Tool::Linker => "a C compiler",
+        };
+
+        let command = match (self, env::consts::OS) {
+            (Tool::Assembler, "macos") => "brew install nasm",
+            (Tool::Linker, "macos") => "xcode-select --install",
+            (Tool::Assembler, _) => "e.g., apt install nasm",
+            (Tool::Linker, _) => "e.g., apt install gcc",
+        };
+
+        let hint = format!("Install {package} ({command})");
+
+        match self {
+            Tool::Assembler => hint,
+            Tool::Linker => format!("{hint} or point {LINKER_ENV} to a linker"),
+        }
+    }
+
+    /// Find the binary of this tool, either via its path or by searching the `PATH`.
+    pub fn find(self) -> Result<PathBuf, ToolNotFound> {
+        let binary = self.binary();
+
+        let found = if Path::new(&binary).components().count() > 1 {
+            Some(PathBuf::from(&binary)).filter(|path| path.is_file())
+        } else {
+            env::var_os("PATH").and_then(|paths| {
+                env::split_paths(&paths)
+                    .map(|path| path.join(&binary))
+                    .find(|path| path.is_file())
+            })
+        };
+
+        found.ok_or(ToolNotFound { tool: self, binary })
+    }
+
+    /// Run this tool with the given arguments.
+    pub fn run<I, S>(self, args: I) -> Result<Output, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let binary = self.binary();
+        let args = args
+            .into_iter()
+            .map(|arg| arg.as_ref().to_owned())
+            .collect::<Vec<_>>();
+
+        debug!(
+            "Running {self}: {binary} {}",
+            args.iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        Command::new(&binary)
+            .args(args)
+            .output()
+            .map_err(|error| match error.kind() {
+                ErrorKind::NotFound => ToolNotFound { tool: self, binary }.into(),
+                _ => error.into(),
+            })
+    }
+}
+
+impl Display for Tool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Tool::Assembler => "assembler",
+            Tool::Linker => "linker",
+        })
+    }
+}
+
+/// Error returned when a tool needed for compiling a program is not installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolNotFound {
+    pub tool: Tool,
+    /// The binary which was tried.
+    pub binary: String,
+}
+
+impl Display for ToolNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not find the {} '{}'. {}.",
+            self.tool,
+            self.binary,
+            self.tool.install_hint()
+        )
+    }
+}
+
+impl Error for ToolNotFound {}
+
+/// Check that all tools needed for compiling a program are installed.
+pub fn check_toolchain() -> Result<(), ToolNotFound> {
+    for tool in Tool::ALL {
+        tool.find()?;
+    }
+
+    Ok(())
+}
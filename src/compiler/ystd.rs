@@ -109,3 +109,63 @@ int_to_str:
         pop     rbp
         lea     rax, int_to_str_val
         ret";
+
+/// Parse a (decimal, optionally negative) integer from a string. Invalid input results in 0.
+pub const STR_TO_INT: &str = "
+str_to_int:
+        xor     rax, rax
+        xor     rcx, rcx
+        xor     r8, r8
+        cmp     byte [rdi], 45
+        jne     .check_empty
+        mov     r8, 1
+        inc     rcx
+.check_empty:
+        cmp     byte [rdi + rcx], 0
+        je      .invalid
+.loop:
+        movzx   rdx, byte [rdi + rcx]
+        test    rdx, rdx
+        jz      .done
+        sub     rdx, 48
+        cmp     rdx, 9
+        ja      .invalid
+        imul    rax, rax, 10
+        add     rax, rdx
+        inc     rcx
+        jmp     .loop
+.invalid:
+        xor     rax, rax
+        ret
+.done:
+        test    r8, r8
+        jz      .end
+        neg     rax
+.end:
+        ret";
+
+/// Check, whether a string is a valid (decimal, optionally negative) integer.
+pub const IS_INT: &str = "
+is_int:
+        xor     rcx, rcx
+        cmp     byte [rdi], 45
+        jne     .check_empty
+        inc     rcx
+.check_empty:
+        cmp     byte [rdi + rcx], 0
+        je      .false
+.loop:
+        movzx   rdx, byte [rdi + rcx]
+        test    rdx, rdx
+        jz      .true
+        sub     rdx, 48
+        cmp     rdx, 9
+        ja      .false
+        inc     rcx
+        jmp     .loop
+.true:
+        mov     rax, 1
+        ret
+.false:
+        xor     rax, rax
+        ret";
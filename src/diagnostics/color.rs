@@ -0,0 +1,94 @@
+use std::env;
+
+/// Policy for coloring the output of the compiler.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color the output, if it is written to a terminal and the user did not opt out via the
+    /// environment (`NO_COLOR`, `CLICOLOR=0`) or forced colors (`CLICOLOR_FORCE`).
+    #[default]
+    Auto,
+    /// Always color the output.
+    Always,
+    /// Never color the output.
+    Never,
+}
+
+impl ColorChoice {
+    /// Check, whether output written to a stream should be colored (given whether the stream is a
+    /// terminal).
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        self.enabled_with_env(is_terminal, |name| env::var(name).ok())
+    }
+
+    fn enabled_with_env(self, is_terminal: bool, var: impl Fn(&str) -> Option<String>) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                // see https://no-color.org and https://bixense.com/clicolors
+                let is_set = |name| var(name).is_some_and(|value: String| !value.is_empty());
+
+                if is_set("NO_COLOR") {
+                    false
+                } else if is_set("CLICOLOR_FORCE") && var("CLICOLOR_FORCE").as_deref() != Some("0")
+                {
+                    true
+                } else if var("CLICOLOR").as_deref() == Some("0") {
+                    false
+                } else {
+                    is_terminal
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorChoice;
+
+    fn enabled(choice: ColorChoice, is_terminal: bool, vars: &[(&str, &str)]) -> bool {
+        choice.enabled_with_env(is_terminal, |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_explicit_choice_ignores_environment() {
+        assert!(enabled(ColorChoice::Always, false, &[("NO_COLOR", "1")]));
+        assert!(!enabled(
+            ColorChoice::Never,
+            true,
+            &[("CLICOLOR_FORCE", "1")]
+        ));
+    }
+
+    #[test]
+    fn test_auto_choice() {
+        assert!(enabled(ColorChoice::Auto, true, &[]));
+        assert!(!enabled(ColorChoice::Auto, false, &[]));
+
+        assert!(!enabled(ColorChoice::Auto, true, &[("NO_COLOR", "1")]));
+        assert!(enabled(ColorChoice::Auto, true, &[("NO_COLOR", "")]));
+
+        assert!(enabled(
+            ColorChoice::Auto,
+            false,
+            &[("CLICOLOR_FORCE", "1")]
+        ));
+        assert!(!enabled(
+            ColorChoice::Auto,
+            false,
+            &[("CLICOLOR_FORCE", "0")]
+        ));
+        assert!(!enabled(
+            ColorChoice::Auto,
+            false,
+            &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]
+        ));
+
+        assert!(!enabled(ColorChoice::Auto, true, &[("CLICOLOR", "0")]));
+    }
+}
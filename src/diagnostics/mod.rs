@@ -0,0 +1,395 @@
+//! Diagnostics of the Y programming language.
+//!
+//! This module provides a unified representation of errors which refer to a position in the
+//! source code (e.g., parse errors or type errors) and renders them together with the offending
+//! line of the source.
+mod color;
+
+use std::{
+    error::Error,
+    fmt::Display,
+    fs,
+    io::{self, IsTerminal},
+};
+
+use crate::{
+    ast::{ParseError, ParseErrors, Position},
+    interpreter::InterpreterError,
+    loader::{ImportError, RecoveredErrors},
+    project::ManifestError,
+    typechecker::{TypeError, TypeErrors},
+};
+
+pub use self::color::ColorChoice;
+
+const BLUE: &str = "\x1b[1;34m";
+const RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// The number of errors rendered (of multiple errors) by default.
+pub const DEFAULT_ERROR_LIMIT: usize = 20;
+
+/// An error (or hint) with a position within the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub position: Position,
+    /// Additional hints, which are rendered below the main message.
+    pub notes: Vec<(String, Position)>,
+}
+
+impl Diagnostic {
+    /// Render this diagnostic together with the lines of the given source it refers to.
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let mut rendered = format_message(&self.message, &self.position);
+        rendered.push_str(&render_snippet(source, &self.position, color));
+
+        for (note, position) in &self.notes {
+            rendered.push_str(&format!("\n\tnote: {}", format_message(note, position)));
+
+            // notes may refer to another file (e.g., the definition within an imported module)
+            if position.0 == self.position.0 {
+                rendered.push_str(&render_snippet(source, position, color));
+            } else if let Ok(source) = fs::read_to_string(&position.0) {
+                rendered.push_str(&render_snippet(&source, position, color));
+            }
+        }
+
+        rendered
+    }
+
+    /// Render this diagnostic with the source of the file it refers to. If this file can not be
+    /// read, the diagnostic is rendered without any source.
+    pub fn render_from_file(&self, color: bool) -> String {
+        match fs::read_to_string(&self.position.0) {
+            Ok(source) => self.render(&source, color),
+            Err(_) => self.to_string(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_message(&self.message, &self.position))?;
+
+        for (note, position) in &self.notes {
+            f.write_str(&format!("\n\tnote: {}", format_message(note, position)))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&TypeError> for Diagnostic {
+    fn from(value: &TypeError) -> Self {
+        Self {
+            message: value.message.clone(),
+            position: value.position.clone(),
+            notes: value.notes.clone(),
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(value: &ParseError) -> Self {
+        Self {
+            message: value.message.clone(),
+            position: value.position.clone(),
+            notes: vec![],
+        }
+    }
+}
+
+impl From<&ImportError> for Diagnostic {
+    fn from(value: &ImportError) -> Self {
+        Self {
+            message: format!(
+                "Failed to load file '{}' from import '{}'",
+                value.path, value.import_statement
+            ),
+            position: value.position.clone(),
+            notes: vec![],
+        }
+    }
+}
+
+impl From<&ManifestError> for Diagnostic {
+    fn from(value: &ManifestError) -> Self {
+        Self {
+            message: value.message.clone(),
+            position: value.position.clone(),
+            notes: vec![],
+        }
+    }
+}
+
+impl From<&InterpreterError> for Diagnostic {
+    fn from(value: &InterpreterError) -> Self {
+        Self {
+            message: value.message.clone(),
+            position: value.position.clone(),
+            notes: vec![],
+        }
+    }
+}
+
+/// Check, whether diagnostics should be colored according to the given policy. Errors are logged
+/// to STDERR, so its terminal status decides in case of [`ColorChoice::Auto`].
+pub fn colors_enabled(choice: ColorChoice) -> bool {
+    choice.enabled(io::stderr().is_terminal())
+}
+
+/// Render an arbitrary error. Errors with a position in the source code are rendered with their
+/// source snippet, all other errors are simply displayed.
+pub fn render_error(error: &(dyn Error + 'static), color: bool) -> String {
+    render_error_with_limit(error, color, 0)
+}
+
+/// Render an arbitrary error like `render_error`. Of multiple errors, only the first `limit` ones
+/// (all of them for a limit of 0) are rendered, followed by the number of omitted errors.
+pub fn render_error_with_limit(error: &(dyn Error + 'static), color: bool, limit: usize) -> String {
+    if let Some(TypeErrors(errors)) = error.downcast_ref::<TypeErrors>() {
+        return render_diagnostics(errors.iter().map(Diagnostic::from), color, limit);
+    }
+
+    if let Some(ParseErrors(errors)) = error.downcast_ref::<ParseErrors>() {
+        return render_diagnostics(errors.iter().map(Diagnostic::from), color, limit);
+    }
+
+    if let Some(RecoveredErrors {
+        parse_errors,
+        type_errors,
+    }) = error.downcast_ref::<RecoveredErrors>()
+    {
+        let diagnostics = parse_errors
+            .iter()
+            .map(Diagnostic::from)
+            .chain(type_errors.iter().map(Diagnostic::from))
+            .collect::<Vec<_>>();
+        return render_diagnostics(diagnostics.into_iter(), color, limit);
+    }
+
+    let diagnostic = if let Some(error) = error.downcast_ref::<TypeError>() {
+        Diagnostic::from(error)
+    } else if let Some(error) = error.downcast_ref::<ParseError>() {
+        Diagnostic::from(error)
+    } else if let Some(error) = error.downcast_ref::<ImportError>() {
+        Diagnostic::from(error)
+    } else if let Some(error) = error.downcast_ref::<InterpreterError>() {
+        Diagnostic::from(error)
+    } else if let Some(error) = error.downcast_ref::<ManifestError>() {
+        Diagnostic::from(error)
+    } else {
+        return error.to_string();
+    };
+
+    diagnostic.render_from_file(color)
+}
+
+fn render_diagnostics(
+    diagnostics: impl ExactSizeIterator<Item = Diagnostic>,
+    color: bool,
+    limit: usize,
+) -> String {
+    let count = diagnostics.len();
+    let shown = if limit == 0 { count } else { limit.min(count) };
+
+    let mut rendered = diagnostics
+        .take(shown)
+        .map(|diagnostic| diagnostic.render_from_file(color))
+        .collect::<Vec<_>>();
+
+    let omitted = count - shown;
+    if omitted > 0 {
+        let errors = if omitted == 1 { "error" } else { "errors" };
+        rendered.push(format!(
+            "… and {omitted} more {errors} not shown (re-run with --error-limit 0)"
+        ));
+    }
+
+    rendered.join("\n")
+}
+
+fn format_message(message: &str, (file, line, col): &Position) -> String {
+    format!("{message} ({file}:{line}:{col})")
+}
+
+/// Render the line of the given position with a gutter containing the line number and mark the
+/// token starting at the column of the position.
+fn render_snippet(source: &str, (_, line, col): &Position, color: bool) -> String {
+    // split manually (instead of using `lines`), so errors at the end of the file still get a line
+    let Some(source_line) = source.split('\n').nth(line.saturating_sub(1)) else {
+        return String::new();
+    };
+    let source_line = source_line.trim_end_matches('\r');
+
+    let (gutter_color, marker_color, reset) = if color {
+        (BLUE, RED, RESET)
+    } else {
+        ("", "", "")
+    };
+
+    let line_number = line.to_string();
+    let padding = " ".repeat(line_number.len());
+
+    // keep tabs in front of the marked token, so the marker lines up with the source line
+    let indentation = source_line
+        .chars()
+        .take(col.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+
+    let token_length = source_line
+        .chars()
+        .skip(col.saturating_sub(1))
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .count()
+        .max(1);
+    let marker = format!("^{}", "~".repeat(token_length - 1));
+
+    format!(
+        "\n{gutter_color}{padding} |{reset}\
+         \n{gutter_color}{line_number} |{reset} {source_line}\
+         \n{gutter_color}{padding} |{reset} {indentation}{marker_color}{marker}{reset}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{Ast, YParser},
+        loader::ModuleRefs,
+        typechecker::Typechecker,
+    };
+
+    use super::Diagnostic;
+
+    /// A program with a multi-line string in front of a type error.
+    const MULTI_LINE_SOURCE: &str = "let text := \"first\nsecond\"\n\nlet b := 1 + text\n";
+
+    /// Parse and type check the given source and render the resulting error.
+    fn render_type_error(source: &str) -> String {
+        let pairs = YParser::parse_program("test.why", source).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+        let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap_err();
+
+        Diagnostic::from(&error).render(source, false)
+    }
+
+    /// Parse the given source and render the resulting error.
+    fn render_parse_error(source: &str) -> String {
+        let error = YParser::parse_program("test.why", source).unwrap_err();
+
+        Diagnostic::from(&error).render(source, false)
+    }
+
+    #[test]
+    fn test_render_error_in_the_middle_of_a_line() {
+        let source = "let a := 42\nlet b := a + foo * 2\n";
+        let diagnostic = Diagnostic {
+            message: "Undefined identifier 'foo'".to_owned(),
+            position: ("test.why".to_owned(), 2, 14),
+            notes: vec![],
+        };
+
+        assert_eq!(
+            diagnostic.render(source, false),
+            "Undefined identifier 'foo' (test.why:2:14)\n  \
+             |\n\
+             2 | let b := a + foo * 2\n  \
+             |              ^~~"
+        );
+    }
+
+    #[test]
+    fn test_render_notes_and_tabs() {
+        let source = "let foo := (): int => {\n\tlet result := 42\n}\n";
+        let diagnostic = Diagnostic {
+            message: "Expected return type of 'int' but got 'void'".to_owned(),
+            position: ("test.why".to_owned(), 1, 12),
+            notes: vec![(
+                "add 'result' as the last expression".to_owned(),
+                ("test.why".to_owned(), 2, 2),
+            )],
+        };
+
+        assert_eq!(
+            diagnostic.render(source, false),
+            "Expected return type of 'int' but got 'void' (test.why:1:12)\n  \
+             |\n\
+             1 | let foo := (): int => {\n  \
+             |            ^\n\
+             \tnote: add 'result' as the last expression (test.why:2:2)\n  \
+             |\n\
+             2 | \tlet result := 42\n  \
+             | \t^~~"
+        );
+    }
+
+    #[test]
+    fn test_render_gutter_of_multi_digit_line() {
+        let source = "\n".repeat(9) + "print(foo)\n";
+        let diagnostic = Diagnostic {
+            message: "Undefined identifier 'foo'".to_owned(),
+            position: ("test.why".to_owned(), 10, 7),
+            notes: vec![],
+        };
+
+        assert_eq!(
+            diagnostic.render(&source, false),
+            "Undefined identifier 'foo' (test.why:10:7)\n   \
+             |\n\
+             10 | print(foo)\n   \
+             |       ^~~"
+        );
+    }
+
+    #[test]
+    fn test_render_with_color() {
+        let source = "foo\n";
+        let diagnostic = Diagnostic {
+            message: "Undefined identifier 'foo'".to_owned(),
+            position: ("test.why".to_owned(), 1, 1),
+            notes: vec![],
+        };
+
+        assert!(diagnostic
+            .render(source, true)
+            .contains("\x1b[1;31m^~~\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_after_multi_line_string() {
+        assert_eq!(
+            render_type_error(MULTI_LINE_SOURCE),
+            "Right value of numeric binary operation has to be of type Int. Found 'str' (test.why:4:14)\n  \
+             |\n\
+             4 | let b := 1 + text\n  \
+             |              ^~~~"
+        );
+    }
+
+    #[test]
+    fn test_render_crlf_source_like_lf_source() {
+        let lf = render_type_error(MULTI_LINE_SOURCE);
+        let crlf = MULTI_LINE_SOURCE.replace('\n', "\r\n");
+        // the line ending within the string and the one of the erroneous line stay LF
+        let mixed = MULTI_LINE_SOURCE
+            .replacen('\n', "\r\n", 3)
+            .replacen("\r\n", "\n", 1);
+
+        assert_eq!(render_type_error(&crlf), lf);
+        assert_eq!(render_type_error(&mixed), lf);
+    }
+
+    #[test]
+    fn test_render_parse_error_at_end_of_crlf_line() {
+        let lf = render_parse_error("let a := 42\nlet b :=\nlet c := 1\n");
+        let crlf = render_parse_error("let a := 42\r\nlet b :=\r\nlet c := 1\r\n");
+
+        assert!(lf.contains("3 | let c := 1\n"), "{lf}");
+        assert_eq!(crlf, lf);
+    }
+}
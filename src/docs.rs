@@ -0,0 +1,237 @@
+//! Extraction of documentation from Y modules.
+//!
+//! This module collects the top-level items of a module (functions, constants and declarations)
+//! together with their documentation for external tooling (e.g., API doc generators). The
+//! documentation of an item is the contiguous block of `//` comments immediately preceding it. A
+//! comment which is separated from the next item by a blank line does not document anything.
+use serde::{Serialize, Serializer};
+
+use crate::{
+    ast::{
+        Ast, CompilerDirective, Declaration, Definition, Expression, Intrinsic, Position, Statement,
+    },
+    ast_json,
+};
+
+/// Documentation of all top-level items of a module.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleDocs {
+    pub functions: Vec<FunctionDoc>,
+    pub constants: Vec<ConstantDoc>,
+    pub declarations: Vec<DeclarationDoc>,
+}
+
+/// Documentation of a function defined via `let name := (...): type => { ... }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionDoc {
+    pub name: String,
+    /// Names and annotated type names of all parameters (`None` if the type is given by a
+    /// declaration of the function).
+    #[serde(serialize_with = "serialize_params")]
+    pub params: Vec<(String, Option<String>)>,
+    /// The annotated return type (`None` if it is inferred).
+    pub return_type: Option<String>,
+    #[serde(with = "ast_json::position")]
+    pub position: Position,
+    pub doc: Option<String>,
+}
+
+/// Documentation of an immutable top-level definition which is no function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConstantDoc {
+    pub name: String,
+    /// The (normalized) source code of the defined value.
+    pub value: String,
+    #[serde(with = "ast_json::position")]
+    pub position: Position,
+    pub doc: Option<String>,
+}
+
+/// Documentation of an external symbol declared via `declare name: type`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeclarationDoc {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(with = "ast_json::position")]
+    pub position: Position,
+    pub doc: Option<String>,
+}
+
+/// Extract the documentation of all top-level items of the given AST. The source code of the
+/// module is needed, since comments are not part of the AST.
+pub fn extract(ast: &Ast<()>, source: &str) -> ModuleDocs {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut docs = ModuleDocs::default();
+
+    for statement in ast.nodes() {
+        // comments document the compiler directive and the statement it annotates
+        let (statement, line) = match statement {
+            Statement::CompilerDirective(CompilerDirective {
+                statement: Some(statement),
+                position,
+                ..
+            }) => (statement.as_ref(), position.1),
+            Statement::Intrinsic(Intrinsic::Definition(Definition { position, .. }))
+            | Statement::Intrinsic(Intrinsic::Declaration(Declaration { position, .. })) => {
+                (statement, position.1)
+            }
+            _ => continue,
+        };
+
+        let doc = preceding_comments(&lines, line);
+
+        match statement {
+            Statement::Intrinsic(Intrinsic::Definition(Definition {
+                ident,
+                value: Expression::FnDef(fn_def),
+                position,
+                ..
+            })) => docs.functions.push(FunctionDoc {
+                name: ident.value.to_owned(),
+                params: fn_def
+                    .params
+                    .iter()
+                    .map(|param| {
+                        (
+                            param.ident.value.to_owned(),
+                            param
+                                .type_annotation
+                                .as_ref()
+                                .map(|type_annotation| type_annotation.value.to_string()),
+                        )
+                    })
+                    .collect(),
+                return_type: fn_def
+                    .type_annotation
+                    .as_ref()
+                    .map(|type_annotation| type_annotation.value.to_string()),
+                position: position.to_owned(),
+                doc,
+            }),
+            Statement::Intrinsic(Intrinsic::Definition(Definition {
+                ident,
+                value,
+                position,
+                is_mutable: false,
+                ..
+            })) => docs.constants.push(ConstantDoc {
+                name: ident.value.to_owned(),
+                value: value.to_source(),
+                position: position.to_owned(),
+                doc,
+            }),
+            Statement::Intrinsic(Intrinsic::Declaration(declaration)) => {
+                docs.declarations.push(DeclarationDoc {
+                    name: declaration.ident.value.to_owned(),
+                    type_name: declaration.type_annotation.value.to_string(),
+                    position: declaration.position.to_owned(),
+                    doc,
+                })
+            }
+            _ => {}
+        }
+    }
+
+    docs
+}
+
+/// Get the text of the `//` comments directly above the given (1-based) line.
+fn preceding_comments(lines: &[&str], line: usize) -> Option<String> {
+    let comments = lines[..line.saturating_sub(1).min(lines.len())]
+        .iter()
+        .rev()
+        .map_while(|line| line.trim().strip_prefix("//"))
+        .map(|comment| comment.strip_prefix(' ').unwrap_or(comment).trim_end())
+        .collect::<Vec<_>>();
+
+    if comments.is_empty() {
+        return None;
+    }
+
+    Some(comments.into_iter().rev().collect::<Vec<_>>().join("\n"))
+}
+
+/// Parameters are objects, i.e., `{ "name": ..., "type": ... }`.
+fn serialize_params<S: Serializer>(
+    params: &[(String, Option<String>)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Param<'a> {
+        name: &'a str,
+        #[serde(rename = "type")]
+        type_name: Option<&'a str>,
+    }
+
+    serializer.collect_seq(params.iter().map(|(name, type_name)| Param {
+        name,
+        type_name: type_name.as_deref(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, YParser};
+
+    use super::{extract, ModuleDocs};
+
+    fn docs(src: &str) -> ModuleDocs {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        extract(&Ast::from_program(pairs.collect(), "test.why"), src)
+    }
+
+    #[test]
+    fn test_documented_function() {
+        let docs = docs(
+            "// Add two numbers.\n//\n//   Works for negative numbers, too.\nlet add := (a: int, b: &[int]): int => {\n    // not documentation\n    a + b[0]\n}\n",
+        );
+
+        assert_eq!(docs.functions.len(), 1);
+        let function = &docs.functions[0];
+        assert_eq!(function.name, "add");
+        assert_eq!(
+            function.params,
+            [
+                ("a".to_owned(), Some("int".to_owned())),
+                ("b".to_owned(), Some("&[int]".to_owned()))
+            ]
+        );
+        assert_eq!(function.return_type.as_deref(), Some("int"));
+        assert_eq!(function.position, ("test.why".to_owned(), 4, 1));
+        assert_eq!(
+            function.doc.as_deref(),
+            Some("Add two numbers.\n\n  Works for negative numbers, too.")
+        );
+    }
+
+    #[test]
+    fn test_comment_followed_by_blank_line() {
+        let docs = docs("// Just a comment.\n\nlet answer := 42\n");
+
+        assert_eq!(docs.constants.len(), 1);
+        assert_eq!(docs.constants[0].value, "42");
+        assert_eq!(docs.constants[0].doc, None);
+    }
+
+    #[test]
+    fn test_documented_directive_and_declaration() {
+        let docs = docs(
+            "// The exit code.\r\ndeclare code: int\r\n// Only on Linux.\r\n#[os == \"linux\"]\r\nlet f := (g: (int) -> void): void => {\r\n    g(1)\r\n}\r\nlet mut counter := 0\r\n",
+        );
+
+        assert_eq!(docs.declarations.len(), 1);
+        assert_eq!(docs.declarations[0].type_name, "int");
+        assert_eq!(docs.declarations[0].doc.as_deref(), Some("The exit code."));
+
+        assert_eq!(docs.functions.len(), 1);
+        assert_eq!(
+            docs.functions[0].params[0].1.as_deref(),
+            Some("(int) -> void")
+        );
+        assert_eq!(docs.functions[0].doc.as_deref(), Some("Only on Linux."));
+
+        // mutable definitions are no constants
+        assert!(docs.constants.is_empty());
+    }
+}
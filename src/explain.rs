@@ -0,0 +1,185 @@
+//! Explanation of how the compiler understands a single top-level item (e.g., for
+//! `--explain-lowering`).
+//!
+//! The item containing a given line is printed as written in the source, as parsed and as type
+//! checked (with the types of all expressions inline). The output is plain text, so it can be
+//! pasted into bug reports. There is no lowering pass between type checking and code generation
+//! (yet), so there is no desugared form of the item.
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    ast::{Ast, ParseError, Rule, Statement, YParser},
+    ast_tree::{self, TreeStyle},
+    typechecker::TypeInfo,
+};
+
+/// The different forms of a single top-level item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub file: String,
+    /// The first and the last line of the item.
+    pub lines: (usize, usize),
+    /// The source of the item.
+    pub source: String,
+    /// The parse tree of the item.
+    pub parsed: String,
+    /// The type-checked tree of the item.
+    pub typed: String,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (start, end) = self.lines;
+        writeln!(f, "=== Source ({}:{start}-{end}) ===", self.file)?;
+        writeln!(f, "{}", self.source)?;
+        writeln!(f, "=== Parsed ===")?;
+        write!(f, "{}", self.parsed)?;
+        writeln!(f, "=== Typed ===")?;
+        write!(f, "{}", self.typed)
+    }
+}
+
+/// Error while explaining an item.
+#[derive(Debug, Clone)]
+pub enum ExplainError {
+    /// There is no top-level item containing the requested line (e.g., since it is empty).
+    NoItemAtLine(usize),
+    Parse(ParseError),
+}
+
+impl Display for ExplainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExplainError::NoItemAtLine(line) => write!(f, "No item at line {line}"),
+            ExplainError::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ExplainError {}
+
+/// Explain the top-level item of the given source (with the given type-checked AST) which
+/// contains the given line.
+pub fn explain_lowering(
+    file: &str,
+    source: &str,
+    typed: &Ast<TypeInfo>,
+    line: usize,
+) -> Result<Explanation, ExplainError> {
+    let pairs = YParser::parse_program(file, source).map_err(ExplainError::Parse)?;
+
+    // the pairs of statements also contain the whitespace (i.e., the empty lines) following them
+    let pair = pairs
+        .filter(|pair| !matches!(pair.as_rule(), Rule::EOI | Rule::shebang))
+        .find(|pair| {
+            let (start, _) = pair.line_col();
+            (start..=last_line(start, pair.as_str())).contains(&line)
+        })
+        .ok_or(ExplainError::NoItemAtLine(line))?;
+
+    let item_source = pair.as_str().trim_end().to_owned();
+    let (start, _) = pair.line_col();
+    let end = last_line(start, &item_source);
+
+    let statement = Statement::from_pair(pair, file);
+    let position = statement.position();
+
+    let typed = typed
+        .nodes()
+        .iter()
+        .filter(|typed_statement| typed_statement.position() == position)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Ok(Explanation {
+        file: file.to_owned(),
+        lines: (start, end),
+        source: item_source,
+        parsed: ast_tree::render(&Ast::from_nodes(vec![statement]), TreeStyle::Compact),
+        typed: ast_tree::render(&Ast::from_nodes(typed), TreeStyle::Compact),
+    })
+}
+
+/// The last line of the given (non-whitespace) source, which starts at the given line.
+fn last_line(start: usize, source: &str) -> usize {
+    start + source.trim_end().matches('\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{Ast, YParser},
+        loader::ModuleRefs,
+        typechecker::Typechecker,
+    };
+
+    use super::{explain_lowering, ExplainError};
+
+    const PROGRAM: &str = "let double := (x: int): int => {
+    let twice := (y: int): int => { y * 2 }
+    twice(x)
+}
+
+let answer := double(21)
+";
+
+    fn explain(line: usize) -> Result<String, ExplainError> {
+        let pairs = YParser::parse_program("test.why", PROGRAM).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+        let typed = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap();
+
+        explain_lowering("test.why", PROGRAM, &typed, line)
+            .map(|explanation| explanation.to_string())
+    }
+
+    #[test]
+    fn test_explain_function_with_lambda() {
+        assert_eq!(
+            explain(2).unwrap(),
+            "=== Source (test.why:1-4) ===
+let double := (x: int): int => {
+    let twice := (y: int): int => { y * 2 }
+    twice(x)
+}
+=== Parsed ===
+Definition double
+  FnDef -> int
+    Param x: int
+    Block
+      Definition twice
+        FnDef -> int
+          Param y: int
+          Block
+            Binary *
+              Ident y
+              Integer 2
+      Call
+        Ident twice
+        Ident x
+=== Typed ===
+Definition double
+  FnDef -> int
+    Param x: int
+    Block: int
+      Definition twice
+        FnDef -> int
+          Param y: int
+          Block: int
+            Binary *: int
+              Ident y: int
+              Integer 2: int
+      Call: int
+        Ident twice: (int) -> int
+        Ident x: int
+"
+        );
+    }
+
+    #[test]
+    fn test_explain_between_items() {
+        assert_eq!(explain(5).unwrap_err().to_string(), "No item at line 5");
+        assert_eq!(explain(7).unwrap_err().to_string(), "No item at line 7");
+    }
+}
@@ -0,0 +1,229 @@
+//! Report of the external symbols of a module.
+//!
+//! Declarations of functions (or data) which are not defined later on within the same scope refer
+//! to symbols of other object files (e.g., of a C library). The report lists each of them with its
+//! declared type, the symbol the linker has to resolve and all references to it (including the
+//! functions they occur in). Declarations which are never referenced are candidates for removal.
+use std::fmt::Display;
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::{
+    ast::{Ast, Position, Type},
+    ast_json::{self, JsonPosition},
+    compiler::external_symbol,
+    xref::{SymbolKind, Xref},
+};
+
+/// The current version of the JSON schema of the report.
+pub const VERSION: i64 = 1;
+
+/// A use of an external symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExternalReference {
+    #[serde(with = "ast_json::position")]
+    pub position: Position,
+    /// The (named) function containing the reference (`None` for top-level statements).
+    pub function: Option<String>,
+}
+
+/// A declaration of an external symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct External {
+    pub name: String,
+    pub type_annotation: Type,
+    /// The name of the symbol within the object files.
+    pub symbol: String,
+    /// The position of the declared name.
+    pub position: Position,
+    pub references: Vec<ExternalReference>,
+}
+
+impl External {
+    pub fn is_used(&self) -> bool {
+        !self.references.is_empty()
+    }
+}
+
+/// All external symbols of a module (ordered by the position of their declaration).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExternalsReport {
+    pub externals: Vec<External>,
+}
+
+impl ExternalsReport {
+    /// Collect the external symbols declared within the given AST.
+    pub fn collect<T: Clone>(ast: &Ast<T>) -> ExternalsReport {
+        let Xref {
+            definitions,
+            references,
+        } = Xref::collect(ast);
+
+        let externals = definitions
+            .into_iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Declaration && symbol.definition.is_none())
+            .filter_map(|symbol| {
+                let references = references
+                    .iter()
+                    .filter(|reference| reference.definition.as_ref() == Some(&symbol.position))
+                    .map(|reference| ExternalReference {
+                        position: reference.position.clone(),
+                        function: reference.function.clone(),
+                    })
+                    .collect();
+
+                Some(External {
+                    symbol: external_symbol(&symbol.name),
+                    type_annotation: symbol.type_annotation?,
+                    name: symbol.name,
+                    position: symbol.position,
+                    references,
+                })
+            })
+            .collect();
+
+        ExternalsReport { externals }
+    }
+
+    /// The external symbols which are declared, but never used.
+    pub fn unused(&self) -> impl Iterator<Item = &External> {
+        self.externals.iter().filter(|external| !external.is_used())
+    }
+
+    /// Serialize this report as (pretty printed) JSON.
+    pub fn to_json_string(&self) -> String {
+        #[derive(Serialize)]
+        struct Document<'a> {
+            version: i64,
+            externals: &'a [External],
+        }
+
+        ast_json::to_json_string(&Document {
+            version: VERSION,
+            externals: &self.externals,
+        })
+    }
+}
+
+impl Display for ExternalsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.externals.is_empty() {
+            return writeln!(f, "No external declarations");
+        }
+
+        for external in &self.externals {
+            let (file, line, col) = &external.position;
+            writeln!(
+                f,
+                "{}: {} (symbol '{}', declared at {file}:{line}:{col})",
+                external.name, external.type_annotation, external.symbol
+            )?;
+
+            if !external.is_used() {
+                writeln!(f, "\tunused (candidate for removal)")?;
+            }
+
+            for ExternalReference { position, function } in &external.references {
+                let (file, line, col) = position;
+                match function {
+                    Some(function) => writeln!(f, "\tused in '{function}' at {file}:{line}:{col}")?,
+                    None => writeln!(f, "\tused at top level at {file}:{line}:{col}")?,
+                }
+            }
+        }
+
+        let unused = self.unused().count();
+        if unused > 0 {
+            writeln!(
+                f,
+                "{unused} of {} declarations unused",
+                self.externals.len()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for External {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut external = serializer.serialize_struct("External", 6)?;
+        external.serialize_field("name", &self.name)?;
+        external.serialize_field("type", &self.type_annotation.to_string())?;
+        external.serialize_field("symbol", &self.symbol)?;
+        external.serialize_field("position", &JsonPosition::from(&self.position))?;
+        external.serialize_field("used", &self.is_used())?;
+        external.serialize_field("references", &self.references)?;
+        external.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, YParser};
+
+    use super::ExternalsReport;
+
+    fn report(src: &str) -> ExternalsReport {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        ExternalsReport::collect(&Ast::from_program(pairs.collect(), "test.why"))
+    }
+
+    #[test]
+    fn test_used_and_unused_externals() {
+        let report = report(
+            "declare puts: (str) -> int\ndeclare abs: (int) -> int\ndeclare answer: int\nlet f := (x: int): int => {\n    abs(x)\n}\nputs(\"hi\")\nf(answer)",
+        );
+
+        let externals = report
+            .externals
+            .iter()
+            .map(|external| {
+                (
+                    external.name.as_str(),
+                    external.type_annotation.to_string(),
+                    external
+                        .references
+                        .iter()
+                        .map(|reference| (reference.position.1, reference.function.clone()))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            externals,
+            [
+                ("puts", "(str) -> int".to_owned(), vec![(7, None)]),
+                (
+                    "abs",
+                    "(int) -> int".to_owned(),
+                    vec![(5, Some("f".to_owned()))]
+                ),
+                ("answer", "int".to_owned(), vec![(8, None)]),
+            ]
+        );
+        assert_eq!(report.unused().count(), 0);
+    }
+
+    #[test]
+    fn test_defined_declarations_are_not_external() {
+        let report = report(
+            "declare square: (int) -> int\ndeclare cube: (int) -> int\nsquare(2)\nlet square := (x: int): int => {\n    x * x\n}",
+        );
+
+        let names = report
+            .externals
+            .iter()
+            .map(|external| external.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["cube"]);
+        assert_eq!(
+            report
+                .unused()
+                .next()
+                .map(|external| external.name.as_str()),
+            Some("cube")
+        );
+    }
+}
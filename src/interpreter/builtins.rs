@@ -0,0 +1,172 @@
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use crate::ast::Position;
+
+use super::{
+    error,
+    value::{Builtin, Value},
+    Control, Eval,
+};
+
+/// The system call wrappers of the core library (which are implemented via inline assembly).
+const SYSCALL_FUNCTIONS: [&str; 4] = ["syscall", "syscall2", "syscall3", "syscall4"];
+
+/// The module of the core library defining the system call wrappers.
+const SYSCALL_MODULE: &str = "core/syscall.why";
+
+/// Check, whether a function is one of the system call wrappers of the core library (and not just
+/// a function of the same name defined elsewhere), given the file it is defined in.
+pub fn is_syscall(name: &str, file: &str) -> bool {
+    SYSCALL_FUNCTIONS.contains(&name) && Path::new(file).ends_with(SYSCALL_MODULE)
+}
+
+/// Call a builtin function. `program_args` are the command line arguments of the program
+/// (including its name).
+pub fn call_builtin(builtin: Builtin, args: &[Value], program_args: &[String]) -> Eval<Value> {
+    let arg = args.first().cloned().unwrap_or(Value::Void);
+
+    Ok(match builtin {
+        Builtin::StrLen => Value::Integer(arg.bytes().unwrap_or_default().len() as i64),
+        Builtin::IntToStr => Value::string(&arg.as_integer().unwrap_or_default().to_string()),
+        Builtin::StrToInt => {
+            let bytes = arg.bytes().unwrap_or_default();
+            Value::Integer(parse_int(&bytes).unwrap_or(0))
+        }
+        Builtin::IsInt => {
+            let bytes = arg.bytes().unwrap_or_default();
+            Value::Boolean(parse_int(&bytes).is_some())
+        }
+        Builtin::CharToInt => Value::Integer(arg.as_byte() as i64),
+        Builtin::IntToChar => {
+            let value = arg.as_integer().unwrap_or_default();
+
+            // characters are single bytes, so everything outside of 0..=255 terminates the program
+            if !(0..=255).contains(&value) {
+                return Err(Control::Exit(1));
+            }
+            Value::Character(value as u8)
+        }
+        Builtin::Args => Value::array(program_args.iter().map(|arg| Value::string(arg)).collect()),
+        Builtin::ArgCount => Value::Integer(program_args.len() as i64),
+        Builtin::Env => {
+            let name = String::from_utf8_lossy(&arg.bytes().unwrap_or_default()).to_string();
+            let value = std::env::var_os(name).unwrap_or_default();
+            Value::string(&value.to_string_lossy())
+        }
+        Builtin::Exit => {
+            let _ = io::stdout().flush();
+            return Err(Control::Exit(arg.as_integer().unwrap_or_default() as i32));
+        }
+        Builtin::Len => match arg {
+            Value::Array(values) => Value::Integer(values.borrow().len() as i64),
+            _ => Value::Integer(0),
+        },
+        Builtin::Assert | Builtin::AssertEq => {
+            unreachable!("Assertions are evaluated by the interpreter itself")
+        }
+    })
+}
+
+/// Parse a (decimal, optionally negative) integer like `str_to_int` and `is_int` of compiled
+/// programs do.
+fn parse_int(bytes: &[u8]) -> Option<i64> {
+    let (is_negative, digits) = match bytes.split_first() {
+        Some((b'-', digits)) => (true, digits),
+        _ => (false, bytes),
+    };
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut value = 0i64;
+    for byte in digits {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        value = value.wrapping_mul(10).wrapping_add((byte - b'0') as i64);
+    }
+
+    Some(if is_negative {
+        value.wrapping_neg()
+    } else {
+        value
+    })
+}
+
+/// Emulate a system call. The first argument is the number of the system call (for the current
+/// operating system), followed by its arguments.
+pub fn syscall(args: &[Value], position: &Position) -> Eval<Value> {
+    let number = args.first().and_then(Value::as_integer).unwrap_or(-1);
+    let arg = |index: usize| args.get(index).cloned().unwrap_or(Value::Void);
+
+    match number {
+        // read
+        0 | 0x2000003 => {
+            let Value::Array(target) = arg(2) else {
+                return error("Invalid target of 'read'", position);
+            };
+            let len = arg(3).as_integer().unwrap_or_default().max(0) as usize;
+
+            let mut buffer = vec![0; len.min(target.borrow().len())];
+            let read = match arg(1).as_integer() {
+                Some(0) => io::stdin().read(&mut buffer).unwrap_or(0),
+                _ => 0,
+            };
+
+            for (index, byte) in buffer.into_iter().take(read).enumerate() {
+                target.borrow_mut()[index] = Value::Character(byte);
+            }
+
+            Ok(Value::Integer(read as i64))
+        }
+        // write
+        1 | 0x2000004 => {
+            let Value::Array(source) = arg(2) else {
+                return error("Invalid source of 'write'", position);
+            };
+            let len = arg(3).as_integer().unwrap_or_default().max(0) as usize;
+
+            let bytes = source
+                .borrow()
+                .iter()
+                .take(len)
+                .map(Value::as_byte)
+                .collect::<Vec<_>>();
+
+            let _ = match arg(1).as_integer() {
+                Some(2) => io::stderr().write_all(&bytes),
+                _ => io::stdout().write_all(&bytes),
+            };
+
+            Ok(Value::Integer(bytes.len() as i64))
+        }
+        // exit
+        60 | 0x2000001 => {
+            let _ = io::stdout().flush();
+            Err(Control::Exit(arg(1).as_integer().unwrap_or_default() as i32))
+        }
+        number => error(
+            format!("System call {number} is not supported by the interpreter"),
+            position,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_int;
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int(b"1234"), Some(1234));
+        assert_eq!(parse_int(b"-42"), Some(-42));
+        assert_eq!(parse_int(b"007"), Some(7));
+        assert_eq!(parse_int(b""), None);
+        assert_eq!(parse_int(b"-"), None);
+        assert_eq!(parse_int(b"12a"), None);
+    }
+}
@@ -0,0 +1,19 @@
+use std::{error::Error, fmt::Display};
+
+use crate::ast::Position;
+
+/// Error which occurs while interpreting a program (e.g., because it uses inline assembly).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpreterError {
+    pub message: String,
+    pub position: Position,
+}
+
+impl Display for InterpreterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (file, line, col) = &self.position;
+        f.write_str(&format!("{} ({}:{}:{})", self.message, file, line, col))
+    }
+}
+
+impl Error for InterpreterError {}
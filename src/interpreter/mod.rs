@@ -0,0 +1,673 @@
+//! Interpreter of the Y programming language.
+//!
+//! This module executes type checked programs directly (i.e., without assembling and linking
+//! them), which is handy for quickly running scripts or on systems without NASM. The semantics
+//! follow the compiled programs as closely as possible. Inline assembly can not be interpreted,
+//! but the system call wrappers of the core library are emulated.
+mod builtins;
+mod error;
+mod value;
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io::Write,
+    rc::Rc,
+};
+
+use crate::{
+    ast::{
+        Assignment, BinaryExpr, BinaryOp, Block, Break, Call, CompilerDirective, Declaration,
+        Definition, Expression, FnDef, Ident, If, Import, Indexing, InlineAssembly, Intrinsic,
+        LabeledBlock, Position, PostfixExpr, PostfixOp, PrefixExpr, PrefixOp, Statement, Type,
+        WhileLoop,
+    },
+    loader::{Module, Modules},
+    typechecker::{TypeInfo, VariableType},
+};
+
+pub use self::error::InterpreterError;
+pub use self::value::{Builtin, Env, Frame, Function, Slot, Value};
+
+/// Reasons for aborting the evaluation of the current expression.
+enum Control {
+    /// A `break` (with its value) targeting the labeled block with the given label or, without a
+    /// label, the innermost loop.
+    Break(Option<String>, Value),
+    /// The program terminates with the given exit code.
+    Exit(i32),
+    Error(InterpreterError),
+}
+
+type Eval<T> = Result<T, Control>;
+
+/// Maximum number of nested function calls. Every call needs a few frames on the stack of the
+/// interpreter, so deeper recursion is reported as an error instead of overflowing that stack.
+const MAX_CALL_DEPTH: usize = 10_000;
+
+/// An argument of a function call.
+enum Argument {
+    Value(Value),
+    /// The slot of a variable passed to a reference parameter.
+    Slot(Slot),
+}
+
+fn error<T>(message: impl ToString, position: &Position) -> Eval<T> {
+    Err(Control::Error(InterpreterError {
+        message: message.to_string(),
+        position: position.to_owned(),
+    }))
+}
+
+/// Struct for interpreting a type checked program.
+pub struct Interpreter<'a> {
+    main: &'a Module<TypeInfo>,
+    modules: &'a Modules<TypeInfo>,
+    /// The global environments of all (already initialized) modules by their path.
+    module_envs: RefCell<HashMap<String, Env>>,
+    /// The command line arguments of the program (including its name).
+    args: Vec<String>,
+    /// The number of currently executing function calls.
+    call_depth: Cell<usize>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(main: &'a Module<TypeInfo>, modules: &'a Modules<TypeInfo>) -> Self {
+        Self {
+            main,
+            modules,
+            module_envs: RefCell::default(),
+            args: vec![],
+            call_depth: Cell::new(0),
+        }
+    }
+
+    /// Set the command line arguments passed to the program (including its name).
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    /// Run the program and return its exit code.
+    pub fn run(&self) -> Result<i32, InterpreterError> {
+        let env = Frame::new(None);
+        let result = self.execute_module(self.main, &env, false);
+
+        let _ = std::io::stdout().flush();
+
+        match result {
+            Ok(()) => Ok(0),
+            Err(Control::Exit(code)) => Ok(code),
+            Err(Control::Error(error)) => Err(error),
+            Err(Control::Break(..)) => unreachable!("'break' outside of a loop"),
+        }
+    }
+
+    /// Execute the top level statements of a module. For imported modules, only definitions (and
+    /// declarations) are executed.
+    fn execute_module(&self, module: &Module<TypeInfo>, env: &Env, is_import: bool) -> Eval<()> {
+        let statements = module.ast.nodes();
+
+        // functions of the top level may be called before their definition
+        for statement in statements {
+            if let Statement::Intrinsic(Intrinsic::Definition(Definition {
+                ident,
+                value: Expression::FnDef(fn_def),
+                is_mutable: false,
+                ..
+            })) = statement
+            {
+                env.define(&ident.value, self.function(&ident.value, fn_def, env));
+            }
+        }
+
+        for (index, statement) in statements.iter().enumerate() {
+            if let Some((name, fn_def)) = forward_definition(&statements[index..]) {
+                env.define(name, self.function(name, fn_def, env));
+                continue;
+            }
+
+            match statement {
+                Statement::Import(import) => self.import(module, import, env)?,
+                statement if is_import && !is_definition(statement) => {}
+                statement => {
+                    self.execute_statement(statement, env)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn import(&self, module: &Module<TypeInfo>, import: &Import, env: &Env) -> Eval<()> {
+        let Import { path, position } = import;
+
+        let Some(imported) = module
+            .imports
+            .iter()
+            .find(|(import_path, _)| import_path == path)
+            .and_then(|(_, real_path)| self.modules.get_key_value(real_path))
+        else {
+            return error(format!("Could not import module '{path}'"), position);
+        };
+
+        let module_env = self.module_env(imported)?;
+
+        for name in imported.1.exports.flatten().keys() {
+            let Some(slot) = module_env.get(name) else {
+                continue;
+            };
+
+            if import.is_wildcard() {
+                env.bind(name, slot);
+            } else {
+                env.bind(&format!("{path}::{name}"), slot);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn module_env(&self, (path, module): (&String, &Module<TypeInfo>)) -> Eval<Env> {
+        if let Some(env) = self.module_envs.borrow().get(path) {
+            return Ok(env.clone());
+        }
+
+        let env = Frame::new(None);
+        // register the environment before initializing it, so cyclic imports terminate
+        self.module_envs
+            .borrow_mut()
+            .insert(path.to_owned(), env.clone());
+        self.execute_module(module, &env, true)?;
+
+        Ok(env)
+    }
+
+    /// Execute a statement and return its value (which is only non-void for expressions).
+    fn execute_statement(&self, statement: &Statement<TypeInfo>, env: &Env) -> Eval<Value> {
+        match statement {
+            Statement::Expression(expression) => self.evaluate(expression, env),
+            Statement::Intrinsic(intrinsic) => {
+                self.execute_intrinsic(intrinsic, env)?;
+                Ok(Value::Void)
+            }
+            Statement::CompilerDirective(CompilerDirective { statement, .. }) => match statement {
+                Some(statement) => self.execute_statement(statement, env),
+                None => Ok(Value::Void),
+            },
+            Statement::InlineAssembly(InlineAssembly { position, .. }) => {
+                error("Inline assembly can not be interpreted", position)
+            }
+            Statement::Import(Import { position, .. }) => error(
+                "Imports are only supported at the top level of a module",
+                position,
+            ),
+        }
+    }
+
+    fn execute_intrinsic(&self, intrinsic: &Intrinsic<TypeInfo>, env: &Env) -> Eval<()> {
+        match intrinsic {
+            Intrinsic::Declaration(Declaration { ident, .. }) => {
+                let value = match Builtin::from_name(&ident.value) {
+                    Some(builtin) => Value::Builtin(builtin),
+                    None => Value::External(ident.value.to_owned()),
+                };
+                env.define(&ident.value, value);
+            }
+            Intrinsic::Definition(Definition { ident, value, .. }) => {
+                let value = match value {
+                    Expression::FnDef(fn_def) => self.function(&ident.value, fn_def, env),
+                    value => self.evaluate(value, env)?.convert_to(&ident.info._type),
+                };
+                env.define(&ident.value, value);
+            }
+            Intrinsic::Assignment(assignment) => self.assign(assignment, env)?,
+            Intrinsic::WhileLoop(WhileLoop {
+                condition, block, ..
+            }) => {
+                while self.evaluate_condition(condition, env)? {
+                    match self.execute_block(block, env) {
+                        Err(Control::Break(None, _)) => break,
+                        Err(control) => return Err(control),
+                        Ok(_) => {}
+                    }
+                }
+            }
+            Intrinsic::Break(Break { label, value, .. }) => {
+                let value = match value {
+                    Some(value) => self.evaluate(value, env)?,
+                    None => Value::Void,
+                };
+                return Err(Control::Break(label.to_owned(), value));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn assign(&self, Assignment { lhs, value, .. }: &Assignment<TypeInfo>, env: &Env) -> Eval<()> {
+        let value = self.evaluate(value, env)?;
+
+        match lhs {
+            Expression::Ident(Ident {
+                value: name,
+                info,
+                position,
+            }) => {
+                let Some(slot) = env.get(name) else {
+                    return error(format!("Undefined identifier '{name}'"), position);
+                };
+
+                let _type = match &info._type {
+                    VariableType::Reference(inner) => inner.as_ref(),
+                    _type => _type,
+                };
+                *slot.borrow_mut() = value.convert_to(_type);
+            }
+            Expression::Postfix(PostfixExpr {
+                lhs,
+                op: PostfixOp::Indexing(indexing),
+                ..
+            }) => {
+                let (values, index) = self.evaluate_indexing(lhs, indexing, env)?;
+                values.borrow_mut()[index] = value.convert_to(&indexing.info._type);
+            }
+            lhs => return error("Invalid left hand side of assignment", &lhs.position()),
+        }
+
+        Ok(())
+    }
+
+    fn execute_block(&self, block: &Block<TypeInfo>, env: &Env) -> Eval<Value> {
+        let env = Frame::new(Some(env.clone()));
+        let mut value = Value::Void;
+
+        for (index, statement) in block.block.iter().enumerate() {
+            if let Some((name, fn_def)) = forward_definition(&block.block[index..]) {
+                env.define(name, self.function(name, fn_def, &env));
+                value = Value::Void;
+                continue;
+            }
+
+            value = self.execute_statement(statement, &env)?;
+        }
+
+        Ok(value)
+    }
+
+    fn function(&self, name: &str, fn_def: &FnDef<TypeInfo>, env: &Env) -> Value {
+        let return_type = match &fn_def.info._type {
+            VariableType::Func { return_type, .. } => return_type.as_ref().clone(),
+            _ => VariableType::Void,
+        };
+
+        Value::Function(Rc::new(Function {
+            name: name.to_owned(),
+            params: fn_def.params.clone(),
+            return_type,
+            block: fn_def.block.clone(),
+            env: env.clone(),
+            is_syscall: builtins::is_syscall(name, &fn_def.position.0),
+        }))
+    }
+
+    fn evaluate_condition(&self, condition: &Expression<TypeInfo>, env: &Env) -> Eval<bool> {
+        match self.evaluate(condition, env)? {
+            Value::Boolean(value) => Ok(value),
+            value => error(
+                format!("Invalid value of condition: {value}"),
+                &condition.position(),
+            ),
+        }
+    }
+
+    fn evaluate(&self, expression: &Expression<TypeInfo>, env: &Env) -> Eval<Value> {
+        match expression {
+            Expression::If(If {
+                condition,
+                if_block,
+                else_block,
+                ..
+            }) => {
+                if self.evaluate_condition(condition, env)? {
+                    self.execute_block(if_block, env)
+                } else if let Some(else_block) = else_block {
+                    self.execute_block(else_block, env)
+                } else {
+                    Ok(Value::Void)
+                }
+            }
+            Expression::Binary(binary) => self.evaluate_binary(binary, env),
+            Expression::Prefix(PrefixExpr { op, rhs, .. }) => {
+                match (op, self.evaluate(rhs, env)?) {
+                    (PrefixOp::UnaryMinus, Value::Integer(value)) => {
+                        Ok(Value::Integer(value.wrapping_neg()))
+                    }
+                    (PrefixOp::Not, Value::Boolean(value)) => Ok(Value::Boolean(!value)),
+                    (PrefixOp::BitNot, Value::Integer(value)) => Ok(Value::Integer(!value)),
+                    (op, value) => error(
+                        format!("Invalid operand of prefix operation '{op}': {value}"),
+                        &rhs.position(),
+                    ),
+                }
+            }
+            Expression::Postfix(PostfixExpr {
+                lhs,
+                op: PostfixOp::Call(call),
+                ..
+            }) => self.call(lhs, call, env),
+            Expression::Postfix(PostfixExpr {
+                lhs,
+                op: PostfixOp::Indexing(indexing),
+                ..
+            }) => {
+                let (values, index) = self.evaluate_indexing(lhs, indexing, env)?;
+                let value = values.borrow()[index].clone();
+                Ok(value)
+            }
+            Expression::Integer(integer) => {
+                Ok(Value::Integer(integer.value).convert_to(&integer.info._type))
+            }
+            Expression::Character(character) => Ok(Value::Character(character.value as u8)),
+            Expression::Ident(Ident {
+                value: name,
+                position,
+                ..
+            }) => {
+                let value = match env.get(name) {
+                    Some(slot) => slot.borrow().clone(),
+                    None => match Builtin::from_name(name) {
+                        Some(builtin) => Value::Builtin(builtin),
+                        None => return error(format!("Undefined identifier '{name}'"), position),
+                    },
+                };
+
+                match value {
+                    Value::External(name) => error(
+                        format!("Can not access external symbol '{name}' in the interpreter"),
+                        position,
+                    ),
+                    value => Ok(value),
+                }
+            }
+            Expression::Str(str) => Ok(Value::string(&str.value)),
+            Expression::FnDef(fn_def) => Ok(self.function("<anonymous>", fn_def, env)),
+            Expression::Block(block) => self.execute_block(block, env),
+            Expression::Boolean(boolean) => Ok(Value::Boolean(boolean.value)),
+            Expression::Array(array) => {
+                let initializer = self.evaluate(&array.initializer, env)?;
+                let item_type = array.initializer.info()._type;
+                let VariableType::TupleArray { size, .. } = array.info._type else {
+                    return error("Invalid type of array", &array.position);
+                };
+
+                // every item gets its own copy of the initializer (if it is an array)
+                Ok(Value::array(
+                    (0..size)
+                        .map(|_| initializer.clone().convert_to(&item_type))
+                        .collect(),
+                ))
+            }
+            Expression::Loop(loop_expr) => loop {
+                match self.execute_block(&loop_expr.block, env) {
+                    Err(Control::Break(None, value)) => return Ok(value),
+                    Err(control) => return Err(control),
+                    Ok(_) => {}
+                }
+            },
+            Expression::LabeledBlock(LabeledBlock { label, block, .. }) => {
+                match self.execute_block(block, env) {
+                    Err(Control::Break(Some(target), value)) if target == *label => Ok(value),
+                    result => result,
+                }
+            }
+        }
+    }
+
+    fn evaluate_binary(
+        &self,
+        BinaryExpr {
+            op, lhs, rhs, info, ..
+        }: &BinaryExpr<TypeInfo>,
+        env: &Env,
+    ) -> Eval<Value> {
+        let l_value = self.evaluate(lhs, env)?;
+        let r_value = self.evaluate(rhs, env)?;
+
+        if let BinaryOp::Equal = op {
+            if let VariableType::TupleArray { .. } = lhs.info()._type {
+                return Ok(Value::Boolean(l_value.equals(&r_value, &lhs.info()._type)));
+            }
+
+            // like in compiled programs, slices (and strings) are compared by their address
+            if let (Value::Array(l), Value::Array(r)) = (&l_value, &r_value) {
+                return Ok(Value::Boolean(Rc::ptr_eq(l, r)));
+            }
+        }
+
+        let (Some(l), Some(r)) = (l_value.as_integer(), r_value.as_integer()) else {
+            return error(
+                format!("Invalid operands of binary operation '{op}': {l_value} and {r_value}"),
+                &lhs.position(),
+            );
+        };
+
+        let result = match op {
+            BinaryOp::GreaterThan => return Ok(Value::Boolean(l > r)),
+            BinaryOp::LessThan => return Ok(Value::Boolean(l < r)),
+            BinaryOp::Equal => return Ok(Value::Boolean(l == r)),
+            BinaryOp::Plus => l.wrapping_add(r),
+            BinaryOp::Minus => l.wrapping_sub(r),
+            BinaryOp::Times => l.wrapping_mul(r),
+            BinaryOp::DividedBy => {
+                if r == 0 {
+                    return error("Division by zero", &rhs.position());
+                }
+                l.wrapping_div(r)
+            }
+            BinaryOp::BitAnd => l & r,
+            BinaryOp::BitOr => l | r,
+            BinaryOp::BitXor => l ^ r,
+            // like in compiled programs, only the lowest 6 bits of the shift count are used and
+            // shifting right keeps the sign
+            BinaryOp::ShiftLeft => l.wrapping_shl(r as u32),
+            BinaryOp::ShiftRight => l.wrapping_shr(r as u32),
+        };
+
+        Ok(Value::Integer(result).convert_to(&info._type))
+    }
+
+    /// Evaluate the array and the index of an indexing expression.
+    fn evaluate_indexing(
+        &self,
+        lhs: &Expression<TypeInfo>,
+        indexing: &Indexing<TypeInfo>,
+        env: &Env,
+    ) -> Eval<(Rc<RefCell<Vec<Value>>>, usize)> {
+        let Value::Array(values) = self.evaluate(lhs, env)? else {
+            return error("Can not index a non-array value", &lhs.position());
+        };
+
+        let index = self
+            .evaluate(&indexing.index, env)?
+            .as_integer()
+            .unwrap_or(-1);
+        let length = values.borrow().len();
+
+        match usize::try_from(index) {
+            Ok(index) if index < length => Ok((values, index)),
+            _ => error(
+                format!("Index {index} is out of bounds for length {length}"),
+                &indexing.position,
+            ),
+        }
+    }
+
+    fn call(&self, callee: &Expression<TypeInfo>, call: &Call<TypeInfo>, env: &Env) -> Eval<Value> {
+        match self.evaluate(callee, env)? {
+            Value::Builtin(Builtin::Assert | Builtin::AssertEq) => self.assert(callee, call, env),
+            Value::Builtin(builtin) => {
+                let mut args = vec![];
+                for param in &call.params {
+                    args.push(self.evaluate(param, env)?);
+                }
+
+                builtins::call_builtin(builtin, &args, &self.args)
+            }
+            Value::Function(function) => self.call_function(&function, call, env),
+            value => error(format!("Can not call {value}"), &call.position),
+        }
+    }
+
+    /// Evaluate a call of `assert` or `assert_eq`. Failed assertions terminate the program with
+    /// the same message and exit code as compiled programs.
+    fn assert(
+        &self,
+        callee: &Expression<TypeInfo>,
+        call: &Call<TypeInfo>,
+        env: &Env,
+    ) -> Eval<Value> {
+        let (file, line, col) = callee.position();
+        let location = format!("{file}:{line}:{col}");
+
+        let message = match call.params.as_slice() {
+            [condition] => {
+                if self.evaluate(condition, env)?.as_integer() != Some(0) {
+                    return Ok(Value::Void);
+                }
+                format!(
+                    "assertion failed: `{}` ({location})\n",
+                    condition.to_source()
+                )
+            }
+            [left, right] => {
+                let l_value = self.evaluate(left, env)?;
+                let r_value = self.evaluate(right, env)?;
+                if l_value.as_integer() == r_value.as_integer() {
+                    return Ok(Value::Void);
+                }
+
+                let show = |value: &Value| match value {
+                    Value::Character(value) => (*value as char).to_string(),
+                    value => value.to_string(),
+                };
+                format!(
+                    "assertion failed: `{} == {}` ({location})\n  left: {}\n right: {}\n",
+                    left.to_source(),
+                    right.to_source(),
+                    show(&l_value),
+                    show(&r_value)
+                )
+            }
+            _ => return error("Invalid amount of parameters!", &call.position),
+        };
+
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().write_all(message.as_bytes());
+        Err(Control::Exit(101))
+    }
+
+    fn call_function(&self, function: &Function, call: &Call<TypeInfo>, env: &Env) -> Eval<Value> {
+        let depth = self.call_depth.get();
+        if depth == MAX_CALL_DEPTH {
+            return error(
+                format!("Maximum call depth of {MAX_CALL_DEPTH} exceeded"),
+                &call.position,
+            );
+        }
+
+        self.call_depth.set(depth + 1);
+        let value = self.execute_function(function, call, env);
+        self.call_depth.set(depth);
+
+        value
+    }
+
+    fn execute_function(
+        &self,
+        function: &Function,
+        call: &Call<TypeInfo>,
+        env: &Env,
+    ) -> Eval<Value> {
+        let frame = Frame::new(Some(function.env.clone()));
+
+        let mut args = vec![];
+        for (param, arg) in function.params.iter().zip(&call.params) {
+            // reference parameters share the slot of the variable passed to them
+            if let (VariableType::Reference(_), Expression::Ident(ident)) =
+                (&param.ident.info._type, arg)
+            {
+                if let Some(slot) = env.get(&ident.value) {
+                    args.push(Argument::Slot(slot));
+                    continue;
+                }
+            }
+
+            let value = self.evaluate(arg, env)?;
+            args.push(Argument::Value(value.convert_to(&param.ident.info._type)));
+        }
+
+        for (param, arg) in function.params.iter().zip(args) {
+            match arg {
+                Argument::Value(value) => frame.define(&param.ident.value, value),
+                Argument::Slot(slot) => frame.bind(&param.ident.value, slot),
+            }
+        }
+
+        if function.is_syscall {
+            let args = function
+                .params
+                .iter()
+                .filter_map(|param| frame.get(&param.ident.value))
+                .map(|slot| slot.borrow().clone())
+                .collect::<Vec<_>>();
+
+            return builtins::syscall(&args, &call.position);
+        }
+
+        let value = self.execute_block(&function.block, &frame)?;
+
+        Ok(value.convert_to(&function.return_type))
+    }
+}
+
+/// If the first of the given statements declares a function, which is defined by one of the
+/// following statements, get the name and the definition of that function. Since the declaration
+/// makes the function callable before its definition, it is bound at the declaration already.
+fn forward_definition(statements: &[Statement<TypeInfo>]) -> Option<(&str, &FnDef<TypeInfo>)> {
+    let Some((
+        Statement::Intrinsic(Intrinsic::Declaration(Declaration {
+            ident,
+            type_annotation,
+            ..
+        })),
+        rest,
+    )) = statements.split_first()
+    else {
+        return None;
+    };
+
+    if !matches!(type_annotation.value, Type::Function { .. }) {
+        return None;
+    }
+
+    rest.iter().find_map(|statement| match statement {
+        Statement::Intrinsic(Intrinsic::Definition(Definition {
+            ident: defined,
+            value: Expression::FnDef(fn_def),
+            ..
+        })) if defined.value == ident.value => Some((defined.value.as_str(), fn_def)),
+        _ => None,
+    })
+}
+
+/// Check, whether a statement defines something (which is exported from a module).
+fn is_definition(statement: &Statement<TypeInfo>) -> bool {
+    match statement {
+        Statement::Intrinsic(Intrinsic::Definition(_) | Intrinsic::Declaration(_)) => true,
+        Statement::CompilerDirective(CompilerDirective {
+            statement: Some(statement),
+            ..
+        }) => is_definition(statement),
+        _ => false,
+    }
+}
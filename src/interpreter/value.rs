@@ -0,0 +1,235 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::{
+    ast::{Block, Param},
+    typechecker::{TypeInfo, VariableType},
+};
+
+/// A slot holding the value of a variable. Reference parameters share the slot of the variable
+/// passed to them.
+pub type Slot = Rc<RefCell<Value>>;
+
+/// A runtime value of the interpreter.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Void,
+    Integer(i64),
+    Boolean(bool),
+    Character(u8),
+    /// Arrays and strings. Like in compiled programs, strings are byte-wise and terminated by a
+    /// `0` character, so they can be used interchangeably with arrays of characters.
+    Array(Rc<RefCell<Vec<Value>>>),
+    Function(Rc<Function>),
+    Builtin(Builtin),
+    /// A symbol which is declared, but defined outside of the program (e.g., in an object file).
+    External(String),
+}
+
+impl Value {
+    pub fn array(values: Vec<Value>) -> Self {
+        Value::Array(Rc::new(RefCell::new(values)))
+    }
+
+    /// Create a new (`0` terminated) string.
+    pub fn string(value: &str) -> Self {
+        Self::array(
+            value
+                .bytes()
+                .chain([0])
+                .map(Value::Character)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// The bytes of the string stored in this value (up to the terminating `0` character).
+    pub fn bytes(&self) -> Option<Vec<u8>> {
+        let Value::Array(values) = self else {
+            return None;
+        };
+
+        Some(
+            values
+                .borrow()
+                .iter()
+                .map(Value::as_byte)
+                .take_while(|byte| *byte != 0)
+                .collect(),
+        )
+    }
+
+    pub fn as_byte(&self) -> u8 {
+        match self {
+            Value::Character(value) => *value,
+            Value::Integer(value) => *value as u8,
+            Value::Boolean(value) => *value as u8,
+            _ => 0,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            Value::Character(value) => Some(*value as i64),
+            Value::Boolean(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    /// Compare this value to another value of the given type. Like in compiled programs, arrays
+    /// are compared element-wise and strings within them by their content.
+    pub fn equals(&self, other: &Value, _type: &VariableType) -> bool {
+        match (_type, self, other) {
+            (VariableType::TupleArray { item_type, .. }, Value::Array(l), Value::Array(r)) => {
+                let (l, r) = (l.borrow(), r.borrow());
+                l.len() == r.len()
+                    && l.iter()
+                        .zip(r.iter())
+                        .all(|(l, r)| match item_type.as_ref() {
+                            VariableType::Str => l.bytes() == r.bytes(),
+                            item_type => l.equals(r, item_type),
+                        })
+            }
+            (_, Value::Array(l), Value::Array(r)) => Rc::ptr_eq(l, r),
+            _ => self.as_integer() == other.as_integer(),
+        }
+    }
+
+    /// Convert this value into the representation of the given type (e.g., when passing a
+    /// character to a function expecting an integer).
+    pub fn convert_to(self, _type: &VariableType) -> Self {
+        match (_type, &self) {
+            (VariableType::Int, Value::Character(value)) => Value::Integer(*value as i64),
+            (VariableType::Char, Value::Integer(value)) => Value::Character(*value as u8),
+            // in contrast to slices (and strings), tuple arrays are passed by value (including
+            // the arrays within them)
+            (VariableType::TupleArray { item_type, .. }, Value::Array(values)) => Value::array(
+                values
+                    .borrow()
+                    .iter()
+                    .map(|value| value.clone().convert_to(item_type))
+                    .collect(),
+            ),
+            _ => self,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Void => f.write_str("void"),
+            Value::Integer(value) => f.write_str(&value.to_string()),
+            Value::Boolean(value) => f.write_str(&value.to_string()),
+            Value::Character(value) => f.write_str(&format!("'{}'", *value as char)),
+            Value::Array(_) => f.write_str("array"),
+            Value::Function(function) => f.write_str(&format!("function '{}'", function.name)),
+            Value::Builtin(builtin) => f.write_str(&format!("builtin '{}'", builtin.name())),
+            Value::External(name) => f.write_str(&format!("external symbol '{name}'")),
+        }
+    }
+}
+
+/// A function (together with the environment it was defined in).
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<Param<TypeInfo>>,
+    pub return_type: VariableType,
+    pub block: Block<TypeInfo>,
+    pub env: Env,
+    /// Whether this function is a system call wrapper of the core library, which is emulated.
+    pub is_syscall: bool,
+}
+
+/// Functions which are part of the prelude of every compiled program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    StrLen,
+    IntToStr,
+    StrToInt,
+    IsInt,
+    CharToInt,
+    IntToChar,
+    Args,
+    ArgCount,
+    Env,
+    Exit,
+    Len,
+    Assert,
+    AssertEq,
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "str_len" => Builtin::StrLen,
+            "int_to_str" => Builtin::IntToStr,
+            "str_to_int" => Builtin::StrToInt,
+            "is_int" => Builtin::IsInt,
+            "char_to_int" => Builtin::CharToInt,
+            "int_to_char" => Builtin::IntToChar,
+            "args" => Builtin::Args,
+            "arg_count" => Builtin::ArgCount,
+            "env" => Builtin::Env,
+            "exit" => Builtin::Exit,
+            "len" => Builtin::Len,
+            "assert" => Builtin::Assert,
+            "assert_eq" => Builtin::AssertEq,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::StrLen => "str_len",
+            Builtin::IntToStr => "int_to_str",
+            Builtin::StrToInt => "str_to_int",
+            Builtin::IsInt => "is_int",
+            Builtin::CharToInt => "char_to_int",
+            Builtin::IntToChar => "int_to_char",
+            Builtin::Args => "args",
+            Builtin::ArgCount => "arg_count",
+            Builtin::Env => "env",
+            Builtin::Exit => "exit",
+            Builtin::Len => "len",
+            Builtin::Assert => "assert",
+            Builtin::AssertEq => "assert_eq",
+        }
+    }
+}
+
+/// An environment, i.e., a stack of frames containing variables.
+pub type Env = Rc<Frame>;
+
+#[derive(Debug, Default)]
+pub struct Frame {
+    variables: RefCell<HashMap<String, Slot>>,
+    parent: Option<Env>,
+}
+
+impl Frame {
+    pub fn new(parent: Option<Env>) -> Env {
+        Rc::new(Frame {
+            variables: RefCell::default(),
+            parent,
+        })
+    }
+
+    /// Define a new variable in this frame (shadowing variables with the same name).
+    pub fn define(&self, name: &str, value: Value) {
+        self.bind(name, Rc::new(RefCell::new(value)));
+    }
+
+    /// Bind an existing slot to a name in this frame.
+    pub fn bind(&self, name: &str, slot: Slot) {
+        self.variables.borrow_mut().insert(name.to_owned(), slot);
+    }
+
+    /// Find the slot of a variable in this frame or one of its parents.
+    pub fn get(&self, name: &str) -> Option<Slot> {
+        match self.variables.borrow().get(name) {
+            Some(slot) => Some(slot.clone()),
+            None => self.parent.as_ref().and_then(|parent| parent.get(name)),
+        }
+    }
+}
@@ -1,12 +1,25 @@
 //! Y
 //!
 //! This library is the primary source for interacting with the Y programming language.
-//! It provides tools for parsing, type checking and compiling Y programs.
+//! It provides tools for parsing, type checking and compiling Y programs. The items meant to be
+//! used by other tools are collected in the [`prelude`].
+#![warn(unnameable_types)]
 #[macro_use]
 extern crate pest_derive;
 
 mod asm;
 pub mod ast;
+pub mod ast_json;
+pub mod ast_tree;
 pub mod compiler;
+pub mod diagnostics;
+pub mod docs;
+pub mod explain;
+pub mod externals;
+pub mod interpreter;
 pub mod loader;
+pub mod pipeline;
+pub mod prelude;
+pub mod project;
 pub mod typechecker;
+pub mod xref;
@@ -1,5 +1,7 @@
 use std::{error::Error, fmt::Display};
 
+use crate::{ast::ParseError, typechecker::TypeError};
+
 #[derive(Debug)]
 pub struct FileLoadError {
     pub message: String,
@@ -14,3 +16,25 @@ impl Display for FileLoadError {
 }
 
 impl Error for FileLoadError {}
+
+/// The errors of a file which could only be parsed partially: its parse errors along with the type
+/// errors of the statements which could be parsed.
+#[derive(Debug)]
+pub struct RecoveredErrors {
+    pub parse_errors: Vec<ParseError>,
+    pub type_errors: Vec<TypeError>,
+}
+
+impl Display for RecoveredErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self
+            .parse_errors
+            .iter()
+            .map(ParseError::to_string)
+            .chain(self.type_errors.iter().map(TypeError::to_string))
+            .collect::<Vec<_>>();
+        f.write_str(&errors.join("\n"))
+    }
+}
+
+impl Error for RecoveredErrors {}
@@ -1,7 +1,7 @@
 mod loaderror;
 
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
     error::Error,
     fmt::Display,
     fs,
@@ -9,15 +9,17 @@ use std::{
     path::PathBuf,
 };
 
-use log::error;
+use log::debug;
 use pest::iterators::Pair;
 
 use crate::{
-    ast::{Ast, Import, Position, Rule, Statement, YParser},
-    typechecker::{extract_exports, TypeInfo, TypeScope, Typechecker},
+    ast::{Ast, Import, ParseError, ParseErrors, Position, Rule, Statement, YParser},
+    pipeline::{run_stage, Cancelled, NoObserver, PipelineObserver, Stage},
+    typechecker::{extract_exports, TypeError, TypeErrors, TypeInfo, TypeScope, Typechecker},
 };
 
 use self::loaderror::FileLoadError;
+pub use self::loaderror::RecoveredErrors;
 
 fn should_be_exported(pair: &Pair<Rule>) -> bool {
     match pair.as_rule() {
@@ -70,7 +72,10 @@ pub struct Module<T> {
     pub imports: Vec<(String, String)>,
 }
 
-pub type Modules<T> = HashMap<String, Module<T>>;
+pub type Modules<T> = BTreeMap<String, Module<T>>;
+
+/// Borrowed modules, e.g., the modules imported by a single module.
+pub type ModuleRefs<'a, T> = BTreeMap<String, &'a Module<T>>;
 
 impl<T> Module<T> {
     /// Resolve a variable name from this module.
@@ -81,25 +86,69 @@ impl<T> Module<T> {
     /// Convert the modules currently stored with their absolute path to modules stored with a
     /// relative path (relative to _this_ module). This is needed to determine the correct module
     /// to import while typechecking.
-    pub fn convert_imports_to_local_names(&self, modules: &Modules<()>) -> Modules<()> {
-        let mut local_modules = Modules::default();
+    pub fn convert_imports_to_local_names<'a>(&self, modules: &'a Modules<T>) -> ModuleRefs<'a, T> {
+        let mut local_modules = ModuleRefs::default();
 
         for (import_path, real_path) in &self.imports {
-            local_modules.insert(
-                import_path.to_owned(),
-                modules.get(real_path).unwrap().to_owned(),
-            );
+            local_modules.insert(import_path.to_owned(), modules.get(real_path).unwrap());
         }
         local_modules
     }
 }
 
 impl Module<()> {
+    /// Type check this module. `cfg` contains overrides for the values used to evaluate compiler
+    /// directives (see `Typechecker::with_cfg`).
+    pub fn type_check(
+        &self,
+        other_modules: &Modules<()>,
+        cfg: &HashMap<String, String>,
+    ) -> Result<Module<TypeInfo>, Box<dyn Error>> {
+        self.with_observer(&NoObserver)
+            .type_check(other_modules, cfg)
+    }
+
+    /// Load all modules imported by this (main) module and type check them together with this
+    /// module.
+    pub fn type_check_program(
+        &self,
+        cfg: &HashMap<String, String>,
+    ) -> Result<(Module<TypeInfo>, Modules<TypeInfo>), Box<dyn Error>> {
+        self.with_observer(&NoObserver).type_check_program(cfg)
+    }
+
+    /// Notify the given observer about the progress of loading and type checking this module.
+    pub fn with_observer<'a>(&'a self, observer: &'a dyn PipelineObserver) -> ObservedModule<'a> {
+        ObservedModule {
+            module: self,
+            observer,
+        }
+    }
+}
+
+/// A module together with an observer of the pipeline (see `Module::with_observer`).
+pub struct ObservedModule<'a> {
+    module: &'a Module<()>,
+    observer: &'a dyn PipelineObserver,
+}
+
+impl ObservedModule<'_> {
+    /// Like `Module::type_check`, but the observer may cancel type checking.
     pub fn type_check(
         &self,
         other_modules: &Modules<()>,
+        cfg: &HashMap<String, String>,
     ) -> Result<Module<TypeInfo>, Box<dyn Error>> {
-        let modules = self.convert_imports_to_local_names(other_modules);
+        self.check(other_modules, cfg, false)
+    }
+
+    fn check(
+        &self,
+        other_modules: &Modules<()>,
+        cfg: &HashMap<String, String>,
+        is_imported: bool,
+    ) -> Result<Module<TypeInfo>, Box<dyn Error>> {
+        let modules = self.module.convert_imports_to_local_names(other_modules);
 
         let Module {
             name,
@@ -107,32 +156,63 @@ impl Module<()> {
             exports,
             imports,
             ast,
-        } = self;
-
-        let typechecker = Typechecker::from_ast(ast.clone(), modules);
-        let ast = match typechecker.check() {
-            Ok(ast) => ast,
-            Err(type_error) => {
-                error!("{}", type_error);
-                std::process::exit(-1);
-            }
-        };
+        } = self.module;
+
+        let mut typechecker = Typechecker::from_ast(ast, modules)
+            .with_cfg(cfg.clone())
+            .with_observer(self.observer);
+        if is_imported {
+            typechecker = typechecker.as_imported();
+        }
+        let result = typechecker.check_all();
+
+        if typechecker.is_cancelled() {
+            return Err(Box::new(Cancelled));
+        }
 
         Ok(Module {
-            ast,
+            ast: result?,
             name: name.clone(),
             exports: exports.clone(),
             imports: imports.clone(),
             file_path: file_path.clone(),
         })
     }
+
+    /// Like `Module::type_check_program`, but reports the stages to the observer (which may
+    /// cancel in between).
+    pub fn type_check_program(
+        &self,
+        cfg: &HashMap<String, String>,
+    ) -> Result<(Module<TypeInfo>, Modules<TypeInfo>), Box<dyn Error>> {
+        // `load_modules` expects the path of a file, but the main module stores the folder which
+        // contains it
+        let file = self.module.file_path.join("_");
+
+        let modules = run_stage(self.observer, Stage::Load, || {
+            load_modules(&self.module.ast, file, Modules::default())
+        })?;
+
+        run_stage(self.observer, Stage::TypeCheck, || {
+            let mut type_safe_modules = Modules::default();
+
+            for (key, module) in &modules {
+                let module = module
+                    .with_observer(self.observer)
+                    .check(&modules, cfg, true)?;
+                type_safe_modules.insert(key.to_owned(), module);
+            }
+
+            Ok((self.type_check(&modules, cfg)?, type_safe_modules))
+        })
+    }
 }
 
 #[derive(Debug)]
-struct ImportError {
-    path: String,
-    import_statement: String,
-    position: Position,
+pub(crate) struct ImportError {
+    pub path: String,
+    pub import_statement: String,
+    pub position: Position,
 }
 
 impl From<(&String, &String, &Position)> for ImportError {
@@ -160,20 +240,69 @@ impl Display for ImportError {
 
 impl Error for ImportError {}
 
-pub fn load_module(mut file: PathBuf) -> Result<Module<()>, Box<dyn Error>> {
+/// Recover from the given parse error to find all other parse errors of the file.
+fn all_parse_errors(error: ParseError, file: &str, content: &str) -> Box<dyn Error> {
+    let (_, mut errors) = Ast::from_source_recovering(file, content);
+
+    if errors.len() > 1 {
+        Box::new(ParseErrors(errors))
+    } else {
+        Box::new(errors.pop().unwrap_or(error))
+    }
+}
+
+/// Like `all_parse_errors`, but for the main module of a program. The statements
+/// which could be parsed are type checked (without overrides for compiler directives), so their
+/// type errors are reported along with the parse errors.
+fn recover(error: ParseError, file: PathBuf, content: &str) -> Box<dyn Error> {
+    let (ast, mut parse_errors) = Ast::from_source_recovering(&file.to_string_lossy(), content);
+
+    let checked = module_from_ast(ast, file)
+        .and_then(|module| module.type_check_program(&HashMap::default()));
+    // other errors (e.g., failing imports) are not reported, the parse errors come first
+    let type_errors = match checked.map_err(|error| error.downcast::<TypeErrors>()) {
+        Err(Ok(errors)) => errors.0,
+        Err(Err(error)) => match error.downcast::<TypeError>() {
+            Ok(error) => vec![*error],
+            Err(_) => vec![],
+        },
+        Ok(_) => vec![],
+    };
+
+    if !type_errors.is_empty() {
+        Box::new(RecoveredErrors {
+            parse_errors,
+            type_errors,
+        })
+    } else if parse_errors.len() > 1 {
+        Box::new(ParseErrors(parse_errors))
+    } else {
+        Box::new(parse_errors.pop().unwrap_or(error))
+    }
+}
+
+pub fn load_module(file: PathBuf) -> Result<Module<()>, Box<dyn Error>> {
     let file_content = std::fs::read_to_string(&file)
         .unwrap_or_else(|_| panic!("Could not read file: '{}'", file.to_string_lossy()));
 
-    let pairs = match YParser::parse_program(&file.to_string_lossy(), &file_content) {
+    let pairs = match YParser::parse_program(file.to_string_lossy(), &file_content) {
         Ok(pairs) => pairs,
-        Err(parse_error) => {
-            error!("{parse_error}");
-            std::process::exit(-1);
-        }
+        Err(error) => return Err(recover(error, file, &file_content)),
     };
 
     let ast = Ast::from_program(pairs.collect(), &file.to_string_lossy());
+    debug!(
+        "Parsed '{}': {} top-level statements in {} lines",
+        file.to_string_lossy(),
+        ast.nodes().len(),
+        file_content.lines().count()
+    );
+
+    module_from_ast(ast, file)
+}
 
+/// Create the module of the given (parsed) file.
+fn module_from_ast(ast: Ast<()>, mut file: PathBuf) -> Result<Module<()>, Box<dyn Error>> {
     file.pop();
 
     let folder = file.to_string_lossy();
@@ -240,13 +369,8 @@ pub fn load_modules(
             }));
         };
 
-        let pairs = match YParser::parse_program(&file, &file_content) {
-            Ok(pairs) => pairs,
-            Err(parse_error) => {
-                error!("{parse_error}");
-                std::process::exit(-1);
-            }
-        };
+        let pairs = YParser::parse_program(&file, &file_content)
+            .map_err(|error| all_parse_errors(error, &file, &file_content))?;
 
         let fns = pairs
             .clone()
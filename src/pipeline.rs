@@ -0,0 +1,116 @@
+//! Progress reporting for the compilation pipeline.
+//!
+//! Callers (e.g., editors) can plug a `PipelineObserver` into the loader (via
+//! `Module::with_observer`) and the compiler (via `Compiler::set_observer`) to get notified about
+//! each stage of the pipeline and to cancel the compilation in between.
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use log::info;
+
+use crate::{ast::ParseErrors, typechecker::TypeErrors};
+
+/// The stages of compiling a program (in the order they are run).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Parsing the main module.
+    Parse,
+    /// Loading (and parsing) all imported modules.
+    Load,
+    /// Type checking all modules.
+    TypeCheck,
+    /// Generating and assembling the code of all modules.
+    Codegen,
+    /// Linking the final executable.
+    Link,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::Parse => "Parsing",
+            Stage::Load => "Loading imports",
+            Stage::TypeCheck => "Type checking",
+            Stage::Codegen => "Generating code",
+            Stage::Link => "Linking",
+        })
+    }
+}
+
+/// Observer of the compilation pipeline. All methods have a default implementation, so
+/// implementors only need to provide the ones they are interested in.
+pub trait PipelineObserver {
+    fn on_stage_start(&self, _stage: Stage) {}
+
+    fn on_stage_end(&self, _stage: Stage, _duration: Duration) {}
+
+    /// Called for every error (e.g., each type error) found during a stage.
+    fn on_diagnostic(&self, _diagnostic: &dyn Error) {}
+
+    /// Whether the compilation should be cancelled. This is consulted before each stage and
+    /// after each top-level statement while type checking.
+    fn should_cancel(&self) -> bool {
+        false
+    }
+}
+
+/// Observer which ignores all events (and never cancels).
+pub struct NoObserver;
+
+impl PipelineObserver for NoObserver {}
+
+/// Error returned when the compilation was cancelled by its observer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Compilation cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Run a single stage of the pipeline and notify the observer about it. If the observer wants to
+/// cancel, the stage is not started at all.
+pub fn run_stage<T>(
+    observer: &dyn PipelineObserver,
+    stage: Stage,
+    run: impl FnOnce() -> Result<T, Box<dyn Error>>,
+) -> Result<T, Box<dyn Error>> {
+    if observer.should_cancel() {
+        return Err(Box::new(Cancelled));
+    }
+
+    observer.on_stage_start(stage);
+    info!("{stage}...");
+    let start = Instant::now();
+
+    let result = run();
+
+    match &result {
+        Ok(_) => info!("{stage} finished after {:.2?}", start.elapsed()),
+        Err(_) => info!("{stage} failed after {:.2?}", start.elapsed()),
+    }
+
+    if let Err(error) = &result {
+        if let Some(TypeErrors(errors)) = error.downcast_ref::<TypeErrors>() {
+            for error in errors {
+                observer.on_diagnostic(error);
+            }
+        } else if let Some(ParseErrors(errors)) = error.downcast_ref::<ParseErrors>() {
+            for error in errors {
+                observer.on_diagnostic(error);
+            }
+        } else if !error.is::<Cancelled>() {
+            observer.on_diagnostic(error.as_ref());
+        }
+    }
+
+    observer.on_stage_end(stage, start.elapsed());
+
+    result
+}
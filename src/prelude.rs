@@ -0,0 +1,26 @@
+//! The public API of this library, which tools building upon it are meant to use.
+//!
+//! Items are only removed from (or changed incompatibly within) the prelude together with an
+//! increment of the (major) version of the crate. Everything else may change in any release.
+//!
+//! ```
+//! use y_lang::prelude::*;
+//! ```
+pub use crate::{
+    ast::{
+        Ast, Expression, Intrinsic, ParseError, ParseErrors, Position, Statement, Type, YParser,
+    },
+    ast_json::{from_json, to_json, JsonError},
+    ast_tree::{render, render_pairs, TreeStyle},
+    compiler::{check_toolchain, Compiler, RelocModel, ToolNotFound},
+    diagnostics::{render_error, render_error_with_limit, ColorChoice, Diagnostic},
+    docs::ModuleDocs,
+    explain::{explain_lowering, ExplainError, Explanation},
+    externals::ExternalsReport,
+    interpreter::{Interpreter, InterpreterError, Value},
+    loader::{load_module, Module, ModuleRefs, Modules},
+    pipeline::{run_stage, Cancelled, NoObserver, PipelineObserver, Stage},
+    project::{find_manifest, ManifestError, ProjectConfig},
+    typechecker::{TypeError, TypeErrors, TypeInfo, Typechecker, VariableType},
+    xref::Xref,
+};
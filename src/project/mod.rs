@@ -0,0 +1,305 @@
+//! Manifests of Y projects.
+//!
+//! A project is described by a `why.toml` in its root directory, which names the entry point of
+//! the project and provides defaults for building it (which flags given on the command line
+//! override). All paths within the manifest are relative to its directory.
+use std::{
+    error::Error,
+    fmt::Display,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+use serde::Deserialize;
+use toml::{
+    de::{DeTable, DeValue},
+    Spanned,
+};
+
+use crate::{ast::Position, compiler::RelocModel};
+
+/// The file name of project manifests.
+pub const MANIFEST_NAME: &str = "why.toml";
+
+/// An invalid manifest (or an invalid value within it).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestError {
+    pub message: String,
+    pub position: Position,
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (file, line, col) = &self.position;
+        f.write_str(&format!("{} ({file}:{line}:{col})", self.message))
+    }
+}
+
+impl Error for ManifestError {}
+
+/// The configuration of a project, as given by its manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectConfig {
+    /// The path of the manifest.
+    pub manifest: PathBuf,
+    /// The name of the project (`package.name`), which is also the name of its binary.
+    pub name: Option<String>,
+    /// The source file of the main program (`package.entry`).
+    pub entry: Option<PathBuf>,
+    /// The directory binaries are written to (`build.output-dir`).
+    pub output_dir: Option<PathBuf>,
+    pub reloc_model: Option<RelocModel>,
+    pub slot_reuse: Option<bool>,
+    pub release_asserts: Option<bool>,
+    pub stack_guard: Option<bool>,
+    pub checked_arithmetic: Option<bool>,
+    /// Additional object files to link into the binary (`build.link-obj`).
+    pub link_obj: Vec<PathBuf>,
+    pub emit_ast: Option<PathBuf>,
+    pub emit_typed_ast: Option<PathBuf>,
+    pub emit_xref: Option<PathBuf>,
+    /// The position of the `[package]` table (for errors about missing keys).
+    package_position: Position,
+}
+
+/// The content of a manifest.
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    package: Option<Spanned<Package>>,
+    #[serde(default)]
+    build: Build,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Package {
+    name: Option<String>,
+    entry: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Build {
+    output_dir: Option<String>,
+    reloc_model: Option<RelocModel>,
+    slot_reuse: Option<bool>,
+    release_asserts: Option<bool>,
+    stack_guard: Option<bool>,
+    checked_arithmetic: Option<bool>,
+    #[serde(default)]
+    link_obj: Vec<String>,
+    emit_ast: Option<String>,
+    emit_typed_ast: Option<String>,
+    emit_xref: Option<String>,
+}
+
+/// Find the manifest of the project the given directory belongs to, i.e., the first `why.toml`
+/// within the directory or any of its parents.
+pub fn find_manifest(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(MANIFEST_NAME))
+        .find(|manifest| manifest.is_file())
+}
+
+impl ProjectConfig {
+    /// Load the manifest at the given path. Unknown keys are reported as warnings, invalid values
+    /// as errors.
+    pub fn load(manifest: &Path) -> Result<ProjectConfig, Box<dyn Error>> {
+        let manifest = fs::canonicalize(manifest)?;
+        let source = fs::read_to_string(&manifest)?;
+
+        Ok(ProjectConfig::parse(&manifest, &source)?)
+    }
+
+    /// Parse the content of the manifest at the given path.
+    pub fn parse(manifest: &Path, source: &str) -> Result<ProjectConfig, ManifestError> {
+        let file = manifest.to_string_lossy().to_string();
+        let syntax_error = |error: toml::de::Error| ManifestError {
+            message: format!("Invalid manifest: {}", error.message()),
+            position: position(&file, source, error.span().unwrap_or_default()),
+        };
+
+        let document = DeTable::parse(source).map_err(syntax_error)?;
+
+        // invalid values are reported with the key they belong to
+        let value_error = |error: toml::de::Error| {
+            let span = error.span().unwrap_or_default();
+            match key_at(document.get_ref(), span.start) {
+                Some((key, span)) => ManifestError {
+                    message: format!("Invalid value for '{key}': {}", error.message()),
+                    position: position(&file, source, span),
+                },
+                None => syntax_error(error),
+            }
+        };
+
+        let mut unknown_keys = vec![];
+        let deserializer = toml::Deserializer::from(document.clone());
+        let Manifest { package, build } =
+            serde_ignored::deserialize(deserializer, |key| unknown_keys.push(key.to_string()))
+                .map_err(value_error)?;
+
+        for key in unknown_keys {
+            let (file, line, col) = key_position(&file, source, document.get_ref(), &key);
+            warn!("Unknown key '{key}' in manifest ({file}:{line}:{col})");
+        }
+
+        let dir = manifest.parent().unwrap_or(Path::new(""));
+        let package_position = package
+            .as_ref()
+            .map(|package| position(&file, source, package.span()))
+            .unwrap_or((file.to_owned(), 1, 1));
+        let Package { name, entry } = package.map(Spanned::into_inner).unwrap_or_default();
+
+        let entry = match entry {
+            Some(entry) => {
+                let path = dir.join(entry.get_ref());
+                let Some(path) = fs::canonicalize(&path).ok().filter(|path| path.is_file()) else {
+                    return Err(ManifestError {
+                        message: format!(
+                            "Invalid value for 'package.entry': '{}' does not exist",
+                            path.display()
+                        ),
+                        position: position(&file, source, entry.span()),
+                    });
+                };
+                Some(path)
+            }
+            None => None,
+        };
+
+        Ok(ProjectConfig {
+            manifest: manifest.to_owned(),
+            name,
+            entry,
+            output_dir: build.output_dir.map(|path| dir.join(path)),
+            reloc_model: build.reloc_model,
+            slot_reuse: build.slot_reuse,
+            release_asserts: build.release_asserts,
+            stack_guard: build.stack_guard,
+            checked_arithmetic: build.checked_arithmetic,
+            link_obj: build.link_obj.iter().map(|path| dir.join(path)).collect(),
+            emit_ast: build.emit_ast.map(|path| dir.join(path)),
+            emit_typed_ast: build.emit_typed_ast.map(|path| dir.join(path)),
+            emit_xref: build.emit_xref.map(|path| dir.join(path)),
+            package_position,
+        })
+    }
+
+    /// Get the entry point of the project, failing if the manifest does not specify one.
+    pub fn entry(&self) -> Result<&Path, ManifestError> {
+        self.entry.as_deref().ok_or_else(|| ManifestError {
+            message: "Missing key 'package.entry' (the source file to build)".to_owned(),
+            position: self.package_position.to_owned(),
+        })
+    }
+
+    /// Get the path of the binary built from the given source file, if the manifest specifies an
+    /// output directory. The binary of the entry point is named after the project.
+    pub fn output_path(&self, file: &Path) -> Option<PathBuf> {
+        let output_dir = self.output_dir.as_ref()?;
+
+        let name = match &self.name {
+            Some(name) if self.entry.as_deref() == Some(file) => name.to_owned(),
+            _ => file.file_stem()?.to_string_lossy().to_string(),
+        };
+
+        Some(output_dir.join(name))
+    }
+}
+
+/// Get the position of the given byte offset (i.e., the start of the span) within the source.
+fn position(file: &str, source: &str, span: Range<usize>) -> Position {
+    let before = &source[..span.start.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let col = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+
+    (file.to_owned(), line, col)
+}
+
+/// Get the position of a (dotted) key within the manifest, e.g., `build.opt-level`.
+fn key_position(file: &str, source: &str, document: &DeTable, key: &str) -> Position {
+    let mut table = document;
+    let mut span = 0..0;
+    for segment in key.split('.') {
+        let Some((key, value)) = table.get_key_value(segment) else {
+            break;
+        };
+        span = key.span();
+        match value.get_ref() {
+            DeValue::Table(value) => table = value,
+            _ => break,
+        }
+    }
+
+    position(file, source, span)
+}
+
+/// Find the (dotted) key of the value containing the given offset, along with the span of the key.
+fn key_at(table: &DeTable, offset: usize) -> Option<(String, Range<usize>)> {
+    table.iter().find_map(|(key, value)| match value.get_ref() {
+        DeValue::Table(table) => {
+            key_at(table, offset).map(|(inner, span)| (format!("{}.{inner}", key.get_ref()), span))
+        }
+        _ if value.span().contains(&offset) => Some((key.get_ref().to_string(), key.span())),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::compiler::RelocModel;
+
+    use super::ProjectConfig;
+
+    fn parse(source: &str) -> Result<ProjectConfig, super::ManifestError> {
+        ProjectConfig::parse(Path::new("/project/why.toml"), source)
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let config = parse(
+            "[package]\nname = \"hello\"\n\n[build]\noutput-dir = \"bin\"\nreloc-model = \"static\"\nchecked-arithmetic = true\nlink-obj = [\"lib/a.o\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("hello"));
+        assert_eq!(config.output_dir, Some(PathBuf::from("/project/bin")));
+        assert_eq!(config.reloc_model, Some(RelocModel::Static));
+        assert_eq!(config.checked_arithmetic, Some(true));
+        assert_eq!(config.stack_guard, None);
+        assert_eq!(config.link_obj, [PathBuf::from("/project/lib/a.o")]);
+
+        assert_eq!(
+            config.output_path(Path::new("/project/src/other.why")),
+            Some(PathBuf::from("/project/bin/other"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_report_key() {
+        let error = parse("[build]\n\nstack-guard = \"yes\"").unwrap_err();
+        assert_eq!(
+            error.message,
+            "Invalid value for 'build.stack-guard': invalid type: string \"yes\", expected a boolean"
+        );
+        assert_eq!(error.position, ("/project/why.toml".to_owned(), 3, 1));
+
+        assert!(parse("[build\nstack-guard = true").is_err());
+        assert!(parse("[build]\nreloc-model = \"dynamic\"").is_err());
+        assert!(parse("[build]\nlink-obj = [1]").is_err());
+        assert!(parse("[package]\nentry = \"does/not/exist.why\"").is_err());
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        let config = parse("[package]\nname = \"hello\"").unwrap();
+
+        let error = config.entry().unwrap_err();
+        assert!(error.message.contains("'package.entry'"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+
+use crate::ast::{
+    Assignment, BinaryExpr, Block, Break, Call, CompilerDirective, Definition, Expression, FnDef,
+    Ident, If, Intrinsic, LabeledBlock, Loop, PostfixExpr, PostfixOp, PrefixExpr, Statement,
+    WhileLoop,
+};
+
+use super::TypeInfo;
+
+/// Collect the names of all identifiers referenced within the bodies of functions (including
+/// lambdas) defined in the given statements, except for their parameters. Since functions may only
+/// capture top-level variables, every top-level variable accessed by a function is part of the
+/// result (along with locals of the functions, which share their name).
+pub fn captured_variables(statements: &[Statement<TypeInfo>]) -> BTreeSet<String> {
+    let mut captured = BTreeSet::new();
+    for statement in statements {
+        collect_statement(statement, None, &mut captured);
+    }
+    captured
+}
+
+fn collect_block(
+    Block { block, .. }: &Block<TypeInfo>,
+    bound: Option<&BTreeSet<String>>,
+    captured: &mut BTreeSet<String>,
+) {
+    for statement in block {
+        collect_statement(statement, bound, captured);
+    }
+}
+
+fn collect_statement(
+    statement: &Statement<TypeInfo>,
+    bound: Option<&BTreeSet<String>>,
+    captured: &mut BTreeSet<String>,
+) {
+    match statement {
+        Statement::Expression(expression) => collect_expression(expression, bound, captured),
+        Statement::Intrinsic(intrinsic) => collect_intrinsic(intrinsic, bound, captured),
+        Statement::CompilerDirective(CompilerDirective {
+            statement: Some(statement),
+            ..
+        }) => collect_statement(statement, bound, captured),
+        Statement::CompilerDirective(_) | Statement::Import(_) | Statement::InlineAssembly(_) => {}
+    }
+}
+
+fn collect_intrinsic(
+    intrinsic: &Intrinsic<TypeInfo>,
+    bound: Option<&BTreeSet<String>>,
+    captured: &mut BTreeSet<String>,
+) {
+    match intrinsic {
+        Intrinsic::Definition(Definition { value, .. }) => {
+            collect_expression(value, bound, captured)
+        }
+        Intrinsic::Assignment(Assignment { lhs, value, .. }) => {
+            collect_expression(lhs, bound, captured);
+            collect_expression(value, bound, captured);
+        }
+        Intrinsic::WhileLoop(WhileLoop {
+            condition, block, ..
+        }) => {
+            collect_expression(condition, bound, captured);
+            collect_block(block, bound, captured);
+        }
+        Intrinsic::Break(Break {
+            value: Some(value), ..
+        }) => collect_expression(value, bound, captured),
+        Intrinsic::Break(_) | Intrinsic::Declaration(_) => {}
+    }
+}
+
+fn collect_expression(
+    expression: &Expression<TypeInfo>,
+    bound: Option<&BTreeSet<String>>,
+    captured: &mut BTreeSet<String>,
+) {
+    match expression {
+        Expression::Ident(Ident { value, .. }) => {
+            if bound.is_some_and(|bound| !bound.contains(value)) {
+                captured.insert(value.to_owned());
+            }
+        }
+        // parameters of all enclosing functions are bound within the body
+        Expression::FnDef(FnDef { params, block, .. }) => {
+            let mut bound = bound.cloned().unwrap_or_default();
+            bound.extend(params.iter().map(|param| param.ident.value.to_owned()));
+            collect_block(block, Some(&bound), captured)
+        }
+        Expression::If(If {
+            condition,
+            if_block,
+            else_block,
+            ..
+        }) => {
+            collect_expression(condition, bound, captured);
+            collect_block(if_block, bound, captured);
+            if let Some(else_block) = else_block {
+                collect_block(else_block, bound, captured);
+            }
+        }
+        Expression::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            collect_expression(lhs, bound, captured);
+            collect_expression(rhs, bound, captured);
+        }
+        Expression::Prefix(PrefixExpr { rhs, .. }) => collect_expression(rhs, bound, captured),
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Call(Call { params, .. }),
+            ..
+        }) => {
+            collect_expression(lhs, bound, captured);
+            for param in params {
+                collect_expression(param, bound, captured);
+            }
+        }
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Indexing(indexing),
+            ..
+        }) => {
+            collect_expression(lhs, bound, captured);
+            collect_expression(&indexing.index, bound, captured);
+        }
+        Expression::Block(block) => collect_block(block, bound, captured),
+        Expression::Array(array) => collect_expression(&array.initializer, bound, captured),
+        Expression::Loop(Loop { block, .. })
+        | Expression::LabeledBlock(LabeledBlock { block, .. }) => {
+            collect_block(block, bound, captured)
+        }
+        Expression::Integer(_)
+        | Expression::Character(_)
+        | Expression::Str(_)
+        | Expression::Boolean(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        ast::{Ast, YParser},
+        loader::ModuleRefs,
+        typechecker::Typechecker,
+    };
+
+    use super::captured_variables;
+
+    fn captured(src: &str) -> BTreeSet<String> {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+        let ast = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap();
+
+        captured_variables(ast.nodes())
+    }
+
+    #[test]
+    fn test_variable_accessed_by_function() {
+        assert_eq!(
+            captured("let mut x := 0\nlet y := 1\nlet inc := (): void => {\n    x = x + 1\n}\ny"),
+            BTreeSet::from(["x".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_variable_accessed_by_lambda() {
+        assert_eq!(
+            captured("let x := 2\nlet apply := (f: (int) -> int): int => { f(1) }\napply((y: int): int => { x * y })"),
+            BTreeSet::from(["x".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_no_functions() {
+        assert!(captured("let x := 1\nlet y := x + 1").is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::ast::{BinaryExpr, BinaryOp, Expression, Ident, Integer, PrefixExpr, PrefixOp};
+
+/// The values of the constants of a module, i.e., of its immutable top-level definitions whose
+/// values are constant integer expressions. Constants can be used where a value has to be known at
+/// compile time (e.g., as the size of an array).
+#[derive(Debug, Default, Clone)]
+pub struct Constants {
+    values: HashMap<String, i64>,
+}
+
+impl Constants {
+    pub fn define(&mut self, name: &str, value: i64) {
+        self.values.insert(name.to_owned(), value);
+    }
+
+    /// Forget a constant, e.g., since it is assigned a new value.
+    pub fn remove(&mut self, name: &str) {
+        self.values.remove(name);
+    }
+
+    /// Get the constants for which the given predicate holds (e.g., which are not shadowed).
+    pub fn filter(&self, predicate: impl Fn(&str) -> bool) -> Constants {
+        Constants {
+            values: self
+                .values
+                .iter()
+                .filter(|(name, _)| predicate(name))
+                .map(|(name, value)| (name.to_owned(), *value))
+                .collect(),
+        }
+    }
+
+    /// Evaluate a constant integer expression, i.e., an expression consisting of integer literals,
+    /// constants and arithmetic operations only.
+    pub fn evaluate<T: Clone>(&self, expression: &Expression<T>) -> Result<i64, String> {
+        match expression {
+            Expression::Integer(Integer { value, .. }) => Ok(*value),
+            Expression::Ident(Ident { value, .. }) => self
+                .values
+                .get(value)
+                .copied()
+                .ok_or_else(|| format!("'{value}' is not a constant")),
+            Expression::Prefix(PrefixExpr { op, rhs, .. }) => {
+                let rhs = self.evaluate(rhs)?;
+
+                match op {
+                    PrefixOp::UnaryMinus => rhs.checked_neg().ok_or_else(overflow),
+                    PrefixOp::BitNot => Ok(!rhs),
+                    PrefixOp::Not => Err(not_constant(expression)),
+                }
+            }
+            Expression::Binary(BinaryExpr { op, lhs, rhs, .. }) => {
+                let lhs = self.evaluate(lhs)?;
+                let rhs = self.evaluate(rhs)?;
+                let shift = || u32::try_from(rhs).ok().filter(|rhs| *rhs < i64::BITS);
+
+                match op {
+                    BinaryOp::Plus => lhs.checked_add(rhs).ok_or_else(overflow),
+                    BinaryOp::Minus => lhs.checked_sub(rhs).ok_or_else(overflow),
+                    BinaryOp::Times => lhs.checked_mul(rhs).ok_or_else(overflow),
+                    BinaryOp::DividedBy if rhs == 0 => {
+                        Err("Division by zero in constant expression".to_owned())
+                    }
+                    BinaryOp::DividedBy => lhs.checked_div(rhs).ok_or_else(overflow),
+                    BinaryOp::BitAnd => Ok(lhs & rhs),
+                    BinaryOp::BitOr => Ok(lhs | rhs),
+                    BinaryOp::BitXor => Ok(lhs ^ rhs),
+                    BinaryOp::ShiftLeft => shift().map(|rhs| lhs << rhs).ok_or_else(overflow),
+                    BinaryOp::ShiftRight => shift().map(|rhs| lhs >> rhs).ok_or_else(overflow),
+                    BinaryOp::GreaterThan | BinaryOp::LessThan | BinaryOp::Equal => {
+                        Err(not_constant(expression))
+                    }
+                }
+            }
+            expression => Err(not_constant(expression)),
+        }
+    }
+}
+
+fn overflow() -> String {
+    "Overflow in constant expression".to_owned()
+}
+
+fn not_constant<T: Clone>(expression: &Expression<T>) -> String {
+    format!(
+        "'{}' is not a constant integer expression",
+        expression.to_source()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, Statement, YParser};
+
+    use super::Constants;
+
+    fn evaluate(constants: &Constants, src: &str) -> Result<i64, String> {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let Some(Statement::Expression(expression)) = ast.nodes().first() else {
+            panic!("expected expression");
+        };
+        constants.evaluate::<()>(expression)
+    }
+
+    #[test]
+    fn test_evaluate_constant_expressions() {
+        let mut constants = Constants::default();
+        constants.define("SIZE", 8);
+
+        assert_eq!(evaluate(&constants, "SIZE"), Ok(8));
+        assert_eq!(evaluate(&constants, "SIZE * 2 + 1"), Ok(17));
+        assert_eq!(evaluate(&constants, "-(SIZE / 3)"), Ok(-2));
+        assert_eq!(evaluate(&constants, "1 << SIZE | ~0 & 3"), Ok(259));
+
+        constants.remove("SIZE");
+        assert_eq!(
+            evaluate(&constants, "SIZE + 1"),
+            Err("'SIZE' is not a constant".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_invalid_constant_expressions() {
+        let constants = Constants::default();
+
+        assert!(evaluate(&constants, "4 / (2 - 2)").is_err());
+        assert!(evaluate(&constants, "9223372036854775807 + 1").is_err());
+        assert!(evaluate(&constants, "1 << 64").is_err());
+        assert_eq!(
+            evaluate(&constants, "\"abc\""),
+            Err("'\"abc\"' is not a constant integer expression".to_owned())
+        );
+        assert!(matches!(
+            evaluate(&constants, "1 == 1"),
+            Err(message) if message.contains("not a constant")
+        ));
+    }
+}
@@ -4,13 +4,59 @@ use std::{error::Error, fmt::Display};
 pub struct TypeError {
     pub message: String,
     pub position: (String, usize, usize),
+    /// Additional hints (with their own position), which are printed below the main message.
+    pub notes: Vec<(String, (String, usize, usize))>,
+}
+
+impl TypeError {
+    pub fn new(message: impl ToString, position: (String, usize, usize)) -> Self {
+        Self {
+            message: message.to_string(),
+            position,
+            notes: vec![],
+        }
+    }
+
+    /// Add a note (at the given position) to this error.
+    pub fn with_note(mut self, note: impl ToString, position: (String, usize, usize)) -> Self {
+        self.notes.push((note.to_string(), position));
+        self
+    }
+
+    /// Add all the given notes to this error.
+    pub fn with_notes(
+        mut self,
+        notes: impl IntoIterator<Item = (String, (String, usize, usize))>,
+    ) -> Self {
+        self.notes.extend(notes);
+        self
+    }
 }
 
 impl Display for TypeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (file, line, col) = &self.position;
-        f.write_str(&format!("{} ({}:{}:{})", self.message, file, line, col))
+        f.write_str(&format!("{} ({}:{}:{})", self.message, file, line, col))?;
+
+        for (note, (file, line, col)) in &self.notes {
+            f.write_str(&format!("\n\tnote: {note} ({file}:{line}:{col})"))?;
+        }
+
+        Ok(())
     }
 }
 
 impl Error for TypeError {}
+
+/// Multiple type errors (in the order they occurred).
+#[derive(Debug)]
+pub struct TypeErrors(pub Vec<TypeError>);
+
+impl Display for TypeErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self.0.iter().map(TypeError::to_string).collect::<Vec<_>>();
+        f.write_str(&errors.join("\n"))
+    }
+}
+
+impl Error for TypeErrors {}
@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use crate::loader::Module;
 
 use super::variabletype::VariableType;
@@ -9,7 +11,7 @@ pub struct TypeInfo {
     pub _type: VariableType,
     /// The module, where this expression (or at least the value of this expression) originates
     /// from.
-    pub source: Option<Module<()>>,
+    pub source: Option<Rc<Module<()>>>,
 }
 
 impl TypeInfo {
@@ -18,7 +20,7 @@ impl TypeInfo {
         self._type.size()
     }
 
-    pub fn source(&self) -> Option<Module<()>> {
+    pub fn source(&self) -> Option<Rc<Module<()>>> {
         self.source.clone()
     }
 
@@ -0,0 +1,310 @@
+use std::collections::BTreeSet;
+
+use crate::ast::{
+    Assignment, BinaryExpr, Block, Break, Call, CompilerDirective, Definition, Expression, Ident,
+    If, Intrinsic, LabeledBlock, Loop, PostfixExpr, PostfixOp, PrefixExpr, Statement, WhileLoop,
+};
+
+use super::{TypeInfo, VariableType};
+
+/// Collect the variables which may be modified by executing the given block, i.e., variables which
+/// are assigned to (directly or via indexing), passed to reference parameters or arrays and slices
+/// passed to any function (which may modify their items). Returns `None` if the block contains
+/// inline assembly, which may modify any variable.
+pub fn modified_variables(block: &Block<TypeInfo>) -> Option<BTreeSet<String>> {
+    let mut modified = BTreeSet::new();
+    collect_block(block, &mut modified)?;
+    Some(modified)
+}
+
+/// Check, whether the condition of the given loop can never change while executing its body, i.e.,
+/// it reads variables, but none of them is modified in the body. Conditions containing calls are
+/// never considered invariant, since the called function may return different values each time.
+pub fn has_invariant_condition(while_loop: &WhileLoop<TypeInfo>) -> bool {
+    let mut read = BTreeSet::new();
+    if collect_reads(&while_loop.condition, &mut read).is_none() || read.is_empty() {
+        return false;
+    }
+
+    modified_variables(&while_loop.block).is_some_and(|modified| read.is_disjoint(&modified))
+}
+
+/// Get the variables read by the given expression, if it is invariant with respect to the given
+/// modified variables, i.e., it only consists of literals, operators and reads of variables which
+/// are not modified.
+pub fn invariant_reads<'a>(
+    expression: &'a Expression<TypeInfo>,
+    modified: &BTreeSet<String>,
+) -> Option<Vec<&'a Ident<TypeInfo>>> {
+    match expression {
+        Expression::Integer(_) | Expression::Character(_) | Expression::Boolean(_) => Some(vec![]),
+        Expression::Ident(ident) if !modified.contains(&ident.value) => Some(vec![ident]),
+        Expression::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            let mut reads = invariant_reads(lhs, modified)?;
+            reads.append(&mut invariant_reads(rhs, modified)?);
+            Some(reads)
+        }
+        Expression::Prefix(PrefixExpr { rhs, .. }) => invariant_reads(rhs, modified),
+        _ => None,
+    }
+}
+
+/// Collect the variables read by a condition. Returns `None` for expressions whose value may
+/// change without modifying a variable (e.g., calls) or which are too complex to analyse.
+fn collect_reads(expression: &Expression<TypeInfo>, read: &mut BTreeSet<String>) -> Option<()> {
+    match expression {
+        Expression::Integer(_)
+        | Expression::Character(_)
+        | Expression::Boolean(_)
+        | Expression::Str(_) => Some(()),
+        Expression::Ident(Ident { value, .. }) => {
+            read.insert(value.to_owned());
+            Some(())
+        }
+        Expression::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            collect_reads(lhs, read)?;
+            collect_reads(rhs, read)
+        }
+        Expression::Prefix(PrefixExpr { rhs, .. }) => collect_reads(rhs, read),
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Indexing(indexing),
+            ..
+        }) => {
+            collect_reads(lhs, read)?;
+            collect_reads(&indexing.index, read)
+        }
+        _ => None,
+    }
+}
+
+fn collect_block(
+    Block { block, .. }: &Block<TypeInfo>,
+    modified: &mut BTreeSet<String>,
+) -> Option<()> {
+    for statement in block {
+        collect_statement(statement, modified)?;
+    }
+
+    Some(())
+}
+
+fn collect_statement(
+    statement: &Statement<TypeInfo>,
+    modified: &mut BTreeSet<String>,
+) -> Option<()> {
+    match statement {
+        Statement::Import(_) => Some(()),
+        Statement::Expression(expression) => collect_expression(expression, modified),
+        Statement::Intrinsic(intrinsic) => collect_intrinsic(intrinsic, modified),
+        Statement::CompilerDirective(CompilerDirective { statement, .. }) => match statement {
+            Some(statement) => collect_statement(statement, modified),
+            None => Some(()),
+        },
+        Statement::InlineAssembly(_) => None,
+    }
+}
+
+fn collect_intrinsic(
+    intrinsic: &Intrinsic<TypeInfo>,
+    modified: &mut BTreeSet<String>,
+) -> Option<()> {
+    match intrinsic {
+        Intrinsic::Definition(Definition { value, .. }) => collect_expression(value, modified),
+        Intrinsic::Assignment(Assignment { lhs, value, .. }) => {
+            if let Some(variable) = assigned_variable(lhs) {
+                modified.insert(variable.to_owned());
+            }
+            collect_expression(lhs, modified)?;
+            collect_expression(value, modified)
+        }
+        Intrinsic::WhileLoop(WhileLoop {
+            condition, block, ..
+        }) => {
+            collect_expression(condition, modified)?;
+            collect_block(block, modified)
+        }
+        Intrinsic::Break(Break { value, .. }) => match value {
+            Some(value) => collect_expression(value, modified),
+            None => Some(()),
+        },
+        Intrinsic::Declaration(_) => Some(()),
+    }
+}
+
+fn collect_expression(
+    expression: &Expression<TypeInfo>,
+    modified: &mut BTreeSet<String>,
+) -> Option<()> {
+    match expression {
+        Expression::If(If {
+            condition,
+            if_block,
+            else_block,
+            ..
+        }) => {
+            collect_expression(condition, modified)?;
+            collect_block(if_block, modified)?;
+            match else_block {
+                Some(else_block) => collect_block(else_block, modified),
+                None => Some(()),
+            }
+        }
+        Expression::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            collect_expression(lhs, modified)?;
+            collect_expression(rhs, modified)
+        }
+        Expression::Prefix(PrefixExpr { rhs, .. }) => collect_expression(rhs, modified),
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Call(Call { params, .. }),
+            ..
+        }) => {
+            if let VariableType::Func {
+                params: param_types,
+                ..
+            } = &lhs.info()._type
+            {
+                for (param_type, param) in param_types.iter().zip(params) {
+                    if let (VariableType::Reference(_), Expression::Ident(Ident { value, .. })) =
+                        (param_type, param)
+                    {
+                        modified.insert(value.to_owned());
+                    }
+                }
+            }
+
+            // arrays and slices are passed by their address, so any called function may modify
+            // their items
+            for param in params {
+                if let Expression::Ident(Ident { value, info, .. }) = param {
+                    if is_array(&info._type) {
+                        modified.insert(value.to_owned());
+                    }
+                }
+            }
+
+            collect_expression(lhs, modified)?;
+            for param in params {
+                collect_expression(param, modified)?;
+            }
+            Some(())
+        }
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Indexing(indexing),
+            ..
+        }) => {
+            collect_expression(lhs, modified)?;
+            collect_expression(&indexing.index, modified)
+        }
+        // variables of surrounding scopes may not be modified by nested functions, but checking
+        // their bodies anyway keeps this analysis on the safe side
+        Expression::FnDef(fn_def) => collect_block(&fn_def.block, modified),
+        Expression::Block(block) => collect_block(block, modified),
+        Expression::Array(array) => collect_expression(&array.initializer, modified),
+        Expression::Loop(Loop { block, .. })
+        | Expression::LabeledBlock(LabeledBlock { block, .. }) => collect_block(block, modified),
+        Expression::Integer(_)
+        | Expression::Character(_)
+        | Expression::Ident(_)
+        | Expression::Str(_)
+        | Expression::Boolean(_) => Some(()),
+    }
+}
+
+/// Check, whether a value of the given type is an array or a slice (or a reference to one).
+fn is_array(_type: &VariableType) -> bool {
+    match _type {
+        VariableType::ArraySlice(_) | VariableType::TupleArray { .. } => true,
+        VariableType::Reference(_type) => is_array(_type),
+        _ => false,
+    }
+}
+
+/// Get the variable an assignment writes to, e.g., `xs` for `xs[i] = 42`.
+fn assigned_variable(lhs: &Expression<TypeInfo>) -> Option<&str> {
+    match lhs {
+        Expression::Ident(Ident { value, .. }) => Some(value),
+        Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Indexing(_),
+            ..
+        }) => assigned_variable(lhs),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{Ast, Intrinsic, Statement, YParser},
+        loader::ModuleRefs,
+        typechecker::Typechecker,
+    };
+
+    use super::has_invariant_condition;
+
+    /// Type check the given program and analyse its last statement, which has to be a while loop.
+    fn invariant_condition(src: &str) -> bool {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+        let ast = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap();
+
+        let Some(Statement::Intrinsic(Intrinsic::WhileLoop(while_loop))) = ast.nodes().last()
+        else {
+            panic!("Expected while loop as last statement");
+        };
+
+        has_invariant_condition(while_loop)
+    }
+
+    #[test]
+    fn test_unmodified_condition() {
+        assert!(invariant_condition(
+            "let mut x := 3\nlet mut y := 0\nwhile (x > 0) {\n    y = y + 1\n}"
+        ));
+    }
+
+    #[test]
+    fn test_modified_condition() {
+        assert!(!invariant_condition(
+            "let mut x := 3\nwhile (x > 0) {\n    x = x - 1\n}"
+        ));
+    }
+
+    #[test]
+    fn test_condition_modified_via_indexing() {
+        assert!(!invariant_condition(
+            "let mut xs := [1; 3]\nlet mut i := 0\nwhile (xs[0] > 0) {\n    xs[i] = 0\n}"
+        ));
+    }
+
+    #[test]
+    fn test_condition_modified_via_reference() {
+        assert!(!invariant_condition(
+            "let dec := (x: &int): void => {\n    x = x - 1\n}\nlet mut x := 3\nwhile (x > 0) {\n    dec(x)\n}"
+        ));
+    }
+
+    #[test]
+    fn test_condition_modified_via_array_parameter() {
+        assert!(!invariant_condition(
+            "let bump := (xs: &[int]): void => {\n    xs[0] = xs[0] + 1\n}\nlet mut xs := [0; 3]\nwhile xs[0] + 0 < 3 {\n    bump(xs)\n}"
+        ));
+    }
+
+    #[test]
+    fn test_condition_with_call() {
+        assert!(!invariant_condition(
+            "let next := (): int => { 0 }\nwhile (next() > 0) {\n    1\n}"
+        ));
+    }
+
+    #[test]
+    fn test_constant_condition() {
+        assert!(!invariant_condition("while (true) {\n    1\n}"));
+    }
+}
@@ -1,71 +1,339 @@
 //! Type checker for Y.
 //!
 //! This module provides type checking capabilities for ASt's.
+mod capture_analysis;
+mod constants;
 mod error;
 mod fn_extractor;
 mod info;
+mod loop_analysis;
 mod typescope;
 mod variabletype;
 
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use log::{debug, warn};
+
 use crate::{
     ast::{
-        Array, Assignment, Ast, BinaryExpr, BinaryOp, Block, Boolean, Call, Character,
+        Array, Assignment, Ast, BinaryExpr, BinaryOp, Block, Boolean, Break, Call, Character,
         CompilerDirective, Declaration, Definition, Expression, FnDef, Ident, If, Import, Indexing,
-        InlineAssembly, Integer, Intrinsic, Param, Position, PostfixExpr, PostfixOp, PrefixExpr,
-        PrefixOp, Statement, Str, Type, WhileLoop,
+        InlineAssembly, Integer, Intrinsic, LabeledBlock, Loop, Param, Position, PostfixExpr,
+        PostfixOp, PrefixExpr, PrefixOp, Statement, Str, Type, WhileLoop,
     },
-    loader::Modules,
+    ast_tree::type_annotation,
+    loader::ModuleRefs,
+    pipeline::PipelineObserver,
 };
 
+pub use self::capture_analysis::captured_variables;
+pub use self::error::{TypeError, TypeErrors};
 pub use self::fn_extractor::extract_exports;
 pub use self::info::TypeInfo;
-pub use self::typescope::TypeScope;
-pub use self::variabletype::VariableType;
-
-use self::{error::TypeError, typescope::setup_scope};
+pub use self::loop_analysis::{has_invariant_condition, invariant_reads, modified_variables};
+pub use self::typescope::{TypeScope, Variable};
+pub use self::variabletype::{VariableConversionError, VariableParseError, VariableType};
+
+use self::{constants::Constants, typescope::setup_scope};
+
+/// Check, whether the given statement is a call of `exit` (which never returns).
+fn is_exit(statement: &Statement<()>) -> bool {
+    matches!(
+        statement,
+        Statement::Expression(Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Call(_),
+            ..
+        })) if matches!(lhs.as_ref(), Expression::Ident(Ident { value, .. }) if value == "exit")
+    )
+}
 
 /// Result of type checking a node within the AST.
 type TResult<T> = Result<T, TypeError>;
 
+/// Functions which are known to the compiler, since their parameters are generic.
+const ASSERTIONS: [&str; 2] = ["assert", "assert_eq"];
+
+/// Check, whether the given name refers to an assertion (if it is not shadowed by a variable).
+pub fn is_assertion(name: &str) -> bool {
+    ASSERTIONS.contains(&name)
+}
+
+/// Name of the entry point of a program, which consists of its top-level statements.
+const ENTRY_POINT: &str = "main";
+
+/// Maximum nesting depth of expressions the type checker accepts.
+const MAX_EXPRESSION_DEPTH: usize = 1000;
+
+/// Number of registers available for passing parameters to functions. Slices occupy two of them
+/// (their pointer and their length).
+const PARAM_REGISTER_COUNT: usize = 6;
+
+/// Information about a loop (or labeled block) which is currently being type checked.
+#[derive(Default)]
+struct LoopFrame {
+    /// The label of a labeled block. Only labeled `break`s target labeled blocks, unlabeled ones
+    /// target the innermost loop.
+    label: Option<String>,
+    /// Whether a `break` within this loop may carry a value (only true for `loop`).
+    allows_value: bool,
+    /// The types (and positions) of all `break`s targeting this loop.
+    breaks: Vec<(VariableType, Position)>,
+}
+
+/// The type a value is expected to have (e.g., since it is passed to a function). Function
+/// definitions take the types of parameters without annotation from it.
+struct ExpectedType {
+    _type: VariableType,
+    /// Explanation of the expectation, which is shown as a note on conflicts.
+    note: String,
+    position: Position,
+}
+
 /// Struct for type checking an AST.
-pub struct Typechecker {
-    ast: Ast<()>,
-    modules: Modules<()>,
+pub struct Typechecker<'a> {
+    ast: &'a Ast<()>,
+    modules: ModuleRefs<'a, ()>,
+    /// Stack of the loops enclosing the node which is currently checked.
+    loops: RefCell<Vec<LoopFrame>>,
+    /// Values compiler directives (e.g., `#[os == "linux"]`) are evaluated against.
+    cfg: HashMap<String, String>,
+    /// Nesting depth of the expression which is currently checked.
+    depth: Cell<usize>,
+    /// Names (and positions) of the functions whose return type is currently being inferred.
+    inferred_functions: RefCell<Vec<(String, Position)>>,
+    /// Names (`None` for anonymous functions) and positions of the functions enclosing the node
+    /// which is currently checked, along with the depth of the scope they are defined in.
+    functions: RefCell<Vec<(Option<String>, Position, usize)>>,
+    /// Errors after which type checking continued (since they do not affect the rest of the
+    /// program).
+    errors: RefCell<Vec<TypeError>>,
+    /// Observer which is asked after each top-level statement whether to cancel type checking.
+    observer: Option<&'a dyn PipelineObserver>,
+    cancelled: Cell<bool>,
+    /// Values of the constants defined so far (e.g., for resolving the sizes of arrays).
+    constants: RefCell<Constants>,
+    /// Whether the AST is a module imported by the program, whose functions are prefixed by the
+    /// name of the module (and, hence, never collide with the entry point).
+    is_imported: bool,
+    /// Functions of imported modules, which are not exported (since their type is not fully
+    /// annotated), along with a note on how to export them.
+    unexported: RefCell<BTreeMap<String, (String, Position)>>,
 }
 
-impl Typechecker {
-    pub fn from_ast(ast: Ast<()>, modules: Modules<()>) -> Self {
-        Self { ast, modules }
+impl<'a> Typechecker<'a> {
+    pub fn from_ast(ast: &'a Ast<()>, modules: ModuleRefs<'a, ()>) -> Self {
+        Self {
+            ast,
+            modules,
+            loops: RefCell::default(),
+            inferred_functions: RefCell::default(),
+            functions: RefCell::default(),
+            cfg: HashMap::from([("os".to_owned(), std::env::consts::OS.to_owned())]),
+            depth: Cell::default(),
+            errors: RefCell::default(),
+            observer: None,
+            cancelled: Cell::default(),
+            constants: RefCell::default(),
+            is_imported: false,
+            unexported: RefCell::default(),
+        }
+    }
+
+    /// Override (or add) values compiler directives are evaluated against, e.g., to check a
+    /// program for another operating system.
+    pub fn with_cfg(mut self, cfg: HashMap<String, String>) -> Self {
+        self.cfg.extend(cfg);
+        self
+    }
+
+    /// Allow the given observer to cancel type checking (between top-level statements).
+    pub fn with_observer(mut self, observer: &'a dyn PipelineObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Check the AST as a module imported by the program (instead of the program itself), which may
+    /// define a function named `main`.
+    pub fn as_imported(mut self) -> Self {
+        self.is_imported = true;
+        self
+    }
+
+    /// Whether the last check was cancelled by the observer (in which case its result is
+    /// incomplete).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
     }
 
     /// Type check the contained AST and return the type correct AST with type information attached
-    /// to each node.
+    /// to each node. If the AST is not type correct, the first error is returned.
     pub fn check(&self) -> Result<Ast<TypeInfo>, TypeError> {
-        let nodes = self.ast.nodes();
+        self.check_all()
+            .map_err(|TypeErrors(errors)| errors.into_iter().next().unwrap())
+    }
 
+    /// Type check the contained AST like `check`, but return all errors which were found (in the
+    /// order they occurred).
+    pub fn check_all(&self) -> Result<Ast<TypeInfo>, TypeErrors> {
         let mut scope = setup_scope();
+        self.hoist_functions(&mut scope);
 
         let mut statements = vec![];
 
-        for node in nodes {
-            statements.push(self.check_statement(&node, &mut scope)?);
+        let nodes = self.ast.nodes();
+        for (index, node) in nodes.iter().enumerate() {
+            let (file, line, col) = node.position();
+            if index > 0 && is_exit(&nodes[index - 1]) {
+                warn!("Unreachable statement after 'exit' ({file}:{line}:{col})");
+            }
+
+            if self
+                .observer
+                .is_some_and(|observer| observer.should_cancel())
+            {
+                self.cancelled.set(true);
+                return Err(TypeErrors(self.errors.take()));
+            }
+
+            debug!("Type checking statement at {file}:{line}:{col}");
+
+            match self.check_statement(node, &mut scope) {
+                Ok(statement) => {
+                    if let Statement::Intrinsic(Intrinsic::Definition(Definition {
+                        ident, ..
+                    })) = &statement
+                    {
+                        debug!(
+                            "Checked '{}': {}",
+                            ident.value,
+                            type_annotation(&ident.info._type)
+                        );
+                    }
+
+                    self.define_constant(&statement);
+                    statements.push(statement);
+                }
+                Err(error) => {
+                    debug!("Type checking statement at {file}:{line}:{col} failed");
+
+                    let mut errors = self.errors.take();
+                    errors.push(error);
+                    return Err(TypeErrors(errors));
+                }
+            }
+        }
+
+        let errors = self.errors.take();
+        if !errors.is_empty() {
+            return Err(TypeErrors(errors));
         }
 
         Ok(Ast::from_nodes(statements))
     }
 
+    /// Declare the functions defined at the top level (whose parameters and return type are
+    /// annotated) before checking any statement, so they can be called by the statements preceding
+    /// their definition, too.
+    fn hoist_functions(&self, scope: &mut TypeScope) {
+        let nodes = self.ast.nodes();
+
+        // names defined (or declared) more than once are left to the checks of their definitions
+        let mut definitions = BTreeMap::<&str, usize>::new();
+        for node in nodes {
+            if let Statement::Intrinsic(
+                Intrinsic::Definition(Definition { ident, .. })
+                | Intrinsic::Declaration(Declaration { ident, .. }),
+            ) = node
+            {
+                *definitions.entry(&ident.value).or_default() += 1;
+            }
+        }
+
+        let mut constants = Constants::default();
+
+        for node in nodes {
+            let Statement::Intrinsic(Intrinsic::Definition(Definition {
+                ident,
+                value,
+                is_mutable,
+                ..
+            })) = node
+            else {
+                continue;
+            };
+
+            // constants may be used as array sizes in the signatures of functions
+            if !is_mutable {
+                if let Ok(value) = constants.evaluate(value) {
+                    constants.define(&ident.value, value);
+                }
+            }
+
+            let Expression::FnDef(fn_def) = value else {
+                continue;
+            };
+            if *is_mutable || definitions[ident.value.as_str()] > 1 {
+                continue;
+            }
+
+            // invalid annotations are reported when checking the definition
+            if let Some(signature) = Self::signature(fn_def, &constants) {
+                scope.declare(&ident.value, signature, &fn_def.position);
+                scope.set_return_type_at(&ident.value, &Self::return_type_position(ident, fn_def));
+            }
+        }
+    }
+
+    /// Get the type of a function definition, if its parameters and its return type are
+    /// annotated (with valid types).
+    fn signature(fn_def: &FnDef<()>, constants: &Constants) -> Option<VariableType> {
+        let params = fn_def
+            .params
+            .iter()
+            .map(|param| {
+                let type_annotation = param.type_annotation.as_ref()?;
+                Self::get_type_def(&type_annotation.value, param.position.clone(), constants).ok()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let type_annotation = fn_def.type_annotation.as_ref()?;
+        let return_type =
+            Self::get_type_def(&type_annotation.value, fn_def.position.clone(), constants).ok()?;
+
+        Some(VariableType::Func {
+            params,
+            return_type: Box::new(return_type),
+            source: None,
+        })
+    }
+
     /// Extract the exports of a given AST. In particular, the exports are only the type
     /// information of the defined functions.
     /// Note: The exports are _not_ type checked.
     pub fn extract_exports(ast: &Ast<()>) -> Result<TypeScope, TypeError> {
-        let nodes = ast.nodes();
-
         let mut scope = setup_scope();
+        let mut constants = Constants::default();
 
-        for intrinsic in nodes.iter() {
+        'nodes: for intrinsic in ast.nodes() {
             match intrinsic {
                 Statement::Intrinsic(Intrinsic::Definition(definition)) => {
-                    let Definition { value, ident, .. } = definition;
+                    let Definition {
+                        value,
+                        ident,
+                        is_mutable,
+                        ..
+                    } = definition;
+
+                    // constants may be used as array sizes in the signatures of functions
+                    if !is_mutable {
+                        if let Ok(value) = constants.evaluate(value) {
+                            constants.define(&ident.value, value);
+                        }
+                    }
 
                     let Expression::FnDef(FnDef {
                         params,
@@ -77,17 +345,42 @@ impl Typechecker {
                         continue;
                     };
 
+                    // the return type of functions without annotation is only known after type
+                    // checking, so they can only be used within their own module
+                    let Some(type_annotation) = type_annotation else {
+                        scope.set_unexported(
+                            &ident.value,
+                            format!("add a return type annotation to export '{}'", ident.value),
+                            &ident.position,
+                        );
+                        continue;
+                    };
+
                     let mut param_types = vec![];
 
                     for Param {
+                        ident: param,
                         type_annotation,
                         position,
-                        ..
                     } in params
                     {
+                        // likewise, parameters without annotation are typed by their context
+                        let Some(type_annotation) = type_annotation else {
+                            scope.set_unexported(
+                                &ident.value,
+                                format!(
+                                    "add a type annotation to parameter '{}' to export '{}'",
+                                    param.value, ident.value
+                                ),
+                                position,
+                            );
+                            continue 'nodes;
+                        };
+
                         param_types.push(Self::get_type_def(
                             &type_annotation.value,
                             position.clone(),
+                            &constants,
                         )?);
                     }
 
@@ -98,11 +391,13 @@ impl Typechecker {
                             return_type: Box::new(Self::get_type_def(
                                 &type_annotation.value,
                                 position.clone(),
+                                &constants,
                             )?),
                             source: None,
                         },
                         false,
-                    )
+                    );
+                    scope.set_return_type_at(&ident.value, &type_annotation.position);
                 }
                 Statement::Intrinsic(Intrinsic::Declaration(declaration)) => {
                     let Declaration {
@@ -112,11 +407,11 @@ impl Typechecker {
                         ..
                     } = declaration;
                     let type_annotation =
-                        Self::get_type_def(&type_annotation.value, position.clone())?;
+                        Self::get_type_def(&type_annotation.value, position.clone(), &constants)?;
 
-                    if let VariableType::Func { .. } = &type_annotation {
-                        scope.set(&ident.value, type_annotation, false);
-                    }
+                    // declared data (in contrast to declared functions) may be written to
+                    let is_mutable = !matches!(type_annotation, VariableType::Func { .. });
+                    scope.set(&ident.value, type_annotation, is_mutable);
                 }
                 _ => {}
             }
@@ -174,8 +469,18 @@ impl Typechecker {
         }: &CompilerDirective<()>,
         scope: &mut TypeScope,
     ) -> TResult<CompilerDirective<TypeInfo>> {
+        let unsupported = || {
+            TypeError::new(
+                format!(
+                    "Unsupported compiler directive '{}'; expected the form 'name == \"value\"'",
+                    directive.to_source()
+                ),
+                directive.position(),
+            )
+        };
+
         let Expression::Binary(directive) = directive.clone() else {
-            unimplemented!("Currently only compiler directives in the form of binary expressions are supported!");
+            return Err(unsupported());
         };
         let Some(statement) = statement.clone() else {
             return Ok(CompilerDirective {
@@ -185,14 +490,15 @@ impl Typechecker {
             });
         };
 
-        let is_valid = match (directive.lhs.as_ref(), directive.rhs.as_ref()) {
-            (Expression::Ident(ident), Expression::Str(rhs)) => match ident.value.as_str() {
-                "os" => std::env::consts::OS == rhs.value,
-                _ => false,
-            },
-            _ => unimplemented!(
-                "Currently only compiler directives in the form of 'ident == str' are supported!"
-            ),
+        let is_valid = match (
+            directive.lhs.as_ref(),
+            &directive.op,
+            directive.rhs.as_ref(),
+        ) {
+            (Expression::Ident(ident), BinaryOp::Equal, Expression::Str(rhs)) => {
+                self.cfg.get(&ident.value) == Some(&rhs.value)
+            }
+            _ => return Err(unsupported()),
         };
 
         if is_valid {
@@ -213,23 +519,34 @@ impl Typechecker {
     fn check_import(&self, import: &Import, scope: &mut TypeScope) -> TResult<Import> {
         let Import { position, path } = import;
         let Some(module) = self.modules.get(path) else {
-            return Err(TypeError {
-                message: format!("Could not import module '{path}'"),
-                position: position.clone(),
-            });
+            return Err(TypeError::new(
+                format!("Could not import module '{path}'"),
+                position.clone(),
+            ));
         };
 
+        for (key, unexported) in module.exports.unexported() {
+            let key = if import.is_wildcard() {
+                key.to_owned()
+            } else {
+                format!("{path}::{key}")
+            };
+            self.unexported.borrow_mut().insert(key, unexported.clone());
+        }
+
         let imports = module.exports.flatten();
+        let source = Rc::new((*module).clone());
 
         for (key, value) in imports {
-            if import.is_wildcard() {
-                scope.set(&key, value.variable_type.set_source(module.clone()), false);
+            let name = if import.is_wildcard() {
+                key
             } else {
-                scope.set(
-                    &format!("{path}::{key}"),
-                    value.variable_type.set_source(module.clone()),
-                    false,
-                );
+                format!("{path}::{key}")
+            };
+
+            scope.set(&name, value.variable_type.set_source(source.clone()), false);
+            if let Some(return_type_at) = &value.return_type_at {
+                scope.set_return_type_at(&name, return_type_at);
             }
         }
 
@@ -254,6 +571,173 @@ impl Typechecker {
             Intrinsic::WhileLoop(while_loop) => {
                 Intrinsic::WhileLoop(self.check_while_loop(while_loop, scope)?)
             }
+            Intrinsic::Break(break_stmt) => Intrinsic::Break(self.check_break(break_stmt, scope)?),
+        })
+    }
+
+    fn check_break(
+        &self,
+        Break {
+            label,
+            value,
+            position,
+            ..
+        }: &Break<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<Break<TypeInfo>> {
+        let value = match value {
+            Some(value) => Some(self.check_expression(None, value, scope)?),
+            None => None,
+        };
+
+        let mut loops = self.loops.borrow_mut();
+
+        let Some(frame) = loops.iter_mut().rev().find(|frame| frame.label == *label) else {
+            let message = match label {
+                Some(label) => format!("Use of undeclared label '{label}'"),
+                None => "'break' outside of a loop".to_owned(),
+            };
+            return Err(TypeError::new(message, position.to_owned()));
+        };
+
+        if value.is_some() && !frame.allows_value {
+            return Err(TypeError::new(
+                "'break' with a value is only allowed inside of 'loop'",
+                position.to_owned(),
+            ));
+        }
+
+        frame.breaks.push((
+            value
+                .as_ref()
+                .map(|value| value.info()._type)
+                .unwrap_or_default(),
+            position.to_owned(),
+        ));
+
+        Ok(Break {
+            label: label.to_owned(),
+            value,
+            position: position.to_owned(),
+            info: TypeInfo {
+                _type: VariableType::Void,
+                source: None,
+            },
+        })
+    }
+
+    fn check_loop(
+        &self,
+        Loop {
+            block, position, ..
+        }: &Loop<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<Loop<TypeInfo>> {
+        self.loops.borrow_mut().push(LoopFrame {
+            allows_value: true,
+            ..LoopFrame::default()
+        });
+
+        let block = self.check_block(block, scope);
+
+        let frame = self.loops.borrow_mut().pop().unwrap_or_default();
+
+        let block = block?;
+
+        let mut breaks = frame.breaks.into_iter();
+
+        let Some((loop_type, _)) = breaks.next() else {
+            let (file, line, col) = position;
+            warn!("Loop never breaks ({file}:{line}:{col})");
+
+            return Ok(Loop {
+                block,
+                position: position.to_owned(),
+                info: TypeInfo {
+                    _type: VariableType::Void,
+                    source: None,
+                },
+            });
+        };
+
+        let mut loop_type = loop_type;
+
+        for (break_type, break_position) in breaks {
+            let Some(joined_type) = loop_type.unify(&break_type) else {
+                return Err(TypeError::new(
+                    format!(
+                        "Type mismatch of break values in loop. Got '{loop_type}' and '{break_type}'"
+                    ),
+                    break_position,
+                ));
+            };
+            loop_type = joined_type;
+        }
+
+        Ok(Loop {
+            block,
+            position: position.to_owned(),
+            info: TypeInfo {
+                _type: loop_type,
+                source: None,
+            },
+        })
+    }
+
+    fn check_labeled_block(
+        &self,
+        LabeledBlock {
+            label,
+            block,
+            position,
+            ..
+        }: &LabeledBlock<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<LabeledBlock<TypeInfo>> {
+        self.loops.borrow_mut().push(LoopFrame {
+            label: Some(label.to_owned()),
+            allows_value: true,
+            ..LoopFrame::default()
+        });
+
+        let block = self.check_block(block, scope);
+
+        let frame = self.loops.borrow_mut().pop().unwrap_or_default();
+
+        let block = block?;
+
+        let mut block_type = block.info._type.clone();
+
+        // a block ending in a `break` is only left via its breaks
+        let ends_with_break = matches!(
+            block.block.last(),
+            Some(Statement::Intrinsic(Intrinsic::Break(_)))
+        );
+        if ends_with_break {
+            block_type = VariableType::Unknown;
+        }
+
+        for (break_type, break_position) in frame.breaks {
+            let Some(joined_type) = block_type.unify(&break_type) else {
+                let message = format!(
+                    "Type mismatch of break value and result of block '{label}'. Got '{break_type}' and '{block_type}'"
+                );
+                return Err(TypeError::new(message, break_position).with_note(
+                    format!("block '{label}' is defined here"),
+                    position.to_owned(),
+                ));
+            };
+            block_type = joined_type;
+        }
+
+        Ok(LabeledBlock {
+            label: label.to_owned(),
+            block,
+            position: position.to_owned(),
+            info: TypeInfo {
+                _type: block_type,
+                source: None,
+            },
         })
     }
 
@@ -262,30 +746,42 @@ impl Typechecker {
         WhileLoop {
             condition,
             block,
+            misplaced_assignment,
             position,
             ..
         }: &WhileLoop<()>,
         scope: &mut TypeScope,
     ) -> TResult<WhileLoop<TypeInfo>> {
-        let condition = self.check_expression(None, condition, scope)?;
+        let condition = self.check_condition(condition, misplaced_assignment, scope)?;
         if condition.info()._type != VariableType::Bool {
-            return Err(TypeError {
-                message: format!("Invalid type of condition '{}'", condition.info()._type),
-                position: position.to_owned(),
-            });
+            return Err(Self::condition_error(&condition));
         }
 
-        let block = self.check_block(block, scope)?;
+        self.loops.borrow_mut().push(LoopFrame::default());
+
+        let block = self.check_block(block, scope);
+
+        self.loops.borrow_mut().pop();
 
-        Ok(WhileLoop {
+        let block = block?;
+
+        let while_loop = WhileLoop {
             condition,
             block,
+            misplaced_assignment: misplaced_assignment.to_owned(),
             position: position.to_owned(),
             info: TypeInfo {
                 _type: VariableType::Void,
                 source: None,
             },
-        })
+        };
+
+        if has_invariant_condition(&while_loop) {
+            let (file, line, col) = position;
+            warn!("Condition of while loop is never modified in its body ({file}:{line}:{col})");
+        }
+
+        Ok(while_loop)
     }
 
     fn check_declaration(
@@ -295,23 +791,26 @@ impl Typechecker {
     ) -> TResult<Declaration> {
         let ident = &declaration.ident;
         let type_annotation = &declaration.type_annotation;
-        let type_def =
-            Self::get_type_def(&type_annotation.value, type_annotation.position.clone())?;
+        let type_def = self.type_def(&type_annotation.value, type_annotation.position.clone())?;
 
-        scope.set(&ident.value, type_def, false);
+        // declared functions may be defined later on, declared data (in contrast) is always
+        // external and may be written to
+        if let VariableType::Func { .. } = type_def {
+            scope.declare(&ident.value, type_def, &declaration.position);
+        } else {
+            scope.set(&ident.value, type_def, true);
+        }
         Ok(declaration.clone())
     }
 
     fn check_if(&self, if_statement: &If<()>, scope: &mut TypeScope) -> TResult<If<TypeInfo>> {
-        let condition = self.check_expression(None, &if_statement.condition, scope)?;
-        let condition_info = condition.info();
-        let condition_type = condition_info._type;
-
-        if condition_type != VariableType::Bool {
-            return Err(TypeError {
-                message: format!("Invalid tye of condition '{condition_type:?}'"),
-                position: if_statement.condition.position(),
-            });
+        let condition = self.check_condition(
+            &if_statement.condition,
+            &if_statement.misplaced_assignment,
+            scope,
+        )?;
+        if condition.info()._type != VariableType::Bool {
+            return Err(Self::condition_error(&condition));
         }
 
         let if_block = self.check_block(&if_statement.if_block, scope)?;
@@ -321,6 +820,7 @@ impl Typechecker {
             condition: Box::new(condition),
             if_block,
             else_block: None,
+            misplaced_assignment: if_statement.misplaced_assignment.clone(),
             position: if_statement.position.clone(),
             info: TypeInfo {
                 _type: if_block_type.clone(),
@@ -332,21 +832,83 @@ impl Typechecker {
             let else_block = self.check_block(else_block, scope)?;
             let else_block_type = else_block.info._type.clone();
 
-            if if_block_type != else_block_type {
-                return Err(TypeError {
-                    message: format!(
+            let Some(joined_type) = if_block_type.unify(&else_block_type) else {
+                return Err(TypeError::new(
+                    format!(
                         "Return type mismatch of if-else. Got '{if_block_type}' and '{else_block_type}'"
                     ),
-                    position: if_statement.position.clone(),
-                });
-            }
+                    if_statement.position.clone(),
+                ));
+            };
 
+            new_if.info._type = joined_type;
             new_if.else_block = Some(else_block);
         }
 
         Ok(new_if)
     }
 
+    /// Explain why the given condition (of an if or a while loop) is not a `bool`. Integers and
+    /// strings are never implicitly converted (like in C), so an explicit check is suggested.
+    fn condition_error(condition: &Expression<TypeInfo>) -> TypeError {
+        let condition_type = condition.info()._type;
+        let value_type = match &condition_type {
+            VariableType::Reference(inner) => inner.as_ref(),
+            condition_type => condition_type,
+        };
+
+        let suggestion = match value_type {
+            VariableType::Int => format!(
+                "; use '!({} == 0)' to check for a non-zero integer",
+                condition.to_source_within(BinaryOp::Equal.precedence())
+            ),
+            VariableType::Str => format!(
+                "; use 'str_len({}) > 0' to check for a non-empty string",
+                condition.to_source()
+            ),
+            _ => String::new(),
+        };
+
+        TypeError::new(
+            format!("Invalid type of condition '{condition_type}'{suggestion}"),
+            condition.position(),
+        )
+    }
+
+    /// Check the condition of an if or while loop. If an assignment was used as the condition, it
+    /// is reported, but checking continues with both of its sides (as if they were compared).
+    fn check_condition(
+        &self,
+        condition: &Expression<()>,
+        misplaced_assignment: &Option<Position>,
+        scope: &mut TypeScope,
+    ) -> TResult<Expression<TypeInfo>> {
+        let (Some(position), Expression::Binary(binary_expression)) =
+            (misplaced_assignment, condition)
+        else {
+            return self.check_value(None, condition, scope);
+        };
+
+        self.errors.borrow_mut().push(TypeError::new(
+            "assignment in condition; did you mean `==`?",
+            position.to_owned(),
+        ));
+
+        let lhs = self.check_value(None, &binary_expression.lhs, scope)?;
+        let rhs = self.check_value(None, &binary_expression.rhs, scope)?;
+
+        Ok(Expression::Binary(BinaryExpr {
+            op: binary_expression.op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+            position: binary_expression.position.clone(),
+            info: TypeInfo {
+                _type: VariableType::Bool,
+                source: None,
+            },
+        }))
+    }
+
     fn check_block(&self, block: &Block<()>, scope: &mut TypeScope) -> TResult<Block<TypeInfo>> {
         scope.push();
 
@@ -359,14 +921,15 @@ impl Typechecker {
             },
         };
 
-        for statement in &block.block {
+        for (index, statement) in block.block.iter().enumerate() {
+            if index > 0 && is_exit(&block.block[index - 1]) {
+                let (file, line, col) = statement.position();
+                warn!("Unreachable statement after 'exit' ({file}:{line}:{col})");
+            }
+
             let statement = self.check_statement(statement, scope)?;
-            if let Statement::CompilerDirective(compiler_directive) = &statement {
-                if compiler_directive.statement.is_some() {
-                    new_block.info._type = statement.info()._type;
-                }
-            } else {
-                new_block.info._type = statement.info()._type;
+            if let Some(statement_type) = Self::statement_type(&statement) {
+                new_block.info._type = statement_type;
             }
             new_block.block.push(statement);
         }
@@ -376,29 +939,103 @@ impl Typechecker {
         Ok(new_block)
     }
 
+    /// Get the type of the value of a statement within a block. Compiler directives whose
+    /// condition does not hold have no type (and do not affect the type of the block).
+    fn statement_type(statement: &Statement<TypeInfo>) -> Option<VariableType> {
+        match statement {
+            Statement::CompilerDirective(CompilerDirective { statement, .. }) => {
+                Self::statement_type(statement.as_ref()?)
+            }
+            Statement::Import(_) | Statement::Intrinsic(Intrinsic::Declaration(_)) => {
+                Some(VariableType::Void)
+            }
+            statement => Some(statement.info()._type),
+        }
+    }
+
     fn check_definition(
         &self,
         definition: &Definition<()>,
         scope: &mut TypeScope,
     ) -> TResult<Definition<TypeInfo>> {
-        let definition_rhs =
-            self.check_expression(Some(&definition.ident), &definition.value, scope)?;
+        // the top-level statements of a program form its entry point, which is named `main`, so
+        // no function of the program may be compiled to a symbol of the same name (functions
+        // within other functions and of imported modules are prefixed)
+        if definition.ident.value == ENTRY_POINT
+            && !self.is_imported
+            && self.functions.borrow().is_empty()
+        {
+            if let Expression::FnDef(_) = definition.value {
+                return Err(TypeError::new(
+                    format!(
+                        "Function '{ENTRY_POINT}' can not be defined, since the top-level statements of a program form its entry point"
+                    ),
+                    definition.position.clone(),
+                ));
+            }
+        }
 
-        if scope.contains_in_current_scope(&definition.ident.value) {
-            return Err(TypeError {
-                message: format!(
-                    "Variable '{}' has already been defined!",
+        let forward_declaration = scope.forward_declaration(&definition.ident.value);
+
+        let expected = forward_declaration
+            .as_ref()
+            .map(|(declared_type, declared_at)| ExpectedType {
+                _type: declared_type.clone(),
+                note: format!(
+                    "'{}' is declared as '{declared_type}' here",
                     definition.ident.value
                 ),
-                position: definition.position.clone(),
+                position: declared_at.clone(),
             });
+
+        let definition_rhs = self.check_value_in_context(
+            Some(&definition.ident),
+            &definition.value,
+            expected.as_ref(),
+            scope,
+        )?;
+
+        if let Some((declared_type, declared_at)) = forward_declaration {
+            // a declared function may be defined once with the very same signature
+            let defined_type = definition_rhs.info()._type;
+            if !matches!(definition.value, Expression::FnDef(_))
+                || !defined_type.is_same(&declared_type)
+            {
+                return Err(TypeError::new(
+                    format!(
+                        "Definition of '{}' with type '{defined_type}' does not match its declaration with type '{declared_type}'",
+                        definition.ident.value
+                    ),
+                    definition.position.clone(),
+                ).with_note(format!(
+                            "'{}' is declared as '{declared_type}' here",
+                            definition.ident.value
+                        ), declared_at));
+            }
+        } else if scope.contains_in_current_scope(&definition.ident.value) {
+            return Err(TypeError::new(
+                format!(
+                    "Variable '{}' has already been defined!",
+                    definition.ident.value
+                ),
+                definition.position.clone(),
+            ));
         }
 
-        scope.set(
-            &definition.ident.value,
-            definition_rhs.info()._type,
-            definition.is_mutable,
-        );
+        match &definition.value {
+            Expression::FnDef(fn_def) if !definition.is_mutable => {
+                scope.set_function(&definition.ident.value, definition_rhs.info()._type);
+                scope.set_return_type_at(
+                    &definition.ident.value,
+                    &Self::return_type_position(&definition.ident, fn_def),
+                );
+            }
+            _ => scope.set(
+                &definition.ident.value,
+                definition_rhs.info()._type,
+                definition.is_mutable,
+            ),
+        }
 
         let ident = &definition.ident;
 
@@ -435,22 +1072,17 @@ impl Typechecker {
                 let indexing_lhs = self.check_expression(None, indexing_lhs, scope)?;
                 let indexing = self.check_indexing(&indexing_lhs, indexing, scope)?;
 
-                let assignment_rhs = self.check_expression(None, &assignment.value, scope)?;
+                let assignment_rhs = self.check_value(None, &assignment.value, scope)?;
 
-                if assignment_rhs
-                    .info()
-                    ._type
-                    .convert_to(&indexing.info._type)
-                    .is_err()
-                {
-                    return Err(TypeError {
-                        message: format!(
+                if !assignment_rhs.info()._type.coerces_to(&indexing.info._type) {
+                    return Err(TypeError::new(
+                        format!(
                             "Can not assign value of type '{}' to indexed variable of type '{}'",
                             assignment_rhs.info()._type,
                             indexing.info._type
                         ),
-                        position: assignment.position.clone(),
-                    });
+                        assignment.position.clone(),
+                    ));
                 }
 
                 Ok(Assignment {
@@ -470,23 +1102,33 @@ impl Typechecker {
             }
             Expression::Ident(lhs) => {
                 if !scope.contains(&lhs.value) {
-                    return Err(TypeError {
-                        message: format!("Undefined identifier '{}'", lhs.value),
-                        position: lhs.position.clone(),
-                    });
+                    return Err(self.undefined_identifier(lhs));
                 }
 
+                self.check_capture(lhs, scope)?;
+
                 if !scope.is_mutable(&lhs.value) {
-                    return Err(TypeError {
-                        message: format!(
+                    return Err(TypeError::new(
+                        format!(
                     "Variable '{}' can not be modified, because it is not defined in current scope",
                     lhs.value
                 ),
-                        position: lhs.position.clone(),
-                    });
+                        lhs.position.clone(),
+                    ));
                 }
 
-                let assignment_rhs = self.check_expression(Some(lhs), &assignment.value, scope)?;
+                let expected = scope.find(&lhs.value).map(|variable_type| ExpectedType {
+                    note: format!("'{}' is of type '{variable_type}'", lhs.value),
+                    _type: variable_type,
+                    position: lhs.position.clone(),
+                });
+
+                let assignment_rhs = self.check_value_in_context(
+                    Some(lhs),
+                    &assignment.value,
+                    expected.as_ref(),
+                    scope,
+                )?;
 
                 scope.update(
                     &lhs.value,
@@ -494,6 +1136,11 @@ impl Typechecker {
                     &assignment.position,
                 )?;
 
+                // a top-level definition which is assigned a new value is no constant anymore
+                if scope.is_global(&lhs.value) {
+                    self.constants.borrow_mut().remove(&lhs.value);
+                }
+
                 Ok(Assignment {
                     lhs: Expression::Ident(Ident {
                         position: lhs.position.clone(),
@@ -508,10 +1155,10 @@ impl Typechecker {
                     },
                 })
             }
-            _ => Err(TypeError {
-                message: format!("Invalid lvalue of assignment '{lhs:?}'"),
-                position: lhs.position(),
-            }),
+            _ => Err(TypeError::new(
+                format!("Invalid lvalue of assignment '{lhs:?}'"),
+                lhs.position(),
+            )),
         }
     }
 
@@ -521,27 +1168,119 @@ impl Typechecker {
         expression: &Expression<()>,
         scope: &mut TypeScope,
     ) -> TResult<Expression<TypeInfo>> {
-        Ok(match expression {
-            Expression::If(if_statement) => Expression::If(self.check_if(if_statement, scope)?),
-            Expression::Binary(binary_expr) => {
-                Expression::Binary(self.check_binary_expression(binary_expr, scope)?)
-            }
-            Expression::Integer(Integer {
-                value, position, ..
-            }) => Expression::Integer(Integer {
-                value: *value,
-                position: position.clone(),
-                info: TypeInfo {
-                    _type: VariableType::Int,
-                    source: None,
-                },
-            }),
-            Expression::Str(Str {
-                value, position, ..
-            }) => Expression::Str(Str {
-                value: value.to_owned(),
-                position: position.clone(),
-                info: TypeInfo {
+        // bail out on (probably generated) absurdly nested expressions before we run out of stack
+        let depth = self.depth.get() + 1;
+        if depth > MAX_EXPRESSION_DEPTH {
+            return Err(TypeError::new(
+                format!(
+                    "Expression is nested too deeply (the limit is {MAX_EXPRESSION_DEPTH} levels)"
+                ),
+                expression.position(),
+            ));
+        }
+
+        self.depth.set(depth);
+        let expression = self.check_nested_expression(identifier, expression, scope);
+        self.depth.set(depth - 1);
+
+        expression
+    }
+
+    /// Check an expression whose value is used (e.g., as an operand or a parameter). In contrast
+    /// to other expressions, these must not be of type 'void'.
+    fn check_value(
+        &self,
+        identifier: Option<&Ident<()>>,
+        expression: &Expression<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<Expression<TypeInfo>> {
+        let value = self.check_expression(identifier, expression, scope)?;
+
+        if value.info()._type != VariableType::Void {
+            return Ok(value);
+        }
+
+        // name the function for calls, since the call itself does not show its return type
+        if let Expression::Postfix(PostfixExpr {
+            lhs,
+            op: PostfixOp::Call(_),
+            ..
+        }) = expression
+        {
+            if let Expression::Ident(ident) = lhs.as_ref() {
+                let note = scope.return_type_at(&ident.value).map(|position| {
+                    (
+                        format!("'{}' is defined to return 'void' here", ident.value),
+                        position,
+                    )
+                });
+
+                return Err(TypeError::new(
+                    format!(
+                        "'{}' returns 'void', so its result can not be used as a value",
+                        ident.value
+                    ),
+                    ident.position.clone(),
+                )
+                .with_notes(note));
+            }
+        }
+
+        Err(TypeError::new(
+            "Expression of type 'void' can not be used as a value",
+            value.position(),
+        ))
+    }
+
+    /// Check an expression whose value is used where a value of the expected type is needed. A
+    /// function definition takes the types of its parameters without annotation from the expected
+    /// type.
+    fn check_value_in_context(
+        &self,
+        identifier: Option<&Ident<()>>,
+        expression: &Expression<()>,
+        expected: Option<&ExpectedType>,
+        scope: &mut TypeScope,
+    ) -> TResult<Expression<TypeInfo>> {
+        match (expression, expected) {
+            (Expression::FnDef(fn_def), Some(expected)) => Ok(Expression::FnDef(
+                self.check_fn_def(identifier, fn_def, Some(expected), scope)?,
+            )),
+            _ => self.check_value(identifier, expression, scope),
+        }
+    }
+
+    fn check_nested_expression(
+        &self,
+        identifier: Option<&Ident<()>>,
+        expression: &Expression<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<Expression<TypeInfo>> {
+        Ok(match expression {
+            Expression::If(if_statement) => Expression::If(self.check_if(if_statement, scope)?),
+            Expression::Binary(binary_expr) => {
+                Expression::Binary(self.check_binary_expression(binary_expr, scope)?)
+            }
+            Expression::Integer(Integer {
+                value, position, ..
+            }) => Expression::Integer(Integer {
+                value: *value,
+                position: position.clone(),
+                info: TypeInfo {
+                    _type: VariableType::Int,
+                    source: None,
+                },
+            }),
+            Expression::Str(Str {
+                value,
+                raw,
+                position,
+                ..
+            }) => Expression::Str(Str {
+                value: value.to_owned(),
+                raw: *raw,
+                position: position.clone(),
+                info: TypeInfo {
                     _type: VariableType::Str,
                     source: None,
                 },
@@ -564,10 +1303,14 @@ impl Typechecker {
                 Expression::Postfix(self.check_postfix_expression(postfix_expr, scope)?)
             }
             Expression::FnDef(fn_def) => {
-                Expression::FnDef(self.check_fn_def(identifier, fn_def, scope)?)
+                Expression::FnDef(self.check_fn_def(identifier, fn_def, None, scope)?)
             }
             Expression::Block(block) => Expression::Block(self.check_block(block, scope)?),
             Expression::Array(array) => Expression::Array(self.check_array(array, scope)?),
+            Expression::Loop(loop_expr) => Expression::Loop(self.check_loop(loop_expr, scope)?),
+            Expression::LabeledBlock(labeled_block) => {
+                Expression::LabeledBlock(self.check_labeled_block(labeled_block, scope)?)
+            }
             Expression::Character(Character {
                 value, position, ..
             }) => Expression::Character(Character {
@@ -591,23 +1334,23 @@ impl Typechecker {
         }: &Array<()>,
         scope: &mut TypeScope,
     ) -> TResult<Array<TypeInfo>> {
-        let initializer = self.check_expression(None, initializer, scope)?;
+        let initializer = self.check_value(None, initializer, scope)?;
+        let size = self.check_value(None, size, scope)?;
+
+        // local variables may shadow constants
+        let constants = self.constants.borrow().filter(|name| scope.is_global(name));
+        let length = constants
+            .evaluate(&size)
+            .map_err(|message| TypeError::new(message, size.position()))?;
 
         Ok(Array {
             initializer: Box::new(initializer.clone()),
-            size: size.to_owned(),
+            size: Box::new(size),
             position: position.to_owned(),
             info: TypeInfo {
                 _type: VariableType::TupleArray {
                     item_type: Box::new(initializer.info()._type),
-                    size: if size.value >= 0 {
-                        size.value as usize
-                    } else {
-                        return Err(TypeError {
-                            message: "Negative length arrays are not supported!".to_string(),
-                            position: position.clone(),
-                        });
-                    },
+                    size: Self::array_size(length, position.clone())?,
                 },
                 source: initializer.info()._type.get_source(),
             },
@@ -620,26 +1363,88 @@ impl Typechecker {
         scope: &mut TypeScope,
     ) -> TResult<Ident<TypeInfo>> {
         match scope.find(&identifier.value) {
-            Some(identifier_type) => Ok(Ident {
-                value: identifier.value.clone(),
-                position: identifier.position.clone(),
-                info: TypeInfo {
-                    _type: identifier_type,
-                    source: None,
-                },
-            }),
-            None => Err(TypeError {
-                message: format!("Undefined identifier '{}'", identifier.value),
-                position: identifier.position.clone(),
-            }),
+            Some(VariableType::Unknown) if self.is_inferred_function(&identifier.value) => {
+                Err(self.recursive_inference_error(&identifier.value, identifier.position.clone()))
+            }
+            Some(identifier_type) => {
+                self.check_capture(identifier, scope)?;
+
+                Ok(Ident {
+                    value: identifier.value.clone(),
+                    position: identifier.position.clone(),
+                    info: TypeInfo {
+                        _type: identifier_type,
+                        source: None,
+                    },
+                })
+            }
+            None => Err(self.undefined_identifier(identifier)),
         }
     }
 
-    fn get_type_def(type_: &Type, position: Position) -> Result<VariableType, TypeError> {
+    /// The error for an identifier, which is not defined. If it refers to a function of an
+    /// imported module, which is not exported, the error points at the definition of the function.
+    fn undefined_identifier(&self, identifier: &Ident<()>) -> TypeError {
+        let Some((note, position)) = self.unexported.borrow().get(&identifier.value).cloned()
+        else {
+            return TypeError::new(
+                format!("Undefined identifier '{}'", identifier.value),
+                identifier.position.clone(),
+            );
+        };
+
+        TypeError::new(
+            format!(
+                "Function '{}' is not exported by its module, since its type is not fully annotated",
+                identifier.value
+            ),
+            identifier.position.clone(),
+        )
+        .with_note(note, position)
+    }
+
+    /// Check, that the given identifier does not refer to a local variable of the scope enclosing
+    /// the named function it occurs in. Named functions are compiled to functions of their own,
+    /// which can not access the stack of the function they are defined in.
+    fn check_capture(&self, identifier: &Ident<()>, scope: &TypeScope) -> TResult<()> {
+        let functions = self.functions.borrow();
+        let Some((function, position, depth)) = functions.last() else {
+            return Ok(());
+        };
+
+        if !scope.is_local_below(&identifier.value, *depth) {
+            return Ok(());
+        }
+
+        let (function, parameter_of) = match function {
+            Some(function) => (format!("Function '{function}'"), format!("'{function}'")),
+            None => ("Anonymous function".to_owned(), "the function".to_owned()),
+        };
+
+        Err(TypeError::new(
+            format!(
+                "{function} can not capture '{}' of its enclosing scope",
+                identifier.value
+            ),
+            identifier.position.clone(),
+        )
+        .with_note(
+            format!(
+                "pass '{}' to {parameter_of} as a parameter instead",
+                identifier.value
+            ),
+            position.clone(),
+        ))
+    }
+
+    fn get_type_def(
+        type_: &Type,
+        position: Position,
+        constants: &Constants,
+    ) -> Result<VariableType, TypeError> {
         match type_ {
-            Type::Literal(literal) => literal.parse().map_err(|_| TypeError {
-                message: format!("Unexpected type annotation '{type_:?}'"),
-                position,
+            Type::Literal(literal) => literal.parse().map_err(|_| {
+                TypeError::new(format!("Unexpected type annotation '{type_:?}'"), position)
             }),
             Type::Function {
                 params,
@@ -647,10 +1452,12 @@ impl Typechecker {
             } => {
                 let mut fn_params = vec![];
                 for param in params {
-                    fn_params.push(Self::get_type_def(param, position.clone())?);
+                    fn_params.push(Self::get_type_def(param, position.clone(), constants)?);
                 }
 
-                let return_type = Self::get_type_def(return_type, position)?;
+                Self::check_param_registers(&fn_params, &position)?;
+
+                let return_type = Self::get_type_def(return_type, position, constants)?;
                 Ok(VariableType::Func {
                     return_type: Box::new(return_type),
                     params: fn_params,
@@ -658,84 +1465,195 @@ impl Typechecker {
                 })
             }
             Type::ArraySlice(item_type) => {
-                let item_type = Self::get_type_def(item_type, position)?;
+                let item_type = Self::get_type_def(item_type, position, constants)?;
 
                 Ok(VariableType::ArraySlice(Box::new(item_type)))
             }
             Type::TupleArray { item_type, size } => {
-                let item_type = Self::get_type_def(item_type, position.clone())?;
+                let item_type = Self::get_type_def(item_type, position.clone(), constants)?;
+
+                let size = constants
+                    .evaluate(size)
+                    .map_err(|message| TypeError::new(message, position.clone()))?;
 
                 Ok(VariableType::TupleArray {
                     item_type: Box::new(item_type),
-                    size: if size.value >= 0 {
-                        size.value as usize
-                    } else {
-                        return Err(TypeError {
-                            message: "Negative length arrays are not supported!".to_string(),
-                            position,
-                        });
-                    },
+                    size: Self::array_size(size, position)?,
                 })
             }
             Type::Reference(type_) => Ok(VariableType::Reference(Box::new(Self::get_type_def(
-                type_, position,
+                type_, position, constants,
             )?))),
         }
     }
 
+    /// Get the type of the given type annotation, resolving array sizes against the constants
+    /// defined so far.
+    /// Check, whether the parameters of a function fit into the registers used for passing them.
+    fn check_param_registers(params: &[VariableType], position: &Position) -> TResult<()> {
+        let registers = params
+            .iter()
+            .map(|param| match param {
+                VariableType::ArraySlice(_) => 2,
+                _ => 1,
+            })
+            .sum::<usize>();
+
+        if registers > PARAM_REGISTER_COUNT {
+            return Err(TypeError::new(
+                format!(
+                    "Parameters of function need {registers} registers, but only {PARAM_REGISTER_COUNT} are available (slices need two)"
+                ),
+                position.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn type_def(&self, type_: &Type, position: Position) -> Result<VariableType, TypeError> {
+        Self::get_type_def(type_, position, &self.constants.borrow())
+    }
+
+    /// Check the (evaluated) size of an array.
+    fn array_size(size: i64, position: Position) -> Result<usize, TypeError> {
+        usize::try_from(size)
+            .map_err(|_| TypeError::new("Negative length arrays are not supported!", position))
+    }
+
+    /// Remember the value of an immutable top-level definition, if it is a constant integer
+    /// expression.
+    fn define_constant(&self, statement: &Statement<TypeInfo>) {
+        let Statement::Intrinsic(Intrinsic::Definition(Definition {
+            ident,
+            value,
+            is_mutable: false,
+            ..
+        })) = statement
+        else {
+            return;
+        };
+
+        let mut constants = self.constants.borrow_mut();
+        if let Ok(value) = constants.evaluate(value) {
+            constants.define(&ident.value, value);
+        }
+    }
+
     fn check_fn_def(
         &self,
         identifier: Option<&Ident<()>>,
         fn_def: &FnDef<()>,
+        expected: Option<&ExpectedType>,
         scope: &mut TypeScope,
     ) -> TResult<FnDef<TypeInfo>> {
-        let type_annotation = Self::get_type_def(
-            &fn_def.type_annotation.value,
-            fn_def.type_annotation.position.clone(),
-        )?;
-        scope.push();
-
-        let mut params = vec![];
+        let type_annotation = fn_def
+            .type_annotation
+            .as_ref()
+            .map(|type_annotation| {
+                self.type_def(&type_annotation.value, type_annotation.position.clone())
+            })
+            .transpose()?;
+        let params = self.param_types(&fn_def.params, expected)?;
+        Self::check_param_registers(&params, &fn_def.position)?;
+
+        // a named function without return type annotation takes its return type from the
+        // expected type (e.g., its declaration), which allows it to call itself
+        let expected_return_type = match (identifier, expected) {
+            (
+                Some(_),
+                Some(ExpectedType {
+                    _type:
+                        VariableType::Func {
+                            params: expected_params,
+                            return_type,
+                            ..
+                        },
+                    ..
+                }),
+            ) if type_annotation.is_none() && expected_params.len() == params.len() => {
+                Some(return_type.as_ref().clone())
+            }
+            _ => None,
+        };
+        let return_annotation = type_annotation.clone().or(expected_return_type);
 
-        for param in &fn_def.params {
-            let param_type = Self::get_type_def(
-                &param.type_annotation.value,
-                param.type_annotation.position.clone(),
-            )?;
+        scope.push();
 
+        for (param, param_type) in fn_def.params.iter().zip(&params) {
             scope.set(&param.ident.value, param_type.clone(), true);
-            params.push(param_type);
         }
 
         if let Some(ident) = identifier {
-            scope.set(
-                &ident.value,
-                VariableType::Func {
+            // the type of a function, whose return type is inferred, is unknown within its body
+            let function_type = match &return_annotation {
+                Some(return_annotation) => VariableType::Func {
                     params: params.clone(),
-                    return_type: Box::new(type_annotation.clone()),
+                    return_type: Box::new(return_annotation.clone()),
                     source: None,
                 },
-                // TODO: This should handle mutable definitions
-                false,
-            )
+                None => {
+                    self.inferred_functions
+                        .borrow_mut()
+                        .push((ident.value.to_owned(), ident.position.clone()));
+                    VariableType::Unknown
+                }
+            };
+
+            // TODO: This should handle mutable definitions
+            scope.set_function(&ident.value, function_type);
+            scope.set_return_type_at(&ident.value, &Self::return_type_position(ident, fn_def));
         }
 
-        let block = self.check_block(&fn_def.block, scope)?;
+        // the parameters (and the function itself) live in the frame pushed above
+        self.functions.borrow_mut().push((
+            identifier.map(|ident| ident.value.to_owned()),
+            fn_def.position.clone(),
+            scope.depth() - 1,
+        ));
 
-        let Ok(return_type) = block.info._type.convert_to(&type_annotation) else {
-            return Err(TypeError {
-                message: format!(
-                    "Expected return type of '{type_annotation}' but got '{}'",
-                    block.info._type
-                ),
-                position: fn_def.position.clone(),
-            });
+        // a function body can never break out of a loop surrounding its definition
+        let outer_loops = self.loops.take();
+
+        let block = self.check_block(&fn_def.block, scope);
+
+        *self.loops.borrow_mut() = outer_loops;
+        self.functions.borrow_mut().pop();
+        if identifier.is_some() && return_annotation.is_none() {
+            self.inferred_functions.borrow_mut().pop();
+        }
+
+        let block = block?;
+
+        let return_type = match &return_annotation {
+            Some(return_annotation) => {
+                let Ok(return_type) = block.info._type.convert_to(return_annotation) else {
+                    let expected_note = expected
+                        .filter(|_| type_annotation.is_none())
+                        .map(|expected| (expected.note.clone(), expected.position.clone()));
+
+                    return Err(TypeError::new(
+                        format!(
+                            "Expected return type of '{return_annotation}' but got '{}'",
+                            block.info._type
+                        ),
+                        fn_def.position.clone(),
+                    )
+                    .with_notes(
+                        Self::trailing_definition_note(&block, return_annotation)
+                            .into_iter()
+                            .chain(expected_note),
+                    ));
+                };
+                return_type
+            }
+            None => block.info._type.clone(),
         };
 
         scope.pop();
 
         Ok(FnDef {
-            params: self.check_fn_params(&fn_def.params)?,
+            params: Self::check_fn_params(&fn_def.params, &params),
             type_annotation: fn_def.type_annotation.clone(),
             block,
             position: fn_def.position.clone(),
@@ -750,32 +1668,126 @@ impl Typechecker {
         })
     }
 
-    fn check_fn_params(&self, params: &Vec<Param<()>>) -> TResult<Vec<Param<TypeInfo>>> {
-        let mut new_params = vec![];
+    /// The position of the return type annotation of a named function, or of its name, if the
+    /// return type is inferred.
+    fn return_type_position(ident: &Ident<()>, fn_def: &FnDef<()>) -> Position {
+        fn_def
+            .type_annotation
+            .as_ref()
+            .map_or(&ident.position, |type_annotation| &type_annotation.position)
+            .clone()
+    }
 
-        for param in params {
-            let Ident {
-                value, position, ..
-            } = &param.ident;
-            let type_annotation = &param.type_annotation;
-            let param_type =
-                Self::get_type_def(&type_annotation.value, type_annotation.position.clone())?;
+    /// Check, whether the return type of the function with the given name is currently being
+    /// inferred, i.e., whether it is used within its own body.
+    fn is_inferred_function(&self, name: &str) -> bool {
+        self.inferred_functions
+            .borrow()
+            .iter()
+            .any(|(function, _)| function == name)
+    }
+
+    fn recursive_inference_error(&self, name: &str, position: Position) -> TypeError {
+        let definition = self
+            .inferred_functions
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(function, _)| function == name)
+            .map(|(_, definition)| {
+                (
+                    format!("alternatively, declare the type of '{name}' before its definition"),
+                    definition.clone(),
+                )
+            });
+
+        TypeError::new(
+            format!("Function '{name}' is recursive and therefore needs a return type annotation"),
+            position,
+        )
+        .with_notes(definition)
+    }
+
+    /// Determine the types of the parameters of a function definition. Parameters without
+    /// annotation take their type from the expected type of the function. If both are given, they
+    /// have to match.
+    fn param_types(
+        &self,
+        params: &[Param<()>],
+        expected: Option<&ExpectedType>,
+    ) -> TResult<Vec<VariableType>> {
+        let expected_params = match expected {
+            Some(ExpectedType {
+                _type:
+                    VariableType::Func {
+                        params: expected_params,
+                        ..
+                    },
+                ..
+            }) if expected_params.len() == params.len() => Some(expected_params),
+            _ => None,
+        };
+
+        let mut param_types = vec![];
+
+        for (index, param) in params.iter().enumerate() {
+            let expected_type = expected_params.map(|expected_params| &expected_params[index]);
+
+            let param_type = match (&param.type_annotation, expected_type) {
+                (Some(type_annotation), expected_type) => {
+                    let param_type =
+                        self.type_def(&type_annotation.value, type_annotation.position.clone())?;
+
+                    if let (Some(expected_type), Some(expected)) = (expected_type, expected) {
+                        if !param_type.is_same(expected_type) {
+                            return Err(TypeError::new(
+                                format!(
+                                    "Type '{param_type}' of parameter '{}' conflicts with the expected type '{expected_type}'",
+                                    param.ident.value
+                                ),
+                                type_annotation.position.clone(),
+                            )
+                            .with_note(expected.note.clone(), expected.position.clone()));
+                        }
+                    }
+
+                    param_type
+                }
+                (None, Some(expected_type)) => expected_type.clone(),
+                (None, None) => {
+                    return Err(TypeError::new(
+                        format!(
+                            "Type of parameter '{}' can not be inferred and needs an annotation",
+                            param.ident.value
+                        ),
+                        param.position.clone(),
+                    ))
+                }
+            };
+
+            param_types.push(param_type);
+        }
+
+        Ok(param_types)
+    }
 
-            new_params.push(Param {
+    fn check_fn_params(params: &[Param<()>], param_types: &[VariableType]) -> Vec<Param<TypeInfo>> {
+        params
+            .iter()
+            .zip(param_types)
+            .map(|(param, param_type)| Param {
                 ident: Ident {
-                    value: value.clone(),
-                    position: position.clone(),
+                    value: param.ident.value.clone(),
+                    position: param.ident.position.clone(),
                     info: TypeInfo {
-                        _type: param_type,
+                        _type: param_type.clone(),
                         source: None,
                     },
                 },
                 position: param.position.clone(),
-                type_annotation: type_annotation.clone(),
-            });
-        }
-
-        Ok(new_params)
+                type_annotation: param.type_annotation.clone(),
+            })
+            .collect()
     }
 
     fn check_fn_call(
@@ -786,58 +1798,107 @@ impl Typechecker {
     ) -> TResult<Call<TypeInfo>> {
         scope.push();
 
+        let callee_position = ident.position.clone();
         let ident = &ident.value;
 
         let Some(fn_def) = scope.find(ident) else {
-            return Err(TypeError {
-                message: format!("Call to undefined function '{ident}'"),
-                position: fn_call.position.clone(),
-            });
+            return Err(TypeError::new(
+                format!("Call to undefined function '{ident}'"),
+                fn_call.position.clone(),
+            ));
         };
 
+        if fn_def == VariableType::Unknown && self.is_inferred_function(ident) {
+            return Err(self.recursive_inference_error(ident, fn_call.position.clone()));
+        }
+
+        let call = self.check_call_params(ident, &callee_position, &fn_def, fn_call, scope);
+
+        scope.pop();
+
+        call
+    }
+
+    /// Check a call of the function which is the value of an arbitrary (already type checked)
+    /// expression, e.g., a function returned by another call or stored in an array.
+    fn check_expression_call(
+        &self,
+        callee: &Expression<TypeInfo>,
+        fn_call: &Call<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<Call<TypeInfo>> {
+        scope.push();
+
+        let call = self.check_call_params(
+            &callee.to_source(),
+            &callee.position(),
+            &callee.info()._type,
+            fn_call,
+            scope,
+        );
+
+        scope.pop();
+
+        call
+    }
+
+    /// Check the parameters of a call of the given callee (named by its source) against the
+    /// parameters of its type.
+    fn check_call_params(
+        &self,
+        ident: &str,
+        callee_position: &Position,
+        fn_def: &VariableType,
+        fn_call: &Call<()>,
+        scope: &mut TypeScope,
+    ) -> TResult<Call<TypeInfo>> {
         let VariableType::Func {
             params,
             return_type,
             ..
         } = fn_def.clone()
         else {
-            return Err(TypeError {
-                message: format!("Trying to call an invalid function '{ident}'"),
-                position: fn_call.position.clone(),
-            });
+            return Err(TypeError::new(
+                format!("Trying to call an invalid function '{ident}'"),
+                fn_call.position.clone(),
+            ));
         };
 
         if params.len() != fn_call.params.len() {
-            return Err(TypeError {
-                message: format!(
+            return Err(TypeError::new(
+                format!(
                     "Invalid amount of parameters! Expected {} but got {}",
                     params.len(),
                     fn_call.params.len()
                 ),
-                position: fn_call.position.clone(),
-            });
+                fn_call.position.clone(),
+            ));
         }
 
         let mut new_params = vec![];
 
         for (i, param) in params.iter().enumerate() {
-            let call_param = self.check_expression(None, &fn_call.params[i], scope)?;
+            let expected = ExpectedType {
+                _type: param.clone(),
+                note: format!("'{ident}' expects a parameter of type '{param}' here"),
+                position: callee_position.clone(),
+            };
+            let call_param =
+                self.check_value_in_context(None, &fn_call.params[i], Some(&expected), scope)?;
             let call_param_type = call_param.info()._type;
 
-            if call_param_type.convert_to(param).is_err() {
-                return Err(TypeError {
-                    message: format!(
+            if !call_param_type.coerces_to(param) {
+                return Err(TypeError::new(
+                    format!(
                         "Invalid type of parameter! Expected '{param}' but got '{call_param_type}'"
                     ),
-                    position: fn_call.params[i].position(),
-                });
+                    fn_call.params[i].position(),
+                ));
             }
 
             new_params.push(call_param);
         }
 
-        scope.pop();
-
         Ok(Call {
             params: new_params,
             position: fn_call.position.clone(),
@@ -848,6 +1909,64 @@ impl Typechecker {
         })
     }
 
+    /// Definitions evaluate to void, so a block ending with one does not yield the defined value.
+    /// If that value would have matched the expected type, create a note pointing this out.
+    fn trailing_definition_note(
+        block: &Block<TypeInfo>,
+        expected: &VariableType,
+    ) -> Option<(String, Position)> {
+        let Some(Statement::Intrinsic(Intrinsic::Definition(definition))) = block.block.last()
+        else {
+            return None;
+        };
+
+        definition.value.info()._type.convert_to(expected).ok()?;
+
+        Some((
+            format!(
+                "definitions evaluate to 'void', add '{}' as the last expression to return its value",
+                definition.ident.value
+            ),
+            definition.position.clone(),
+        ))
+    }
+
+    /// Explain a type error of a comparison whose left operand is a comparison itself (e.g.,
+    /// `0 < x < 10`), since this is parsed as `(0 < x) < 10` (i.e., it compares a `bool`).
+    fn chained_comparison_error(binary_expression: &BinaryExpr<()>) -> Option<TypeError> {
+        let Expression::Binary(BinaryExpr {
+            op: BinaryOp::LessThan | BinaryOp::GreaterThan | BinaryOp::Equal,
+            rhs: middle,
+            ..
+        }) = binary_expression.lhs.as_ref()
+        else {
+            return None;
+        };
+
+        Some(TypeError::new(
+            format!(
+                "Chained comparisons are not supported; compare '{}' and '{} {} {}' separately",
+                binary_expression.lhs.to_source(),
+                middle.to_source(),
+                binary_expression.op,
+                binary_expression.rhs.to_source()
+            ),
+            binary_expression.position.clone(),
+        ))
+    }
+
+    /// Suggest an explicit conversion for booleans used as an integer (e.g., `flag + 1`).
+    fn bool_conversion_hint(operand: &Expression<TypeInfo>) -> String {
+        if operand.info()._type != VariableType::Bool {
+            return String::new();
+        }
+
+        format!(
+            "; use '(if {} then 1 else 0)' to convert it to an integer",
+            operand.to_source()
+        )
+    }
+
     fn check_binary_expression(
         &self,
         binary_expression: &BinaryExpr<()>,
@@ -858,21 +1977,40 @@ impl Typechecker {
         let lhs = &binary_expression.lhs;
         let rhs = &binary_expression.rhs;
 
-        let lhs = self.check_expression(None, lhs, scope)?;
+        let lhs = self.check_value(None, lhs, scope)?;
         let l_type = lhs.info()._type;
 
-        let rhs = self.check_expression(None, rhs, scope)?;
+        let rhs = self.check_value(None, rhs, scope)?;
         let r_type = rhs.info()._type;
 
         match binary_expression.op {
             BinaryOp::Equal => {
-                if l_type.convert_to(&r_type).is_err() {
-                    return Err(TypeError {
-                        message: format!(
-                        "Left and right value of binary operation do not match! ('{l_type}' and '{r_type}')"
-                    ),
+                // arrays are compared element-wise, which is only possible for arrays of the
+                // same type (and size)
+                let is_array = |_type: &VariableType| match _type {
+                    VariableType::Reference(inner) => {
+                        matches!(**inner, VariableType::TupleArray { .. })
+                    }
+                    _type => matches!(_type, VariableType::TupleArray { .. }),
+                };
+                if (is_array(&l_type) || is_array(&r_type)) && !l_type.is_same(&r_type) {
+                    return Err(TypeError::new(
+                        format!("Can not compare values of type '{l_type}' and '{r_type}'"),
+                        position,
+                    ));
+                }
+
+                if !l_type.coerces_to(&r_type) {
+                    if let Some(error) = Self::chained_comparison_error(binary_expression) {
+                        return Err(error);
+                    }
+
+                    return Err(TypeError::new(
+                        format!(
+                            "Left and right value of binary operation do not match! ('{l_type}' and '{r_type}')"
+                        ),
                         position,
-                    });
+                    ));
                 }
                 Ok(BinaryExpr {
                     op: binary_expression.op,
@@ -889,13 +2027,17 @@ impl Typechecker {
                 if l_type.convert_to(&VariableType::Int).is_err()
                     || r_type.convert_to(&VariableType::Int).is_err()
                 {
-                    return Err(TypeError {
-                        message: format!(
+                    if let Some(error) = Self::chained_comparison_error(binary_expression) {
+                        return Err(error);
+                    }
+
+                    return Err(TypeError::new(
+                        format!(
                             "Invalid types for binary operation '{}'. Got '{}' and '{}'",
                             binary_expression.op, l_type, r_type
                         ),
                         position,
-                    });
+                    ));
                 }
                 Ok(BinaryExpr {
                     op: binary_expression.op,
@@ -908,21 +2050,61 @@ impl Typechecker {
                     },
                 })
             }
-            BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Times | BinaryOp::DividedBy => {
+            // characters can be shifted by an integer offset, e.g., 'a' + 1
+            BinaryOp::Plus | BinaryOp::Minus
+                if matches!(
+                    (&binary_expression.op, &l_type, &r_type),
+                    (_, VariableType::Char, VariableType::Int)
+                        | (BinaryOp::Plus, VariableType::Int, VariableType::Char)
+                ) =>
+            {
+                Ok(BinaryExpr {
+                    op: binary_expression.op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    position: binary_expression.position.clone(),
+                    info: TypeInfo {
+                        _type: VariableType::Char,
+                        source: None,
+                    },
+                })
+            }
+            BinaryOp::Plus
+            | BinaryOp::Minus
+            | BinaryOp::Times
+            | BinaryOp::DividedBy
+            | BinaryOp::BitAnd
+            | BinaryOp::BitOr
+            | BinaryOp::BitXor
+            | BinaryOp::ShiftLeft
+            | BinaryOp::ShiftRight => {
+                // any other arithmetic would only operate on the (single) byte of a character
+                if l_type == VariableType::Char || r_type == VariableType::Char {
+                    return Err(TypeError::new(
+                        format!(
+                            "Invalid types for binary operation '{}'. Got '{}' and '{}'",
+                            binary_expression.op, l_type, r_type
+                        ),
+                        position,
+                    ));
+                }
+
                 if l_type.convert_to(&VariableType::Int).is_err() {
-                    return Err(TypeError {
-                        message: format!(
-                        "Left value of numeric binary operation has to be of type Int. Found '{l_type}'"
-                    ),
-                        position: lhs.position(),
-                    });
+                    return Err(TypeError::new(
+                        format!(
+                            "Left value of numeric binary operation has to be of type Int. Found '{l_type}'{}",
+                            Self::bool_conversion_hint(&lhs)
+                        ),
+                        lhs.position(),
+                    ));
                 } else if r_type.convert_to(&VariableType::Int).is_err() {
-                    return Err(TypeError {
-                        message: format!(
-                        "Right value of numeric binary operation has to be of type Int. Found '{r_type}'"
-                    ),
-                        position: rhs.position(),
-                    });
+                    return Err(TypeError::new(
+                        format!(
+                            "Right value of numeric binary operation has to be of type Int. Found '{r_type}'{}",
+                            Self::bool_conversion_hint(&rhs)
+                        ),
+                        rhs.position(),
+                    ));
                 }
 
                 Ok(BinaryExpr {
@@ -954,13 +2136,13 @@ impl Typechecker {
         match prefix_expression.op {
             PrefixOp::Not => {
                 if r_type != VariableType::Bool {
-                    return Err(TypeError {
-                        message: format!(
+                    return Err(TypeError::new(
+                        format!(
                             "Invalid type for boolean prefix operation '{}'. Got '{}'",
                             prefix_expression.op, r_type
                         ),
                         position,
-                    });
+                    ));
                 }
                 Ok(PrefixExpr {
                     op: prefix_expression.op,
@@ -972,15 +2154,15 @@ impl Typechecker {
                     },
                 })
             }
-            PrefixOp::UnaryMinus => {
+            PrefixOp::UnaryMinus | PrefixOp::BitNot => {
                 if r_type != VariableType::Int {
-                    return Err(TypeError {
-                        message: format!(
+                    return Err(TypeError::new(
+                        format!(
                             "Invalid type for integral prefix operation '{}'. Got '{}'",
                             prefix_expression.op, r_type
                         ),
                         position,
-                    });
+                    ));
                 }
                 Ok(PrefixExpr {
                     op: prefix_expression.op,
@@ -1002,16 +2184,28 @@ impl Typechecker {
     ) -> TResult<PostfixExpr<TypeInfo>> {
         let postfix_expression = postfix_expression.clone();
 
+        // assertions are known to the compiler (unless a variable with the same name exists)
+        if let (Expression::Ident(ident), PostfixOp::Call(call)) =
+            (postfix_expression.lhs.as_ref(), &postfix_expression.op)
+        {
+            if is_assertion(&ident.value) && !scope.contains(&ident.value) {
+                return self.check_assertion(ident, call, &postfix_expression.position, scope);
+            }
+        }
+
         let lhs = &postfix_expression.lhs;
 
         let lhs = self.check_expression(None, lhs, scope)?;
 
         match postfix_expression.op {
             PostfixOp::Call(call) => {
-                let Expression::Ident(ident) = *postfix_expression.lhs else {
-                    unimplemented!("Calls on non-identifier-expressions are not implemented yet")
+                // functions referred to by name are looked up in the scope (e.g., to detect
+                // recursive calls of functions whose type is inferred), all other callees are
+                // resolved via their type only
+                let call = match postfix_expression.lhs.as_ref() {
+                    Expression::Ident(ident) => self.check_fn_call(ident, &call, scope)?,
+                    _ => self.check_expression_call(&lhs, &call, scope)?,
                 };
-                let call = self.check_fn_call(&ident, &call, scope)?;
                 let info = call.info.clone();
                 Ok(PostfixExpr {
                     op: PostfixOp::Call(call),
@@ -1034,6 +2228,97 @@ impl Typechecker {
         }
     }
 
+    /// Check a call of `assert` (which takes a condition) or `assert_eq` (which takes two values of
+    /// the same type).
+    fn check_assertion(
+        &self,
+        ident: &Ident<()>,
+        call: &Call<()>,
+        position: &Position,
+        scope: &mut TypeScope,
+    ) -> TResult<PostfixExpr<TypeInfo>> {
+        let expected_params = if ident.value == "assert" { 1 } else { 2 };
+
+        if call.params.len() != expected_params {
+            return Err(TypeError::new(
+                format!(
+                    "Invalid amount of parameters! Expected {expected_params} but got {}",
+                    call.params.len()
+                ),
+                call.position.clone(),
+            ));
+        }
+
+        let mut params = vec![];
+        for param in &call.params {
+            params.push(self.check_value(None, param, scope)?);
+        }
+
+        let param_types = params
+            .iter()
+            .map(|param| param.info()._type)
+            .collect::<Vec<_>>();
+
+        if let [condition] = param_types.as_slice() {
+            if *condition != VariableType::Bool {
+                return Err(TypeError::new(
+                    format!("'assert' expects a condition of type 'bool' but got '{condition}'"),
+                    params[0].position(),
+                ));
+            }
+        }
+
+        if let [left, right] = param_types.as_slice() {
+            if left != right {
+                return Err(TypeError::new(
+                    format!("Can not compare values of type '{left}' and '{right}'"),
+                    params[0].position(),
+                ));
+            }
+
+            // the values are printed if the assertion fails (and strings are only compared by
+            // their address)
+            if !matches!(
+                left,
+                VariableType::Int | VariableType::Char | VariableType::Bool
+            ) {
+                return Err(TypeError::new(
+                    format!(
+                        "'assert_eq' can only compare values of type 'int', 'char' or 'bool', but got '{left}'"
+                    ),
+                    params[0].position(),
+                ));
+            }
+        }
+
+        let void = TypeInfo {
+            _type: VariableType::Void,
+            source: None,
+        };
+
+        Ok(PostfixExpr {
+            lhs: Box::new(Expression::Ident(Ident {
+                value: ident.value.to_owned(),
+                position: ident.position.clone(),
+                info: TypeInfo {
+                    _type: VariableType::Func {
+                        params: param_types,
+                        return_type: Box::new(VariableType::Void),
+                        source: None,
+                    },
+                    source: None,
+                },
+            })),
+            op: PostfixOp::Call(Call {
+                params,
+                position: call.position.clone(),
+                info: void.clone(),
+            }),
+            position: position.to_owned(),
+            info: void,
+        })
+    }
+
     fn check_indexing(
         &self,
         lhs: &Expression<TypeInfo>,
@@ -1042,13 +2327,23 @@ impl Typechecker {
         }: &Indexing<()>,
         scope: &mut TypeScope,
     ) -> TResult<Indexing<TypeInfo>> {
-        let Expression::Integer(index) = self.check_expression(None, index, scope)? else {
-            unimplemented!("Indexing with a non-numeric index is currently not supported")
-        };
+        let index = self.check_value(None, index, scope)?;
+
+        if index.info()._type != VariableType::Int {
+            return Err(TypeError::new(
+                format!(
+                    "Index must be of type 'int' but got '{}'",
+                    index.info()._type
+                ),
+                index.position(),
+            ));
+        }
+
+        let index = Box::new(index);
 
         match lhs.info()._type {
             VariableType::ArraySlice(item_type) => Ok(Indexing {
-                index: Box::new(Expression::Integer(index)),
+                index,
                 position: position.to_owned(),
                 info: TypeInfo {
                     _type: *item_type.clone(),
@@ -1056,22 +2351,597 @@ impl Typechecker {
                 },
             }),
             VariableType::TupleArray { item_type, .. } => Ok(Indexing {
-                index: Box::new(Expression::Integer(index)),
+                index,
                 position: position.to_owned(),
                 info: TypeInfo {
                     _type: *item_type.clone(),
                     source: item_type.get_source(),
                 },
             }),
+            // strings are indexed byte-wise (i.e., they are treated as ASCII)
             VariableType::Str => Ok(Indexing {
-                index: Box::new(Expression::Integer(index)),
+                index,
                 position: position.to_owned(),
                 info: TypeInfo {
                     _type: VariableType::Char,
                     source: lhs.info()._type.get_source(),
                 },
             }),
-            _ => unimplemented!("Indexing on non-array types is currently not supported"),
+            ref _type => Err(TypeError::new(
+                format!("Can not index value of type '{_type}'"),
+                lhs.position(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::{Ast, Intrinsic, Statement, YParser},
+        loader::ModuleRefs,
+    };
+
+    use super::{TypeErrors, Typechecker, VariableType};
+
+    #[test]
+    fn test_error_position_of_inner_binary_expression() {
+        let src = "let a := 1\na < (2 == \"x\")";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap_err();
+
+        assert_eq!(error.position, ("test.why".to_owned(), 2, 6));
+    }
+
+    /// Type check the given (erroneous) program and check that no loop frame is left behind,
+    /// which would allow a later `break` outside of a loop.
+    fn assert_no_stale_loop_frames(src: &str) {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let typechecker = Typechecker::from_ast(&ast, ModuleRefs::default());
+        assert!(typechecker.check_all().is_err());
+        assert!(typechecker.loops.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_no_stale_loop_frame_after_error_in_loop() {
+        assert_no_stale_loop_frames("loop {\n    missing\n}");
+    }
+
+    #[test]
+    fn test_no_stale_loop_frame_after_error_in_while_loop() {
+        assert_no_stale_loop_frames("while true {\n    missing\n}");
+    }
+
+    #[test]
+    fn test_note_on_trailing_definition_in_function() {
+        let src = "let foo := (): int => {\n    let result := 42\n}";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Expected return type of 'int' but got 'void' (test.why:1:12)\n\
+             \tnote: definitions evaluate to 'void', add 'result' as the last expression to return its value (test.why:2:5)"
+        );
+    }
+
+    #[test]
+    fn test_no_note_on_mismatching_trailing_definition() {
+        let src = "let foo := (): int => {\n    let result := \"foo\"\n}";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap_err();
+
+        assert!(error.notes.is_empty());
+    }
+
+    #[test]
+    fn test_void_call_used_as_value() {
+        let positions = [
+            ("let x := nothing()", (2, 10)),
+            ("let x := 1 + nothing()", (2, 14)),
+            ("let id := (x: int): int => { x }\nid(nothing())", (3, 4)),
+            ("let a := [nothing(); 3]", (2, 11)),
+            ("let a := [1; 3]\na[nothing()]", (3, 3)),
+            ("let mut x := 1\nx = nothing()", (3, 5)),
+            ("if nothing() { 1 }", (2, 4)),
+        ];
+
+        for (src, (line, col)) in positions {
+            let src = format!("let nothing := (): void => {{ let a := 1 }}\n{src}");
+            let pairs = YParser::parse_program("test.why", &src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+
+            let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+                .check()
+                .unwrap_err();
+
+            assert_eq!(
+                error.message, "'nothing' returns 'void', so its result can not be used as a value",
+                "{src}"
+            );
+            assert_eq!(error.position, ("test.why".to_owned(), line, col), "{src}");
+            assert_eq!(
+                error.notes,
+                [(
+                    "'nothing' is defined to return 'void' here".to_owned(),
+                    ("test.why".to_owned(), 1, 18)
+                )],
+                "{src}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_void_call_of_function_without_annotation() {
+        let src = "let nothing := () => { let a := 1 }
+let x := nothing()";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap_err();
+
+        // without annotation, the note points at the definition
+        assert_eq!(
+            error.notes,
+            [(
+                "'nothing' is defined to return 'void' here".to_owned(),
+                ("test.why".to_owned(), 1, 5)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_void_block_used_as_value() {
+        let src = "let x := 1 + { let a := 1 }";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Expression of type 'void' can not be used as a value"
+        );
+    }
+
+    #[test]
+    fn test_assignment_in_condition() {
+        let conditions = [
+            ("if x = 2 { 1 }", (3, 6)),
+            ("let y := if x = 2 then 1 else 0", (3, 15)),
+            ("let y := if x=2 { 1 } else { 0 }", (3, 14)),
+            ("while x = 2 { x = 3 }", (3, 9)),
+            (
+                "let f := (): void => {\n    while x = 2 { x = 3 }\n}",
+                (4, 13),
+            ),
+        ];
+
+        for (src, (line, col)) in conditions {
+            let src = format!("let mut x := 1\n\n{src}\nlet z := 1 + \"a\"");
+            let pairs = YParser::parse_program("test.why", &src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+
+            let TypeErrors(errors) = Typechecker::from_ast(&ast, ModuleRefs::default())
+                .check_all()
+                .unwrap_err();
+
+            assert_eq!(errors.len(), 2, "{src}");
+            assert_eq!(
+                errors[0].message, "assignment in condition; did you mean `==`?",
+                "{src}"
+            );
+            assert_eq!(
+                errors[0].position,
+                ("test.why".to_owned(), line, col),
+                "{src}"
+            );
+            // the rest of the program is still checked
+            assert_ne!(errors[1].position.1, line, "{src}");
         }
     }
+
+    #[test]
+    fn test_comparison_in_condition() {
+        let src = "let x := 1\nwhile x == 2 { 1 }\nif x==1 { 2 }";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        assert!(Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_chained_comparison() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        let error = check("let x := 5\nlet inside := 0 < x < 10").unwrap_err();
+        assert_eq!(
+            error.message,
+            "Chained comparisons are not supported; compare '0 < x' and 'x < 10' separately"
+        );
+        assert_eq!(error.position, ("test.why".to_owned(), 2, 15));
+
+        let error = check("let x := 5\nlet same := x == 5 == 5").unwrap_err();
+        assert_eq!(
+            error.message,
+            "Chained comparisons are not supported; compare 'x == 5' and '5 == 5' separately"
+        );
+
+        // comparing the result of a comparison with a bool is fine
+        assert!(check("let a := 1\nlet b := 2\nlet c := true\nlet d := (a < b) == c").is_ok());
+        assert!(check("let a := 1\nlet b := 2\nlet d := a < b == true").is_ok());
+    }
+
+    #[test]
+    fn test_nested_function_capture() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        // assignments capture variables as well
+        let error = check(
+            "let outer := (): int => {\n    let mut count := 0\n    let inc := (): void => {\n        count = count + 1\n    }\n    count\n}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.message,
+            "Function 'inc' can not capture 'count' of its enclosing scope"
+        );
+        assert_eq!(error.position, ("test.why".to_owned(), 4, 9));
+
+        // anonymous functions are compiled to functions of their own, too
+        let error = check(
+            "let apply := (f: (int) -> int): int => { f(1) }\nlet outer := (): int => {\n    let x := 5\n    apply((y: int): int => { x + y })\n}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.message,
+            "Anonymous function can not capture 'x' of its enclosing scope"
+        );
+        assert_eq!(error.position, ("test.why".to_owned(), 4, 30));
+
+        // locals of a block at the top level live on the stack of the entry point
+        assert!(
+            check("{\n    let x := 1\n    let f := (): int => {\n        x\n    }\n}").is_err()
+        );
+
+        // other functions, top-level definitions and declarations can be referenced
+        assert!(check(
+            "let g := 1\nlet outer := (): int => {\n    declare ext : (int) -> int\n    let a := (x: int): int => {\n        x + g\n    }\n    let b := (x: int): int => {\n        ext(a(x))\n    }\n    b(1)\n}"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_invalid_assertions() {
+        let assertions = [
+            ("assert(1)", "'assert' expects a condition of type 'bool' but got 'int'"),
+            (
+                "assert(true, false)",
+                "Invalid amount of parameters! Expected 1 but got 2",
+            ),
+            (
+                "assert_eq(1)",
+                "Invalid amount of parameters! Expected 2 but got 1",
+            ),
+            ("assert_eq(1, 'a')", "Can not compare values of type 'int' and 'char'"),
+            (
+                "assert_eq(\"a\", \"a\")",
+                "'assert_eq' can only compare values of type 'int', 'char' or 'bool', but got 'str'",
+            ),
+        ];
+
+        for (src, message) in assertions {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+
+            let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+                .check()
+                .unwrap_err();
+
+            assert_eq!(error.message, message, "{src}");
+        }
+    }
+
+    #[test]
+    fn test_shadowed_assertion() {
+        let src = "let assert := (x: int): int => { x }\nlet y := assert(1)";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        assert!(Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_inferred_return_types() {
+        let functions = [
+            ("(x: int) => { x * 2 }", VariableType::Int),
+            ("() => { 'a' }", VariableType::Char),
+            ("() => { let a := 1 }", VariableType::Void),
+            (
+                "(x: int) => { if x < 1 then \"a\" else \"b\" }",
+                VariableType::Str,
+            ),
+        ];
+
+        for (function, return_type) in functions {
+            let src = format!("let f := {function}\nf");
+            let pairs = YParser::parse_program("test.why", &src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+
+            let ast = Typechecker::from_ast(&ast, ModuleRefs::default())
+                .check()
+                .unwrap();
+
+            let Some(VariableType::Func {
+                return_type: inferred,
+                ..
+            }) = ast.nodes().last().map(|node| node.info()._type)
+            else {
+                panic!("Expected function type for '{function}'");
+            };
+            assert_eq!(*inferred, return_type, "{function}");
+        }
+    }
+
+    #[test]
+    fn test_recursion_without_return_type() {
+        let positions = [
+            ("let f := (x: int) => { f(x) }", (1, 24)),
+            ("let f := () => { let g := f\n1 }", (1, 27)),
+            (
+                "let f := (x: int) => {\n    let g := (y: int): int => { f(y) }\n    1\n}",
+                (2, 33),
+            ),
+        ];
+
+        for (src, (line, col)) in positions {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+
+            let error = Typechecker::from_ast(&ast, ModuleRefs::default())
+                .check()
+                .unwrap_err();
+
+            assert_eq!(
+                error.message,
+                "Function 'f' is recursive and therefore needs a return type annotation",
+                "{src}"
+            );
+            assert_eq!(error.position, ("test.why".to_owned(), line, col), "{src}");
+        }
+    }
+
+    #[test]
+    fn test_shadowed_function_without_return_type() {
+        let src = "let f := (x: int) => {\n    let f := 2\n    f + x\n}\nf(1)";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        assert!(Typechecker::from_ast(&ast, ModuleRefs::default())
+            .check()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_functions_without_return_type_are_not_exported() {
+        let src = "let f := (x: int): int => { x }\nlet g := (x: int) => { x }\nlet h := (x): int => { x }";
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+
+        let exports = Typechecker::extract_exports(&ast).unwrap();
+
+        assert!(exports.find("f").is_some());
+        assert!(exports.find("g").is_none());
+        assert_eq!(
+            exports.unexported().get("g"),
+            Some(&(
+                "add a return type annotation to export 'g'".to_owned(),
+                ("test.why".to_owned(), 2, 5)
+            ))
+        );
+        assert!(exports.find("h").is_none());
+        assert_eq!(
+            exports.unexported().get("h"),
+            Some(&(
+                "add a type annotation to parameter 'x' to export 'h'".to_owned(),
+                ("test.why".to_owned(), 3, 11)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_main_function_is_reserved() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        let error = check("let x := 3\nlet main := (): int => { x }").unwrap_err();
+        assert_eq!(error.position, ("test.why".to_owned(), 2, 1));
+
+        // functions within blocks at the top level are emitted with their own name, too
+        assert!(check("{\n    let main := (): int => { 1 }\n}").is_err());
+
+        // functions within other functions are prefixed with the name of the enclosing function
+        assert!(
+            check("let f := (): int => {\n    let main := (): int => { 1 }\n    main()\n}").is_ok()
+        );
+
+        // functions of imported modules are prefixed with the name of the module
+        let pairs = YParser::parse_program("test.why", "let main := (): int => { 1 }").unwrap();
+        let ast = Ast::from_program(pairs.collect(), "test.why");
+        assert!(Typechecker::from_ast(&ast, ModuleRefs::default())
+            .as_imported()
+            .check()
+            .is_ok());
+
+        // other values may be named `main`
+        assert!(check("let main := 3\nmain + 1").is_ok());
+    }
+
+    #[test]
+    fn test_forward_declared_function() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        assert!(check("declare f: (int) -> int\nf(1)\nlet f := (x: int): int => { x }").is_ok());
+
+        let error =
+            check("declare f: (int) -> int\nlet f := (x: int): bool => { x > 1 }").unwrap_err();
+        assert_eq!(error.position, ("test.why".to_owned(), 2, 1));
+        assert_eq!(error.notes[0].1, ("test.why".to_owned(), 1, 1));
+
+        // the declaration is merged with a single definition only
+        let src = "declare f: (int) -> int\nlet f := (x: int): int => { x }\nlet f := (x: int): int => { x }";
+        assert!(check(src).is_err());
+
+        // declared data can not be defined
+        assert!(check("declare x: int\nlet x := 1").is_err());
+    }
+
+    #[test]
+    fn test_constant_array_sizes() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        assert!(check("let N := 2\nlet a := [0; N * 3]\ndeclare b: [int; N + 4]\nb = a").is_ok());
+
+        // local variables shadow constants
+        let error =
+            check("let N := 2\nlet f := (N: int): void => {\n    let a := [0; N]\n}").unwrap_err();
+        assert_eq!(error.message, "'N' is not a constant");
+        assert_eq!(error.position, ("test.why".to_owned(), 3, 18));
+
+        // constants have to be defined before they are used
+        assert!(check("let a := [0; N]\nlet N := 2").is_err());
+
+        // assigned definitions are no constants
+        assert!(check("let N := 2\nN = 3\nlet a := [0; N]").is_err());
+
+        assert!(check("let N := 2 > 1\nlet a := [0; N]").is_err());
+        assert!(check("let N := 1 - 2\nlet a := [0; N]").is_err());
+    }
+
+    #[test]
+    fn test_labeled_blocks() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        let ast =
+            check("let a := 'b: {\n    if true {\n        break 'b 1\n    }\n    2\n}").unwrap();
+        let Some(Statement::Intrinsic(Intrinsic::Definition(definition))) = ast.nodes().first()
+        else {
+            panic!("expected definition");
+        };
+        assert_eq!(definition.value.info()._type, VariableType::Int);
+
+        // a block ending in a break gets its type from its breaks
+        assert!(check("let a := 'b: { break 'b 'c' }\ndeclare c: char\nc = a").is_ok());
+
+        // unlabeled breaks target the innermost loop, even within a labeled block
+        assert!(check("'b: { while true { 'c: { break } } }").is_ok());
+        let error = check("'b: { break }").unwrap_err();
+        assert_eq!(error.message, "'break' outside of a loop");
+
+        // labels are not visible within nested functions
+        let error = check("'b: { let f := (): void => { break 'b } }").unwrap_err();
+        assert_eq!(error.message, "Use of undeclared label 'b'");
+        assert_eq!(error.position, ("test.why".to_owned(), 1, 30));
+    }
+
+    #[test]
+    fn test_parameter_inference() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        // annotated functions need no context
+        assert!(check("let f := (x: int) => { x + 1 }\nf(1)").is_ok());
+
+        let apply = "let apply := (f: (int, str) -> int): int => { f(1, \"a\") }\n";
+        assert!(check(&format!("{apply}apply((x, s: str) => {{ x }})")).is_ok());
+        assert!(check(&format!(
+            "{apply}declare g: (int, str) -> int\ng = (x, s) => {{ x }}"
+        ))
+        .is_ok());
+
+        let error = check(&format!("{apply}apply((x: str, s) => {{ 1 }})")).unwrap_err();
+        assert_eq!(error.position, ("test.why".to_owned(), 2, 9));
+        assert_eq!(error.notes[0].1, ("test.why".to_owned(), 2, 1));
+
+        let error = check("let f := (x) => { x }").unwrap_err();
+        assert_eq!(error.position, ("test.why".to_owned(), 1, 11));
+
+        // the context only gives types to functions with the same amount of parameters
+        assert!(check(&format!("{apply}apply((x) => {{ x }})")).is_err());
+    }
+
+    #[test]
+    fn test_recursion_with_declaration() {
+        let check = |src: &str| {
+            let pairs = YParser::parse_program("test.why", src).unwrap();
+            let ast = Ast::from_program(pairs.collect(), "test.why");
+            Typechecker::from_ast(&ast, ModuleRefs::default()).check()
+        };
+
+        // the declaration gives the return type of the recursive function
+        assert!(check("declare f: (int) -> int\nlet f := (x) => { f(x) }").is_ok());
+        assert!(check("declare f: (int) -> int\nlet f := (x: int) => { f(x) }").is_ok());
+        assert!(check(
+            "declare f: (int) -> bool\ndeclare g: (int) -> bool\nlet f := (x) => { g(x) }\nlet g := (x) => { f(x) }"
+        )
+        .is_ok());
+
+        let error = check("declare f: (int) -> int\nlet f := (x) => { f(x) == 1 }").unwrap_err();
+        assert_eq!(
+            error.message,
+            "Expected return type of 'int' but got 'bool'"
+        );
+        assert_eq!(error.notes[0].1, ("test.why".to_owned(), 1, 1));
+
+        // without declaration, a note points to the definition of the recursive function
+        let error = check("let f := (x: int) => { f(x) }").unwrap_err();
+        assert_eq!(error.notes[0].1, ("test.why".to_owned(), 1, 5));
+    }
 }
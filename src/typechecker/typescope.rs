@@ -1,4 +1,6 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::ast::Position;
 
 use super::{error::TypeError, variabletype::VariableType};
 
@@ -6,15 +8,26 @@ use super::{error::TypeError, variabletype::VariableType};
 pub struct Variable {
     pub variable_type: VariableType,
     pub is_mutable: bool,
+    /// The position of the declaration of a function, which has not been defined (yet).
+    pub declared_at: Option<Position>,
+    /// Whether this is a named function, which (in contrast to other variables) does not live on
+    /// the stack of the function it is defined in.
+    pub is_function: bool,
+    /// The position of the return type annotation of a function (or of its definition, if its
+    /// return type is inferred), which errors about the result of a call refer to.
+    pub return_type_at: Option<Position>,
 }
 
-type ScopeFrame = HashMap<String, Variable>;
+type ScopeFrame = BTreeMap<String, Variable>;
 
 type ScopeFrameReference = Rc<RefCell<ScopeFrame>>;
 
 #[derive(Default, Debug, Clone)]
 pub struct TypeScope {
     scope_stack: Vec<ScopeFrameReference>,
+    /// Functions, which can not be exported (since their type is not fully annotated), along with
+    /// a note on how to export them and the position the note refers to.
+    unexported: BTreeMap<String, (String, Position)>,
 }
 
 impl PartialEq for TypeScope {
@@ -28,15 +41,12 @@ impl Eq for TypeScope {}
 impl TypeScope {
     /// Find a value/reference in this scope by iterating over the scopes from back to front.
     pub fn find(&self, name: &str) -> Option<VariableType> {
-        let mut scopes = self.scope_stack.clone();
-        scopes.reverse();
-        for scope in scopes {
-            if let Some(variable) = scope.borrow().get(name) {
-                return Some(variable.variable_type.clone());
-            }
-        }
-
-        None
+        self.scope_stack.iter().rev().find_map(|scope| {
+            scope
+                .borrow()
+                .get(name)
+                .map(|variable| variable.variable_type.clone())
+        })
     }
 
     pub fn is_mutable(&self, name: &str) -> bool {
@@ -53,15 +63,39 @@ impl TypeScope {
 
     /// Check, if a variable with a given name is present.
     pub fn contains(&self, name: &str) -> bool {
-        let mut scopes = self.scope_stack.clone();
-        scopes.reverse();
-        for scope in &scopes {
-            if scope.borrow().contains_key(name) {
-                return true;
-            }
-        }
+        self.scope_stack
+            .iter()
+            .rev()
+            .any(|scope| scope.borrow().contains_key(name))
+    }
 
-        false
+    /// Check, if a variable refers to a definition of the outermost (i.e., top-level) scope.
+    pub fn is_global(&self, name: &str) -> bool {
+        self.scope_stack
+            .iter()
+            .rposition(|scope| scope.borrow().contains_key(name))
+            == Some(0)
+    }
+
+    /// Check, if a variable refers to a local variable (i.e., neither a function nor a declaration)
+    /// of one of the frames below the given depth, except for the top-level scope.
+    pub fn is_local_below(&self, name: &str, depth: usize) -> bool {
+        let Some(index) = self
+            .scope_stack
+            .iter()
+            .rposition(|scope| scope.borrow().contains_key(name))
+        else {
+            return false;
+        };
+
+        let scope = self.scope_stack[index].borrow();
+        let variable = &scope[name];
+        index > 0 && index < depth && !variable.is_function && variable.declared_at.is_none()
+    }
+
+    /// The number of frames of this scope.
+    pub fn depth(&self) -> usize {
+        self.scope_stack.len()
     }
 
     /// Check, if a variable is present in the current scope.
@@ -74,7 +108,8 @@ impl TypeScope {
 
     /// Push a new scope frame.
     pub fn push(&mut self) {
-        self.scope_stack.push(Rc::new(RefCell::new(HashMap::new())))
+        self.scope_stack
+            .push(Rc::new(RefCell::new(BTreeMap::new())))
     }
 
     /// Pop the last scope frame.
@@ -88,11 +123,82 @@ impl TypeScope {
             let variable = Variable {
                 variable_type: value,
                 is_mutable,
+                declared_at: None,
+                is_function: false,
+                return_type_at: None,
+            };
+            scope.borrow_mut().insert(name.to_owned(), variable);
+        }
+    }
+
+    /// Define a named function on the current scope.
+    pub fn set_function(&mut self, name: &str, value: VariableType) {
+        if let Some(scope) = self.scope_stack.last_mut() {
+            let variable = Variable {
+                variable_type: value,
+                is_mutable: false,
+                declared_at: None,
+                is_function: true,
+                return_type_at: None,
             };
             scope.borrow_mut().insert(name.to_owned(), variable);
         }
     }
 
+    /// Declare a function on the current scope, which may be defined later on in the same scope.
+    pub fn declare(&mut self, name: &str, value: VariableType, position: &Position) {
+        if let Some(scope) = self.scope_stack.last_mut() {
+            let variable = Variable {
+                variable_type: value,
+                is_mutable: false,
+                declared_at: Some(position.to_owned()),
+                is_function: false,
+                return_type_at: None,
+            };
+            scope.borrow_mut().insert(name.to_owned(), variable);
+        }
+    }
+
+    /// Get the type and the position of the declaration of a function in the current scope, if
+    /// it has not been defined so far.
+    pub fn forward_declaration(&self, name: &str) -> Option<(VariableType, Position)> {
+        let last = self.scope_stack.last()?;
+        let last = last.borrow();
+        let variable = last.get(name)?;
+
+        variable
+            .declared_at
+            .clone()
+            .map(|position| (variable.variable_type.clone(), position))
+    }
+
+    /// Remember the position of the return type annotation (or of the definition) of a function
+    /// in the current scope.
+    pub fn set_return_type_at(&mut self, name: &str, position: &Position) {
+        if let Some(scope) = self.scope_stack.last() {
+            if let Some(variable) = scope.borrow_mut().get_mut(name) {
+                variable.return_type_at = Some(position.to_owned());
+            }
+        }
+    }
+
+    /// Get the position of the return type annotation of a function, falling back to the position
+    /// of its declaration.
+    pub fn return_type_at(&self, name: &str) -> Option<Position> {
+        let scope = self
+            .scope_stack
+            .iter()
+            .rev()
+            .find(|scope| scope.borrow().contains_key(name))?;
+        let scope = scope.borrow();
+        let variable = &scope[name];
+
+        variable
+            .return_type_at
+            .clone()
+            .or_else(|| variable.declared_at.clone())
+    }
+
     /// Update a value of an already present variable.
     pub fn update(
         &mut self,
@@ -100,37 +206,44 @@ impl TypeScope {
         value: VariableType,
         position: &(String, usize, usize),
     ) -> Result<(), TypeError> {
-        let mut scopes = self.scope_stack.clone();
-        scopes.reverse();
-
-        for scope in &mut scopes {
+        for scope in self.scope_stack.iter().rev() {
             let mut scope = scope.borrow_mut();
-            if let Some(old_variable) = scope.get(name) {
+            if let Some(old_variable) = scope.get_mut(name) {
                 let old_type = &old_variable.variable_type;
-                if old_type.convert_to(&value).is_err() {
-                    return Err(TypeError {
-                        message: format!(
+                if !value.coerces_to(old_type) {
+                    return Err(TypeError::new(
+                        format!(
                             "Could not assign variable '{name}' with type '{old_type}' a value of type '{value}'"
                         ),
-                        position: position.to_owned(),
-                    });
+                        position.to_owned(),
+                    ));
+                }
+                // the variable keeps its type (e.g., a slice stays a slice, even if an array is
+                // assigned to it), unless it was unknown so far
+                if *old_type == VariableType::Unknown {
+                    old_variable.variable_type = value;
                 }
-                let mut new_variable = old_variable.clone();
-                new_variable.variable_type = value;
-                scope.insert(name.to_owned(), new_variable);
 
                 break;
             }
         }
 
-        scopes.reverse();
-        self.scope_stack = scopes;
-
         Ok(())
     }
 
-    pub fn flatten(&self) -> HashMap<String, Variable> {
-        let mut entries = HashMap::default();
+    /// Remember a function, which can not be exported, along with a note on how to export it.
+    pub fn set_unexported(&mut self, name: &str, note: String, position: &Position) {
+        self.unexported
+            .insert(name.to_owned(), (note, position.to_owned()));
+    }
+
+    /// Get the functions, which can not be exported, along with a note on how to export them.
+    pub fn unexported(&self) -> &BTreeMap<String, (String, Position)> {
+        &self.unexported
+    }
+
+    pub fn flatten(&self) -> BTreeMap<String, Variable> {
+        let mut entries = BTreeMap::default();
 
         for scope in &self.scope_stack {
             let scope = scope.borrow();
@@ -151,3 +264,73 @@ pub fn setup_scope() -> TypeScope {
 
     scope
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::typechecker::variabletype::VariableType;
+
+    use super::setup_scope;
+
+    fn position() -> (String, usize, usize) {
+        ("test.why".to_owned(), 1, 1)
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let mut scope = setup_scope();
+        scope.set("x", VariableType::Int, false);
+
+        scope.push();
+        scope.set("x", VariableType::Bool, true);
+        assert_eq!(scope.find("x"), Some(VariableType::Bool));
+        assert!(scope.is_mutable("x"));
+        assert!(!scope.is_global("x"));
+
+        scope.pop();
+        assert_eq!(scope.find("x"), Some(VariableType::Int));
+        assert!(scope.is_global("x"));
+        assert!(scope.contains("x"));
+        assert!(!scope.contains("y"));
+    }
+
+    #[test]
+    fn test_update_innermost_variable() {
+        let mut scope = setup_scope();
+        scope.set("x", VariableType::Int, true);
+
+        // an update refers to the innermost variable with the given name
+        scope.push();
+        scope.set("x", VariableType::Bool, true);
+        assert!(scope.update("x", VariableType::Bool, &position()).is_ok());
+        assert!(scope.update("x", VariableType::Int, &position()).is_err());
+
+        scope.pop();
+        assert!(scope.update("x", VariableType::Int, &position()).is_ok());
+        assert!(scope.update("x", VariableType::Bool, &position()).is_err());
+        assert_eq!(scope.find("x"), Some(VariableType::Int));
+
+        // updating an unknown variable is a no-op
+        assert!(scope.update("y", VariableType::Int, &position()).is_ok());
+        assert!(!scope.contains("y"));
+    }
+
+    #[test]
+    fn test_locals_below_depth() {
+        let mut scope = setup_scope();
+        scope.set("global", VariableType::Int, false);
+
+        scope.push();
+        scope.set("local", VariableType::Int, false);
+        scope.set_function("function", VariableType::Void);
+        let depth = scope.depth();
+
+        scope.push();
+        scope.set("inner", VariableType::Int, false);
+
+        assert!(scope.is_local_below("local", depth));
+        assert!(!scope.is_local_below("global", depth));
+        assert!(!scope.is_local_below("function", depth));
+        assert!(!scope.is_local_below("inner", depth));
+        assert!(!scope.is_local_below("unknown", depth));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, rc::Rc, str::FromStr};
 
 use crate::loader::Module;
 
@@ -16,7 +16,8 @@ pub enum VariableType {
     Func {
         params: Vec<VariableType>,
         return_type: Box<VariableType>,
-        source: Option<Module<()>>,
+        /// The module an imported function is defined in (shared by all functions of the module).
+        source: Option<Rc<Module<()>>>,
     },
     ArraySlice(Box<VariableType>),
     TupleArray {
@@ -26,7 +27,7 @@ pub enum VariableType {
     Reference(Box<VariableType>),
 }
 
-pub struct VariableParseError(String);
+pub struct VariableParseError(pub String);
 
 impl FromStr for VariableType {
     type Err = VariableParseError;
@@ -91,7 +92,7 @@ impl VariableType {
         }
     }
 
-    pub fn set_source(self, source: Module<()>) -> Self {
+    pub fn set_source(self, source: Rc<Module<()>>) -> Self {
         match self {
             VariableType::Func {
                 params,
@@ -106,30 +107,55 @@ impl VariableType {
         }
     }
 
-    pub fn get_source(&self) -> Option<Module<()>> {
+    pub fn get_source(&self) -> Option<Rc<Module<()>>> {
         match self {
             VariableType::Func { source, .. } => source.clone(),
             _ => None,
         }
     }
 
+    /// Convert the type of the items of an array or slice to the items of a slice. The items are
+    /// accessed in place, so they have to be of the same type (e.g., `char` items are smaller than
+    /// `int` items), unless the target is a slice of `any`. Hence, arrays within arrays can not
+    /// decay to slices (or strings), either.
+    fn convert_item_to(&self, to_convert_to: &Self) -> Result<Self, VariableConversionError> {
+        use VariableType::*;
+        match (self, to_convert_to) {
+            (Unknown, other) => Ok(other.clone()),
+            (_, Any) => Ok(Any),
+            (left, right) if left.is_same(right) => Ok(left.clone()),
+            _ => Err(VariableConversionError),
+        }
+    }
+
     /// Try to convert this variable type to another. If the conversion is successful, it returns
     /// the new variable type. If it is not successful, it returns Err(VariableConversionError).
+    /// This is the single place defining which values may be used where a value of another type
+    /// is expected (e.g., for call arguments, assignments or return values).
     ///
     /// Note the rules:
     ///     - `unknown` can be converted to anything
     ///     - nothing can be converted to `unknown` (except `unknown` itself)
     ///     - everything can be converted to `any`
     ///     - `any` can not be converted to anything else
-    ///     - every basic type can be converted to itself
+    ///     - `char` and `int` can be converted into each other
+    ///     - arrays decay to slices of the same items (or `any`), and arrays of `char` to `str`
+    ///     - slices convert to slices of the same items (or `any`), and `str` to `&[char]`
+    ///     - references convert like the type they reference (in both directions)
+    ///     - functions are invariant, i.e., their parameters and return types have to be the same
+    ///       (regardless of the module they are defined in)
+    ///     - every other type can only be converted to itself
     pub fn convert_to(&self, to_convert_to: &Self) -> Result<Self, VariableConversionError> {
         use VariableType::*;
         match (self, to_convert_to) {
             (Unknown, other) => Ok(other.clone()),
             (_, Any) => Ok(Any),
-            (TupleArray { item_type, .. }, ArraySlice(other_item_type)) => {
-                Ok(ArraySlice(Box::new(item_type.convert_to(other_item_type)?)))
-            }
+            (TupleArray { item_type, .. }, ArraySlice(other_item_type)) => Ok(ArraySlice(
+                Box::new(item_type.convert_item_to(other_item_type)?),
+            )),
+            (ArraySlice(item_type), ArraySlice(other_item_type)) => Ok(ArraySlice(Box::new(
+                item_type.convert_item_to(other_item_type)?,
+            ))),
             (Str, ArraySlice(other_item_type)) => {
                 if *other_item_type == Box::new(Char) {
                     Ok(ArraySlice(Box::new(Char)))
@@ -150,6 +176,13 @@ impl VariableType {
             (left, Reference(inner)) => left.convert_to(inner),
             // TODO: Allow conversion of same-sized strings to tuple arrays
             // (Str, TupleArray { size, .. }) => todo!(),
+            (left @ Func { .. }, right @ Func { .. }) => {
+                if left.is_same(right) {
+                    Ok(left.clone())
+                } else {
+                    Err(VariableConversionError)
+                }
+            }
             (left, right) => {
                 if left == right {
                     Ok(right.clone())
@@ -159,11 +192,72 @@ impl VariableType {
             }
         }
     }
+
+    /// Check, whether a value of this type can be used where a value of the target type is
+    /// expected (see `convert_to` for the rules).
+    pub fn coerces_to(&self, target: &Self) -> bool {
+        self.convert_to(target).is_ok()
+    }
+
+    /// Join the types of two values which may both be the result of the same expression (e.g., the
+    /// branches of an if-else or the breaks of a loop). Both have to be of the same type, except
+    /// for `unknown`, which joins with every type. There are no implicit conversions (e.g., of
+    /// arrays to slices), since both values have to be stored the same way.
+    pub fn unify(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (VariableType::Unknown, other) => Some(other.clone()),
+            (left, VariableType::Unknown) => Some(left.clone()),
+            (left, right) if left.is_same(right) => Some(left.clone()),
+            _ => None,
+        }
+    }
+
+    /// Structural equality of types, which ignores the module a function is defined in.
+    pub fn is_same(&self, other: &Self) -> bool {
+        use VariableType::*;
+        match (self, other) {
+            (
+                Func {
+                    params,
+                    return_type,
+                    ..
+                },
+                Func {
+                    params: other_params,
+                    return_type: other_return_type,
+                    ..
+                },
+            ) => {
+                params.len() == other_params.len()
+                    && params
+                        .iter()
+                        .zip(other_params)
+                        .all(|(param, other_param)| param.is_same(other_param))
+                    && return_type.is_same(other_return_type)
+            }
+            (ArraySlice(item_type), ArraySlice(other_item_type))
+            | (Reference(item_type), Reference(other_item_type)) => {
+                item_type.is_same(other_item_type)
+            }
+            (
+                TupleArray { item_type, size },
+                TupleArray {
+                    item_type: other_item_type,
+                    size: other_size,
+                },
+            ) => size == other_size && item_type.is_same(other_item_type),
+            (left, right) => left == right,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{VariableConversionError, VariableType::*};
+    use std::rc::Rc;
+
+    use crate::loader::Module;
+
+    use super::{VariableConversionError, VariableType, VariableType::*};
 
     #[test]
     fn test_convert_to_any() {
@@ -186,10 +280,156 @@ mod tests {
         assert_eq!(Unknown.convert_to(&Unknown), Ok(Unknown));
     }
 
+    #[test]
+    fn test_convert_slices() {
+        assert_eq!(
+            ArraySlice(Box::new(Int)).convert_to(&ArraySlice(Box::new(Any))),
+            Ok(ArraySlice(Box::new(Any)))
+        );
+        assert_eq!(
+            TupleArray {
+                item_type: Box::new(Int),
+                size: 3
+            }
+            .convert_to(&ArraySlice(Box::new(Int))),
+            Ok(ArraySlice(Box::new(Int)))
+        );
+        assert_eq!(
+            ArraySlice(Box::new(Str)).convert_to(&ArraySlice(Box::new(Int))),
+            Err(VariableConversionError)
+        );
+        // the items are accessed in place, so they are not converted
+        assert_eq!(
+            ArraySlice(Box::new(Char)).convert_to(&ArraySlice(Box::new(Int))),
+            Err(VariableConversionError)
+        );
+        assert_eq!(
+            TupleArray {
+                item_type: Box::new(Char),
+                size: 3
+            }
+            .convert_to(&ArraySlice(Box::new(Int))),
+            Err(VariableConversionError)
+        );
+    }
+
     #[test]
     fn test_conver_to_unknown() {
         assert_eq!(Int.convert_to(&Unknown), Err(VariableConversionError));
         assert_eq!(Any.convert_to(&Unknown), Err(VariableConversionError));
         assert_eq!(Void.convert_to(&Unknown), Err(VariableConversionError));
     }
+
+    #[test]
+    fn test_convert_nested_arrays() {
+        let row = TupleArray {
+            item_type: Box::new(Int),
+            size: 3,
+        };
+        let matrix = TupleArray {
+            item_type: Box::new(row.clone()),
+            size: 2,
+        };
+
+        assert_eq!(
+            matrix.convert_to(&ArraySlice(Box::new(row.clone()))),
+            Ok(ArraySlice(Box::new(row.clone())))
+        );
+        // the rows are stored inline, so they can not decay to slices
+        assert_eq!(
+            matrix.convert_to(&ArraySlice(Box::new(ArraySlice(Box::new(Int))))),
+            Err(VariableConversionError)
+        );
+        assert_eq!(
+            ArraySlice(Box::new(row)).convert_to(&ArraySlice(Box::new(ArraySlice(Box::new(Int))))),
+            Err(VariableConversionError)
+        );
+    }
+
+    fn slice(item_type: VariableType) -> VariableType {
+        ArraySlice(Box::new(item_type))
+    }
+
+    fn array(item_type: VariableType, size: usize) -> VariableType {
+        TupleArray {
+            item_type: Box::new(item_type),
+            size,
+        }
+    }
+
+    fn func(params: Vec<VariableType>, return_type: VariableType) -> VariableType {
+        Func {
+            params,
+            return_type: Box::new(return_type),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_coercion_matrix() {
+        let imported_print = func(vec![Str], Void).set_source(Rc::new(Module {
+            name: "std_io".to_owned(),
+            ..Module::default()
+        }));
+
+        // (from, to, coerces)
+        let matrix = [
+            (Int, Int, true),
+            (Int, Char, true),
+            (Char, Int, true),
+            (Int, Bool, false),
+            (Bool, Int, false),
+            (Str, Int, false),
+            (Void, Int, false),
+            (Int, Any, true),
+            (Any, Int, false),
+            (Unknown, Str, true),
+            (Str, Unknown, false),
+            (Str, slice(Char), true),
+            (slice(Char), Str, false),
+            (array(Char, 4), Str, true),
+            (array(Int, 4), Str, false),
+            (array(Int, 3), slice(Int), true),
+            (array(Int, 3), array(Int, 3), true),
+            (array(Int, 3), array(Int, 4), false),
+            (slice(Int), array(Int, 3), false),
+            (slice(Int), slice(Any), true),
+            (slice(Str), slice(Int), false),
+            (array(array(Int, 2), 2), slice(array(Int, 2)), true),
+            (array(array(Int, 2), 2), slice(slice(Int)), false),
+            (Reference(Box::new(Int)), Int, true),
+            (Int, Reference(Box::new(Int)), true),
+            (Reference(Box::new(Str)), Reference(Box::new(Int)), false),
+            (func(vec![Int], Int), func(vec![Int], Int), true),
+            (func(vec![Int], Int), func(vec![Int], Void), false),
+            (func(vec![Int], Int), func(vec![Char], Int), false),
+            (func(vec![Int], Int), func(vec![Int, Int], Int), false),
+            (imported_print.clone(), func(vec![Str], Void), true),
+            (func(vec![Str], Void), imported_print, true),
+        ];
+
+        for (from, to, coerces) in matrix {
+            assert_eq!(
+                from.coerces_to(&to),
+                coerces,
+                "'{from}' should {}coerce to '{to}'",
+                if coerces { "" } else { "not " }
+            );
+        }
+    }
+
+    #[test]
+    fn test_unify() {
+        assert_eq!(Int.unify(&Int), Some(Int));
+        assert_eq!(Unknown.unify(&Str), Some(Str));
+        assert_eq!(Str.unify(&Unknown), Some(Str));
+        // no implicit conversions are applied to the branches
+        assert_eq!(Int.unify(&Char), None);
+        assert_eq!(array(Int, 2).unify(&array(Int, 3)), None);
+        assert_eq!(array(Int, 2).unify(&slice(Int)), None);
+
+        let print = func(vec![Str], Void);
+        let imported_print = print.clone().set_source(Rc::default());
+        assert_eq!(imported_print.unify(&print), Some(imported_print));
+    }
 }
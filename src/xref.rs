@@ -0,0 +1,463 @@
+//! Cross-reference index of a module.
+//!
+//! This module lists all definitions of a single module (functions, variables, constants,
+//! parameters and declarations) together with all references to them, e.g., for editors which
+//! only need a ctags-like index. References to symbols of other modules (e.g., imported functions)
+//! are listed without a definition. The index can be written as JSON or in the ctags file format.
+//! It is also the base of the report of external symbols (see `crate::externals`).
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        Array, Assignment, Ast, BinaryExpr, Block, Break, Call, CompilerDirective, Declaration,
+        Definition, Expression, FnDef, Ident, If, Intrinsic, LabeledBlock, Loop, Position,
+        PostfixExpr, PostfixOp, PrefixExpr, Statement, Type, WhileLoop,
+    },
+    ast_json,
+};
+
+/// The current version of the JSON schema of the index.
+pub const VERSION: i64 = 2;
+
+/// The kind of a defined symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    /// Immutable top-level definition which is no function.
+    Constant,
+    Variable,
+    Parameter,
+    Declaration,
+}
+
+impl SymbolKind {
+    /// The (single letter) kind used in tags files.
+    fn tag_kind(self) -> char {
+        match self {
+            SymbolKind::Function => 'f',
+            SymbolKind::Constant => 'c',
+            SymbolKind::Variable => 'v',
+            SymbolKind::Parameter => 'p',
+            SymbolKind::Declaration => 'd',
+        }
+    }
+}
+
+impl Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Variable => "variable",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Declaration => "declaration",
+        })
+    }
+}
+
+/// A defined symbol. Its position is the position of its name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    #[serde(with = "ast_json::position")]
+    pub position: Position,
+    /// The declared type of a declaration.
+    #[serde(
+        rename = "type",
+        serialize_with = "ast_json::serialize_optional_display"
+    )]
+    pub type_annotation: Option<Type>,
+    /// Position of the definition of a declared function, which is defined later on in the same
+    /// scope (`None` for all other symbols, including declarations of external symbols).
+    #[serde(with = "ast_json::optional_position")]
+    pub definition: Option<Position>,
+}
+
+/// A use of a symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Reference {
+    pub name: String,
+    #[serde(with = "ast_json::position")]
+    pub position: Position,
+    /// Position of the definition the reference resolves to (`None` for symbols which are not
+    /// defined within the module).
+    #[serde(with = "ast_json::optional_position")]
+    pub definition: Option<Position>,
+    /// The (named) function containing the reference (`None` for top-level statements).
+    pub function: Option<String>,
+}
+
+/// Index of all definitions and references of a module, both ordered by their position.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Xref {
+    pub definitions: Vec<Symbol>,
+    pub references: Vec<Reference>,
+}
+
+impl Xref {
+    /// Collect all definitions and references of the given AST. Names are resolved like the type
+    /// checker does, i.e., a definition is visible after it within its block, and a function is
+    /// also visible within its own body.
+    pub fn collect<T: Clone>(ast: &Ast<T>) -> Xref {
+        let mut collector = Collector::default();
+        collector.scopes.push(HashMap::new());
+
+        for statement in ast.nodes() {
+            collector.statement(statement);
+        }
+
+        let Collector {
+            mut definitions,
+            mut references,
+            ..
+        } = collector;
+
+        definitions.sort_by(|lhs, rhs| lhs.position.cmp(&rhs.position));
+        references.sort_by(|lhs, rhs| lhs.position.cmp(&rhs.position));
+
+        Xref {
+            definitions,
+            references,
+        }
+    }
+
+    /// Serialize this index as (pretty printed) JSON.
+    pub fn to_json_string(&self) -> String {
+        #[derive(Serialize)]
+        struct Document<'a> {
+            version: i64,
+            definitions: &'a [Symbol],
+            references: &'a [Reference],
+        }
+
+        ast_json::to_json_string(&Document {
+            version: VERSION,
+            definitions: &self.definitions,
+            references: &self.references,
+        })
+    }
+
+    /// Serialize the definitions of this index in the ctags file format. Tags are sorted by their
+    /// name and address each definition by its line.
+    pub fn to_tags(&self) -> String {
+        let mut tags = self
+            .definitions
+            .iter()
+            .map(|symbol| {
+                let (file, line, column) = &symbol.position;
+                let tag = format!(
+                    "{}\t{file}\t{line};\"\t{}\tline:{line}\tcolumn:{column}",
+                    symbol.name,
+                    symbol.kind.tag_kind()
+                );
+                (&symbol.name, file, *line, tag)
+            })
+            .collect::<Vec<_>>();
+        tags.sort();
+
+        let mut content = "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+                           !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/\n"
+            .to_owned();
+
+        for (.., tag) in tags {
+            content.push_str(&tag);
+            content.push('\n');
+        }
+
+        content
+    }
+}
+
+/// Walks an AST and keeps track of the symbols visible at the current node.
+#[derive(Default)]
+struct Collector {
+    /// Visible symbols (by name) of all enclosing blocks, mapped to the position of their
+    /// definition.
+    scopes: Vec<HashMap<String, Position>>,
+    /// Names of the functions enclosing the current node (from the outermost to the innermost).
+    functions: Vec<String>,
+    definitions: Vec<Symbol>,
+    references: Vec<Reference>,
+}
+
+impl Collector {
+    fn define<T>(&mut self, ident: &Ident<T>, kind: SymbolKind, type_annotation: Option<Type>) {
+        let previous = self
+            .scopes
+            .last_mut()
+            .expect("there is always a scope")
+            .insert(ident.value.to_owned(), ident.position.clone());
+
+        // a function defined after its declaration within the same scope is no external symbol
+        if kind == SymbolKind::Function {
+            if let Some(declaration) = self.definitions.iter_mut().find(|symbol| {
+                symbol.kind == SymbolKind::Declaration
+                    && Some(&symbol.position) == previous.as_ref()
+            }) {
+                declaration.definition = Some(ident.position.clone());
+            }
+        }
+
+        self.definitions.push(Symbol {
+            name: ident.value.to_owned(),
+            kind,
+            position: ident.position.clone(),
+            type_annotation,
+            definition: None,
+        });
+    }
+
+    fn reference<T>(&mut self, ident: &Ident<T>) {
+        let definition = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.value))
+            .cloned();
+
+        self.references.push(Reference {
+            name: ident.value.to_owned(),
+            position: ident.position.clone(),
+            definition,
+            function: self.functions.last().cloned(),
+        });
+    }
+
+    fn block<T>(&mut self, Block { block, .. }: &Block<T>) {
+        self.scopes.push(HashMap::new());
+
+        for statement in block {
+            self.statement(statement);
+        }
+
+        self.scopes.pop();
+    }
+
+    fn statement<T>(&mut self, statement: &Statement<T>) {
+        match statement {
+            Statement::Expression(expression) => self.expression(expression),
+            Statement::Intrinsic(intrinsic) => self.intrinsic(intrinsic),
+            Statement::CompilerDirective(CompilerDirective {
+                statement: Some(statement),
+                ..
+            }) => self.statement(statement),
+            Statement::Import(_)
+            | Statement::CompilerDirective(_)
+            | Statement::InlineAssembly(_) => {}
+        }
+    }
+
+    fn intrinsic<T>(&mut self, intrinsic: &Intrinsic<T>) {
+        match intrinsic {
+            // functions are visible within their own body
+            Intrinsic::Definition(Definition {
+                ident,
+                value: value @ Expression::FnDef(_),
+                ..
+            }) => {
+                self.define(ident, SymbolKind::Function, None);
+
+                self.functions.push(ident.value.to_owned());
+                self.expression(value);
+                self.functions.pop();
+            }
+            Intrinsic::Definition(Definition {
+                ident,
+                value,
+                is_mutable,
+                ..
+            }) => {
+                self.expression(value);
+
+                let kind = if !is_mutable && self.scopes.len() == 1 {
+                    SymbolKind::Constant
+                } else {
+                    SymbolKind::Variable
+                };
+                self.define(ident, kind, None);
+            }
+            Intrinsic::Declaration(Declaration {
+                ident,
+                type_annotation,
+                ..
+            }) => self.define(
+                ident,
+                SymbolKind::Declaration,
+                Some(type_annotation.value.clone()),
+            ),
+            Intrinsic::Assignment(Assignment { lhs, value, .. }) => {
+                self.expression(lhs);
+                self.expression(value);
+            }
+            Intrinsic::WhileLoop(WhileLoop {
+                condition, block, ..
+            }) => {
+                self.expression(condition);
+                self.block(block);
+            }
+            Intrinsic::Break(Break { value, .. }) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+            }
+        }
+    }
+
+    fn expression<T>(&mut self, expression: &Expression<T>) {
+        match expression {
+            Expression::Ident(ident) => self.reference(ident),
+            Expression::If(If {
+                condition,
+                if_block,
+                else_block,
+                ..
+            }) => {
+                self.expression(condition);
+                self.block(if_block);
+                if let Some(else_block) = else_block {
+                    self.block(else_block);
+                }
+            }
+            Expression::Binary(BinaryExpr { lhs, rhs, .. }) => {
+                self.expression(lhs);
+                self.expression(rhs);
+            }
+            Expression::Prefix(PrefixExpr { rhs, .. }) => self.expression(rhs),
+            Expression::Postfix(PostfixExpr { lhs, op, .. }) => {
+                self.expression(lhs);
+                match op {
+                    PostfixOp::Call(Call { params, .. }) => {
+                        for param in params {
+                            self.expression(param);
+                        }
+                    }
+                    PostfixOp::Indexing(indexing) => self.expression(&indexing.index),
+                }
+            }
+            Expression::FnDef(FnDef { params, block, .. }) => {
+                self.scopes.push(HashMap::new());
+                for param in params {
+                    self.define(&param.ident, SymbolKind::Parameter, None);
+                }
+                self.block(block);
+                self.scopes.pop();
+            }
+            Expression::Block(block) => self.block(block),
+            Expression::Array(Array {
+                initializer, size, ..
+            }) => {
+                self.expression(initializer);
+                self.expression(size);
+            }
+            Expression::Loop(Loop { block, .. })
+            | Expression::LabeledBlock(LabeledBlock { block, .. }) => self.block(block),
+            Expression::Integer(_)
+            | Expression::Character(_)
+            | Expression::Str(_)
+            | Expression::Boolean(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Ast, YParser};
+
+    use super::{Reference, SymbolKind, Xref};
+
+    fn xref(src: &str) -> Xref {
+        let pairs = YParser::parse_program("test.why", src).unwrap();
+        Xref::collect(&Ast::from_program(pairs.collect(), "test.why"))
+    }
+
+    fn position(line: usize, column: usize) -> (String, usize, usize) {
+        ("test.why".to_owned(), line, column)
+    }
+
+    #[test]
+    fn test_definitions() {
+        let xref = xref(
+            "declare exit: (int) -> void\nlet limit := 3\nlet mut count := 0\nlet add := (a: int, b: int): int => {\n    let sum := a + b\n    sum\n}",
+        );
+
+        let definitions = xref
+            .definitions
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind, symbol.position.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            definitions,
+            [
+                ("exit", SymbolKind::Declaration, position(1, 9)),
+                ("limit", SymbolKind::Constant, position(2, 5)),
+                ("count", SymbolKind::Variable, position(3, 9)),
+                ("add", SymbolKind::Function, position(4, 5)),
+                ("a", SymbolKind::Parameter, position(4, 13)),
+                ("b", SymbolKind::Parameter, position(4, 21)),
+                ("sum", SymbolKind::Variable, position(5, 9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        let xref = xref(
+            "let x := 1\nlet f := (x: int): int => {\n    f(x)\n}\nlet y := { let x := 2\nx }\nprint(x)",
+        );
+
+        assert_eq!(
+            xref.references,
+            [
+                Reference {
+                    name: "f".to_owned(),
+                    position: position(3, 5),
+                    definition: Some(position(2, 5)),
+                    function: Some("f".to_owned()),
+                },
+                Reference {
+                    name: "x".to_owned(),
+                    position: position(3, 7),
+                    definition: Some(position(2, 11)),
+                    function: Some("f".to_owned()),
+                },
+                Reference {
+                    name: "x".to_owned(),
+                    position: position(6, 1),
+                    definition: Some(position(5, 16)),
+                    function: None,
+                },
+                Reference {
+                    name: "print".to_owned(),
+                    position: position(7, 1),
+                    definition: None,
+                    function: None,
+                },
+                Reference {
+                    name: "x".to_owned(),
+                    position: position(7, 7),
+                    definition: Some(position(1, 5)),
+                    function: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        let xref = xref("let b := 1\nlet a := (x: int): int => { x }");
+
+        assert_eq!(
+            xref.to_tags(),
+            "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+             !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/\n\
+             a\ttest.why\t2;\"\tf\tline:2\tcolumn:5\n\
+             b\ttest.why\t1;\"\tc\tline:1\tcolumn:5\n\
+             x\ttest.why\t2;\"\tp\tline:2\tcolumn:11\n"
+        );
+    }
+}
@@ -1,14 +1,156 @@
 use std::{
+    env,
     error::Error,
-    io,
-    path::Path,
-    process::{Command, Output},
+    fmt::Display,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
     str,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
 const WHY_PATH: &str = "./target/debug/why";
 const OUTPUT_PATH: &str = "./output";
 
+/// Environment variable overriding the number of seconds a compiled program may run.
+const TEST_TIMEOUT: &str = "WHY_TEST_TIMEOUT";
+
+/// Limits of running a compiled program (or the compiler), so a broken program (e.g., an endless
+/// loop) fails its test instead of hanging or exhausting the memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub timeout: Duration,
+    /// The maximum number of bytes of each of the output streams.
+    pub max_output: usize,
+}
+
+impl Limits {
+    /// The limits of running the compiler, which has to assemble and link the program as well.
+    pub fn compiler() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        let timeout = env::var(TEST_TIMEOUT)
+            .ok()
+            .and_then(|seconds| seconds.parse().ok())
+            .unwrap_or(10);
+
+        Self {
+            timeout: Duration::from_secs(timeout),
+            max_output: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// A program which was killed, since it exceeded its limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Timeout(Duration),
+    Output(usize),
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitExceeded::Timeout(timeout) => {
+                write!(f, "program timed out after {}s", timeout.as_secs_f64())
+            }
+            LimitExceeded::Output(bytes) if bytes % (1024 * 1024) == 0 => {
+                write!(f, "output exceeded {} MiB", bytes / (1024 * 1024))
+            }
+            LimitExceeded::Output(bytes) => write!(f, "output exceeded {bytes} bytes"),
+        }
+    }
+}
+
+impl Error for LimitExceeded {}
+
+/// Run a command like `Command::output`, but kill it once it exceeds the given limits.
+pub fn run_with_limits(command: &mut Command, limits: Limits) -> Result<Output, Box<dyn Error>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err("Could not capture the output of the program".into());
+    };
+
+    let exceeded_output = AtomicBool::new(false);
+
+    // read a stream until it ends (or exceeds the limit), so the program does not block on a full
+    // pipe
+    let read = |mut stream: Box<dyn Read + Send>| {
+        let mut output = vec![];
+        let mut buffer = [0; 8192];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) if output.len() + read > limits.max_output => {
+                    exceeded_output.store(true, Ordering::SeqCst);
+                    break;
+                }
+                Ok(read) => output.extend_from_slice(&buffer[..read]),
+            }
+        }
+        output
+    };
+
+    thread::scope(|scope| {
+        let stdout = scope.spawn(|| read(Box::new(stdout)));
+        let stderr = scope.spawn(|| read(Box::new(stderr)));
+
+        let start = Instant::now();
+        let status = loop {
+            let status = child.try_wait();
+            if let Ok(Some(status)) = status {
+                break status;
+            }
+
+            let exceeded = if exceeded_output.load(Ordering::SeqCst) {
+                Some(LimitExceeded::Output(limits.max_output).into())
+            } else if start.elapsed() > limits.timeout {
+                Some(LimitExceeded::Timeout(limits.timeout).into())
+            } else {
+                status.err().map(Box::<dyn Error>::from)
+            };
+
+            // the readers only finish once the program is gone
+            if let Some(error) = exceeded {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(error);
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        // the program may have been terminated by closing its output after exceeding the limit
+        if exceeded_output.load(Ordering::SeqCst) {
+            return Err(LimitExceeded::Output(limits.max_output).into());
+        }
+
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    })
+}
+
 pub struct Expected<'a> {
     pub stdout: &'a str,
     pub stderr: &'a str,
@@ -23,22 +165,123 @@ impl<'a> Expected<'a> {
 }
 
 fn run_type_checker(src_path: &Path) -> Result<Output, io::Error> {
-    Command::new(WHY_PATH).arg(src_path).output()
+    Command::new(WHY_PATH).arg("build").arg(src_path).output()
+}
+
+fn run_compiler(
+    src_path: &Path,
+    out_path: &Path,
+    args: &[&str],
+) -> Result<Output, Box<dyn Error>> {
+    run_with_limits(
+        Command::new(WHY_PATH)
+            .arg("build")
+            .arg("-o")
+            .arg(out_path)
+            .args(args)
+            .arg(src_path),
+        Limits::compiler(),
+    )
 }
 
-fn run_compiler(src_path: &Path, out_path: &Path) -> Result<Output, io::Error> {
+/// Run `why` with the given arguments and additional environment variables.
+pub fn run_why(args: &[&str], envs: &[(&str, &str)]) -> Result<Output, io::Error> {
     Command::new(WHY_PATH)
-        .arg("build")
-        .arg("-o")
-        .arg(out_path)
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+}
+
+/// Run `why` with the given arguments within the given directory.
+pub fn run_why_in(dir: &Path, args: &[&str]) -> Result<Output, io::Error> {
+    Command::new(fs::canonicalize(WHY_PATH)?)
+        .current_dir(dir)
+        .args(args)
+        .output()
+}
+
+/// Run a program via `why run`, passing the given arguments to it.
+pub fn run_interpreter(src_path: &Path, args: &[&str]) -> Result<Output, io::Error> {
+    Command::new(WHY_PATH)
+        .arg("run")
         .arg(src_path)
+        .args(args)
+        .output()
+}
+
+/// Run a program via `why script`, caching the compiled binary in the given directory.
+pub fn run_script(src_path: &Path, args: &[&str], cache_dir: &Path) -> Result<Output, io::Error> {
+    Command::new(WHY_PATH)
+        .env("XDG_CACHE_HOME", cache_dir)
+        .arg("script")
+        .arg(src_path)
+        .args(args)
+        .output()
+}
+
+pub fn run_build_all(src_dir: &Path, out_dir: &Path) -> Result<Output, io::Error> {
+    Command::new(WHY_PATH)
+        .arg("build-all")
+        .arg("-o")
+        .arg(out_dir)
+        .arg(src_dir)
         .output()
 }
 
 pub fn check_compilation(src_path: &Path, expected: Expected) -> Result<(), Box<dyn Error>> {
+    check_compilation_with_args(src_path, &[], expected)
+}
+
+/// Like `check_compilation`, but passes additional arguments to the compiler.
+pub fn check_compilation_with_args(
+    src_path: &Path,
+    args: &[&str],
+    expected: Expected,
+) -> Result<(), Box<dyn Error>> {
+    check_execution(src_path, args, expected, 0)
+}
+
+/// Compile a program and check that running it terminates with the given exit code.
+pub fn check_execution(
+    src_path: &Path,
+    args: &[&str],
+    expected: Expected,
+    exit_code: i32,
+) -> Result<(), Box<dyn Error>> {
+    let output = run_compiled(src_path, args)?;
+
+    expected.assert_matches(&output)?;
+    assert_eq!(
+        output.status.code(),
+        Some(exit_code),
+        "Compiled program exited with unexpected status"
+    );
+
+    Ok(())
+}
+
+/// Compile a program (passing additional arguments to the compiler) and return the output of
+/// running it.
+pub fn run_compiled(src_path: &Path, args: &[&str]) -> Result<Output, Box<dyn Error>> {
+    run_compiled_with_limits(src_path, args, Limits::default())
+}
+
+/// Like `run_compiled`, but the program is killed (and an error returned) once it exceeds the
+/// given limits.
+pub fn run_compiled_with_limits(
+    src_path: &Path,
+    args: &[&str],
+    limits: Limits,
+) -> Result<Output, Box<dyn Error>> {
+    run_with_limits(&mut Command::new(compile(src_path, args)?), limits)
+}
+
+/// Compile a program (passing additional arguments to the compiler) and return the path of the
+/// resulting binary.
+pub fn compile(src_path: &Path, args: &[&str]) -> Result<PathBuf, Box<dyn Error>> {
     let out_path = Path::new(OUTPUT_PATH).join(src_path.file_stem().unwrap());
 
-    let compile_output = run_compiler(src_path, &out_path)?;
+    let compile_output = run_compiler(src_path, &out_path, args)?;
     let compile_stdout = std::str::from_utf8(&compile_output.stdout)?;
     let compile_stderr = std::str::from_utf8(&compile_output.stderr)?;
 
@@ -50,18 +293,113 @@ pub fn check_compilation(src_path: &Path, expected: Expected) -> Result<(), Box<
         compile_output.status.code()
     );
 
-    let output = Command::new(out_path).output()?;
+    Ok(out_path)
+}
+
+/// Compile a program with warnings enabled and return the warnings logged by the compiler.
+pub fn compiler_warnings(src_path: &Path) -> Result<String, Box<dyn Error>> {
+    let out_path = Path::new(OUTPUT_PATH).join(format!(
+        "{}_warnings",
+        src_path.file_stem().unwrap().to_string_lossy()
+    ));
+
+    let compile_output = Command::new(WHY_PATH)
+        .args(["--verbosity", "warn", "build", "-o"])
+        .arg(out_path)
+        .arg(src_path)
+        .output()?;
+    assert!(
+        compile_output.status.success(),
+        "Why compiler exited with status {:?}",
+        compile_output.status.code()
+    );
+
+    // logs are written to stderr
+    Ok(String::from_utf8(compile_output.stderr)?)
+}
+
+/// Interpret a program and check that it terminates with the given exit code.
+pub fn check_interpretation(
+    src_path: &Path,
+    expected: Expected,
+    exit_code: i32,
+) -> Result<(), Box<dyn Error>> {
+    check_interpretation_with_args(src_path, &[], expected, exit_code)
+}
+
+/// Like `check_interpretation`, but passes arguments to the interpreted program.
+pub fn check_interpretation_with_args(
+    src_path: &Path,
+    args: &[&str],
+    expected: Expected,
+    exit_code: i32,
+) -> Result<(), Box<dyn Error>> {
+    let output = run_interpreter(src_path, args)?;
 
     expected.assert_matches(&output)?;
+    assert_eq!(
+        output.status.code(),
+        Some(exit_code),
+        "Interpreted program exited with unexpected status"
+    );
+
+    Ok(())
+}
+
+/// Run a program through both the compiler and the interpreter and check that both produce the
+/// same output and exit code.
+pub fn check_backends_agree(src_path: &Path) -> Result<(), Box<dyn Error>> {
+    let name = format!("{}_backends", src_path.file_stem().unwrap().to_string_lossy());
+    let out_path = Path::new(OUTPUT_PATH).join(name);
+
+    let compile_output = run_compiler(src_path, &out_path, &[])?;
     assert!(
-        output.status.success(),
-        "Compiled program exited with status {:?}",
+        compile_output.status.success(),
+        "Why compiler exited with status {:?}",
         compile_output.status.code()
     );
 
+    let compiled = run_with_limits(&mut Command::new(out_path), Limits::default())?;
+    let interpreted = run_interpreter(src_path, &[])?;
+
+    assert_eq!(
+        str::from_utf8(&interpreted.stdout)?,
+        str::from_utf8(&compiled.stdout)?,
+        "Backends disagree on the output of {src_path:?}"
+    );
+    assert_eq!(
+        interpreted.status.code(),
+        compiled.status.code(),
+        "Backends disagree on the exit code of {src_path:?}"
+    );
+
     Ok(())
 }
 
+/// Compile a program into the output directory (using the given name for the artifacts) and
+/// return the generated assembly.
+pub fn compile_to_assembly(src_path: &Path, name: &str) -> Result<String, Box<dyn Error>> {
+    compile_to_assembly_with_args(src_path, name, &[])
+}
+
+/// Like `compile_to_assembly`, but passes additional arguments to the compiler.
+pub fn compile_to_assembly_with_args(
+    src_path: &Path,
+    name: &str,
+    args: &[&str],
+) -> Result<String, Box<dyn Error>> {
+    let out_path = Path::new(OUTPUT_PATH).join(name);
+
+    let compile_output = run_compiler(src_path, &out_path, args)?;
+    assert!(
+        compile_output.status.success(),
+        "Why compiler exited with status {:?}",
+        compile_output.status.code()
+    );
+
+    Ok(fs::read_to_string(out_path.with_extension("asm"))?)
+}
+
 pub fn check_failing_type_checking(src_path: &Path) -> Result<(), Box<dyn Error>> {
     let type_check_output = run_type_checker(src_path)?;
 
@@ -73,3 +411,404 @@ pub fn check_failing_type_checking(src_path: &Path) -> Result<(), Box<dyn Error>
 
     Ok(())
 }
+
+/// Check that type checking fails gracefully (i.e., without crashing) with an error containing
+/// the given message.
+pub fn check_type_error(src_path: &Path, message: &str) -> Result<(), Box<dyn Error>> {
+    let type_check_output = run_type_checker(src_path)?;
+    // errors are rendered to stderr
+    let stderr = str::from_utf8(&type_check_output.stderr)?;
+
+    println!("{type_check_output:?}");
+    assert_eq!(
+        type_check_output.status.code(),
+        Some(255),
+        "Why type checker should exit with status -1"
+    );
+    assert!(stderr.contains(message), "{stderr}");
+
+    Ok(())
+}
+
+/// Environment variable which makes `check_programs` (and `check_compile_fail`) rewrite the
+/// expected output files instead of comparing against them.
+const UPDATE_EXPECT: &str = "UPDATE_EXPECT";
+
+/// Environment variable limiting the number of programs `check_programs` builds concurrently.
+const TEST_JOBS: &str = "WHY_TEST_JOBS";
+
+/// Compile and run every program within a directory and compare the results to the expected
+/// output files next to it. For a program `foo.why`, these are
+/// - `foo.stdout`: the expected standard output,
+/// - `foo.stderr` (optional): the expected standard error, empty if missing,
+/// - `foo.exit` (optional): the expected exit code, `0` if missing,
+/// - `foo.skip-os` (optional): operating systems (one per line) to skip the program on.
+///
+/// If `UPDATE_EXPECT=1` is set, the expected output files are rewritten instead.
+pub fn check_programs(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let mut programs = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "why") && !skip_on_os(&path)? {
+            programs.push(path);
+        }
+    }
+    programs.sort();
+
+    fs::create_dir_all(Path::new(OUTPUT_PATH).join("programs"))?;
+
+    let update = env::var(UPDATE_EXPECT).is_ok_and(|value| value == "1");
+    let jobs = env::var(TEST_JOBS)
+        .ok()
+        .and_then(|jobs| jobs.parse().ok())
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |jobs| jobs.get()))
+        .clamp(1, programs.len().max(1));
+
+    let next = AtomicUsize::new(0);
+    let failures = Mutex::new(vec![]);
+
+    // every program is assembled and linked separately, so limit the number of programs built at
+    // the same time
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some(program) = programs.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let result = if update {
+                        update_program(program)
+                    } else {
+                        check_program(program)
+                    };
+
+                    if let Err(failure) = result {
+                        failures.lock().unwrap().push(format!(
+                            "=== {}\n{failure}",
+                            program.to_string_lossy()
+                        ));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} programs failed (run with {UPDATE_EXPECT}=1 to update the expected output):\n\n{}",
+        failures.len(),
+        programs.len(),
+        failures.join("\n")
+    );
+
+    Ok(())
+}
+
+fn skip_on_os(src_path: &Path) -> Result<bool, io::Error> {
+    let skip_path = src_path.with_extension("skip-os");
+    if !skip_path.exists() {
+        return Ok(false);
+    }
+
+    Ok(fs::read_to_string(skip_path)?
+        .lines()
+        .any(|os| os.trim() == env::consts::OS))
+}
+
+/// Compile and run a program, returning its output or a description of what went wrong.
+fn build_and_run(src_path: &Path) -> Result<Output, String> {
+    let out_path: PathBuf = Path::new(OUTPUT_PATH)
+        .join("programs")
+        .join(src_path.file_stem().unwrap());
+
+    let compile_output =
+        run_compiler(src_path, &out_path, &[]).map_err(|error| error.to_string())?;
+    if !compile_output.status.success() {
+        return Err(format!(
+            "Why compiler exited with status {:?}:\n{}{}",
+            compile_output.status.code(),
+            String::from_utf8_lossy(&compile_output.stdout),
+            String::from_utf8_lossy(&compile_output.stderr)
+        ));
+    }
+
+    run_with_limits(&mut Command::new(&out_path), Limits::default())
+        .map_err(|error| format!("Could not run '{}': {error}", out_path.to_string_lossy()))
+}
+
+fn check_program(src_path: &Path) -> Result<(), String> {
+    let read = |extension: &str| fs::read_to_string(src_path.with_extension(extension));
+
+    let expected_stdout = read("stdout").map_err(|error| format!("Missing .stdout: {error}"))?;
+    let expected_stderr = read("stderr").unwrap_or_default();
+    let expected_exit_code = match read("exit") {
+        Ok(code) => code
+            .trim()
+            .parse::<i32>()
+            .map_err(|error| format!("Invalid .exit: {error}"))?,
+        Err(_) => 0,
+    };
+
+    let output = build_and_run(src_path)?;
+
+    let mut report = String::new();
+    report.push_str(&diff(
+        "stdout",
+        &expected_stdout,
+        &String::from_utf8_lossy(&output.stdout),
+    ));
+    report.push_str(&diff(
+        "stderr",
+        &expected_stderr,
+        &String::from_utf8_lossy(&output.stderr),
+    ));
+    if output.status.code() != Some(expected_exit_code) {
+        report.push_str(&format!(
+            "exit code: expected {expected_exit_code}, got {:?}\n",
+            output.status.code()
+        ));
+    }
+
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+fn update_program(src_path: &Path) -> Result<(), String> {
+    let output = build_and_run(src_path)?;
+    let exit_code = output.status.code().unwrap_or(-1);
+
+    let write = |extension: &str, content: Option<String>| {
+        let path = src_path.with_extension(extension);
+        match content {
+            Some(content) => fs::write(path, content),
+            None if path.exists() => fs::remove_file(path),
+            None => Ok(()),
+        }
+        .map_err(|error| error.to_string())
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    write("stdout", Some(String::from_utf8_lossy(&output.stdout).into()))?;
+    write("stderr", Some(stderr).filter(|stderr| !stderr.is_empty()))?;
+    write(
+        "exit",
+        Some(format!("{exit_code}\n")).filter(|_| exit_code != 0),
+    )
+}
+
+/// Describe the differences between the expected and the actual content of an output stream
+/// (line by line), or return an empty string if they match.
+fn diff(stream: &str, expected: &str, actual: &str) -> String {
+    if expected == actual {
+        return String::new();
+    }
+
+    let expected = expected.split('\n').collect::<Vec<_>>();
+    let actual = actual.split('\n').collect::<Vec<_>>();
+
+    let mut report = format!("{stream} differs (- expected, + actual):\n");
+    for line in 0..expected.len().max(actual.len()) {
+        match (expected.get(line), actual.get(line)) {
+            (Some(expected), Some(actual)) if expected == actual => {
+                report.push_str(&format!("  {expected}\n"))
+            }
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    report.push_str(&format!("- {expected:?}\n"));
+                }
+                if let Some(actual) = actual {
+                    report.push_str(&format!("+ {actual:?}\n"));
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// An error reported by the compiler.
+#[derive(Debug, PartialEq, Eq)]
+struct ReportedError {
+    line: usize,
+    col: usize,
+    message: String,
+}
+
+/// Prefix of expectations which match a single error at a given position.
+const ERROR_PREFIX: &str = "ERROR: ";
+
+/// Type check every program within a directory, which all have to be rejected, and compare the
+/// errors to the expectations next to them. For a program `foo.why`, `foo.expected` contains one
+/// expectation per line, which is either
+/// - `ERROR: <line>:<col>: <message>`: an error at the given position, whose message contains the
+///   given fragment, or
+/// - any other text, which has to appear somewhere in the output of the compiler.
+///
+/// Every error reported by the compiler has to be matched by an `ERROR` expectation. If
+/// `UPDATE_EXPECT=1` is set, the expectations are rewritten to the reported errors instead.
+pub fn check_compile_fail(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let mut programs = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "why") {
+            programs.push(path);
+        }
+    }
+    programs.sort();
+
+    let update = env::var(UPDATE_EXPECT).is_ok_and(|value| value == "1");
+
+    let mut failures = vec![];
+    for program in &programs {
+        let result = if update {
+            update_compile_fail(program)
+        } else {
+            check_compile_fail_program(program)
+        };
+
+        if let Err(failure) = result {
+            failures.push(format!("=== {}\n{failure}", program.to_string_lossy()));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} programs failed (run with {UPDATE_EXPECT}=1 to update the expectations):\n\n{}",
+        failures.len(),
+        programs.len(),
+        failures.join("\n")
+    );
+
+    Ok(())
+}
+
+/// Type check a program, which has to be rejected, and return the output of the compiler together
+/// with the errors it reported.
+fn type_check_failing(src_path: &Path) -> Result<(String, Vec<ReportedError>), String> {
+    let output = Command::new(WHY_PATH)
+        .args(["--color", "never", "build"])
+        .arg(src_path)
+        .output()
+        .map_err(|error| error.to_string())?;
+
+    // errors are rendered to stderr
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    if output.status.code() != Some(255) {
+        return Err(format!(
+            "Why type checker exited with status {:?} instead of -1:\n{}{stderr}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout)
+        ));
+    }
+
+    let errors = stderr.lines().filter_map(parse_reported_error).collect();
+    Ok((stderr, errors))
+}
+
+/// Parse a line of the output of the compiler, if it contains an error (i.e., no note or source
+/// snippet), which is formatted as `<message> (<file>:<line>:<col>)`.
+fn parse_reported_error(line: &str) -> Option<ReportedError> {
+    if line.starts_with(char::is_whitespace) || is_snippet(line) {
+        return None;
+    }
+
+    let (message, location) = line.strip_suffix(')')?.rsplit_once(" (")?;
+    let mut location = location.rsplitn(3, ':');
+    let col = location.next()?.parse().ok()?;
+    let line = location.next()?.parse().ok()?;
+
+    Some(ReportedError {
+        line,
+        col,
+        message: message.to_owned(),
+    })
+}
+
+/// Check, whether a line belongs to a source snippet (e.g., `12 | let a := b`).
+fn is_snippet(line: &str) -> bool {
+    line.split_once('|')
+        .is_some_and(|(gutter, _)| gutter.trim().chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Parse an expectation of the form `ERROR: <line>:<col>: <message>`.
+fn parse_expected_error(expectation: &str) -> Option<ReportedError> {
+    let mut parts = expectation.strip_prefix(ERROR_PREFIX)?.splitn(3, ':');
+    let line = parts.next()?.trim().parse().ok()?;
+    let col = parts.next()?.trim().parse().ok()?;
+
+    Some(ReportedError {
+        line,
+        col,
+        message: parts.next()?.trim().to_owned(),
+    })
+}
+
+fn check_compile_fail_program(src_path: &Path) -> Result<(), String> {
+    let expectations = fs::read_to_string(src_path.with_extension("expected"))
+        .map_err(|error| format!("Missing .expected: {error}"))?;
+
+    let (stderr, errors) = type_check_failing(src_path)?;
+    let mut matched = vec![false; errors.len()];
+    let mut report = String::new();
+
+    for expectation in expectations.lines().map(str::trim) {
+        if expectation.is_empty() {
+            continue;
+        }
+
+        if expectation.starts_with(ERROR_PREFIX) {
+            let Some(expected) = parse_expected_error(expectation) else {
+                report.push_str(&format!("invalid expectation: {expectation}\n"));
+                continue;
+            };
+
+            let found = errors.iter().enumerate().find(|(index, error)| {
+                !matched[*index]
+                    && error.line == expected.line
+                    && error.col == expected.col
+                    && error.message.contains(&expected.message)
+            });
+
+            match found {
+                Some((index, _)) => matched[index] = true,
+                None => report.push_str(&format!("unmatched expectation: {expectation}\n")),
+            }
+        } else if !stderr.contains(expectation) {
+            report.push_str(&format!("unmatched expectation: {expectation}\n"));
+        }
+    }
+
+    for (error, _) in errors.iter().zip(&matched).filter(|(_, matched)| !**matched) {
+        report.push_str(&format!(
+            "unexpected error: {ERROR_PREFIX}{}:{}: {}\n",
+            error.line, error.col, error.message
+        ));
+    }
+
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{report}output:\n{stderr}"))
+    }
+}
+
+fn update_compile_fail(src_path: &Path) -> Result<(), String> {
+    let (_, errors) = type_check_failing(src_path)?;
+
+    let expectations = errors
+        .iter()
+        .map(|error| {
+            format!(
+                "{ERROR_PREFIX}{}:{}: {}\n",
+                error.line, error.col, error.message
+            )
+        })
+        .collect::<String>();
+
+    fs::write(src_path.with_extension("expected"), expectations).map_err(|error| error.to_string())
+}
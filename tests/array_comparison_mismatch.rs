@@ -0,0 +1,13 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./examples/array_comparison_mismatch.why";
+
+#[test]
+fn type_check_array_comparison_mismatch() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new(SRC_PATH),
+        "Can not compare values of type '[int; 5]' and '[int; 6]'",
+    )
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+const SRC_PATH: &str = "./examples/array_equality.why";
+const EXPECTED: Expected = Expected {
+    stdout: "true false true true false true ",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_array_equality() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
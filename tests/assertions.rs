@@ -0,0 +1,78 @@
+use std::{error::Error, path::Path, str};
+
+use test_utils::{
+    check_compilation, compile_to_assembly_with_args, run_compiled, run_interpreter, Expected,
+};
+
+#[test]
+fn compile_and_run_passing_assertions() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/assert_pass.why"),
+        Expected {
+            stdout: "ok",
+            stderr: "",
+        },
+    )
+}
+
+#[test]
+fn failing_assert_reports_location() -> Result<(), Box<dyn Error>> {
+    let src_path = Path::new("./examples/assert_fail.why");
+
+    for output in [
+        run_compiled(src_path, &[])?,
+        run_interpreter(src_path, &[])?,
+    ] {
+        let stderr = str::from_utf8(&output.stderr)?;
+
+        assert_eq!(str::from_utf8(&output.stdout)?, "before");
+        assert_eq!(output.status.code(), Some(101));
+        assert!(
+            stderr.starts_with("assertion failed: `x > 3` ("),
+            "unexpected message: {stderr}"
+        );
+        assert!(
+            stderr.ends_with("assert_fail.why:5:1)\n"),
+            "unexpected message: {stderr}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn failing_assert_eq_reports_values() -> Result<(), Box<dyn Error>> {
+    let src_path = Path::new("./examples/assert_eq_fail.why");
+
+    for output in [
+        run_compiled(src_path, &[])?,
+        run_interpreter(src_path, &[])?,
+    ] {
+        let stderr = str::from_utf8(&output.stderr)?;
+
+        assert_eq!(output.status.code(), Some(101));
+        assert!(
+            stderr.starts_with("assertion failed: `add(2, 3) == 6` ("),
+            "unexpected message: {stderr}"
+        );
+        assert!(
+            stderr.ends_with("assert_eq_fail.why:8:1)\n  left: 5\n right: 6\n"),
+            "unexpected message: {stderr}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn release_asserts_omits_assertions() -> Result<(), Box<dyn Error>> {
+    let src_path = Path::new("./examples/assert_fail.why");
+    let checked = compile_to_assembly_with_args(src_path, "assert_fail_checked", &[])?;
+    let released =
+        compile_to_assembly_with_args(src_path, "assert_fail_released", &["--release-asserts"])?;
+
+    assert!(checked.contains("assertion failed"));
+    assert!(!released.contains("assertion failed"));
+
+    Ok(())
+}
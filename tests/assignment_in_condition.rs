@@ -0,0 +1,18 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./examples/assignment_in_condition.why";
+
+#[test]
+fn type_check_assignment_in_condition() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new(SRC_PATH),
+        "assignment in condition; did you mean `==`? (",
+    )?;
+    check_type_error(Path::new(SRC_PATH), "assignment_in_condition.why:7:9)")?;
+    check_type_error(
+        Path::new(SRC_PATH),
+        "Expected return type of 'int' but got 'str'",
+    )
+}
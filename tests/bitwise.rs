@@ -0,0 +1,22 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_type_error, Expected};
+
+#[test]
+fn compile_and_run_bitwise() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/bitwise.why"),
+        Expected {
+            stdout: "60 240 8 -4 9 5 -16 15",
+            stderr: "",
+        },
+    )
+}
+
+#[test]
+fn type_check_bitwise_type_error() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/bitwise_type_error.why"),
+        "Left value of numeric binary operation has to be of type Int. Found 'bool'",
+    )
+}
@@ -0,0 +1,32 @@
+use std::{error::Error, fs, path::Path};
+
+use test_utils::run_build_all;
+
+const SRC_PATH: &str = "./output/build_all_src";
+const OUT_PATH: &str = "./output/build_all";
+
+#[test]
+fn build_all_files_in_directory() -> Result<(), Box<dyn Error>> {
+    let src_dir = Path::new(SRC_PATH);
+    let out_dir = Path::new(OUT_PATH);
+
+    let _ = fs::remove_dir_all(src_dir);
+    let _ = fs::remove_dir_all(out_dir);
+
+    fs::create_dir_all(src_dir.join("nested"))?;
+    fs::write(src_dir.join("first.why"), "let a := 42\n")?;
+    fs::write(src_dir.join("nested/second.why"), "let b := \"foo\"\n")?;
+    fs::write(src_dir.join("broken.why"), "let c := 42\nc = \"foo\"\n")?;
+
+    let output = run_build_all(src_dir, out_dir)?;
+    let stderr = std::str::from_utf8(&output.stderr)?;
+    println!("{stderr}");
+
+    assert!(!output.status.success(), "build-all should fail");
+    assert!(out_dir.join("first").exists());
+    assert!(out_dir.join("nested/second").exists());
+    assert!(!out_dir.join("broken").exists());
+    assert!(stderr.contains("3/3 compiled"));
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_interpretation, Expected};
+
+const SRC_PATH: &str = "./examples/call_on_expression.why";
+const EXPECTED: Expected = Expected {
+    stdout: "10 4 21 10 15\n",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_call_on_expression() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
+
+#[test]
+fn interpret_call_on_expression() -> Result<(), Box<dyn Error>> {
+    check_interpretation(Path::new(SRC_PATH), EXPECTED, 0)
+}
@@ -0,0 +1,27 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation_with_args, Expected};
+
+const SRC_PATH: &str = "./examples/cfg.why";
+
+// both variants share the same output binary, so they are compiled within a single test
+#[test]
+fn compile_and_run_cfg() -> Result<(), Box<dyn Error>> {
+    check_compilation_with_args(
+        Path::new(SRC_PATH),
+        &["--cfg", "variant=foo"],
+        Expected {
+            stdout: "foo",
+            stderr: "",
+        },
+    )?;
+
+    check_compilation_with_args(
+        Path::new(SRC_PATH),
+        &["--cfg", "variant=bar"],
+        Expected {
+            stdout: "bar",
+            stderr: "",
+        },
+    )
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+const SRC_PATH: &str = "./examples/char_arithmetic.why";
+const EXPECTED: Expected = Expected {
+    stdout: "abcdefghijklmnopqrstuvwxyz 97 98 yes",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_char_arithmetic() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
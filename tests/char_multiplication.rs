@@ -0,0 +1,13 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./examples/char_multiplication.why";
+
+#[test]
+fn type_check_char_multiplication() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new(SRC_PATH),
+        "Invalid types for binary operation '*'. Got 'char' and 'char'",
+    )
+}
@@ -0,0 +1,31 @@
+use std::{error::Error, fs, path::Path};
+
+use test_utils::run_compiled;
+use y_lang::compiler::INTEGER_OVERFLOW_EXIT_CODE;
+
+const SRC_PATH: &str = "./examples/checked_arithmetic.why";
+
+// both variants are compiled to the same binary, so they have to run one after another
+#[test]
+fn overflow_wraps_by_default_and_aborts_when_checked() -> Result<(), Box<dyn Error>> {
+    let output = run_compiled(Path::new(SRC_PATH), &[])?;
+
+    assert!(output.status.success());
+    assert_eq!(std::str::from_utf8(&output.stdout)?, "275 wrapped");
+
+    let output = run_compiled(Path::new(SRC_PATH), &["--checked-arithmetic"])?;
+
+    let file = fs::canonicalize(SRC_PATH)?;
+    assert_eq!(
+        output.status.code(),
+        Some(INTEGER_OVERFLOW_EXIT_CODE as i32)
+    );
+    // the loop does not overflow, so it behaves the same
+    assert_eq!(std::str::from_utf8(&output.stdout)?, "275 ");
+    assert_eq!(
+        std::str::from_utf8(&output.stderr)?,
+        format!("integer overflow at {}:15:4\n", file.to_string_lossy())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::error::Error;
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/bitwise_type_error.why";
+
+/// Type check the example (which contains an error) with the given color mode and environment,
+/// and return what has been logged.
+fn logged_error(color: &str, envs: &[(&str, &str)]) -> Result<String, Box<dyn Error>> {
+    let output = run_why(&["build", SRC_PATH, "--color", color], envs)?;
+    assert!(!output.status.success());
+
+    Ok(String::from_utf8(output.stderr)?)
+}
+
+#[test]
+fn color_never_strips_escape_codes() -> Result<(), Box<dyn Error>> {
+    let stderr = logged_error("never", &[("CLICOLOR_FORCE", "1")])?;
+
+    assert!(stderr.contains("Left value of numeric binary operation"));
+    assert!(!stderr.contains('\x1b'));
+
+    Ok(())
+}
+
+#[test]
+fn color_always_colors_without_terminal() -> Result<(), Box<dyn Error>> {
+    let stderr = logged_error("always", &[("NO_COLOR", "1")])?;
+
+    assert!(stderr.contains("\x1b[1;31m^"));
+
+    Ok(())
+}
+
+#[test]
+fn color_auto_respects_environment() -> Result<(), Box<dyn Error>> {
+    // the output of the tests is no terminal
+    assert!(!logged_error("auto", &[])?.contains('\x1b'));
+    assert!(logged_error("auto", &[("CLICOLOR_FORCE", "1")])?.contains('\x1b'));
+    assert!(!logged_error("auto", &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")])?.contains('\x1b'));
+
+    Ok(())
+}
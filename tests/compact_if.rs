@@ -2,13 +2,13 @@ use std::{error::Error, path::Path};
 
 use test_utils::{check_compilation, Expected};
 
-const SRC_PATH: &str = "./examples/hello.why";
+const SRC_PATH: &str = "./examples/compact_if.why";
 const EXPECTED: Expected = Expected {
-    stdout: "Hello, World!",
+    stdout: "7 11 -0+ 4 1",
     stderr: "",
 };
 
 #[test]
-fn compile_and_run_hello() -> Result<(), Box<dyn Error>> {
+fn compile_and_run_compact_if() -> Result<(), Box<dyn Error>> {
     check_compilation(Path::new(SRC_PATH), EXPECTED)
 }
@@ -0,0 +1,10 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_compile_fail;
+
+const PROGRAMS_PATH: &str = "./tests/compile-fail";
+
+#[test]
+fn reject_compile_fail_programs() -> Result<(), Box<dyn Error>> {
+    check_compile_fail(Path::new(PROGRAMS_PATH))
+}
@@ -0,0 +1,30 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_type_error, Expected};
+
+#[test]
+fn compile_and_run_constant_sizes() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/constant_sizes.why"),
+        Expected {
+            stdout: "31 5 4",
+            stderr: "",
+        },
+    )
+}
+
+#[test]
+fn type_check_constant_size_variable() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/constant_size_variable.why"),
+        "'size' is not a constant",
+    )
+}
+
+#[test]
+fn type_check_constant_size_mismatch() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/constant_size_mismatch.why"),
+        "Invalid type of parameter! Expected '[int; 8]' but got '[int; 4]'",
+    )
+}
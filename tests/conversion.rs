@@ -2,13 +2,13 @@ use std::{error::Error, path::Path};
 
 use test_utils::{check_compilation, Expected};
 
-const SRC_PATH: &str = "./examples/loop.why";
+const SRC_PATH: &str = "./examples/conversion.why";
 const EXPECTED: Expected = Expected {
-    stdout: "0123456789",
+    stdout: "1235 -84 -1 0 7",
     stderr: "",
 };
 
 #[test]
-fn compile_and_run_loop() -> Result<(), Box<dyn Error>> {
+fn compile_and_run_conversion() -> Result<(), Box<dyn Error>> {
     check_compilation(Path::new(SRC_PATH), EXPECTED)
 }
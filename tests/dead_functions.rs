@@ -0,0 +1,32 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, compile_to_assembly, Expected};
+
+const SRC_PATH: &str = "./examples/dead_functions.why";
+const EXPECTED: Expected = Expected {
+    stdout: "42 40",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_dead_functions() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
+
+#[test]
+fn omit_unreferenced_functions() -> Result<(), Box<dyn Error>> {
+    let assembly = compile_to_assembly(Path::new(SRC_PATH), "dead_functions_asm")?;
+    let defines = |function: &str| {
+        assembly
+            .lines()
+            .any(|line| line.starts_with(&format!("{function}:")))
+    };
+
+    assert!(defines("used_helper"));
+    assert!(defines("apply"));
+    assert!(defines("passed_as_value"));
+    assert!(!defines("unused_helper"));
+    assert!(!defines("transitively_unused"));
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_declared_recursion() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/declared_recursion.why"),
+        Expected {
+            stdout: "120 even odd",
+            stderr: "",
+        },
+    )
+}
@@ -0,0 +1,94 @@
+use std::{error::Error, fs};
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/dump_parsed.why";
+
+fn dump(flag: &str) -> Result<String, Box<dyn Error>> {
+    let output = run_why(&["build", SRC_PATH, flag], &[])?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let file = fs::canonicalize(SRC_PATH)?.to_string_lossy().to_string();
+    Ok(String::from_utf8(output.stdout)?.replace(&file, "<file>"))
+}
+
+#[test]
+fn dump_parsed_without_positions() -> Result<(), Box<dyn Error>> {
+    assert_eq!(
+        dump("--dump-parsed")?,
+        "Parsed AST:
+Definition square
+  FnDef -> int
+    Param x: int
+    Block
+      Binary *
+        Ident x
+        Ident x
+Definition mut total
+  Integer 0
+WhileLoop
+  Binary <
+    Ident total
+    Integer 10
+  Block
+    Assignment
+      Ident total
+      Binary +
+        Ident total
+        Call
+          Ident square
+          Integer 2
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn dump_parsed_with_positions() -> Result<(), Box<dyn Error>> {
+    let dump = dump("--dump-parsed=verbose")?;
+
+    assert!(dump.starts_with("Parsed AST:\nDefinition square (<file>:1:1)\n"));
+    assert!(dump.contains("\n          Integer 2 (<file>:7:28)\n"));
+
+    Ok(())
+}
+
+#[test]
+fn dump_lexed() -> Result<(), Box<dyn Error>> {
+    let dump = dump("--dump-lexed")?;
+
+    assert!(dump.starts_with("Parse tree:\ndefinition\n  localIdent \"square\"\n"));
+    assert!(!dump.contains("<file>"));
+
+    Ok(())
+}
+
+#[test]
+fn dump_program_without_statements() -> Result<(), Box<dyn Error>> {
+    let output = run_why(
+        &[
+            "build",
+            "./tests/programs/comment_only.why",
+            "--dump-lexed",
+            "--dump-parsed",
+        ],
+        &[],
+    )?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "Parse tree:\nEOI \"\"\nParsed AST:\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::{error::Error, io, process::Output};
+
+use test_utils::run_why;
+
+/// Run `why build` on the given example, requesting the assembly of the given functions.
+fn emit_asm_function(example: &str, names: &[&str]) -> io::Result<Output> {
+    let mut args = vec!["build", example];
+    for name in names {
+        args.extend(["--emit-asm-function", name]);
+    }
+
+    run_why(&args, &[])
+}
+
+#[test]
+fn emit_function_with_referenced_functions() -> Result<(), Box<dyn Error>> {
+    let output = emit_asm_function("./examples/functions.why", &["nested"])?;
+    assert!(output.status.success());
+
+    let assembly = String::from_utf8(output.stdout)?;
+    let labels = assembly
+        .lines()
+        .filter_map(|line| line.split_once(':').map(|(label, _)| label))
+        .filter(|label| !label.starts_with(char::is_whitespace))
+        .collect::<Vec<_>>();
+
+    // `nested` references `test`, but none of the other functions
+    assert_eq!(labels, ["nested", "test"]);
+    assert!(assembly.contains("call \trax"));
+
+    Ok(())
+}
+
+#[test]
+fn emit_function_by_symbol() -> Result<(), Box<dyn Error>> {
+    let by_name = emit_asm_function("./examples/unicode_identifiers.why", &["größe"])?;
+    let by_symbol = emit_asm_function("./examples/unicode_identifiers.why", &["gr_$uf6$_$udf$e"])?;
+
+    assert!(by_name.status.success());
+    assert!(String::from_utf8(by_name.stdout.clone())?.contains("gr_$uf6$_$udf$e:"));
+    assert_eq!(by_name.stdout, by_symbol.stdout);
+
+    Ok(())
+}
+
+#[test]
+fn emit_unknown_function() -> Result<(), Box<dyn Error>> {
+    let output = emit_asm_function("./examples/functions.why", &["triple", "missing"])?;
+    assert!(!output.status.success());
+
+    // the error is rendered to stderr, the assembly of the known function is not printed at all
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(
+        "Unknown function 'missing'. Available functions: fn_0_2, foo, nested, six_params, test, triple"
+    ));
+    assert!(!String::from_utf8(output.stdout)?.contains("triple:"));
+
+    Ok(())
+}
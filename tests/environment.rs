@@ -0,0 +1,37 @@
+use std::{error::Error, path::Path, process::Command, str};
+
+use test_utils::{check_interpretation_with_args, compile, Expected};
+
+const SRC_PATH: &str = "./examples/environment.why";
+
+#[test]
+fn compile_and_run_environment() -> Result<(), Box<dyn Error>> {
+    let binary = compile(Path::new(SRC_PATH), &[])?;
+
+    let output = Command::new(binary)
+        .arg("hello")
+        .env("WHY_TEST_VALUE", "from the environment")
+        .env_remove("WHY_TEST")
+        .output()?;
+
+    assert_eq!(
+        str::from_utf8(&output.stdout)?,
+        "from the environment [] hello"
+    );
+    assert_eq!(output.status.code(), Some(7));
+
+    Ok(())
+}
+
+#[test]
+fn interpret_environment() -> Result<(), Box<dyn Error>> {
+    check_interpretation_with_args(
+        Path::new(SRC_PATH),
+        &["hello"],
+        Expected {
+            stdout: " [] hello",
+            stderr: "",
+        },
+        7,
+    )
+}
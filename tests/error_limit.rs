@@ -0,0 +1,46 @@
+use std::error::Error;
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/error_limit.why";
+
+/// Build the example with the given arguments and return the logged errors.
+fn errors(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = run_why(&[&["build", SRC_PATH], args].concat(), &[])?;
+    assert_eq!(output.status.code(), Some(255));
+
+    // errors are rendered to stderr
+    Ok(String::from_utf8(output.stderr)?)
+}
+
+#[test]
+fn report_first_20_errors_by_default() -> Result<(), Box<dyn Error>> {
+    let errors = errors(&[])?;
+
+    assert_eq!(errors.matches("expected expr (").count(), 20);
+    assert!(errors.contains("error_limit.why:21:12)\n"));
+    assert!(!errors.contains("error_limit.why:22:12)"));
+    assert!(errors.ends_with("… and 30 more errors not shown (re-run with --error-limit 0)\n"));
+
+    Ok(())
+}
+
+#[test]
+fn report_errors_up_to_the_given_limit() -> Result<(), Box<dyn Error>> {
+    let errors = errors(&["--error-limit", "5"])?;
+
+    assert_eq!(errors.matches("expected expr (").count(), 5);
+    assert!(errors.ends_with("… and 45 more errors not shown (re-run with --error-limit 0)\n"));
+
+    Ok(())
+}
+
+#[test]
+fn report_all_errors_without_limit() -> Result<(), Box<dyn Error>> {
+    let errors = errors(&["--error-limit", "0"])?;
+
+    assert_eq!(errors.matches("expected expr (").count(), 50);
+    assert!(!errors.contains("not shown"));
+
+    Ok(())
+}
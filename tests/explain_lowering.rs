@@ -0,0 +1,59 @@
+use std::{error::Error, fs};
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/explain_lowering.why";
+
+#[test]
+fn explain_line_inside_function_with_lambda() -> Result<(), Box<dyn Error>> {
+    let output = run_why(&["build", SRC_PATH, "--explain-lowering", "6"], &[])?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let file = fs::canonicalize(SRC_PATH)?.to_string_lossy().to_string();
+    assert_eq!(
+        String::from_utf8(output.stdout)?.replace(&file, "<file>"),
+        "=== Source (<file>:5-7) ===
+let result := apply((y: int): int => {
+    y * 2
+}, 21)
+=== Parsed ===
+Definition result
+  Call
+    Ident apply
+    FnDef -> int
+      Param y: int
+      Block
+        Binary *
+          Ident y
+          Integer 2
+    Integer 21
+=== Typed ===
+Definition result: int
+  Call: int
+    Ident apply: ((int) -> int, int) -> int
+    FnDef -> int
+      Param y: int
+      Block: int
+        Binary *: int
+          Ident y: int
+          Integer 2: int
+    Integer 21: int
+"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn explain_line_between_items() -> Result<(), Box<dyn Error>> {
+    let output = run_why(&["build", SRC_PATH, "--explain-lowering", "4"], &[])?;
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No item at line 4"));
+
+    Ok(())
+}
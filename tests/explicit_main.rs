@@ -0,0 +1,11 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+#[test]
+fn type_check_explicit_main() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/explicit_main.why"),
+        "Function 'main' can not be defined, since the top-level statements of a program form its entry point",
+    )
+}
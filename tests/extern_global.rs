@@ -0,0 +1,21 @@
+use std::{error::Error, path::Path, process::Command};
+
+use test_utils::{check_compilation_with_args, Expected};
+
+const SRC_PATH: &str = "./examples/extern_global.why";
+const C_SRC_PATH: &str = "./examples/extern_global.c";
+const C_OBJ_PATH: &str = "./output/extern_global_c.o";
+const EXPECTED: Expected = Expected {
+    stdout: "41 42",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_extern_global() -> Result<(), Box<dyn Error>> {
+    let cc_output = Command::new("cc")
+        .args(["-c", "-o", C_OBJ_PATH, C_SRC_PATH])
+        .output()?;
+    assert!(cc_output.status.success(), "{cc_output:?}");
+
+    check_compilation_with_args(Path::new(SRC_PATH), &["--link-obj", C_OBJ_PATH], EXPECTED)
+}
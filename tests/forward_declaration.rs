@@ -0,0 +1,22 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_type_error, Expected};
+
+#[test]
+fn compile_and_run_forward_declaration() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/forward_declaration.why"),
+        Expected {
+            stdout: "49 even odd",
+            stderr: "",
+        },
+    )
+}
+
+#[test]
+fn type_check_forward_declaration_mismatch() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/forward_declaration_mismatch.why"),
+        "Definition of 'half' with type '[Int] -> Bool' does not match its declaration with type '[Int] -> Int'",
+    )
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_function_coercion() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/function_coercion.why"),
+        Expected {
+            stdout: "Hello World! Bye! 3",
+            stderr: "",
+        },
+    )
+}
@@ -0,0 +1,250 @@
+//! Fuzzing of the front end: mutated programs are fed through parsing, export extraction and type
+//! checking, which may reject them with errors, but must never panic.
+//!
+//! The seeds are all programs of the test suite plus the inputs in `tests/fuzz-corpus`, which
+//! contains the inputs of panics found so far (and thereby serves as their regression tests). The
+//! mutations are deterministic; `FUZZ_ITERATIONS` and `FUZZ_SEED` allow longer (or other) runs.
+use std::{
+    env, fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use y_lang::{
+    ast::{Ast, YParser},
+    loader::ModuleRefs,
+    typechecker::Typechecker,
+};
+
+const SEED_DIRS: [&str; 4] = [
+    "./examples",
+    "./tests/compile-fail",
+    "./tests/programs",
+    "./tests/fuzz-corpus",
+];
+
+const DEFAULT_ITERATIONS: usize = 2000;
+
+/// Fragments of the grammar which are inserted into the seeds.
+const TOKENS: [&str; 50] = [
+    "let ",
+    "let mut ",
+    " := ",
+    "declare ",
+    "import ",
+    "@super::",
+    "::*",
+    "(",
+    ")",
+    "{",
+    "}",
+    "[",
+    "]",
+    "[;]",
+    "=>",
+    ": ",
+    ", ",
+    " = ",
+    "'a: ",
+    "break",
+    " 'a",
+    "loop ",
+    "while ",
+    "if ",
+    "else ",
+    "#[os == \"linux\"]\n",
+    "#[true]\n",
+    "asm { nop }",
+    "&",
+    "-",
+    "!",
+    "~",
+    " + ",
+    " * ",
+    " / ",
+    " << ",
+    " == ",
+    " > ",
+    "int",
+    "str",
+    "void",
+    "(int) -> int",
+    "99999999999999999999",
+    "0xFFFFFFFFFFFFFFFFFFFF",
+    "\"\\q\"",
+    "\"\\uD800\"",
+    "\"\\/\\b\\f\"",
+    "'\\q'",
+    "main",
+    "\n",
+];
+
+/// Minimal xorshift generator, so runs are reproducible without further dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound.max(1) as u64) as usize
+    }
+}
+
+fn collect_seeds(dir: &Path, seeds: &mut Vec<(PathBuf, String)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_seeds(&path, seeds);
+        } else if path.extension().is_some_and(|extension| extension == "why") {
+            if let Ok(source) = fs::read_to_string(&path) {
+                seeds.push((path, source));
+            }
+        }
+    }
+}
+
+fn seeds() -> Vec<(PathBuf, String)> {
+    let mut seeds = vec![];
+    for dir in SEED_DIRS {
+        collect_seeds(Path::new(dir), &mut seeds);
+    }
+    seeds
+}
+
+/// Index of a char boundary within the given source.
+fn boundary(rng: &mut Rng, source: &str) -> usize {
+    let mut index = rng.below(source.len() + 1);
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn mutate(rng: &mut Rng, source: &str, seeds: &[(PathBuf, String)]) -> String {
+    let mut source = source.to_owned();
+
+    for _ in 0..=rng.below(4) {
+        let start = boundary(rng, &source);
+        let end = start.max(boundary(rng, &source)).min(start + 16);
+        let end = (end..=source.len())
+            .find(|index| source.is_char_boundary(*index))
+            .unwrap_or(source.len());
+
+        match rng.below(5) {
+            0 => source.insert_str(start, TOKENS[rng.below(TOKENS.len())]),
+            1 => source.replace_range(start..end, ""),
+            2 => source.replace_range(start..end, TOKENS[rng.below(TOKENS.len())]),
+            3 => {
+                let range = source[start..end].to_owned();
+                source.insert_str(end, &range);
+            }
+            _ => {
+                // splice a line of another program
+                let other = &seeds[rng.below(seeds.len())].1;
+                let lines = other.lines().collect::<Vec<_>>();
+                if !lines.is_empty() {
+                    let line = format!("{}\n", lines[rng.below(lines.len())]);
+                    source.insert_str(start, &line);
+                }
+            }
+        }
+    }
+
+    source
+}
+
+/// Run the front end on the given source. Errors are fine, only panics are reported.
+fn run_front_end(source: &str) -> Result<(), String> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let (ast, _) = Ast::from_source_recovering("fuzz.why", source);
+        let _ = Typechecker::extract_exports(&ast);
+
+        if let Ok(pairs) = YParser::parse_program("fuzz.why", source) {
+            let ast = Ast::from_program(pairs.collect(), "fuzz.why");
+            let _ = Typechecker::from_ast(&ast, ModuleRefs::default()).check_all();
+        }
+    }))
+    .map_err(|payload| {
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| {
+                payload
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Run all inputs, silencing the panic messages of the caught panics.
+fn find_panics(inputs: impl Iterator<Item = (String, String)>) -> Vec<String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let panics = inputs
+        .filter_map(|(name, source)| {
+            run_front_end(&source)
+                .err()
+                .map(|message| format!("{name}: {message}\n--- input ---\n{source}\n-------------"))
+        })
+        .collect::<Vec<_>>();
+
+    panic::set_hook(hook);
+    panics
+}
+
+#[test]
+fn seeds_do_not_panic() {
+    let panics = find_panics(
+        seeds()
+            .into_iter()
+            .map(|(path, source)| (path.display().to_string(), source)),
+    );
+
+    assert!(panics.is_empty(), "{}", panics.join("\n"));
+}
+
+#[test]
+fn mutated_seeds_do_not_panic() {
+    let iterations = env::var("FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|iterations| iterations.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let seed = env::var("FUZZ_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(0x5eed_u64);
+
+    let seeds = seeds();
+    let mut rng = Rng(seed | 1);
+
+    let inputs = (0..iterations).map(|iteration| {
+        let (path, source) = &seeds[rng.below(seeds.len())];
+        (
+            format!("iteration {iteration} ({})", path.display()),
+            mutate(&mut rng, source, &seeds),
+        )
+    });
+    let panics = find_panics(inputs.collect::<Vec<_>>().into_iter());
+
+    assert!(
+        panics.is_empty(),
+        "{} of {iterations} inputs panicked:\n{}",
+        panics.len(),
+        panics.join("\n")
+    );
+}
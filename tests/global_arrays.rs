@@ -0,0 +1,20 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_interpretation, Expected};
+
+const SRC_PATH: &str = "./examples/global_arrays.why";
+
+const EXPECTED: Expected = Expected {
+    stdout: "16 5 12 2\n",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_global_arrays() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
+
+#[test]
+fn interpret_global_arrays() -> Result<(), Box<dyn Error>> {
+    check_interpretation(Path::new(SRC_PATH), EXPECTED, 0)
+}
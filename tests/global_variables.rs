@@ -0,0 +1,19 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_interpretation, Expected};
+
+const SRC_PATH: &str = "./examples/global_variables.why";
+const EXPECTED: Expected = Expected {
+    stdout: "hello bye! 3 42 11\n",
+    stderr: "",
+};
+
+#[test]
+fn compile_and_run_global_variables() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
+
+#[test]
+fn interpret_global_variables() -> Result<(), Box<dyn Error>> {
+    check_interpretation(Path::new(SRC_PATH), EXPECTED, 0)
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_hoisted_functions() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/hoisted_functions.why"),
+        Expected {
+            stdout: "42 8 1 10",
+            stderr: "",
+        },
+    )
+}
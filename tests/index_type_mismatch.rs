@@ -0,0 +1,13 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./examples/index_type_mismatch.why";
+
+#[test]
+fn type_check_index_type_mismatch() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new(SRC_PATH),
+        "Index must be of type 'int' but got 'str'",
+    )
+}
@@ -0,0 +1,22 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, check_type_error, Expected};
+
+#[test]
+fn compile_and_run_inferred_return_type() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/inferred_return_type.why"),
+        Expected {
+            stdout: "Hello, World 49 small",
+            stderr: "",
+        },
+    )
+}
+
+#[test]
+fn type_check_recursive_inferred_return_type() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/recursive_inferred_return_type.why"),
+        "Function 'fac' is recursive and therefore needs a return type annotation",
+    )
+}
@@ -0,0 +1,125 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{
+    check_backends_agree, check_interpretation, check_interpretation_with_args, run_compiled,
+    run_interpreter, Expected,
+};
+
+/// Programs which only use features supported by the interpreter.
+const PROGRAMS: [&str; 34] = [
+    "ackermann",
+    "array_equality",
+    "arrays",
+    "assert_pass",
+    "assignment",
+    "bitwise",
+    "boolean",
+    "char_arithmetic",
+    "compact_if",
+    "constant_sizes",
+    "conversion",
+    "dead_functions",
+    "expressions",
+    "fib",
+    "forward_declaration",
+    "function_coercion",
+    "functions",
+    "hoisted_functions",
+    "import",
+    "labeled_block",
+    "inferred_return_type",
+    "loop_break",
+    "loop_invariant",
+    "mutable",
+    "nested_arrays",
+    "parameter_inference",
+    "raw_strings",
+    "references",
+    "scope",
+    "shadowed_syscall",
+    "slices",
+    "slot_reuse",
+    "string_iteration",
+    "unicode_identifiers",
+];
+
+#[test]
+fn interpret_fib() -> Result<(), Box<dyn Error>> {
+    check_interpretation(
+        Path::new("./examples/fib.why"),
+        Expected {
+            stdout: "6765",
+            stderr: "",
+        },
+        0,
+    )
+}
+
+#[test]
+fn interpret_programs_without_statements() -> Result<(), Box<dyn Error>> {
+    for program in ["empty", "comment_only", "whitespace_only"] {
+        check_interpretation(
+            &Path::new("./tests/programs")
+                .join(program)
+                .with_extension("why"),
+            Expected {
+                stdout: "",
+                stderr: "",
+            },
+            0,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn interpret_char_out_of_range() -> Result<(), Box<dyn Error>> {
+    check_interpretation(
+        Path::new("./tests/programs/char_out_of_range.why"),
+        Expected {
+            stdout: "before",
+            stderr: "",
+        },
+        1,
+    )
+}
+
+#[test]
+fn interpret_script_with_arguments() -> Result<(), Box<dyn Error>> {
+    check_interpretation_with_args(
+        Path::new("./examples/script.why"),
+        &["hello", "--world"],
+        Expected {
+            stdout: "hello",
+            stderr: "",
+        },
+        0,
+    )
+}
+
+#[test]
+fn report_too_deep_recursion() -> Result<(), Box<dyn Error>> {
+    let src_path = Path::new("./examples/deep_recursion.why");
+
+    // the interpreter reports an error instead of overflowing its own stack
+    let interpreted = run_interpreter(src_path, &[])?;
+    assert_eq!(interpreted.status.code(), Some(255));
+    assert!(
+        String::from_utf8(interpreted.stderr)?.contains("Maximum call depth of 10000 exceeded (")
+    );
+
+    let compiled = run_compiled(src_path, &[])?;
+    assert_eq!(String::from_utf8(compiled.stdout)?, "100000");
+
+    Ok(())
+}
+
+#[test]
+fn backends_agree() -> Result<(), Box<dyn Error>> {
+    for program in PROGRAMS {
+        check_backends_agree(&Path::new("./examples").join(program).with_extension("why"))?;
+    }
+
+    Ok(())
+}
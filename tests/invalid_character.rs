@@ -0,0 +1,11 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+#[test]
+fn parse_invalid_character() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/invalid_character.why"),
+        "Invalid character '😀' (U+1F600)",
+    )
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_labeled_block() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/labeled_block.why"),
+        Expected {
+            stdout: "0 20 4 9 10 ok",
+            stderr: "",
+        },
+    )
+}
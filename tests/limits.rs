@@ -0,0 +1,41 @@
+use std::{error::Error, path::Path, time::Duration};
+
+use test_utils::{run_compiled_with_limits, LimitExceeded, Limits};
+
+#[test]
+fn kill_program_after_timeout() -> Result<(), Box<dyn Error>> {
+    let limits = Limits {
+        timeout: Duration::from_secs(1),
+        ..Limits::default()
+    };
+
+    let error = run_compiled_with_limits(Path::new("./examples/endless_loop.why"), &[], limits)
+        .expect_err("endless loop should time out");
+
+    assert_eq!(
+        error.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded::Timeout(Duration::from_secs(1)))
+    );
+    assert_eq!(error.to_string(), "program timed out after 1s");
+
+    Ok(())
+}
+
+#[test]
+fn kill_program_exceeding_output_limit() -> Result<(), Box<dyn Error>> {
+    let limits = Limits {
+        max_output: 1024 * 1024,
+        ..Limits::default()
+    };
+
+    let error = run_compiled_with_limits(Path::new("./examples/endless_output.why"), &[], limits)
+        .expect_err("endless output should exceed the limit");
+
+    assert_eq!(
+        error.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded::Output(1024 * 1024))
+    );
+    assert_eq!(error.to_string(), "output exceeded 1 MiB");
+
+    Ok(())
+}
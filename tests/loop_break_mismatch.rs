@@ -0,0 +1,10 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_failing_type_checking;
+
+const SRC_PATH: &str = "./examples/loop_break_mismatch.why";
+
+#[test]
+fn type_check_loop_break_mismatch() -> Result<(), Box<dyn Error>> {
+    check_failing_type_checking(Path::new(SRC_PATH))
+}
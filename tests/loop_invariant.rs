@@ -0,0 +1,60 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, compile_to_assembly, compiler_warnings, Expected};
+
+const SRC_PATH: &str = "./examples/loop_invariant.why";
+const WARNING: &str = "Condition of while loop is never modified in its body";
+
+#[test]
+fn compile_and_run_loop_invariant() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new(SRC_PATH),
+        Expected {
+            stdout: "15 3",
+            stderr: "",
+        },
+    )
+}
+
+#[test]
+fn hoist_invariant_part_of_condition() -> Result<(), Box<dyn Error>> {
+    let assembly = compile_to_assembly(Path::new(SRC_PATH), "loop_invariant_hoisting")?;
+
+    // `limit * step` is computed (and stored) once before entering the loop
+    let (before_loop, loop_body) = assembly
+        .split_once("\n.while_0_0:")
+        .expect("loop should be part of the assembly");
+    let (loop_body, _) = loop_body
+        .split_once("\n.while_0_0_end:")
+        .expect("loop should end");
+
+    let (_, hoisted) = before_loop
+        .rsplit_once("; LOAD step")
+        .expect("invariant should be computed before the loop");
+    assert!(hoisted.contains("imul"));
+    assert!(!loop_body.contains("imul"));
+
+    Ok(())
+}
+
+#[test]
+fn warn_about_unmodified_condition() -> Result<(), Box<dyn Error>> {
+    let warnings = compiler_warnings(Path::new("./examples/unmodified_loop_condition.why"))?;
+
+    assert!(warnings.contains(&format!("{WARNING} (")), "{warnings}");
+    assert!(
+        warnings.contains("unmodified_loop_condition.why:5:1)"),
+        "{warnings}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn no_warning_for_modified_condition() -> Result<(), Box<dyn Error>> {
+    let warnings = compiler_warnings(Path::new(SRC_PATH))?;
+
+    assert!(!warnings.contains(WARNING), "{warnings}");
+
+    Ok(())
+}
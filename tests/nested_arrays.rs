@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_nested_arrays() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/nested_arrays.why"),
+        Expected {
+            stdout: "6 21 0 6 1 42 4 7 36",
+            stderr: "",
+        },
+    )
+}
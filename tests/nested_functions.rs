@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_nested_functions() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/nested_functions.why"),
+        Expected {
+            stdout: "14 160 16 20 4 3",
+            stderr: "",
+        },
+    )
+}
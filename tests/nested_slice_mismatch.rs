@@ -0,0 +1,11 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+#[test]
+fn type_check_nested_slice_mismatch() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new("./examples/nested_slice_mismatch.why"),
+        "Invalid type of parameter! Expected '&[&[int]]' but got '[[int; 3]; 2]'",
+    )
+}
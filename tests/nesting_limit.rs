@@ -0,0 +1,14 @@
+use std::{error::Error, fs, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./output/nesting_limit.why";
+const DEPTH: usize = 5000;
+
+#[test]
+fn type_check_deeply_nested_expression() -> Result<(), Box<dyn Error>> {
+    let expression = format!("{}1{}", "1 + (".repeat(DEPTH), ")".repeat(DEPTH));
+    fs::write(SRC_PATH, format!("let a := {expression}\n"))?;
+
+    check_type_error(Path::new(SRC_PATH), "Expression is nested too deeply")
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_parameter_inference() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/parameter_inference.why"),
+        Expected {
+            stdout: "42 70 8 15",
+            stderr: "",
+        },
+    )
+}
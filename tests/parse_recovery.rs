@@ -0,0 +1,35 @@
+use std::{error::Error, fs};
+
+use test_utils::run_why;
+use y_lang::{ast::Ast, loader::ModuleRefs, typechecker::Typechecker};
+
+const SRC_PATH: &str = "./examples/parse_recovery.why";
+
+#[test]
+fn report_all_parse_errors() -> Result<(), Box<dyn Error>> {
+    let output = run_why(&["build", SRC_PATH], &[])?;
+    // errors are rendered to stderr
+    let stderr = String::from_utf8(output.stderr)?;
+
+    assert_eq!(output.status.code(), Some(255));
+    assert!(stderr.contains("parse_recovery.why:7:1)"), "{stderr}");
+    assert!(stderr.contains("parse_recovery.why:13:5)"), "{stderr}");
+
+    Ok(())
+}
+
+#[test]
+fn type_check_statements_around_parse_errors() -> Result<(), Box<dyn Error>> {
+    let source = fs::read_to_string(SRC_PATH)?;
+
+    let (ast, errors) = Ast::from_source_recovering(SRC_PATH, &source);
+
+    assert_eq!(errors.len(), 2);
+    // `square`, `cube` and the call of `cube` remain
+    assert_eq!(ast.nodes().len(), 3);
+    assert!(Typechecker::from_ast(&ast, ModuleRefs::default())
+        .check_all()
+        .is_ok());
+
+    Ok(())
+}
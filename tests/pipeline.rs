@@ -0,0 +1,202 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use y_lang::{
+    compiler::Compiler,
+    loader::{load_module, Module},
+    pipeline::{run_stage, Cancelled, PipelineObserver, Stage},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+    Start(Stage),
+    End(Stage),
+    Diagnostic,
+}
+
+use Event::*;
+
+/// Observer which records all events and cancels once the given event happened.
+#[derive(Default)]
+struct Recorder {
+    events: RefCell<Vec<Event>>,
+    cancel_after: Option<Event>,
+}
+
+impl Recorder {
+    fn cancelling_after(event: Event) -> Rc<Self> {
+        Rc::new(Recorder {
+            cancel_after: Some(event),
+            ..Default::default()
+        })
+    }
+
+    fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+}
+
+impl PipelineObserver for Recorder {
+    fn on_stage_start(&self, stage: Stage) {
+        self.events.borrow_mut().push(Start(stage));
+    }
+
+    fn on_stage_end(&self, stage: Stage, _duration: Duration) {
+        self.events.borrow_mut().push(End(stage));
+    }
+
+    fn on_diagnostic(&self, _diagnostic: &dyn Error) {
+        self.events.borrow_mut().push(Diagnostic);
+    }
+
+    fn should_cancel(&self) -> bool {
+        self.cancel_after
+            .as_ref()
+            .is_some_and(|event| self.events.borrow().contains(event))
+    }
+}
+
+/// Build a program like `why build` does, reporting to the given observer.
+fn build(src_path: &str, target: &Path, observer: Rc<Recorder>) -> Result<(), Box<dyn Error>> {
+    let file = fs::canonicalize(src_path)?;
+    let main_module = run_stage(observer.as_ref(), Stage::Parse, || load_module(file))?;
+
+    let (Module { ast, .. }, modules) = main_module
+        .with_observer(observer.as_ref())
+        .type_check_program(&HashMap::new())?;
+
+    let mut compiler = Compiler::from_ast(ast, modules);
+    compiler.set_observer(observer);
+    compiler.compile_program(target.to_path_buf())
+}
+
+/// Get the path of a target in the output directory (without any artifacts of previous runs).
+fn target(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    fs::create_dir_all("./output")?;
+    let target = Path::new("./output").join(name);
+
+    for artifact in artifacts(&target) {
+        fs::remove_file(artifact)?;
+    }
+
+    Ok(target)
+}
+
+/// Get the executable, assembly and object files generated for the given target.
+fn artifacts(target: &Path) -> Vec<PathBuf> {
+    let name = target.file_name().unwrap().to_string_lossy().to_string();
+
+    fs::read_dir(target.parent().unwrap())
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with(&name)
+        })
+        .filter(|path| {
+            path.extension()
+                .is_none_or(|extension| extension == "asm" || extension == "o")
+        })
+        .collect()
+}
+
+#[test]
+fn report_stages_in_order() -> Result<(), Box<dyn Error>> {
+    let target = target("pipeline_stages")?;
+    let observer = Rc::new(Recorder::default());
+
+    build("./examples/import.why", &target, observer.clone())?;
+
+    assert_eq!(
+        observer.events(),
+        [
+            Start(Stage::Parse),
+            End(Stage::Parse),
+            Start(Stage::Load),
+            End(Stage::Load),
+            Start(Stage::TypeCheck),
+            End(Stage::TypeCheck),
+            Start(Stage::Codegen),
+            End(Stage::Codegen),
+            Start(Stage::Link),
+            End(Stage::Link),
+        ]
+    );
+    assert!(target.exists());
+
+    Ok(())
+}
+
+#[test]
+fn report_diagnostics() -> Result<(), Box<dyn Error>> {
+    let target = target("pipeline_diagnostics")?;
+    let observer = Rc::new(Recorder::default());
+
+    assert!(build(
+        "./examples/assignment_in_condition.why",
+        &target,
+        observer.clone()
+    )
+    .is_err());
+
+    let events = observer.events();
+    assert_eq!(
+        events.iter().filter(|event| **event == Diagnostic).count(),
+        2
+    );
+    assert_eq!(events.last(), Some(&End(Stage::TypeCheck)));
+
+    Ok(())
+}
+
+#[test]
+fn cancel_between_parsing_and_type_checking() -> Result<(), Box<dyn Error>> {
+    let target = target("pipeline_cancel_parse")?;
+    let observer = Recorder::cancelling_after(End(Stage::Parse));
+
+    let error = build("./examples/import.why", &target, observer.clone()).unwrap_err();
+
+    assert!(error.is::<Cancelled>());
+    assert_eq!(observer.events(), [Start(Stage::Parse), End(Stage::Parse)]);
+    assert!(artifacts(&target).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn cancel_while_type_checking() -> Result<(), Box<dyn Error>> {
+    let target = target("pipeline_cancel_check")?;
+    let observer = Recorder::cancelling_after(Start(Stage::TypeCheck));
+
+    let error = build("./examples/import.why", &target, observer.clone()).unwrap_err();
+
+    assert!(error.is::<Cancelled>());
+    assert_eq!(observer.events().last(), Some(&End(Stage::TypeCheck)));
+    assert!(!observer.events().contains(&Start(Stage::Codegen)));
+    assert!(artifacts(&target).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn cancel_before_linking_removes_artifacts() -> Result<(), Box<dyn Error>> {
+    let target = target("pipeline_cancel_link")?;
+    let observer = Recorder::cancelling_after(End(Stage::Codegen));
+
+    let error = build("./examples/import.why", &target, observer.clone()).unwrap_err();
+
+    assert!(error.is::<Cancelled>());
+    assert!(!observer.events().contains(&Start(Stage::Link)));
+    assert!(artifacts(&target).is_empty());
+
+    Ok(())
+}
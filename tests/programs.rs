@@ -0,0 +1,10 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_programs;
+
+const PROGRAMS_PATH: &str = "./tests/programs";
+
+#[test]
+fn compile_and_run_programs() -> Result<(), Box<dyn Error>> {
+    check_programs(Path::new(PROGRAMS_PATH))
+}
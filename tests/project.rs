@@ -0,0 +1,126 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use test_utils::run_why_in;
+
+/// The program of every project (`./output/projects/<name>/src/main.why`), which imports the
+/// standard library of this repository.
+const PROGRAM: &str =
+    "import @super::@super::@super::@super::lib::std::io::*\n\nprint(\"hello from the project\")\n";
+
+/// Create a project with the given manifest (and a `src/main.why`) in a fresh directory.
+fn create_project(name: &str, manifest: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = Path::new("./output/projects").join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(dir.join("src"))?;
+
+    fs::write(dir.join("why.toml"), manifest)?;
+    fs::write(dir.join("src/main.why"), PROGRAM)?;
+
+    Ok(fs::canonicalize(dir)?)
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn build_via_manifest() -> Result<(), Box<dyn Error>> {
+    let dir = create_project(
+        "manifest_only",
+        "[package]\nname = \"hello\"\nentry = \"src/main.why\"\n\n[build]\noutput-dir = \"bin\"\nchecked-arithmetic = true\n",
+    )?;
+
+    // the manifest is found from within any subdirectory of the project
+    let output = run_why_in(&dir.join("src"), &["build"])?;
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let run = Command::new(dir.join("bin/hello")).output()?;
+    assert_eq!(stdout(&run), "hello from the project");
+
+    Ok(())
+}
+
+#[test]
+fn command_line_overrides_manifest() -> Result<(), Box<dyn Error>> {
+    let dir = create_project(
+        "cli_override",
+        "[package]\nname = \"hello\"\nentry = \"src/main.why\"\n\n[build]\noutput-dir = \"bin\"\n",
+    )?;
+
+    let output = run_why_in(&dir, &["build", "-o", "custom"])?;
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    assert!(dir.join("custom").exists());
+    assert!(!dir.join("bin/hello").exists());
+
+    Ok(())
+}
+
+#[test]
+fn unknown_keys_are_reported() -> Result<(), Box<dyn Error>> {
+    let dir = create_project(
+        "unknown_key",
+        "[package]\nentry = \"src/main.why\"\n\n[build]\nopt-level = 3\n",
+    )?;
+
+    // `-v <level>` is deprecated in favor of `--verbosity <level>`, but still accepted
+    let output = run_why_in(&dir, &["-v", "warn", "build"])?;
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // warnings are logged to stderr
+    let manifest = dir.join("why.toml");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!(
+        "Unknown key 'build.opt-level' in manifest ({}:5:1)",
+        manifest.display()
+    )));
+    assert!(stderr.contains("`-v <level>` is deprecated"));
+
+    Ok(())
+}
+
+#[test]
+fn missing_entry_is_an_error() -> Result<(), Box<dyn Error>> {
+    let dir = create_project("missing_entry", "[package]\nname = \"hello\"\n")?;
+
+    let output = run_why_in(&dir, &["--color", "never", "build"])?;
+    assert!(!output.status.success());
+
+    let manifest = dir.join("why.toml");
+    assert!(stderr(&output).contains(&format!(
+        "Missing key 'package.entry' (the source file to build) ({}:1:1)",
+        manifest.display()
+    )));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_values_report_file_and_key() -> Result<(), Box<dyn Error>> {
+    let dir = create_project(
+        "invalid_value",
+        "[package]\nentry = \"src/main.why\"\n\n[build]\nstack-guard = \"yes\"\n",
+    )?;
+
+    let output = run_why_in(&dir, &["--color", "never", "build"])?;
+    assert!(!output.status.success());
+
+    let manifest = dir.join("why.toml");
+    assert!(stderr(&output).contains(&format!(
+        "Invalid value for 'build.stack-guard': invalid type: string \"yes\", expected a boolean ({}:5:1)",
+        manifest.display()
+    )));
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Use of every item of the prelude, so that removing (or renaming) one of them fails to compile.
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use y_lang::prelude::*;
+
+const SRC_PATH: &str = "./examples/fib.why";
+
+/// Name all types of the prelude (including the type parameters of the AST).
+#[allow(clippy::type_complexity)]
+fn _types(
+    _: Option<(
+        Ast<()>,
+        Ast<TypeInfo>,
+        Statement<TypeInfo>,
+        Expression<TypeInfo>,
+        Intrinsic<TypeInfo>,
+        Type,
+        Position,
+    )>,
+    _: Option<(
+        ParseError,
+        ParseErrors,
+        TypeError,
+        TypeErrors,
+        InterpreterError,
+    )>,
+    _: Option<(
+        JsonError,
+        ManifestError,
+        ToolNotFound,
+        Cancelled,
+        Diagnostic,
+    )>,
+    _: Option<(
+        Module<()>,
+        Modules<TypeInfo>,
+        ModuleRefs<()>,
+        VariableType,
+        Value,
+    )>,
+    _: Option<(ModuleDocs, ExternalsReport, Xref, ProjectConfig)>,
+    _: Option<(RelocModel, ColorChoice, TreeStyle, Stage, NoObserver)>,
+    _: Option<&dyn PipelineObserver>,
+) {
+}
+
+#[test]
+fn parse_check_and_interpret() -> Result<(), Box<dyn Error>> {
+    let source = fs::read_to_string(SRC_PATH)?;
+    let pairs = YParser::parse_program(SRC_PATH, &source)?;
+    let ast = Ast::from_program(pairs.collect(), SRC_PATH);
+
+    assert_eq!(from_json(&to_json(&ast))?, ast);
+    assert!(render(&ast, TreeStyle::Compact).starts_with("Import "));
+    assert!(render_pairs(
+        YParser::parse_program(SRC_PATH, &source)?,
+        SRC_PATH,
+        TreeStyle::Compact
+    )
+    .starts_with("importDirective"));
+    assert!(Xref::collect(&ast).definitions.len() > 1);
+    assert!(ExternalsReport::collect(&ast).externals.is_empty());
+
+    let main_module = run_stage(&NoObserver, Stage::Parse, || {
+        load_module(fs::canonicalize(SRC_PATH)?)
+    })?;
+    let (main_module, modules) = main_module.type_check_program(&HashMap::new())?;
+
+    let interpreter = Interpreter::new(&main_module, &modules);
+    assert_eq!(interpreter.run()?, 0);
+
+    let _ = Compiler::from_ast(main_module.ast, modules);
+    let _ = (check_toolchain, find_manifest(Path::new(".")), render_error);
+
+    let pairs = YParser::parse_program("test.why", "let answer := 42")?;
+    let ast = Ast::from_program(pairs.collect(), "test.why");
+    Typechecker::from_ast(&ast, ModuleRefs::default()).check()?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_raw_strings() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/raw_strings.why"),
+        Expected {
+            stdout: r##"C:\Users\why\n say "hi\t" a "# inside"##,
+            stderr: "",
+        },
+    )
+}
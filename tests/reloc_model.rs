@@ -0,0 +1,19 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation_with_args, Expected};
+
+const SRC_PATH: &str = "./examples/reloc_model.why";
+const EXPECTED: Expected = Expected {
+    stdout: "Hello, Relocation!",
+    stderr: "",
+};
+
+// all models share the same output binary, so they are compiled within a single test
+#[test]
+fn compile_and_run_reloc_model() -> Result<(), Box<dyn Error>> {
+    for model in ["pic", "static", "default"] {
+        check_compilation_with_args(Path::new(SRC_PATH), &["--reloc-model", model], EXPECTED)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::{error::Error, fs};
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/report_externals.why";
+
+#[test]
+fn report_externals_on_stdout() -> Result<(), Box<dyn Error>> {
+    let output = run_why(&["build", SRC_PATH, "--report-externals"], &[])?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let file = fs::canonicalize(SRC_PATH)?.to_string_lossy().to_string();
+    let report = String::from_utf8(output.stdout)?.replace(&file, "<file>");
+
+    assert_eq!(
+        report,
+        "abs: (int) -> int (symbol 'abs', declared at <file>:2:9)\n\
+         \tused in 'magnitude' at <file>:10:5\n\
+         \tused in 'distance' at <file>:16:5\n\
+         labs: (int) -> int (symbol 'labs', declared at <file>:3:9)\n\
+         \tunused (candidate for removal)\n\
+         errno: int (symbol 'errno', declared at <file>:4:9)\n\
+         \tused at top level at <file>:13:42\n\
+         1 of 3 declarations unused\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn report_externals_as_json() -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all("./output")?;
+    let path = "./output/report_externals.json";
+
+    let output = run_why(
+        &["build", SRC_PATH, &format!("--report-externals={path}")],
+        &[],
+    )?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.is_empty());
+
+    let report = fs::read_to_string(path)?;
+    assert!(report.contains("\"version\": 1"));
+    assert!(report.contains("\"type\": \"(int) -> int\""));
+    assert!(report.contains("\"function\": \"magnitude\""));
+
+    // the declared function, which is defined later on, is no external symbol
+    assert!(!report.contains("\"name\": \"distance\""));
+    assert_eq!(report.matches("\"used\": false").count(), 1);
+
+    Ok(())
+}
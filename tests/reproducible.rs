@@ -0,0 +1,15 @@
+use std::{error::Error, path::Path};
+
+use test_utils::compile_to_assembly;
+
+const SRC_PATH: &str = "./examples/import.why";
+
+#[test]
+fn compile_reproducibly() -> Result<(), Box<dyn Error>> {
+    let first = compile_to_assembly(Path::new(SRC_PATH), "reproducible_first")?;
+    let second = compile_to_assembly(Path::new(SRC_PATH), "reproducible_second")?;
+
+    assert_eq!(first, second);
+
+    Ok(())
+}
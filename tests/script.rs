@@ -0,0 +1,52 @@
+use std::{error::Error, fs, path::Path, str};
+
+use test_utils::run_script;
+
+const SRC_PATH: &str = "./examples/script.why";
+const CACHE_DIR: &str = "./output/script_cache";
+
+#[test]
+fn run_script_with_arguments() -> Result<(), Box<dyn Error>> {
+    let cache_dir = Path::new(CACHE_DIR).join("arguments");
+    let _ = fs::remove_dir_all(&cache_dir);
+
+    let output = run_script(Path::new(SRC_PATH), &["hello", "--world"], &cache_dir)?;
+    assert_eq!(str::from_utf8(&output.stdout)?, "hello");
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = run_script(Path::new(SRC_PATH), &[], &cache_dir)?;
+    assert_eq!(str::from_utf8(&output.stdout)?, "no arguments");
+    assert_eq!(output.status.code(), Some(0));
+
+    Ok(())
+}
+
+#[test]
+fn reuse_cached_script() -> Result<(), Box<dyn Error>> {
+    let cache_dir = Path::new(CACHE_DIR).join("cached");
+    let _ = fs::remove_dir_all(&cache_dir);
+
+    run_script(Path::new(SRC_PATH), &["first"], &cache_dir)?;
+
+    // the compiled binary is the only artifact without an extension
+    let binaries = || -> Result<Vec<_>, Box<dyn Error>> {
+        let mut binaries = vec![];
+        for entry in fs::read_dir(cache_dir.join("why"))? {
+            let entry = entry?;
+            if entry.path().extension().is_none() {
+                binaries.push((entry.path(), entry.metadata()?.modified()?));
+            }
+        }
+        Ok(binaries)
+    };
+
+    let before = binaries()?;
+    assert_eq!(before.len(), 1);
+
+    let output = run_script(Path::new(SRC_PATH), &["second"], &cache_dir)?;
+    assert_eq!(str::from_utf8(&output.stdout)?, "second");
+
+    assert_eq!(binaries()?, before);
+
+    Ok(())
+}
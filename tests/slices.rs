@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_slices() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/slices.why"),
+        Expected {
+            stdout: "6 16 4 16 4",
+            stderr: "",
+        },
+    )
+}
@@ -0,0 +1,37 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, compile_to_assembly_with_args, Expected};
+
+const SRC_PATH: &str = "./examples/slot_reuse.why";
+const EXPECTED: Expected = Expected {
+    stdout: "20 172 984",
+    stderr: "",
+};
+
+/// Get the size of the stack frame allocated by the given function.
+fn frame_size(assembly: &str, function: &str) -> usize {
+    let (_, body) = assembly
+        .split_once(&format!("\n{function}:"))
+        .expect("function should be part of the assembly");
+
+    body.lines()
+        .find_map(|line| line.trim().strip_prefix("sub \trsp, \t"))
+        .and_then(|size| size.trim().parse().ok())
+        .expect("function should allocate a stack frame")
+}
+
+#[test]
+fn compile_and_run_slot_reuse() -> Result<(), Box<dyn Error>> {
+    check_compilation(Path::new(SRC_PATH), EXPECTED)
+}
+
+#[test]
+fn reuse_stack_slots_of_ended_blocks() -> Result<(), Box<dyn Error>> {
+    let reused = compile_to_assembly_with_args(Path::new(SRC_PATH), "slot_reuse_on", &[])?;
+    let not_reused =
+        compile_to_assembly_with_args(Path::new(SRC_PATH), "slot_reuse_off", &["--no-slot-reuse"])?;
+
+    assert!(frame_size(&reused, "compute") < frame_size(&not_reused, "compute"));
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{compile_to_assembly, compile_to_assembly_with_args, run_compiled};
+use y_lang::compiler::STACK_OVERFLOW_EXIT_CODE;
+
+#[test]
+fn report_stack_overflow() -> Result<(), Box<dyn Error>> {
+    let output = run_compiled(
+        Path::new("./examples/stack_overflow.why"),
+        &["--stack-guard"],
+    )?;
+
+    assert_eq!(output.status.code(), Some(STACK_OVERFLOW_EXIT_CODE as i32));
+    assert_eq!(std::str::from_utf8(&output.stdout)?, "");
+    assert_eq!(
+        std::str::from_utf8(&output.stderr)?,
+        "stack overflow in recurse\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn complete_deep_recursion_with_stack_guard() -> Result<(), Box<dyn Error>> {
+    let output = run_compiled(
+        Path::new("./examples/deep_recursion.why"),
+        &["--stack-guard"],
+    )?;
+
+    assert!(output.status.success());
+    assert_eq!(std::str::from_utf8(&output.stdout)?, "100000");
+
+    Ok(())
+}
+
+#[test]
+fn guard_only_functions_calling_others() -> Result<(), Box<dyn Error>> {
+    let assembly = compile_to_assembly_with_args(
+        Path::new("./examples/functions.why"),
+        "functions_stack_guard",
+        &["--stack-guard"],
+    )?;
+
+    assert!(assembly.contains("foo_stack_overflow:"));
+    // `test` and `triple` never call another function
+    assert!(!assembly.contains("test_stack_overflow:"));
+    assert!(!assembly.contains("triple_stack_overflow:"));
+
+    Ok(())
+}
+
+#[test]
+fn no_stack_guard_by_default() -> Result<(), Box<dyn Error>> {
+    let assembly = compile_to_assembly(
+        Path::new("./examples/stack_overflow.why"),
+        "stack_overflow_unguarded",
+    )?;
+
+    assert!(!assembly.contains("stack_limit"));
+    assert!(!assembly.contains("stack_overflow:"));
+    assert!(!assembly.contains("recurse_stack_overflow"));
+
+    Ok(())
+}
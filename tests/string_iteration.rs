@@ -2,13 +2,13 @@ use std::{error::Error, path::Path};
 
 use test_utils::{check_compilation, Expected};
 
-const SRC_PATH: &str = "./examples/print.why";
+const SRC_PATH: &str = "./examples/string_iteration.why";
 const EXPECTED: Expected = Expected {
-    stdout: "literal variable function block if else ",
+    stdout: "5 0 111",
     stderr: "",
 };
 
 #[test]
-fn compile_and_run_print() -> Result<(), Box<dyn Error>> {
+fn compile_and_run_string_iteration() -> Result<(), Box<dyn Error>> {
     check_compilation(Path::new(SRC_PATH), EXPECTED)
 }
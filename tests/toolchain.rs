@@ -0,0 +1,71 @@
+use std::{error::Error, fs, path::Path, str};
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/fib.why";
+const TOOL_NOT_FOUND_EXIT_CODE: i32 = 127;
+
+/// Get a directory without any executables, so a `PATH` pointing to it contains no tools.
+fn empty_dir() -> Result<String, Box<dyn Error>> {
+    let dir = Path::new("./output/empty_path");
+    fs::create_dir_all(dir)?;
+
+    Ok(fs::canonicalize(dir)?.to_string_lossy().to_string())
+}
+
+#[test]
+fn report_missing_assembler() -> Result<(), Box<dyn Error>> {
+    let output = run_why(
+        &["build", "-o", "./output/missing_assembler", SRC_PATH],
+        &[("PATH", &empty_dir()?)],
+    )?;
+    let stderr = str::from_utf8(&output.stderr)?;
+
+    assert_eq!(output.status.code(), Some(TOOL_NOT_FOUND_EXIT_CODE));
+    assert!(
+        stderr.contains("Could not find the assembler 'nasm'"),
+        "{stderr}"
+    );
+    assert!(!Path::new("./output/missing_assembler.asm").exists());
+
+    Ok(())
+}
+
+#[test]
+fn report_missing_linker_from_environment() -> Result<(), Box<dyn Error>> {
+    let output = run_why(
+        &["build", "-o", "./output/missing_linker", SRC_PATH],
+        &[("WHY_LINKER", "/nonexistent/linker")],
+    )?;
+    let stderr = str::from_utf8(&output.stderr)?;
+
+    assert_eq!(output.status.code(), Some(TOOL_NOT_FOUND_EXIT_CODE));
+    assert!(
+        stderr.contains("Could not find the linker '/nonexistent/linker'"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn interpret_without_tools() -> Result<(), Box<dyn Error>> {
+    let output = run_why(&["run", SRC_PATH], &[("PATH", &empty_dir()?)])?;
+
+    assert!(output.status.success());
+    assert_eq!(str::from_utf8(&output.stdout)?, "6765");
+
+    Ok(())
+}
+
+#[test]
+fn doctor_lists_missing_tools() -> Result<(), Box<dyn Error>> {
+    let output = run_why(&["doctor"], &[("PATH", &empty_dir()?)])?;
+    let stdout = str::from_utf8(&output.stdout)?;
+
+    assert_eq!(output.status.code(), Some(TOOL_NOT_FOUND_EXIT_CODE));
+    assert!(stdout.contains("[MISSING] assembler: 'nasm'"), "{stdout}");
+    assert!(stdout.contains("[MISSING] linker: 'cc'"), "{stdout}");
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./examples/unannotated_export.why";
+
+#[test]
+fn type_check_unannotated_export() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new(SRC_PATH),
+        "Function 'inc' is not exported by its module, since its type is not fully annotated",
+    )?;
+    check_type_error(
+        Path::new(SRC_PATH),
+        "note: add a return type annotation to export 'inc' (",
+    )?;
+    check_type_error(Path::new(SRC_PATH), "helpers.why:2:5)")
+}
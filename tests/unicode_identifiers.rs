@@ -0,0 +1,14 @@
+use std::{error::Error, path::Path};
+
+use test_utils::{check_compilation, Expected};
+
+#[test]
+fn compile_and_run_unicode_identifiers() -> Result<(), Box<dyn Error>> {
+    check_compilation(
+        Path::new("./examples/unicode_identifiers.why"),
+        Expected {
+            stdout: "Hallo 12 42",
+            stderr: "",
+        },
+    )
+}
@@ -0,0 +1,51 @@
+use std::{error::Error, process::Output};
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/verbose_logging.why";
+
+fn build(args: &[&str], out: &str) -> Result<Output, Box<dyn Error>> {
+    let output = run_why(&[args, &["build", "-o", out, SRC_PATH]].concat(), &[])?;
+    assert!(output.status.success(), "{output:?}");
+
+    Ok(output)
+}
+
+#[test]
+fn log_stages_to_stderr() -> Result<(), Box<dyn Error>> {
+    let output = build(&["-vv"], "./output/verbose_logging_vv")?;
+    let stderr = String::from_utf8(output.stderr)?;
+
+    assert!(stderr.contains("INFO  [y_lang::pipeline] Type checking...\n"));
+    assert!(stderr.contains("DEBUG [y_lang::typechecker] Checked 'square': (int) -> int\n"));
+    assert!(stderr.contains("INFO  [y_lang::pipeline] Generating code...\n"));
+    assert!(stderr.contains("DEBUG [y_lang::compiler] Generated function 'square' ("));
+    assert!(stderr.contains("DEBUG [y_lang::compiler::toolchain] Running linker: "));
+
+    // logs never end up in the output of the compiler
+    assert!(output.stdout.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn log_with_timestamps_at_highest_verbosity() -> Result<(), Box<dyn Error>> {
+    let output = build(&["-vvv"], "./output/verbose_logging_vvv")?;
+    let stderr = String::from_utf8(output.stderr)?;
+
+    assert!(stderr.lines().all(|line| line
+        .split_once("s ")
+        .is_some_and(|(timestamp, _)| timestamp.trim().parse::<f64>().is_ok())));
+
+    Ok(())
+}
+
+#[test]
+fn no_logs_by_default() -> Result<(), Box<dyn Error>> {
+    let output = build(&[], "./output/verbose_logging")?;
+
+    assert!(output.stderr.is_empty());
+    assert!(output.stdout.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use std::{error::Error, path::Path};
+
+use test_utils::check_type_error;
+
+const SRC_PATH: &str = "./examples/void_value.why";
+
+#[test]
+fn type_check_void_value() -> Result<(), Box<dyn Error>> {
+    check_type_error(
+        Path::new(SRC_PATH),
+        "'print' returns 'void', so its result can not be used as a value",
+    )
+}
@@ -0,0 +1,113 @@
+use std::{error::Error, fs};
+
+use test_utils::run_why;
+
+const SRC_PATH: &str = "./examples/xref.why";
+
+/// Run `why build` on the example, writing the index to the given path, and return its content.
+fn emit_xref(path: &str) -> Result<String, Box<dyn Error>> {
+    fs::create_dir_all("./output")?;
+
+    let output = run_why(&["build", SRC_PATH, "--emit-xref", path], &[])?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(fs::read_to_string(path)?)
+}
+
+#[test]
+fn emit_xref_json() -> Result<(), Box<dyn Error>> {
+    let xref = emit_xref("./output/xref.json")?;
+
+    // positions contain the absolute path of the source file
+    let file = fs::canonicalize(SRC_PATH)?;
+    let xref = xref.replace(&file.to_string_lossy().to_string(), "<file>");
+
+    assert_eq!(xref, fs::read_to_string("./tests/snapshots/xref.json")?);
+
+    Ok(())
+}
+
+/// A single entry of a tags file.
+#[derive(Debug)]
+struct Tag {
+    name: String,
+    file: String,
+    line: usize,
+    kind: String,
+}
+
+/// Parse a tags file (as described in the ctags documentation), failing on invalid lines or
+/// unsorted tags.
+fn parse_tags(content: &str) -> Result<Vec<Tag>, String> {
+    let mut tags: Vec<Tag> = vec![];
+
+    for line in content.lines() {
+        if line.starts_with("!_TAG_") {
+            continue;
+        }
+
+        let fields = line.split('\t').collect::<Vec<_>>();
+        let [name, file, address, kind, ..] = fields[..] else {
+            return Err(format!("Too few fields in '{line}'"));
+        };
+
+        let line_number = address
+            .strip_suffix(";\"")
+            .ok_or(format!("Invalid address in '{line}'"))?
+            .parse()
+            .map_err(|_| format!("Invalid line number in '{line}'"))?;
+
+        if name.is_empty() || file.is_empty() || kind.len() != 1 {
+            return Err(format!("Invalid tag '{line}'"));
+        }
+
+        if tags.last().is_some_and(|last| last.name.as_str() > name) {
+            return Err(format!("Tag '{name}' is not sorted"));
+        }
+
+        tags.push(Tag {
+            name: name.to_owned(),
+            file: file.to_owned(),
+            line: line_number,
+            kind: kind.to_owned(),
+        });
+    }
+
+    Ok(tags)
+}
+
+#[test]
+fn emit_xref_tags() -> Result<(), Box<dyn Error>> {
+    let tags = parse_tags(&emit_xref("./output/xref.tags")?)?;
+
+    let file = fs::canonicalize(SRC_PATH)?.to_string_lossy().to_string();
+    assert!(tags.iter().all(|tag| tag.file == file));
+
+    let tags = tags
+        .iter()
+        .map(|tag| (tag.name.as_str(), tag.kind.as_str(), tag.line))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tags,
+        [
+            ("countdown", "f", 8),
+            ("current", "v", 9),
+            ("exit", "d", 3),
+            ("from", "p", 8),
+            ("limit", "c", 5),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn tags_parser_rejects_unsorted_tags() {
+    assert!(parse_tags("b\tfile.why\t1;\"\tv\na\tfile.why\t2;\"\tv\n").is_err());
+    assert!(parse_tags("a\tfile.why\t1\tv\n").is_err());
+}